@@ -1,7 +1,10 @@
 import { describe, it, expect, beforeAll, afterAll } from 'vitest'
 import { tmpdir } from 'os'
-import { mkdtempSync } from 'fs'
+import { mkdtempSync, readFileSync, writeFileSync } from 'fs'
 import { join } from 'path'
+import { Worker } from 'worker_threads'
+import { Readable } from 'stream'
+import { pipeline } from 'stream/promises'
 
 import {
   Document,
@@ -13,14 +16,34 @@ import {
   FieldType,
   TokenizerStatic,
   FilterStatic,
+  CharFilterStatic,
   SnippetGenerator,
+  TextAnalyzer,
   TextAnalyzerBuilder,
   Facet,
+  FacetNavigator,
   DocAddress,
+  SearchResult,
+  SearchHit,
+  Term,
+  fuseResults,
+  metrics,
+  releaseHandle,
+  setLogHandler,
+  LogRecord,
+  ParseQueryOptions,
+  UserQueryOptions,
+  QueryLeaf,
+  CommitEvent,
+  CompactProgress,
+  RangeQueryOptions,
+  RescoreBatch,
+  SearchSort,
 } from '../index'
 
 import {
   schema,
+  schemaNumericFields,
   createIndex,
   createIndexWithNumericFields,
   createIndexWithDateField,
@@ -112,6 +135,63 @@ describe('TestClass', () => {
     expect(docFreq).toBe(3)
   })
 
+  it('test_export_dictionary', () => {
+    const terms = [...ramIndex.exportDictionary('title')].map((entry) => entry.term)
+    expect(terms).toEqual(['and', 'frankenstein', 'man', 'men', 'mice', 'modern', 'of', 'old', 'prometheus', 'sea', 'the'])
+    expect([...ramIndex.exportDictionary('title', { minDocFreq: 2 })]).toEqual([
+      { term: 'and', docFreq: 2, totalTermFreq: 2 },
+      { term: 'the', docFreq: 2, totalTermFreq: 3 },
+    ])
+    expect([...ramIndex.exportDictionary('title', { limit: 2 })].map((entry) => entry.term)).toEqual(['and', 'frankenstein'])
+    expect(() => ramIndex.exportDictionary('unknown')).toThrow()
+
+    // Terms spread over several segments.
+    const index = new Index(schema())
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ title: 'sea sea man' }))
+    writer.commit()
+    writer.addDocument(Document.fromDict({ title: 'sea old' }))
+    writer.commit()
+    index.reload()
+    expect([...index.exportDictionary('title')]).toEqual([
+      { term: 'man', docFreq: 1, totalTermFreq: 1 },
+      { term: 'old', docFreq: 1, totalTermFreq: 1 },
+      { term: 'sea', docFreq: 2, totalTermFreq: 3 },
+    ])
+  })
+
+  it('test_term_objects', () => {
+    const searcher = ramIndex.searcher()
+    const schema = ramIndex.schema
+    const term = Term.fromText(schema, 'body', 'and')
+    expect(searcher.docFreq('body', term)).toBe(3)
+    expect(searcher.search(Query.termQuery(schema, 'body', term)).count).toBe(3)
+    const terms = [Term.fromText(schema, 'title', 'men'), 'sea']
+    expect(searcher.search(Query.termSetQuery(schema, 'title', terms)).count).toBe(2)
+    // Unlike plain values, terms are used as is.
+    expect(searcher.docFreq('body', Term.fromText(schema, 'body', 'And'))).toBe(0)
+    expect(() => Query.termQuery(schema, 'title', term)).toThrow("The term is not a term of field 'title'.")
+    expect(() => Term.fromBytes(schema, 'title', Buffer.from('men'))).toThrow("Field 'title' is not a bytes field.")
+
+    const jsonSchema = new SchemaBuilder().addJsonField('attrs').addBytesField('data', { indexed: true }).build()
+    const index = new Index(jsonSchema)
+    const writer = index.writer()
+    writer.addJson(JSON.stringify({ attrs: { user: { name: 'alice' }, age: 7, ok: true } }))
+    writer.addDocument(Document.fromDict({ data: Buffer.from('abc') }, jsonSchema))
+    writer.commit()
+    index.reload()
+    const jsonSearcher = index.searcher()
+    expect(jsonSearcher.docFreq('attrs', Term.fromJsonPath(jsonSchema, 'attrs', 'user.name', 'alice'))).toBe(1)
+    expect(jsonSearcher.docFreq('attrs', Term.fromJsonPath(jsonSchema, 'attrs', 'age', 7))).toBe(1)
+    expect(jsonSearcher.docFreq('attrs', Term.fromJsonPath(jsonSchema, 'attrs', 'ok', true))).toBe(1)
+    expect(jsonSearcher.docFreq('data', Term.fromBytes(jsonSchema, 'data', Buffer.from('abc')))).toBe(1)
+
+    writer.deleteDocumentsByTerm('attrs', Term.fromJsonPath(jsonSchema, 'attrs', 'age', 7))
+    writer.commit()
+    index.reload()
+    expect(index.searcher().numDocs).toBe(1)
+  })
+
   it('test_and_aggregate', () => {
     const query = Query.allQuery()
     const aggQuery = {
@@ -294,6 +374,116 @@ describe('TestClass', () => {
     expect(result).toEqual(expectedResult)
   })
 
+  it('test_aggregate_filters', () => {
+    const searcher = ramIndexNumericFields.searcher()
+    const filters = {
+      good: ramIndexNumericFields.parseQuery('is_good:true', ['is_good']),
+      bad: ramIndexNumericFields.parseQuery('is_good:false', ['is_good']),
+    }
+    const subAggs = { avg_rating: { avg: { field: 'rating' } } }
+    const result = JSON.parse(searcher.aggregateFilters(Query.allQuery(), filters, JSON.stringify(subAggs)))
+    expect(result.good.doc_count).toBe(1)
+    expect(result.good.avg_rating.value).toBe(3.5)
+    expect(result.bad.doc_count).toBe(1)
+    expect(result.bad.avg_rating.value).toBe(4.5)
+
+    const counts = JSON.parse(searcher.aggregateFilters(Query.allQuery(), { good: filters.good }))
+    expect(counts).toEqual({ good: { doc_count: 1 } })
+  })
+
+  it('test_aggregate_json_field_paths', () => {
+    const schema = new SchemaBuilder().addJsonField('attrs', { fast: true, tokenizerName: 'raw' }).build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ attrs: { color: 'red', size: 3 } }, schema))
+    writer.addDocument(Document.fromDict({ attrs: { color: 'blue', size: 5 } }, schema))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+
+    const aggs = {
+      colors: { terms: { field: 'attrs.color', order: { _key: 'asc' } } },
+      sizes: { stats: { field: 'attrs.size' } },
+    }
+    const result = JSON.parse(searcher.aggregate(Query.allQuery(), JSON.stringify(aggs)))
+    expect(result.colors.buckets.map((b: { key: string }) => b.key)).toEqual(['blue', 'red'])
+    expect(result.sizes.sum).toBe(8)
+
+    const missing = { m: { terms: { field: 'attrs.missing' } } }
+    expect(() => searcher.aggregate(Query.allQuery(), JSON.stringify(missing))).toThrow(/missing' of JSON field 'attrs/)
+    const undefinedField = { m: { terms: { field: 'nope' } } }
+    expect(() => searcher.aggregate(Query.allQuery(), JSON.stringify(undefinedField))).toThrow(/not defined/)
+  })
+
+  it('test_aggregate_fast_text_normalizer', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('tag', { tokenizerName: 'raw', fast: 'lowercase' })
+      .addTextField('exact', { tokenizerName: 'raw', fast: 'raw' })
+      .addTextField('title', { fast: 'raw' })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    for (const tag of ['Rust Lang', 'rust lang', 'RUST LANG']) {
+      writer.addDocument(Document.fromDict({ tag, exact: tag, title: tag }))
+    }
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+
+    const aggs = {
+      tags: { terms: { field: 'tag' } },
+      exact: { terms: { field: 'exact' } },
+      titles: { terms: { field: 'title' } },
+    }
+    const result = JSON.parse(searcher.aggregate(Query.allQuery(), JSON.stringify(aggs)))
+    expect(result.tags.buckets).toEqual([{ key: 'rust lang', doc_count: 3 }])
+    expect(result.exact.buckets).toHaveLength(3)
+    expect(result.titles.buckets).toHaveLength(3)
+    expect(() => new SchemaBuilder().addTextField('tag', { fast: '' })).toThrow('must not be empty')
+  })
+
+  it('test_aggregate_global', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addKeywordField('color', { fast: true })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ title: 'red shoes', color: 'red' }))
+    writer.addDocument(Document.fromDict({ title: 'red hat', color: 'red' }))
+    writer.addDocument(Document.fromDict({ title: 'blue shoes', color: 'blue' }))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+
+    const aggs = {
+      colors: { terms: { field: 'color', order: { _key: 'asc' } } },
+      allColors: { terms: { field: 'color', order: { _key: 'asc' } }, global: true },
+    }
+    const query = index.parseQuery('shoes', ['title'])
+    const result = JSON.parse(searcher.aggregate(query, JSON.stringify(aggs)))
+    const counts = (buckets: Array<{ key: string; doc_count: number }>) => buckets.map((b) => [b.key, b.doc_count])
+    expect(counts(result.colors.buckets)).toEqual([
+      ['blue', 1],
+      ['red', 1],
+    ])
+    expect(counts(result.allColors.buckets)).toEqual([
+      ['blue', 1],
+      ['red', 2],
+    ])
+
+    const all = JSON.parse(searcher.aggregate(null, JSON.stringify({ colors: aggs.colors })))
+    expect(counts(all.colors.buckets)).toEqual([
+      ['blue', 1],
+      ['red', 2],
+    ])
+    const global = JSON.parse(searcher.aggregate(query, JSON.stringify({ allColors: aggs.allColors })))
+    expect(Object.keys(global)).toEqual(['allColors'])
+    expect(counts(global.allColors.buckets)).toEqual(counts(result.allColors.buckets))
+    const invalid = { colors: { ...aggs.colors, global: 'yes' } }
+    expect(() => searcher.aggregate(null, JSON.stringify(invalid))).toThrow('must be a boolean')
+  })
+
   it('test_and_query_numeric_fields', () => {
     // Test numeric fields schema
     expect(ramIndexNumericFields.schema.numFields()).toBe(4)
@@ -347,6 +537,156 @@ describe('TestClass', () => {
     )
   })
 
+  it('test_parse_query_date_math', () => {
+    const now = new Date('2021-01-02T12:00:00Z').getTime()
+    const searcher = ramIndexWithDateField.searcher()
+
+    const lastDay = ramIndexWithDateField.parseQuery('date:[now-1d TO now]', undefined, undefined, undefined, now)
+    expect(searcher.search(lastDay).count).toBe(1)
+
+    const sinceYesterday = ramIndexWithDateField.parseQuery('date:>=now-1d/d', undefined, undefined, undefined, now)
+    expect(searcher.search(sinceYesterday).count).toBe(2)
+
+    const today = ramIndexWithDateField.parseQuery('date:now/d', undefined, undefined, undefined, now)
+    expect(today.toString()).toContain('2021-01-02T00:00:00Z')
+  })
+
+  it('test_debug_query', () => {
+    const tree = JSON.parse(ramIndex.debugQuery('title:Winter^2 AND sea', { defaultFieldNames: ['body'], fieldBoosts: { body: 1.5 } }))
+    expect(tree).toEqual({
+      type: 'bool',
+      clauses: [
+        { occur: 'must', query: { type: 'boost', boost: 2, query: { type: 'term', field: 'title', term: 'winter' } } },
+        { occur: 'must', query: { type: 'term', field: 'body', term: 'sea', boost: 1.5 } },
+      ],
+    })
+
+    const range = JSON.parse(ramIndexNumericFields.debugQuery('id:[1 TO 5}'))
+    expect(range).toEqual({ type: 'range', field: 'id', lower: { value: '1', inclusive: true }, upper: { value: '5', inclusive: false } })
+
+    expect(() => ramIndex.debugQuery('title:(winter')).toThrow()
+  })
+
+  it('test_estimate_cost', () => {
+    const searcher = ramIndex.searcher()
+    const cost = ramIndex.parseQuery('title:men AND body:and', ['title', 'body']).estimateCost(searcher)
+    expect(cost.kind).toBe('bool')
+    expect(cost.clauses!.map((clause) => [clause.occur, clause.kind, clause.field])).toEqual([
+      ['must', 'term', 'title'],
+      ['must', 'term', 'body'],
+    ])
+    expect(cost.clauses![0].estimatedDocs).toBe(searcher.docFreq('title', 'men'))
+    expect(cost.clauses![1].estimatedDocs).toBe(searcher.docFreq('body', 'and'))
+    expect(cost.estimatedDocs).toBe(searcher.docFreq('title', 'men'))
+    expect(cost.terms).toBe(2)
+
+    // Alternatives add up, without exceeding the number of documents.
+    const alternatives = ramIndex.parseQuery('and OR the', ['body']).estimateCost(searcher)
+    expect(alternatives.estimatedDocs).toBe(searcher.numDocs)
+
+    const regex = Query.regexQuery(ramIndex.schema, 'body', '.*a').estimateCost(searcher)
+    expect(regex).toMatchObject({ kind: 'regex', terms: 0, estimatedDocs: searcher.numDocs })
+  })
+
+  it('test_parse_query_json_default_paths', () => {
+    const schema = new SchemaBuilder().addJsonField('attrs').addTextField('title').build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ attrs: { color: 'red', name: 'blue car' }, title: 'first' }, schema))
+    writer.addDocument(Document.fromDict({ attrs: { color: 'blue' }, title: 'red second' }, schema))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+
+    // Without paths, terms only match at the root of the JSON objects.
+    expect(searcher.search(index.parseQuery('blue', ['attrs', 'title'])).count).toBe(0)
+
+    index.setDefaultJsonPaths('attrs', ['color'])
+    expect(searcher.search(index.parseQuery('blue', ['attrs', 'title'])).count).toBe(1)
+    expect(searcher.search(index.parseQuery('red', ['attrs', 'title'])).count).toBe(2)
+    expect(searcher.search(index.parseQuery('red', ['attrs'])).count).toBe(1)
+
+    const jsonPaths = { attrs: ['color', 'name'] }
+    const query = index.parseQuery('"blue car"', ['attrs'], undefined, undefined, undefined, jsonPaths)
+    expect(searcher.search(query).count).toBe(1)
+    const [lenient, errors] = index.parseQueryLenient('blue', ['attrs'], undefined, undefined, undefined, jsonPaths)
+    expect(errors).toEqual([])
+    expect(searcher.search(lenient).count).toBe(2)
+
+    index.setDefaultJsonPaths('attrs', [])
+    expect(searcher.search(index.parseQuery('blue', ['attrs', 'title'])).count).toBe(0)
+    expect(() => index.setDefaultJsonPaths('title', ['color'])).toThrow(/not a JSON field/)
+  })
+
+  it('test_register_tokenizer_query_json_fields', () => {
+    const keyword = new TextAnalyzerBuilder(TokenizerStatic.raw()).filter(FilterStatic.lowercase()).build()
+    const schema = new SchemaBuilder()
+      .addJsonField('attrs', { tokenizerName: 'keyword' })
+      .addTextField('title', { tokenizerName: 'keyword' })
+      .build()
+    const index = new Index(schema)
+    index.registerTokenizer('keyword', keyword)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ attrs: { name: 'Blue Car' }, title: 'Blue Car' }, schema))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const count = (query: string) => searcher.search(index.parseQuery(query)).count
+    expect(count('attrs.name:"blue car"')).toBe(1)
+
+    // The analyzer registered under the name no longer matches the indexed values.
+    index.registerTokenizer('keyword', new TextAnalyzerBuilder(TokenizerStatic.simple()).build())
+    expect(count('attrs.name:"blue car"')).toBe(0)
+
+    index.registerTokenizer('keyword_v1', keyword, ['attrs'])
+    expect(count('attrs.name:"blue car"')).toBe(1)
+    expect(count('attrs.name:"BLUE CAR"')).toBe(1)
+    // Only the queries on the given JSON fields are affected.
+    expect(count('title:"blue car"')).toBe(0)
+
+    expect(() => index.registerTokenizer('keyword_v1', keyword, ['title'])).toThrow(/not a JSON field/)
+  })
+
+  it('test_parse_query_phrase_options', () => {
+    const searcher = ramIndex.searcher()
+    const count = (query: string, options?: ParseQueryOptions) =>
+      searcher.search(ramIndex.parseQuery(query, ['title'], undefined, undefined, undefined, undefined, options)).count
+
+    expect(count('"old sea"')).toBe(0)
+    expect(count('"old sea"', { phraseSlop: 3 })).toBe(1)
+    // An explicit slop wins over the default one.
+    expect(count('"old sea"~1', { phraseSlop: 3 })).toBe(0)
+
+    expect(count('"old man and the se*"')).toBe(0)
+    expect(count('"old man and the se*"', { phrasePrefix: true })).toBe(1)
+    expect(count('"old man and the se"*')).toBe(1)
+
+    const [query, errors] = ramIndex.parseQueryLenient('"old sea"', ['title'], undefined, undefined, undefined, undefined, {
+      phraseSlop: 3,
+    })
+    expect(errors).toEqual([])
+    expect(searcher.search(query).count).toBe(1)
+  })
+
+  it('test_parse_user_query', () => {
+    const searcher = ramIndex.searcher()
+    const count = (text: string, options?: UserQueryOptions) =>
+      searcher.search(ramIndex.parseUserQuery(text, ['title'], options)).count
+
+    // Escaped, the field prefix is searched as the phrase "title mice".
+    expect(count('title:mice')).toBe(0)
+    expect(count('title:mice', { strip: true })).toBe(1)
+    expect(count('title:mice', { allowFields: true })).toBe(1)
+    expect(count('body:mice', { allowFields: true })).toBe(0)
+
+    expect(count('"old man"~1 -sea')).toBe(0)
+    expect(count('"old man"~1 -sea', { allowOperators: false })).toBe(1)
+    expect(count('"old man"', { allowPhrases: false })).toBe(1)
+    expect(count('"old man')).toBe(1)
+    expect(count('(mice OR [a TO z]')).toBe(1)
+    expect(count('mice^0 AND', { allowOperators: false, strip: true })).toBe(1)
+  })
+
   it('test_query_lenient', () => {
     // Test with valid query - should have no errors
     let [query, errors] = ramIndexNumericFields.parseQueryLenient('rating:3.5')
@@ -597,6 +937,9 @@ describe('TestClass', () => {
     const result = searcher.search(query, 10, true, 'order')
 
     expect(result.hits.length).toBe(3)
+    expect(result.hits.map((hit) => hit.order)).toEqual(
+      ['2022-01-01', '2021-01-01', '2020-01-01'].map((date) => new Date(date).getTime()),
+    )
 
     let { docAddress } = result.hits[0]
     let searchedDoc = index.searcher().doc(docAddress)
@@ -677,29 +1020,36 @@ describe('TestClass', () => {
       schema,
     )
 
-    // Note: Node.js version is more lenient than Python version
-    // It accepts negative values for unsigned fields and doesn't validate integer/float type mismatches
-    // Only string values for numeric fields are rejected
+    // Negative values of unsigned fields and fractional values of integer
+    // fields are rejected (same as Python)
+    expect(() => {
+      Document.fromDict(
+        {
+          unsigned: -50,
+          signed: -5,
+          float: 0.4,
+        },
+        schema,
+      )
+    }).toThrow('is not a valid u64 integer')
 
-    // This does NOT throw in Node.js version (unlike Python)
-    Document.fromDict(
-      {
-        unsigned: -50,
-        signed: -5,
-        float: 0.4,
-      },
-      schema,
-    )
+    expect(() => {
+      Document.fromDict(
+        {
+          unsigned: 1000,
+          signed: 50.4,
+          float: 0.4,
+        },
+        schema,
+      )
+    }).toThrow('is not a valid i64 integer')
 
-    // This does NOT throw in Node.js version (unlike Python)
-    Document.fromDict(
-      {
-        unsigned: 1000,
-        signed: 50.4,
-        float: 0.4,
-      },
-      schema,
-    )
+    expect(() => Document.fromDict({ unsigned: true }, schema)).toThrow('Expected U64 type for field unsigned')
+    expect(() => Document.fromDict({ float: false }, schema)).toThrow('Expected F64 type for field float')
+
+    // Unless they are explicitly coerced
+    const coerced = Document.fromDict({ unsigned: -50, signed: 50.4, float: true }, schema, true)
+    expect(coerced.toDict()).toEqual({ unsigned: [50], signed: [50], float: [1] })
 
     // This DOES throw in Node.js version (same as Python)
     expect(() => {
@@ -716,7 +1066,7 @@ describe('TestClass', () => {
     // Arrays are supported for single value fields in Node.js version (unlike Python)
     Document.fromDict(
       {
-        unsigned: [1000, -50],
+        unsigned: [1000, 50],
         signed: -5,
         float: 0.4,
       },
@@ -726,11 +1076,18 @@ describe('TestClass', () => {
     Document.fromDict(
       {
         unsigned: 1000,
-        signed: [-5, 150, -3.14],
+        signed: [-5, 150, -3],
         float: 0.4,
       },
       schema,
     )
+    expect(() => Document.fromDict({ signed: [-5, -3.14] }, schema)).toThrow('is not a valid i64 integer')
+
+    const doc = new Document()
+    expect(() => doc.set('unsigned', [1, -1], schema)).toThrow('is not a valid u64 integer')
+    doc.set('unsigned', [1, -1], schema, true)
+    doc.extend({ signed: 2.5 }, schema, false, true)
+    expect(doc.toDict()).toEqual({ unsigned: [1, 1], signed: [2] })
   })
 
   it('test_doc_from_dict_bytes_validation', () => {
@@ -881,104 +1238,1413 @@ describe('TestUpdateClass', () => {
     const result = index.searcher().search(searchQuery, 10)
     expect(result.hits.length).toBeLessThan(3) // Originally had 3 docs
   })
-})
 
-describe('TestFromDiskClass', () => {
-  it('test_opens_from_dir_invalid_schema', () => {
-    // Test opening index from directory with invalid/incompatible schema
-    createIndex(tempDir) // Create an index first
+  it('test_delete_by_query_string', () => {
+    const schema = new SchemaBuilder().addJsonField('attrs').addTextField('title', { stored: true }).build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ attrs: { color: 'red' }, title: 'first' }, schema))
+    writer.addDocument(Document.fromDict({ attrs: { color: 'blue' }, title: 'second' }, schema))
+    writer.addDocument(Document.fromDict({ attrs: { color: 'green' }, title: 'third' }, schema))
+    writer.commit()
 
-    // Create a different schema (incompatible)
-    const invalidSchema = new SchemaBuilder().addTextField('different_field').build()
+    writer.deleteByQueryString('title:first')
+    // The JSON paths registered on the index apply to the writers already created.
+    index.setDefaultJsonPaths('attrs', ['color'])
+    writer.deleteByQueryString('blue', ['attrs'])
+    writer.commit()
+    index.reload()
 
-    // Attempting to open existing index with different schema should throw a specific error
-    expect(() => {
-      new Index(invalidSchema, tempDir)
-    }).toThrow()
+    const titles = index
+      .searcher()
+      .searchDocs(Query.allQuery(), { fields: ['title'] })
+      .hits.map((hit) => hit.doc.title[0])
+    expect(titles).toEqual(['third'])
+    expect(() => writer.deleteByQueryString('title:(first')).toThrow()
+    expect(() => writer.deleteByQueryString('first', ['unknown'])).toThrow('is not defined in the schema')
   })
 
-  it('test_opens_from_dir', () => {
-    // Test that we can open an existing index from directory
-    const dirIndex = createIndex(tempDir)
-    const searcher = dirIndex.searcher()
+  it('test_writer_unique_field', () => {
+    const schema = schemaNumericFields()
+    const index = new Index(schema)
+    const writer = index.writer({ heapSize: 15_000_000, uniqueField: 'id' })
+    writer.addDocument(Document.fromDict({ id: 1, body: 'first version' }, schema))
+    writer.addDocument(Document.fromDict({ id: 2, body: 'other' }, schema))
+    writer.commit()
+    writer.addDocument(Document.fromDict({ id: 1, body: 'second version' }, schema))
+    writer.addJson(JSON.stringify({ id: 2, body: 'other again' }))
+    writer.commit()
+    index.reload()
 
-    // Now create a new index instance from the same directory
-    const reopenedIndex = new Index(schema(), tempDir)
-    const reopenedSearcher = reopenedIndex.searcher()
+    const searcher = index.searcher()
+    expect(searcher.numDocs).toBe(2)
+    const hits = searcher.search(index.parseQuery('id:1')).hits
+    expect(hits.length).toBe(1)
+    expect((searcher.doc(hits[0].docAddress).toDict() as TestDoc).body).toEqual(['second version'])
 
-    // Should have the same number of documents
-    const query = Query.allQuery()
-    const originalResult = searcher.search(query)
-    const reopenedResult = reopenedSearcher.search(query)
+    expect(() => writer.addDocument(Document.fromDict({ body: 'no id' }, schema))).toThrow(
+      /no value for the unique field/,
+    )
+    const textIndex = new Index(new SchemaBuilder().addTextField('key').build())
+    expect(() => textIndex.writer({ uniqueField: 'key' })).toThrow(/raw/)
+
+    const dateIndex = new Index(new SchemaBuilder().addDateField('at', { stored: true, indexed: true }).build())
+    const dateWriter = dateIndex.writer({ heapSize: 15_000_000, uniqueField: 'at' })
+    for (let i = 0; i < 2; i++) {
+      dateWriter.addJson(JSON.stringify({ at: '2021-01-01T00:00:00.250Z' }))
+      dateWriter.commit()
+    }
+    dateIndex.reload()
+    expect(dateIndex.searcher().numDocs).toBe(1)
+  })
 
-    expect(reopenedResult.hits.length).toBe(originalResult.hits.length)
+  it('test_purge_expired', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addDateField('expires', { fast: true })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer({ heapSize: 15_000_000, expiryField: 'expires' })
+    for (const [title, expires] of [
+      ['old', '2020-01-01'],
+      ['recent', '2021-01-01'],
+      ['future', '2030-01-01'],
+    ]) {
+      const doc = new Document()
+      doc.addText('title', title)
+      doc.addDate('expires', new Date(expires).getTime())
+      writer.addDocument(doc)
+    }
+    writer.commit()
+
+    expect(writer.purgeExpired(new Date('2021-01-01').getTime())).toBe(2)
+    writer.commit()
+    index.reload()
+    const titles = index
+      .searcher()
+      .searchDocs(Query.allQuery(), { fields: ['title'] })
+      .hits.map((hit) => hit.doc.title[0])
+    expect(titles).toEqual(['future'])
+    expect(writer.purgeExpired()).toBe(0)
+    expect(writer.options().expiryField).toBe('expires')
+
+    expect(() => index.writer({ expiryField: 'title' })).toThrow(/not a date field/)
+    writer.waitMergingThreads()
+    expect(() => index.writer().purgeExpired()).toThrow(/no expiry field/)
   })
 
-  it('test_create_readers', () => {
-    const index = createIndex()
+  it('test_log_handler', async () => {
+    const records: LogRecord[] = []
+    setLogHandler((record) => records.push(record), 'info')
+    try {
+      const index = new Index(schema())
+      const writer = index.writer()
+      writer.addDocument(Document.fromDict({ title: 'logged' }))
+      writer.commit()
+      await new Promise((resolve) => setTimeout(resolve, 50))
+    } finally {
+      setLogHandler()
+    }
+    expect(records.some((record) => record.message === 'Preparing commit')).toBe(true)
+    expect(records.every((record) => ['error', 'warn', 'info'].includes(record.level))).toBe(true)
+    expect(records.some((record) => record.target.startsWith('tantivy'))).toBe(true)
+    expect(() => setLogHandler(() => {}, 'verbose')).toThrow('Invalid log level')
+  })
 
-    // Test that we can create multiple searchers
-    const searcher1 = index.searcher()
-    const searcher2 = index.searcher()
+  it('test_on_commit', async () => {
+    const events: CommitEvent[] = []
+    const index = new Index(schema())
+    const writer = index.writer()
+    writer.onCommit((event) => events.push(event))
 
-    const query = Query.allQuery()
-    const result1 = searcher1.search(query)
-    const result2 = searcher2.search(query)
+    writer.addDocument(Document.fromDict({ title: 'first' }))
+    const first = writer.commit('offset-1')
+    writer.addDocument(Document.fromDict({ title: 'second' }))
+    const second = writer.commit()
+    await new Promise((resolve) => setTimeout(resolve, 50))
+    expect(events).toEqual([{ opstamp: first, payload: 'offset-1' }, { opstamp: second }])
 
-    // Both searchers should return the same results
-    expect(result1.hits.length).toBe(result2.hits.length)
-    expect(result1.count).toBe(result2.count)
+    writer.onCommit()
+    writer.commit()
+    await new Promise((resolve) => setTimeout(resolve, 50))
+    expect(events.length).toBe(2)
   })
-})
-
-describe('TestSearcher', () => {
-  it('test_searcher_repr', () => {
-    const searcher = ramIndex.searcher()
 
-    // Test that searcher has expected properties
-    expect(searcher.numSegments).toBeGreaterThan(0)
-    expect(typeof searcher.numSegments).toBe('number')
+  it('test_metrics', () => {
+    const before = metrics()
+    const index = new Index(schema())
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ title: 'counted' }))
+    writer.addDocument(Document.fromDict({ title: 'counted twice' }))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const result = searcher.search(index.parseQuery('counted', ['title']))
+    searcher.doc(result.hits[0].docAddress)
+    searcher.doc(result.hits[0].docAddress)
+
+    const after = metrics()
+    expect(after.documentsAdded - before.documentsAdded).toBe(2)
+    expect(after.commits - before.commits).toBe(1)
+    expect(after.searches - before.searches).toBe(1)
+    const buckets = after.searchLatencyBuckets
+    expect(buckets[buckets.length - 1].le).toBe(Infinity)
+    expect(buckets[buckets.length - 1].count).toBe(after.searches)
+    expect(buckets.every((bucket, i) => i === 0 || bucket.count >= buckets[i - 1].count)).toBe(true)
+    expect(after.docStoreCacheMisses - before.docStoreCacheMisses).toBe(1)
+    expect(after.docStoreCacheHits - before.docStoreCacheHits).toBe(1)
+    expect(after.docStoreCacheHitRate).toBeGreaterThan(0)
   })
-})
 
-describe('TestDocument', () => {
-  it('test_document', () => {
-    const doc = new Document()
-    doc.addText('title', 'Test Document')
-    doc.addInteger('id', 123)
-    doc.addFloat('rating', 4.5)
-    doc.addBoolean('is_good', true)
+  it('test_serialized_writer', async () => {
+    const dir = mkdtempSync(join(tempDir, 'serialized-'))
+    const schema = schemaNumericFields()
+    const index = new Index(schema, dir)
+    const writer = index.serializedWriter({ uniqueField: 'id' })
+    // A second index on the same directory shares the queue instead of failing on the lock.
+    const other = new Index(schemaNumericFields(), dir).serializedWriter()
+
+    const pending = [1, 2, 3].map((id) => writer.addDocument(Document.fromDict({ id, body: 'first' }, schema)))
+    pending.push(other.addDocument(Document.fromDict({ id: 2, body: 'second' }, schema)))
+    pending.push(other.deleteDocumentsByTerm('id', 3))
+    const commitOpstamp = await writer.commit()
+    const opstamps = await Promise.all(pending)
+    expect(opstamps.every((opstamp) => opstamp <= commitOpstamp)).toBe(true)
 
-    // Test that document was created successfully
-    expect(doc).toBeDefined()
+    index.reload()
+    const searcher = index.searcher()
+    expect(searcher.numDocs).toBe(2)
+    const hits = searcher.search(index.parseQuery('id:2')).hits
+    expect((searcher.doc(hits[0].docAddress).toDict() as TestDoc).body).toEqual(['second'])
 
-    // Test document conversion to dict
-    const dict = doc.toDict() as TestDoc
-    expect(dict.title).toEqual(['Test Document'])
-    expect(dict.id).toEqual([123])
-    expect(dict.rating).toEqual([4.5])
-    expect(dict.is_good).toEqual([true])
+    writer.close()
+    expect(() => writer.commit()).toThrow(/closed/)
+    other.close()
   })
 
-  it('test_document_with_date', () => {
-    const doc = new Document()
-    const testDate = new Date('2021-01-01T00:00:00.000Z')
-    doc.addDate('date', testDate.getTime())
-
-    const dict = doc.toDict() as TestDoc
-    expect(dict.date?.[0]).toBe(testDate.getTime())
+  it('test_handles', async () => {
+    const index = createIndex()
+    const indexHandle = index.toHandle()
+    const query = index.parseQuery('sea', ['title', 'body'])
+    const expected = index.searcher().search(query).count
+    const queryHandle = query.toHandle()
+
+    const shared = Index.fromHandle(indexHandle)
+    expect(shared.searcher().search(Query.fromHandle(queryHandle)).count).toBe(expected)
+    expect(Schema.fromHandle(index.schema.toHandle()).fieldNames()).toEqual(index.schema.fieldNames())
+    expect(() => Query.fromHandle(indexHandle)).toThrow(/not of a Query/)
+
+    const worker = new Worker(
+      `
+      const { parentPort, workerData } = require('worker_threads')
+      const { Index, Query } = require(workerData.binding)
+      const index = Index.fromHandle(workerData.index)
+      parentPort.postMessage(index.searcher().search(Query.fromHandle(workerData.query)).count)
+      `,
+      {
+        eval: true,
+        workerData: { binding: join(__dirname, '..', 'index.js'), index: indexHandle, query: queryHandle },
+      },
+    )
+    const count = await new Promise((resolve, reject) => {
+      worker.once('message', resolve)
+      worker.once('error', reject)
+    })
+    await worker.terminate()
+    expect(count).toBe(expected)
+
+    expect(releaseHandle(indexHandle)).toBe(true)
+    expect(releaseHandle(indexHandle)).toBe(false)
+    expect(() => Index.fromHandle(indexHandle)).toThrow(/Unknown handle/)
+    releaseHandle(queryHandle)
   })
 
-  it('test_document_repr', () => {
-    // Test string representation of documents (Node.js equivalent via toDict)
-    const doc = new Document()
-    doc.addText('name', 'Bill')
-    doc.addInteger('reference', 1)
-    doc.addInteger('reference', 2)
+  it('test_add_json_batch', () => {
+    const index = new Index(schemaNumericFields())
+    const writer = index.writer({ heapSize: 15_000_000, uniqueField: 'id' })
+    const opstamps = writer.addJsonBatch([
+      JSON.stringify({ id: 1, body: 'first' }),
+      JSON.stringify({ id: 2, body: 'second' }),
+      JSON.stringify({ id: 1, body: 'first again' }),
+    ])
+    expect(opstamps.length).toBe(3)
+    expect(opstamps[0] < opstamps[1] && opstamps[1] < opstamps[2]).toBe(true)
+    expect(writer.commit() > opstamps[2]).toBe(true)
+    index.reload()
+    const searcher = index.searcher()
+    expect(searcher.numDocs).toBe(2)
+    const hits = searcher.search(index.parseQuery('id:1')).hits
+    expect(searcher.docObject(hits[0].docAddress, { fields: ['body'] })).toEqual({ body: 'first again' })
 
-    const dict = doc.toDict() as any
-    expect(dict.name).toEqual(['Bill'])
-    expect(dict.reference).toEqual([1, 2])
+    expect(() => writer.addJsonBatch([JSON.stringify({ id: 3 }), '{"id": '])).toThrow(/at index 1/)
+    writer.commit()
+    index.reload()
+    expect(index.searcher().numDocs).toBe(2)
+    expect(writer.addJsonBatch([])).toEqual([])
+  })
+
+  it('test_create_write_stream', async () => {
+    const schema = schemaNumericFields()
+    const index = new Index(schema)
+    const writer = index.writer({ heapSize: 15_000_000 })
+    // Lines split across chunks, and a last line without newline.
+    const chunks = ['{"id": 1, "body": "first"}\n{"id": 2, "bo', 'dy": "second"}\n\n', '{"id": 3, "body": "third"}']
+    await pipeline(Readable.from(chunks), writer.createWriteStream({ batchSize: 2 }))
+    index.reload()
+    expect(index.searcher().numDocs).toBe(3)
+
+    const docs = [
+      Document.fromDict({ id: 4, body: 'fourth' }, schema),
+      Document.fromDict({ id: 5, body: 'fifth' }, schema),
+    ]
+    await pipeline(Readable.from(docs), writer.createWriteStream({ format: 'document', commitEvery: 1 }))
+    index.reload()
+    expect(index.searcher().numDocs).toBe(5)
+
+    await expect(pipeline(Readable.from(['{"id": 6}\n{"id": \n']), writer.createWriteStream())).rejects.toThrow(
+      /on line 2/,
+    )
+    expect(() => writer.createWriteStream({ format: 'csv' })).toThrow(/Invalid stream format/)
+  })
+
+  it('test_batch_results', () => {
+    const schema = schemaNumericFields()
+    const index = new Index(schema)
+    const writer = index.writer({ heapSize: 15_000_000, uniqueField: 'id' })
+    const docs = [
+      Document.fromDict({ id: 1, body: 'first' }, schema),
+      Document.fromDict({ body: 'no id' }, schema),
+      Document.fromDict({ id: 2, body: 'second' }, schema),
+    ]
+
+    const results = writer.addDocuments(docs)
+    expect(results.map((result) => result.ok)).toEqual([true, false, true])
+    expect(results[0].opstamp! < results[2].opstamp!).toBe(true)
+    expect(results[1].opstamp ?? null).toBeNull()
+    expect(results[1].error).toContain('id')
+
+    const failFast = writer.addDocuments(docs, { failFast: true })
+    expect(failFast.map((result) => result.ok)).toEqual([true, false])
+    writer.commit()
+    index.reload()
+    expect(index.searcher().numDocs).toBe(2)
+
+    const deletions = writer.deleteDocumentsByTerms('id', [1, 'one', 2])
+    expect(deletions.map((result) => result.ok)).toEqual([true, false, true])
+    writer.commit()
+    index.reload()
+    expect(index.searcher().numDocs).toBe(0)
+  })
+
+  it('test_index_settings_and_writer_options', () => {
+    const index = new Index(schemaNumericFields())
+    expect(index.settings()).toEqual({
+      docstoreCompression: 'lz4',
+      docstoreBlocksize: 16384,
+      docstoreCompressDedicatedThread: true,
+    })
+
+    const writer = index.writer(15_000_000, 1)
+    expect(writer.options()).toEqual({ heapSize: 15_000_000, numThreads: 1 })
+    // Release the lock of the index.
+    writer.waitMergingThreads()
+    const options = index.writer({ heapSize: 30_000_000, uniqueField: 'id' }).options()
+    expect(options.heapSize).toBe(30_000_000)
+    expect([1, 2]).toContain(options.numThreads)
+    expect(options.uniqueField).toBe('id')
+  })
+
+  it('test_validate_json', () => {
+    const index = new Index(schemaNumericFields())
+
+    const valid = index.validateJson(JSON.stringify({ id: 7, rating: [1.5, 2], body: 'valid', unknown: 'x' }))
+    expect(valid.valid).toBe(true)
+    expect(valid.errors).toEqual([])
+
+    const invalid = index.validateJson(JSON.stringify({ rating: [1.5, 'bad'], id: 'seven', is_good: true }))
+    expect(invalid.valid).toBe(false)
+    expect(invalid.jsonError ?? null).toBeNull()
+    expect(invalid.errors.map((issue) => issue.fieldName)).toEqual(['id', 'rating'])
+    expect(invalid.errors[1].reason).toContain('bad')
+
+    const malformed = index.validateJson('{"id": ')
+    expect(malformed.valid).toBe(false)
+    expect(malformed.jsonError).toBeDefined()
+    expect(index.validateJson('[1, 2]').jsonError).toBeDefined()
+
+    // Nothing was added.
+    index.reload()
+    expect(index.searcher().numDocs).toBe(0)
+  })
+
+  it('test_add_document_lenient', () => {
+    const index = new Index(schemaNumericFields())
+    const writer = index.writer(15_000_000, 1)
+
+    const report = writer.addDocumentLenient(Document.fromDict({ id: '7', rating: 4, unknown: 'x', body: 'lenient' }))
+    expect(report.dropped.map((issue) => issue.fieldName)).toEqual(['unknown'])
+    expect(report.coerced.map((issue) => issue.fieldName).sort()).toEqual(['id', 'rating'])
+
+    const reports = writer.addDocumentsLenient([
+      Document.fromDict({ id: 'not a number', body: 'malformed' }),
+      Document.fromDict({ id: 8, body: 'valid' }),
+    ])
+    expect(reports[0].dropped.map((issue) => issue.fieldName)).toEqual(['id'])
+    expect(reports[1].dropped).toEqual([])
+    writer.commit()
+    index.reload()
+
+    const searcher = index.searcher()
+    expect(searcher.search(Query.allQuery()).count).toBe(3)
+    expect(searcher.search(Query.termQuery(index.schema, 'id', 7)).count).toBe(1)
+  })
+
+  it('test_keyword_field', () => {
+    const schema = new SchemaBuilder()
+      .addKeywordField('sku', { stored: true, fast: true })
+      .addTextField('title', { stored: true })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer(15_000_000, 1)
+    writer.addDocument(Document.fromDict({ sku: 'AB-12 x', title: 'first' }))
+    writer.addDocument(Document.fromDict({ sku: 'AB-13', title: 'second' }))
+    writer.commit()
+
+    writer.deleteDocumentsByTerm('sku', 'AB-12 x')
+    writer.commit()
+    index.reload()
+
+    const searcher = index.searcher()
+    expect(searcher.search(Query.allQuery()).count).toBe(1)
+    expect(searcher.search(Query.termQuery(schema, 'sku', 'AB-13')).count).toBe(1)
+    expect(searcher.search(Query.termQuery(schema, 'sku', 'ab')).count).toBe(0)
+  })
+
+  it('test_search_tie_break', () => {
+    const index = new Index(schema())
+    const writer = index.writer(15_000_000, 1)
+    for (let i = 0; i < 3; i++) {
+      writer.addDocument(Document.fromDict({ title: 'same', body: 'same text' }))
+      writer.commit()
+    }
+    index.reload()
+
+    const searcher = index.searcher()
+    const query = index.parseQuery('same', ['title'])
+    const pages = [0, 1, 2].map((offset) => searcher.search(query, 1, true, undefined, offset, undefined, { tieBreak: 'docAddress' }).hits[0])
+    const addresses = pages.map((hit) => [hit.docAddress.segmentOrd, hit.docAddress.doc])
+    expect([...addresses].sort()).toEqual(addresses)
+    expect(new Set(pages.map((hit) => hit.globalDoc)).size).toBe(3)
+
+    expect(() => searcher.search(query, 10, true, undefined, 0, undefined, { tieBreak: 'random' })).toThrow()
+  })
+
+  it('test_searcher_generation', () => {
+    const index = createIndex()
+
+    const counts = index.withSearcher((searcher) => {
+      const before = searcher.search(Query.allQuery()).count
+      const writer = index.writer()
+      writer.deleteDocumentsByQuery(index.parseQuery('Mice', ['title']))
+      writer.commit()
+      index.reload()
+
+      expect(index.generation).not.toBe(searcher.generation)
+      return [before, searcher.search(Query.allQuery()).count]
+    }) as number[]
+
+    expect(counts[0]).toBe(counts[1])
+    expect(index.searcher().generation).toBe(index.generation)
+  })
+
+  it('test_searcher_generation_id', () => {
+    const index = createIndex()
+    const generationId = index.searcher().generationId()
+    expect(generationId).toMatch(/^[0-9a-f]{16}$/)
+
+    index.reload()
+    expect(index.searcher().generationId()).toBe(generationId)
+
+    const writer = index.writer()
+    writer.deleteDocumentsByQuery(index.parseQuery('Mice', ['title']))
+    writer.commit()
+    index.reload()
+    const afterDelete = index.searcher().generationId()
+    expect(afterDelete).not.toBe(generationId)
+
+    writer.addDocument(Document.fromDict({ title: 'Sea Wolf' }))
+    writer.commit()
+    index.reload()
+    expect(index.searcher().generationId()).not.toBe(afterDelete)
+  })
+})
+
+describe('TestFromDiskClass', () => {
+  it('test_opens_from_dir_invalid_schema', () => {
+    // Test opening index from directory with invalid/incompatible schema
+    createIndex(tempDir) // Create an index first
+
+    // Create a different schema (incompatible)
+    const invalidSchema = new SchemaBuilder().addTextField('different_field').build()
+
+    // Attempting to open existing index with different schema should throw a specific error
+    expect(() => {
+      new Index(invalidSchema, tempDir)
+    }).toThrow()
+  })
+
+  it('test_opens_from_dir', () => {
+    // Test that we can open an existing index from directory
+    const dirIndex = createIndex(tempDir)
+    const searcher = dirIndex.searcher()
+
+    // Now create a new index instance from the same directory
+    const reopenedIndex = new Index(schema(), tempDir)
+    const reopenedSearcher = reopenedIndex.searcher()
+
+    // Should have the same number of documents
+    const query = Query.allQuery()
+    const originalResult = searcher.search(query)
+    const reopenedResult = reopenedSearcher.search(query)
+
+    expect(reopenedResult.hits.length).toBe(originalResult.hits.length)
+  })
+
+  it('test_open_checked', () => {
+    const dir = mkdtempSync(join(tempDir, 'checked-'))
+    createIndex(dir)
+    expect(Index.openChecked(dir, schema()).searcher().numDocs).toBe(3)
+
+    const expected = new SchemaBuilder()
+      .addTextField('title')
+      .addIntegerField('year', { indexed: true })
+      .build()
+    let message = ''
+    try {
+      Index.openChecked(dir, expected)
+    } catch (error) {
+      message = (error as Error).message
+    }
+    expect(message).toMatch(/does not match the expected schema/)
+    expect(message).toMatch(/`title` differs, `year` is missing, `body` is not expected$/)
+  })
+
+  it('test_create_readers', () => {
+    const index = createIndex()
+
+    // Test that we can create multiple searchers
+    const searcher1 = index.searcher()
+    const searcher2 = index.searcher()
+
+    const query = Query.allQuery()
+    const result1 = searcher1.search(query)
+    const result2 = searcher2.search(query)
+
+    // Both searchers should return the same results
+    expect(result1.hits.length).toBe(result2.hits.length)
+    expect(result1.count).toBe(result2.count)
+  })
+
+  it('test_reindex_with_schema', () => {
+    const index = createIndex()
+    const newSchema = new SchemaBuilder()
+      .addTextField('name', { stored: true })
+      .addIntegerField('year', { stored: true, indexed: true })
+      .build()
+
+    const reindexed = index.reindexWithSchema(newSchema, undefined, { fieldMap: { title: 'name' } }, (doc) => {
+      if (doc.getFirst('name') === 'Frankenstein') {
+        return null
+      }
+      doc.addInteger('year', 1900)
+      return doc
+    })
+
+    const searcher = reindexed.searcher()
+    expect(searcher.numDocs).toBe(2)
+    const result = searcher.search(reindexed.parseQuery('name:mice'))
+    expect(result.hits.length).toBe(1)
+    expect(searcher.doc(result.hits[0].docAddress).toDict()).toEqual({ name: ['Of Mice and Men'], year: [1900] })
+  })
+
+  it('test_compact', () => {
+    const schema = new SchemaBuilder().addKeywordField('id', { stored: true }).build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    for (const batch of [0, 1, 2]) {
+      for (let i = 0; i < 20; i++) {
+        writer.addDocument(Document.fromDict({ id: `${batch}-${i}` }))
+      }
+      writer.commit()
+    }
+    writer.deleteDocumentsByTerm('id', '0-0')
+    writer.commit()
+    writer.waitMergingThreads()
+
+    const progress: Array<CompactProgress> = []
+    const result = index.compact({ onProgress: (event) => progress.push(event) })
+    expect(result.segmentsBefore).toBe(3)
+    expect(result.segmentsAfter).toBe(1)
+    expect(result.filesDeleted).toBeGreaterThan(0)
+    expect(result.bytesReclaimed).toBeGreaterThan(0)
+    expect(progress).toEqual([
+      { step: 'merge', completed: 1, total: 2 },
+      { step: 'garbageCollect', completed: 2, total: 2 },
+    ])
+    expect(index.searcher().numDocs).toBe(59)
+    expect(index.searcher().search(Query.termQuery(schema, 'id', '0-0')).count).toBe(0)
+
+    // A single segment without deleted documents is left as is.
+    const again = index.compact()
+    expect(again.segmentsBefore).toBe(1)
+    expect(again.filesDeleted).toBe(0)
+    expect(() => index.compact({ targetSegments: 0 })).toThrow('at least 1')
+  })
+
+  it('test_set_merge_operations_enabled', () => {
+    const schema = new SchemaBuilder().addKeywordField('id').build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.setMergeOperationsEnabled(false)
+    // Enough segments for the default policy to merge them.
+    for (let i = 0; i < 10; i++) {
+      writer.addDocument(Document.fromDict({ id: `${i}` }))
+      writer.commit()
+    }
+    writer.waitMergingThreads()
+    index.reload()
+    expect(index.searcher().numSegments).toBe(10)
+
+    expect(index.compact().segmentsAfter).toBe(1)
+    expect(index.searcher().numDocs).toBe(10)
+    expect(() => writer.setMergeOperationsEnabled(true)).toThrow(/consumed/)
+  })
+
+  it('test_commit_with_guard', () => {
+    const schema = schemaNumericFields()
+    const index = new Index(schema, mkdtempSync(join(tempDir, 'guard-')))
+    const writer = index.writer({ heapSize: 15_000_000 })
+    writer.addDocument(Document.fromDict({ id: 1, body: 'first' }, schema))
+    let error: any
+    try {
+      writer.commitWithGuard({ minFreeBytes: Number.MAX_SAFE_INTEGER })
+    } catch (e) {
+      error = e
+    }
+    expect(error.code).toBe('ENOSPC')
+    expect(error.message).toMatch(/Not enough disk space/)
+    index.reload()
+    expect(index.searcher().numDocs).toBe(0)
+
+    writer.commitWithGuard({ payload: 'guarded' })
+    index.reload()
+    expect(index.searcher().numDocs).toBe(1)
+
+    // Indexes in RAM are not checked.
+    const ramWriter = new Index(schemaNumericFields()).writer({ heapSize: 15_000_000 })
+    expect(() => ramWriter.commitWithGuard({ minFreeBytes: Number.MAX_SAFE_INTEGER })).not.toThrow()
+  })
+
+  it('test_verify_checksums', () => {
+    const dir = mkdtempSync(join(tempDir, 'checksums-'))
+    const schema = schemaNumericFields()
+    const index = new Index(schema, dir)
+    const writer = index.writer({ heapSize: 15_000_000 })
+    writer.addDocument(Document.fromDict({ id: 1, body: 'first' }, schema))
+    writer.commit()
+    writer.waitMergingThreads()
+    index.reload()
+
+    const results = index.verifyChecksums()
+    expect(results.length).toBeGreaterThan(0)
+    expect(results.every((result) => result.status === 'ok')).toBe(true)
+    expect(results.map((result) => result.path)).toEqual(results.map((result) => result.path).sort())
+
+    const store = results.find((result) => result.path.endsWith('.store'))!
+    const data = readFileSync(join(dir, store.path))
+    data[0] ^= 0xff
+    writeFileSync(join(dir, store.path), data)
+    const corrupted = Index.open(dir).verifyChecksums()
+    expect(corrupted.filter((result) => result.status !== 'ok')).toEqual([
+      { path: store.path, segmentId: store.segmentId, status: 'corrupted' },
+    ])
+  })
+
+  it('test_index_inspect', () => {
+    const dir = mkdtempSync(join(tempDir, 'inspect-'))
+    const schema = schemaNumericFields()
+    const index = new Index(schema, dir)
+    const writer = index.writer({ heapSize: 15_000_000 })
+    writer.addDocument(Document.fromDict({ id: 1, body: 'first' }, schema))
+    writer.addDocument(Document.fromDict({ id: 2, body: 'second' }, schema))
+    writer.commit()
+    writer.waitMergingThreads()
+
+    const inspection = Index.inspect(dir)
+    expect(inspection.compatible).toBe(true)
+    expect(inspection.advice).toBeUndefined()
+    expect(inspection.indexFormatVersion).toBeGreaterThan(0)
+    expect(inspection.tantivyVersion).toMatch(/^\d+\.\d+\.\d+$/)
+    expect(inspection.numSegments).toBe(1)
+    expect(inspection.numDocs).toBe(2)
+    expect(inspection.schema.map((field: { name: string }) => field.name)).toContain('body')
+
+    // A field type unknown to this version of tantivy.
+    const meta = JSON.parse(readFileSync(join(dir, 'meta.json'), 'utf8'))
+    meta.schema[0].type = 'vector'
+    writeFileSync(join(dir, 'meta.json'), JSON.stringify(meta))
+    const incompatible = Index.inspect(dir)
+    expect(incompatible.compatible).toBe(false)
+    expect(incompatible.advice).toMatch(/upgrade the binding/)
+    expect(() => Index.open(dir)).toThrow(/upgrade the binding/)
+
+    expect(() => Index.inspect(mkdtempSync(join(tempDir, 'empty-')))).toThrow(/No index found/)
+  })
+
+  it('test_deleted_docs_tracking', () => {
+    const schema = new SchemaBuilder().addKeywordField('id').build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    for (const id of ['1', '2', '3']) {
+      writer.addDocument(Document.fromDict({ id }))
+    }
+    writer.commit()
+    index.reload()
+    expect(index.hasPendingDeletes()).toBe(false)
+    expect(index.searcher().numDeletedDocs).toBe(0)
+
+    writer.deleteDocumentsByTerm('id', '2')
+    writer.commit()
+    writer.waitMergingThreads()
+    index.reload()
+    const searcher = index.searcher()
+    expect(index.hasPendingDeletes()).toBe(true)
+    expect(searcher.numDeletedDocs).toBe(1)
+    const [segment] = searcher.segments()
+    expect(segment.segmentId).toMatch(/^[0-9a-f]{32}$/)
+    expect(segment).toMatchObject({ segmentOrd: 0, maxDoc: 3, numDocs: 2, numDeletedDocs: 1 })
+
+    index.compact()
+    expect(index.hasPendingDeletes()).toBe(false)
+    expect(index.searcher().numDeletedDocs).toBe(0)
+  })
+
+  it('test_index_session', () => {
+    const schema = new SchemaBuilder().addKeywordField('id', { stored: true }).addTextField('title').build()
+    const index = new Index(schema)
+    const session = index.session()
+    session.add(Document.fromDict({ id: '1', title: 'The Old Man and the Sea' }))
+    expect(session.search(index.parseQuery('sea')).count).toBe(1)
+    expect(index.searcher().numDocs).toBe(1)
+
+    session.add(Document.fromDict({ id: '2', title: 'Sea of Tranquility' }))
+    expect(session.searcher().numDocs).toBe(2)
+    session.deleteDocumentsByTerm('id', '1')
+    expect(session.search(index.parseQuery('sea')).count).toBe(1)
+
+    session.add(Document.fromDict({ id: '3', title: 'Sea Wolf' }))
+    session.rollback()
+    expect(session.searcher().numDocs).toBe(1)
+
+    session.close()
+    expect(() => session.add(Document.fromDict({ id: '4' }))).toThrow('no longer in a valid state')
+  })
+
+  it('test_tenant_index', () => {
+    const schema = new SchemaBuilder()
+      .addKeywordField('tenant', { stored: true })
+      .addKeywordField('id', { stored: true })
+      .addTextField('title')
+      .build()
+    const index = new Index(schema)
+    const tenants = index.tenantIndex('tenant', { uniqueField: 'id' })
+    tenants.add('acme', Document.fromDict({ id: '1', title: 'The Old Man and the Sea' }))
+    tenants.add('acme', Document.fromDict({ id: '2', title: 'Sea of Tranquility' }))
+    tenants.add('globex', Document.fromDict({ id: '1', title: 'Sea Wolf' }))
+    tenants.commit()
+
+    expect(tenants.search('acme', index.parseQuery('sea')).count).toBe(2)
+    expect(tenants.search('globex', index.parseQuery('sea')).count).toBe(1)
+    expect(tenants.search('initech', Query.allQuery()).count).toBe(0)
+    const unscoped = index.searcher().search(index.parseQuery('sea'), 10).hits
+    const scoped = tenants.search('globex', index.parseQuery('sea')).hits
+    const globexHit = unscoped.find((hit) => index.searcher().doc(hit.docAddress).getFirst('tenant') === 'globex')
+    expect(scoped[0].score).toBeCloseTo(globexHit!.score)
+
+    tenants.add('acme', Document.fromDict({ id: '1', title: 'The Old Man and the Sea, revised' }))
+    tenants.deleteDocumentsByTerm('globex', 'id', '2')
+    tenants.deleteDocumentsByQuery('globex', index.parseQuery('tranquility'))
+    tenants.commit()
+    expect(tenants.search('acme', Query.allQuery()).count).toBe(2)
+    expect(tenants.search('globex', Query.allQuery()).count).toBe(1)
+
+    expect(() => tenants.add('acme', Document.fromDict({ tenant: 'globex', id: '3' }))).toThrow(/another tenant/)
+    tenants.deleteTenant('acme')
+    tenants.commit()
+    expect(index.searcher().numDocs).toBe(1)
+
+    tenants.close()
+    expect(() => index.tenantIndex('title')).toThrow(/raw/)
+  })
+
+  it('test_replication_files', () => {
+    const primary = createIndex()
+    const replica = new Index(schema())
+
+    const files = primary.listFilesForReplication()
+    expect(files[files.length - 1].path).toBe('meta.json')
+    replica.applyReplicatedFiles(files)
+
+    const result = replica.searcher().search(Query.allQuery())
+    expect(result.count).toBe(primary.searcher().numDocs)
+
+    // Files the replica already holds are not shipped again
+    const shipped = files.map((file) => file.path)
+    expect(primary.listFilesForReplication(shipped).map((file) => file.path)).toEqual(['meta.json'])
+    expect(() => replica.applyReplicatedFiles([{ path: '../meta.json', data: Buffer.from('{}') }])).toThrow()
+  })
+})
+
+describe('TestSearcher', () => {
+  it('test_searcher_repr', () => {
+    const searcher = ramIndex.searcher()
+
+    // Test that searcher has expected properties
+    expect(searcher.numSegments).toBeGreaterThan(0)
+    expect(typeof searcher.numSegments).toBe('number')
+  })
+
+  it('test_search_without_scoring', () => {
+    const searcher = ramIndex.searcher()
+    const query = ramIndex.parseQuery('sea OR winter', ['title', 'body'])
+
+    const result = searcher.search(query, 10, true, undefined, undefined, undefined, { scoring: false })
+    expect(result.count).toBe(searcher.search(query, 10).count)
+    expect(result.hits.every((hit) => hit.score === 1)).toBe(true)
+    const addresses = result.hits.map((hit) => hit.docAddress)
+    expect(addresses).toEqual(
+      [...addresses].sort((a, b) => a.segmentOrd - b.segmentOrd || a.doc - b.doc),
+    )
+
+    const paged = searcher.search(query, 1, false, undefined, 1, undefined, { scoring: false })
+    expect(paged.hits.map((hit) => hit.docAddress)).toEqual(addresses.slice(1, 2))
+    expect(paged.count ?? null).toBeNull()
+  })
+
+  it('test_global_doc', () => {
+    const searcher = ramIndex.searcher()
+    const result = searcher.search(Query.allQuery(), 10)
+
+    const globalDocs = result.hits.map((hit) => hit.globalDoc)
+    expect(new Set(globalDocs).size).toBe(result.hits.length)
+    for (const hit of result.hits) {
+      expect(searcher.globalDoc(hit.docAddress)).toBe(hit.globalDoc)
+      expect(searcher.docAddressForGlobalDoc(hit.globalDoc)).toEqual(hit.docAddress)
+    }
+    expect(() => searcher.docAddressForGlobalDoc(1_000_000)).toThrow()
+  })
+
+  it('test_knn', () => {
+    const vectorSchema = new SchemaBuilder()
+      .addTextField('name', { stored: true })
+      .addVectorField('embedding')
+      .build()
+    const index = new Index(vectorSchema)
+    const writer = index.writer(15_000_000, 1)
+    const vectors: [string, number[]][] = [
+      ['x', [1, 0, 0]],
+      ['xy', [1, 1, 0]],
+      ['y', [0, 1, 0]],
+      ['z', [0, 0, 1]],
+    ]
+    for (const [name, vector] of vectors) {
+      const doc = new Document()
+      doc.addText('name', name)
+      doc.addVector('embedding', vector)
+      writer.addDocument(doc)
+    }
+    writer.commit()
+    index.reload()
+
+    const searcher = index.searcher()
+    const names = (result: SearchResult) =>
+      result.hits.map((hit) => (searcher.doc(hit.docAddress).toDict() as { name: string[] }).name[0])
+
+    const result = searcher.knn('embedding', [1, 0.1, 0], 2)
+    expect(names(result)).toEqual(['x', 'xy'])
+    expect(result.hits[0].score).toBeGreaterThan(result.hits[1].score!)
+
+    const filter = Query.termQuery(vectorSchema, 'name', 'y')
+    expect(names(searcher.knn('embedding', [1, 0, 0], 2, filter))).toEqual(['y'])
+    expect(names(searcher.knn('embedding', [0, 0, 1], 1, undefined, 'l2'))).toEqual(['z'])
+    expect(searcher.knn('embedding', [1, 0], 2).hits).toEqual([])
+  })
+
+  it('test_fuse_results', () => {
+    const searcher = ramIndex.searcher()
+    const keyword = searcher.search(ramIndex.parseQuery('sea OR mice', ['title']), 10).hits
+    const other = searcher.search(ramIndex.parseQuery('mice OR frankenstein', ['title']), 10).hits
+
+    const fused = fuseResults(keyword, other)
+    expect(fused.length).toBe(3)
+    // The only document in both lists comes first
+    expect(searcher.doc(fused[0].docAddress).getFirst('title')).toBe('Of Mice and Men')
+
+    const linear = fuseResults(keyword, other, { method: 'linear', weights: [1, 0], limit: 2 })
+    expect(linear.length).toBe(2)
+    expect(linear[0].score).toBe(1)
+
+    expect(() => fuseResults(keyword, other, { method: 'nope' })).toThrow()
+  })
+
+  it('test_search_docs', () => {
+    const searcher = ramIndexNumericFields.searcher()
+
+    const result = searcher.searchDocs(Query.termQuery(ramIndexNumericFields.schema, 'id', 1), {
+      fields: ['id', 'rating'],
+    })
+    expect(result.count).toBe(1)
+    expect(result.hits[0].doc).toEqual({ id: [1], rating: [3.5] })
+    expect(result.hits[0].globalDoc).toBe(searcher.globalDoc(result.hits[0].docAddress))
+
+    const all = searcher.searchDocs(Query.allQuery(), { limit: 1, count: false })
+    expect(all.hits.length).toBe(1)
+    expect(all.count ?? null).toBeNull()
+    expect(Object.keys(all.hits[0].doc).sort()).toEqual(['body', 'id', 'is_good', 'rating'])
+  })
+
+  it('test_doc_object', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addKeywordField('tag', { stored: true })
+      .addDateField('published', { stored: true })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    const published = new Date('2021-01-01').getTime()
+    writer.addDocument(Document.fromDict({ title: 'The Old Man and the Sea', tag: ['novel', 'sea'], published }))
+    writer.commit()
+    index.reload()
+
+    const searcher = index.searcher()
+    const docAddress = searcher.search(Query.allQuery()).hits[0].docAddress
+    expect(searcher.docObject(docAddress)).toEqual({ title: 'The Old Man and the Sea', tag: ['novel', 'sea'], published })
+    expect(searcher.docObject(docAddress, { fields: ['title'] })).toEqual({ title: 'The Old Man and the Sea' })
+  })
+
+  it('test_execute_search_request', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addKeywordField('color', { fast: true, stored: true })
+      .addUnsignedField('price', { fast: true, stored: true })
+      .addFacetField('category')
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    const products = [
+      { title: 'red running shoes', color: 'red', price: 80, category: '/shoes/running' },
+      { title: 'blue running shoes', color: 'blue', price: 60, category: '/shoes/running' },
+      { title: 'red leather shoes', color: 'red', price: 120, category: '/shoes/city' },
+      { title: 'red hat', color: 'red', price: 20, category: '/hats' },
+    ]
+    for (const product of products) {
+      const doc = Document.fromDict({ title: product.title, color: product.color, price: product.price })
+      doc.addFacet('category', Facet.fromString(product.category))
+      writer.addDocument(doc)
+    }
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const query = index.parseQuery('shoes', ['title'])
+
+    const response = searcher.execute({
+      query,
+      limit: 2,
+      sort: { field: 'price', order: Order.Asc },
+      facets: [{ field: 'category', root: '/shoes' }],
+      aggregations: {
+        colors: { terms: { field: 'color', order: { _key: 'asc' } } },
+        allColors: { terms: { field: 'color', order: { _key: 'asc' } }, global: true },
+      },
+      snippetFields: ['title'],
+      retrieveFields: ['price'],
+    })
+    expect(response.count).toBe(3)
+    expect(response.timedOut).toBe(false)
+    expect(response.hits.map((hit) => hit.order)).toEqual([60, 80])
+    expect(response.hits.map((hit) => hit.doc)).toEqual([{ price: [60] }, { price: [80] }])
+    expect(response.hits[0].snippets).toEqual({ title: 'blue running <b>shoes</b>' })
+    expect(response.facets[0].counts).toEqual([
+      { facet: '/shoes/city', count: 1 },
+      { facet: '/shoes/running', count: 2 },
+    ])
+    expect(response.aggregations.colors.buckets.map((b: { doc_count: number }) => b.doc_count)).toEqual([1, 2])
+    expect(response.aggregations.allColors.buckets.map((b: { doc_count: number }) => b.doc_count)).toEqual([1, 3])
+
+    const scored = searcher.execute({ query, offset: 1, aggregations: '{}', timeoutMs: 1000 })
+    expect(scored.hits.length).toBe(2)
+    expect(scored.hits[0].score).toBeGreaterThan(0)
+    expect(scored.hits[0].doc ?? null).toBeNull()
+    expect(scored.aggregations).toEqual({})
+    expect(scored.facets).toEqual([])
+    // The hits of a search with global aggregations are scored the same.
+    const scoredWithGlobal = searcher.execute({
+      query,
+      offset: 1,
+      aggregations: { allColors: { terms: { field: 'color' }, global: true } },
+    })
+    expect(scoredWithGlobal.hits).toEqual(scored.hits)
+    expect(scoredWithGlobal.count).toBe(3)
+    expect(scoredWithGlobal.aggregations.allColors.buckets.length).toBe(2)
+
+    const all = searcher.execute({ query: Query.allQuery(), retrieveFields: true })
+    expect(all.hits.length).toBe(4)
+    expect(Object.keys(all.hits[0].doc).sort()).toEqual(['color', 'price', 'title'])
+    expect(all.aggregations ?? null).toBeNull()
+
+    expect(() => searcher.execute({ query, sort: { field: 'title' } })).toThrow()
+  })
+
+  it('test_execute_sort_missing', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addUnsignedField('price', { fast: true })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ title: 'boat', price: 30 }))
+    writer.addDocument(Document.fromDict({ title: 'boat' }))
+    writer.addDocument(Document.fromDict({ title: 'boat', price: 0 }))
+    writer.addDocument(Document.fromDict({ title: 'boat', price: 10 }))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const orders = (sort: SearchSort) =>
+      searcher.execute({ query: Query.allQuery(), sort }).hits.map((hit) => hit.order ?? null)
+
+    expect(orders({ field: 'price' })).toEqual([30, 10, 0, null])
+    expect(orders({ field: 'price', order: Order.Asc })).toEqual([0, 10, 30, null])
+    expect(orders({ field: 'price', missing: 'first' })).toEqual([null, 30, 10, 0])
+    expect(orders({ field: 'price', order: Order.Asc, missing: 'first' })).toEqual([null, 0, 10, 30])
+    expect(orders({ field: 'price', missing: 20 })).toEqual([30, 20, 10, 0])
+    expect(() => orders({ field: 'price', missing: 'middle' })).toThrow('valid choices are')
+    expect(() => orders({ field: 'price', missing: -1 })).toThrow("doesn't fit the type")
+
+    const signedSchema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addIntegerField('rank', { fast: true })
+      .build()
+    const signedIndex = new Index(signedSchema)
+    const signedWriter = signedIndex.writer()
+    signedWriter.addDocument(Document.fromDict({ title: 'boat', rank: 3 }, signedSchema))
+    signedWriter.addDocument(Document.fromDict({ title: 'boat', rank: -5 }, signedSchema))
+    signedWriter.addDocument(Document.fromDict({ title: 'boat' }, signedSchema))
+    signedWriter.commit()
+    signedIndex.reload()
+    const ranks = (sort: SearchSort) =>
+      signedIndex
+        .searcher()
+        .execute({ query: Query.allQuery(), sort })
+        .hits.map((hit) => hit.order ?? null)
+
+    expect(ranks({ field: 'rank', missing: -1 })).toEqual([3, -1, -5])
+    expect(ranks({ field: 'rank', missing: -10 })).toEqual([3, -5, -10])
+    expect(ranks({ field: 'rank', order: Order.Asc, missing: -10 })).toEqual([-10, -5, 3])
+    expect(() => ranks({ field: 'rank', missing: 1.5 })).toThrow("doesn't fit the type")
+  })
+
+  it('test_execute_sort_signed', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addIntegerField('rank', { fast: true })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    for (const rank of [3, -5, 0]) {
+      writer.addDocument(Document.fromDict({ title: 'boat', rank }, schema))
+    }
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+
+    const orders = (order: Order) =>
+      searcher.execute({ query: Query.allQuery(), sort: { field: 'rank', order } }).hits.map((hit) => hit.order)
+    expect(orders(Order.Desc)).toEqual([3, 0, -5])
+    expect(orders(Order.Asc)).toEqual([-5, 0, 3])
+    expect(searcher.search(Query.allQuery(), 10, true, 'rank', 0, Order.Asc).hits.map((hit) => hit.order)).toEqual([
+      -5, 0, 3,
+    ])
+  })
+
+  it('test_search_segment', () => {
+    const schema = new SchemaBuilder().addTextField('title', { stored: true }).build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    for (const batch of [['old sea', 'sea'], ['the sea and the sky', 'sky'], ['sea sea']]) {
+      for (const title of batch) {
+        writer.addDocument(Document.fromDict({ title }))
+      }
+      writer.commit()
+    }
+    index.reload()
+    const searcher = index.searcher()
+    const query = index.parseQuery('sea', ['title'])
+
+    const perSegment = searcher.segments().map((segment) => searcher.searchSegment(segment.segmentOrd, query))
+    expect(perSegment.reduce((sum, result) => sum + result.count!, 0)).toBe(4)
+    for (const [segmentOrd, result] of perSegment.entries()) {
+      expect(result.hits.every((hit) => hit.docAddress.segmentOrd === segmentOrd)).toBe(true)
+    }
+    const merged = perSegment
+      .flatMap((result) => result.hits)
+      .sort((a, b) => b.score! - a.score!)
+      .map((hit) => hit.score)
+    expect(merged).toEqual(searcher.search(query).hits.map((hit) => hit.score))
+
+    expect(searcher.searchSegment(0, query, 1).hits.length).toBe(1)
+    expect(() => searcher.searchSegment(searcher.numSegments, query)).toThrow('out of range')
+  })
+
+  it('test_matched_clauses', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addTextField('body', { stored: true })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ title: 'The Old Man and the Sea', body: 'A fisherman at sea' }))
+    writer.addDocument(Document.fromDict({ title: 'Of Mice and Men', body: 'Two men by the sea' }))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const query = Query.booleanQuery([
+      { occur: 1, query: index.parseQuery('sea', ['title']).named('title') }, // Should
+      { occur: 1, query: index.parseQuery('sea', ['body']).named('body') }, // Should
+    ])
+
+    const hits = searcher.search(query).hits
+    expect(hits.length).toBe(2)
+    const matched = hits.map((hit) => [searcher.doc(hit.docAddress).getFirst('title'), searcher.matchedClauses(query, hit.docAddress)])
+    expect(Object.fromEntries(matched)).toEqual({
+      'The Old Man and the Sea': ['title', 'body'],
+      'Of Mice and Men': ['body'],
+    })
+    expect(searcher.search(query).hits.map((hit) => hit.score)).toEqual(
+      searcher.search(Query.booleanQuery([
+        { occur: 1, query: index.parseQuery('sea', ['title']) },
+        { occur: 1, query: index.parseQuery('sea', ['body']) },
+      ])).hits.map((hit) => hit.score),
+    )
+    expect(searcher.matchedClauses(index.parseQuery('sea', ['title']), hits[0].docAddress)).toEqual([])
+    expect(() => searcher.matchedClauses(query, { segmentOrd: 0, doc: 100 })).toThrow('Invalid document address')
+  })
+
+  it('test_named_boolean_clauses', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addTextField('body', { stored: true })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ title: 'The Old Man and the Sea', body: 'A fisherman at sea' }))
+    writer.addDocument(Document.fromDict({ title: 'Of Mice and Men', body: 'Two men by the sea' }))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const query = Query.booleanQuery([
+      { occur: 1, query: index.parseQuery('sea', ['title']), name: 'title' }, // Should
+      { occur: 1, query: index.parseQuery('sea', ['body']), name: 'body' }, // Should
+    ])
+
+    const hits = searcher.search(query).hits
+    expect(hits.map((hit) => hit.matchedQueries)).toEqual([['title', 'body'], ['body']])
+    for (const hit of hits) {
+      expect(hit.matchedQueries).toEqual(searcher.matchedClauses(query, hit.docAddress))
+    }
+    expect(searcher.search(query, 10, true, undefined, undefined, undefined, { scoring: false }).hits.map((hit) => hit.matchedQueries)).toEqual([
+      ['title', 'body'],
+      ['body'],
+    ])
+    expect(searcher.execute({ query }).hits.map((hit) => hit.matchedQueries)).toEqual([['title', 'body'], ['body']])
+    expect(searcher.search(index.parseQuery('sea', ['title'])).hits[0].matchedQueries ?? null).toBeNull()
+  })
+
+  it('test_search_profile', () => {
+    const searcher = ramIndexNumericFields.searcher()
+    const query = Query.termQuery(ramIndexNumericFields.schema, 'id', 1)
+
+    expect(searcher.search(query).profile ?? null).toBeNull()
+    const { profile } = searcher.search(query, 10, true, undefined, undefined, undefined, { profile: true })
+    expect(profile!.collectMs).toBeGreaterThanOrEqual(0)
+    expect(profile!.fetchMs ?? null).toBeNull()
+    expect(profile!.segments.length).toBe(searcher.numSegments)
+    expect(profile!.segments.reduce((sum, segment) => sum + segment.docsCollected, 0)).toBe(1)
+    expect(profile!.segments.reduce((sum, segment) => sum + segment.maxDoc, 0)).toBeGreaterThanOrEqual(searcher.numDocs)
+
+    const docs = searcher.searchDocs(Query.allQuery(), { profile: true })
+    expect(docs.profile!.fetchMs).toBeGreaterThanOrEqual(0)
+    expect(docs.profile!.segments.reduce((sum, segment) => sum + segment.docsCollected, 0)).toBe(searcher.numDocs)
+  })
+
+  it('test_normalize_scores', () => {
+    const searcher = ramIndex.searcher()
+    const query = ramIndex.parseQuery('the old sea', ['title', 'body'])
+    const raw = searcher.search(query, 10).hits.map((hit) => hit.score!)
+    expect(raw.length).toBeGreaterThan(1)
+    const max = Math.max(...raw)
+    const min = Math.min(...raw)
+
+    const byMax = searcher.search(query, 10, true, undefined, undefined, undefined, { normalizeScores: 'max' })
+    byMax.hits.forEach((hit, idx) => expect(hit.score).toBeCloseTo(raw[idx] / max))
+
+    // The range is computed over every matching document, not only the page.
+    const page = searcher.search(query, 1, true, undefined, 1, undefined, { normalizeScores: 'minmax' })
+    expect(page.hits[0].score).toBeCloseTo((raw[1] - min) / (max - min))
+
+    const unscored = searcher.search(query, 10, true, undefined, undefined, undefined, {
+      scoring: false,
+      normalizeScores: 'minmax',
+    })
+    expect(unscored.hits.every((hit) => hit.score === 1)).toBe(true)
+
+    const docs = searcher.searchDocs(query, { normalizeScores: 'max' })
+    expect(Math.max(...docs.hits.map((hit) => hit.score!))).toBeCloseTo(1)
+
+    expect(() =>
+      searcher.search(query, 10, true, undefined, undefined, undefined, { normalizeScores: 'sum' }),
+    ).toThrow()
+  })
+
+  it('test_search_min_score', () => {
+    const searcher = ramIndex.searcher()
+    const query = ramIndex.parseQuery('the old sea', ['title', 'body'])
+    const scores = searcher.search(query, 10).hits.map((hit) => hit.score!)
+    expect(scores.length).toBeGreaterThan(1)
+    const threshold = (scores[0] + scores[scores.length - 1]) / 2
+    const expected = scores.filter((score) => score >= threshold)
+
+    const result = searcher.search(query, 10, true, undefined, undefined, undefined, { minScore: threshold })
+    expect(result.hits.map((hit) => hit.score)).toEqual(expected)
+    expect(result.count).toBe(expected.length)
+
+    const docs = searcher.searchDocs(query, { minScore: scores[0] + 1 })
+    expect(docs.hits).toEqual([])
+    expect(docs.count).toBe(0)
+
+    expect(() =>
+      searcher.search(query, 10, true, undefined, undefined, undefined, { scoring: false, minScore: 1 }),
+    ).toThrow()
+  })
+
+  it('test_distinct_counts', () => {
+    const searcher = ramIndexNumericFields.searcher()
+
+    const result = searcher.distinctCounts(Query.allQuery(), 'id')
+    expect(result.distinct).toBe(2)
+    expect(result.values).toEqual([])
+
+    const limited = searcher.distinctCounts(Query.allQuery(), 'id', 1)
+    expect(limited.distinct).toBe(2)
+    expect(limited.values).toEqual([{ value: 1, count: 1 }])
+
+    expect(() => searcher.distinctCounts(Query.allQuery(), 'is_good')).toThrow()
+  })
+
+  it('test_fast_field_values_for_docs', () => {
+    const searcher = ramIndexNumericFields.searcher()
+    const addresses = searcher
+      .search(Query.allQuery(), 10, true, 'id', 0, Order.Desc)
+      .hits.map((hit) => hit.docAddress)
+
+    const ratings = searcher.fastFieldValuesForDocs('rating', addresses)
+    expect(ratings).toBeInstanceOf(Float64Array)
+    expect(Array.from(ratings)).toEqual([4.5, 3.5])
+    expect(Array.from(searcher.fastFieldValuesForDocs('id', [...addresses].reverse()))).toEqual([1, 2])
+
+    expect(() => searcher.fastFieldValuesForDocs('body', addresses)).toThrow()
+    expect(() => searcher.fastFieldValuesForDocs('is_good', addresses)).toThrow()
+  })
+
+  it('test_term_ords', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addKeywordField('color', { fast: true })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    for (const batch of [['red', 'blue'], ['green', 'red'], [undefined]]) {
+      for (const color of batch) {
+        writer.addDocument(Document.fromDict(color ? { title: 'shoes', color } : { title: 'shoes' }))
+      }
+      writer.commit()
+    }
+    index.reload()
+    const searcher = index.searcher()
+    const addresses = searcher.search(Query.allQuery(), 10, true).hits.map((hit) => hit.docAddress)
+
+    const ords = searcher.termOrds('color', addresses)
+    expect(ords).toBeInstanceOf(Int32Array)
+    const colors = Array.from(ords).map((ord) => (ord < 0 ? null : searcher.ordToTerm('color', ord)))
+    expect(colors.filter((color) => color !== null).sort()).toEqual(['blue', 'green', 'red', 'red'])
+    expect(colors.filter((color) => color === null)).toHaveLength(1)
+    // Ordinals follow the order of the terms, whatever the segment.
+    expect(['blue', 'green', 'red'].map((color) => Array.from(ords)[colors.indexOf(color)])).toEqual([0, 1, 2])
+
+    expect(() => searcher.ordToTerm('color', 3)).toThrow('out of range')
+    expect(() => searcher.termOrds('title', addresses)).toThrow()
+  })
+
+  it('test_field_min_max', () => {
+    const searcher = ramIndexNumericFields.searcher()
+
+    const stats = searcher.fieldMinMax('rating')
+    expect(stats).toEqual({ min: 3.5, max: 4.5, count: 2, sum: 8, mean: 4 })
+    const good = searcher.fieldMinMax('id', Query.termQuery(ramIndexNumericFields.schema, 'id', 1))
+    expect(good).toEqual({ min: 1, max: 1, count: 1, sum: 1, mean: 1 })
+
+    const none = searcher.fieldMinMax('rating', Query.termQuery(ramIndexNumericFields.schema, 'id', 3))
+    expect(none.count).toBe(0)
+    expect(none.min ?? null).toBeNull()
+    expect(none.mean ?? null).toBeNull()
+
+    expect(() => searcher.fieldMinMax('body')).toThrow()
+  })
+
+  it('test_search_rescore', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addFloatField('popularity', { fast: true })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    const docs = [
+      { title: 'sea', popularity: 0.5 },
+      { title: 'sea sea', popularity: 3 },
+      { title: 'the sea and the sky', popularity: 2 },
+    ]
+    for (const doc of docs) {
+      writer.addDocument(Document.fromDict(doc, schema))
+    }
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const query = index.parseQuery('sea', ['title'])
+    const byPopularity = (batch: RescoreBatch) => {
+      expect(batch.scores).toBeInstanceOf(Float64Array)
+      expect(batch.globalDocs.length).toBe(batch.scores.length)
+      return batch.fields.popularity.map((popularity, idx) => popularity * batch.scores[idx])
+    }
+
+    let calls = 0
+    const rescored = searcher.search(query, 10, true, undefined, undefined, undefined, {
+      rescore: {
+        fields: ['popularity'],
+        callback: (batch) => {
+          calls += 1
+          return byPopularity(batch)
+        },
+      },
+    })
+    expect(calls).toBe(1)
+    expect(rescored.count).toBe(3)
+    const titles = rescored.hits.map((hit) => searcher.doc(hit.docAddress).getFirst('title'))
+    expect(titles).toEqual(['sea sea', 'the sea and the sky', 'sea'])
+
+    const page = searcher.search(query, 1, true, undefined, 1, undefined, {
+      rescore: { fields: ['popularity'], callback: byPopularity },
+    })
+    expect(page.hits).toEqual([rescored.hits[1]])
+
+    const bm25 = searcher.search(query).hits
+    const partial = searcher.search(query, 10, true, undefined, undefined, undefined, {
+      rescore: { topN: 1, callback: (batch) => [-batch.scores[0]] },
+    })
+    expect(partial.hits.map((hit) => hit.docAddress)).toEqual(bm25.map((hit) => hit.docAddress))
+    expect(partial.hits[0].score).toBe(-bm25[0].score!)
+
+    expect(() =>
+      searcher.search(query, 10, true, undefined, undefined, undefined, {
+        rescore: { callback: () => [1] },
+      }),
+    ).toThrow('returned 1 scores for 3 hits')
+    expect(() =>
+      searcher.search(query, 10, true, 'popularity', undefined, undefined, {
+        rescore: { callback: byPopularity },
+      }),
+    ).toThrow("Rescoring can't be used")
+  })
+})
+
+describe('TestDocument', () => {
+  it('test_document', () => {
+    const doc = new Document()
+    doc.addText('title', 'Test Document')
+    doc.addInteger('id', 123)
+    doc.addFloat('rating', 4.5)
+    doc.addBoolean('is_good', true)
+
+    // Test that document was created successfully
+    expect(doc).toBeDefined()
+
+    // Test document conversion to dict
+    const dict = doc.toDict() as TestDoc
+    expect(dict.title).toEqual(['Test Document'])
+    expect(dict.id).toEqual([123])
+    expect(dict.rating).toEqual([4.5])
+    expect(dict.is_good).toEqual([true])
+  })
+
+  it('test_document_with_date', () => {
+    const doc = new Document()
+    const testDate = new Date('2021-01-01T00:00:00.000Z')
+    doc.addDate('date', testDate.getTime())
+
+    const dict = doc.toDict() as TestDoc
+    expect(dict.date?.[0]).toBe(testDate.getTime())
+  })
+
+  it('test_document_to_dict_flatten', () => {
+    const doc = Document.fromDict({ title: 'Test Document', tag: ['a', 'b'], rating: 4.5 })
+    expect(doc.toDict({ flatten: true })).toEqual({ title: 'Test Document', tag: ['a', 'b'], rating: 4.5 })
+    expect(doc.toDict({ flatten: false })).toEqual({ title: ['Test Document'], tag: ['a', 'b'], rating: [4.5] })
+  })
+
+  it('test_document_mutation', () => {
+    const doc = Document.fromDict({ id: 1, body: 'first', rating: 3.5 })
+
+    expect(doc.remove('rating')).toBe(true)
+    expect(doc.remove('rating')).toBe(false)
+
+    doc.set('body', ['second', 'third'])
+    doc.set('id', 2, schemaNumericFields())
+    expect(() => doc.set('unknown', 1, schemaNumericFields())).toThrow()
+
+    doc.merge(Document.fromDict({ body: 'fourth', is_good: true }))
+    const dict = doc.toDict() as any
+    expect(dict).toEqual({ id: [2], body: ['second', 'third', 'fourth'], is_good: [true] })
+  })
+
+  it('test_document_extend', () => {
+    const doc = new Document()
+    doc.addText('tag', 'a')
+    doc.extend({ tag: ['b', 'c'] })
+    doc.extend({ tag: 'd' })
+    expect(doc.getAll('tag')).toEqual(['a', 'b', 'c', 'd'])
+
+    doc.extend({ tag: 'e' }, null, true)
+    expect(doc.getAll('tag')).toEqual(['e'])
+  })
+
+  it('test_document_repr', () => {
+    // Test string representation of documents (Node.js equivalent via toDict)
+    const doc = new Document()
+    doc.addText('name', 'Bill')
+    doc.addInteger('reference', 1)
+    doc.addInteger('reference', 2)
+
+    const dict = doc.toDict() as any
+    expect(dict.name).toEqual(['Bill'])
+    expect(dict.reference).toEqual([1, 2])
 
     // Test string representation via JSON serialization
     const representation = JSON.stringify(dict)
@@ -1153,6 +2819,66 @@ it('test_schema_eq', () => {
   expect(schema3.hasField('body')).toBe(false)
 })
 
+it('test_schema_diff', () => {
+  const before = new SchemaBuilder()
+    .addTextField('title', { stored: true })
+    .addTextField('body')
+    .addIntegerField('year', { indexed: true })
+    .build()
+  const after = new SchemaBuilder()
+    .addTextField('title', { stored: true, tokenizerName: 'en_stem' })
+    .addFloatField('year', { indexed: true })
+    .addTextField('summary')
+    .build()
+
+  expect(before.diff(before)).toEqual({ added: [], removed: [], changed: [] })
+  const diff = before.diff(after)
+  expect(diff.added).toEqual(['summary'])
+  expect(diff.removed).toEqual(['body'])
+  expect(diff.changed.map((change) => [change.name, change.options])).toEqual([
+    ['title', ['indexing.tokenizer']],
+    ['year', ['type']],
+  ])
+  expect(diff.changed[0].after.options.indexing.tokenizer).toBe('en_stem')
+  expect(after.diff(before).added).toEqual(['body'])
+})
+
+it('test_supports_phrase_queries', () => {
+  const schema = new SchemaBuilder()
+    .addTextField('body')
+    .addTextField('tags', { indexOption: 'freq' })
+    .addIntegerField('year', { indexed: true })
+    .build()
+  expect(schema.supportsPhraseQueries('body')).toBe(true)
+  expect(schema.supportsPhraseQueries('tags')).toBe(false)
+  expect(schema.supportsPhraseQueries('year')).toBe(false)
+  expect(() => schema.supportsPhraseQueries('missing')).toThrow("Field 'missing' not found")
+
+  // The phrase is rejected when the query is built, not when it is searched.
+  const message = "The field 'tags' does not have positions indexed"
+  expect(() => Query.phraseQuery(schema, 'tags', ['red', 'fox'])).toThrow(message)
+  expect(() => Query.phrasePrefixQuery(schema, 'tags', ['red', 'f'])).toThrow(message)
+  expect(() => new Index(schema).parseQuery('tags:"red fox"')).toThrow(message)
+  expect(Query.phraseQuery(schema, 'body', ['red', 'fox']).toString()).toMatch(/PhraseQuery/)
+})
+
+it('test_schema_builder_field_names', () => {
+  const builder = new SchemaBuilder().addTextField('title').addGeoPointField('loc')
+
+  expect(() => builder.addIntegerField('title')).toThrow("SchemaBuilder.addIntegerField('title'): field 'title' already exists")
+  expect(() => builder.addFloatField('loc.lat')).toThrow('already exists')
+  expect(() => builder.addTextField('')).toThrow('not a valid field name')
+  expect(() => builder.addTextField('-title')).toThrow('not a valid field name')
+  expect(() => builder.addTextField('_nested')).toThrow('reserved')
+  // A field rejected for its options doesn't take its name.
+  expect(() => builder.addTextField('body', { indexOption: 'all' })).toThrow('Invalid index option')
+
+  // The builder is still usable after a rejected field.
+  const schema = builder.enableNestedDocuments().addTextField('body').build()
+  expect(schema.fieldNames()).toEqual(['title', 'loc.lat', 'loc.lon', '_nested', 'body'])
+  expect(() => builder.addTextField('other')).toThrow('no longer valid')
+})
+
 it('test_facet_eq', () => {
   // Test facet equality like Python implementation
   const facet1 = Facet.fromString('/europe/france')
@@ -1169,6 +2895,38 @@ it('test_facet_eq', () => {
   expect(facet1.toPath()).not.toEqual(facet3.toPath())
 })
 
+it('test_stored_only_fields', () => {
+  const schema = new SchemaBuilder()
+    .addTextField('title', { stored: true })
+    .addTextField('payload', { stored: true, indexed: false })
+    .addStoredJsonField('meta')
+    .build()
+  const fields = JSON.parse(schema.toJSON())
+  // Text fields leave their indexing options out rather than null.
+  expect(fields[1].options.indexing).toBeUndefined()
+  expect(fields[2].options.indexing).toBeNull()
+  expect(fields[2].options.stored).toBe(true)
+
+  const index = new Index(schema)
+  const writer = index.writer()
+  writer.addDocument(
+    Document.fromDict({ title: 'render', payload: '<div>render</div>', meta: { width: 640 } }, schema),
+  )
+  writer.commit()
+  index.reload()
+  const searcher = index.searcher()
+  const result = searcher.search(Query.termQuery(schema, 'title', 'render'))
+  expect(searcher.doc(result.hits[0].docAddress).toDict()).toEqual({
+    title: ['render'],
+    payload: ['<div>render</div>'],
+    meta: [{ width: 640 }],
+  })
+
+  expect(() => new SchemaBuilder().addTextField('payload', { indexed: false, indexOption: 'basic' })).toThrow(
+    'not indexed',
+  )
+})
+
 it('test_schema_pickle', () => {
   // Test schema serialization using new toJson/fromJson methods
   const originalSchema = new SchemaBuilder()
@@ -1228,23 +2986,110 @@ describe('TestSnippets', () => {
     const snippetGenerator = SnippetGenerator.create(searcher, query, ramIndex.schema, 'title')
     expect(snippetGenerator).toBeDefined()
 
-    // Set max characters
-    snippetGenerator.setMaxNumChars(150)
+    // Set max characters
+    snippetGenerator.setMaxNumChars(150)
+
+    // Get the document and generate snippet
+    const { docAddress } = result.hits[0]
+    const doc = searcher.doc(docAddress)
+    const snippet = snippetGenerator.snippetFromDoc(doc)
+
+    // Test snippet methods
+    expect(snippet).toBeDefined()
+    expect(typeof snippet.toHtml()).toBe('string')
+    expect(typeof snippet.fragment()).toBe('string')
+    expect(Array.isArray(snippet.highlighted())).toBe(true)
+
+    // Check that the snippet contains the search term
+    const htmlSnippet = snippet.toHtml()
+    expect(htmlSnippet.toLowerCase()).toContain('sea')
+  })
+
+  it('test_snippet_best_field', () => {
+    const query = ramIndex.parseQuery('sea OR fish', ['title', 'body'])
+    const searcher = ramIndex.searcher()
+    const hit = searcher.search(ramIndex.parseQuery('sea', ['title'])).hits[0]
+    const doc = searcher.doc(hit.docAddress)
+
+    const titleSnippet = SnippetGenerator.create(searcher, query, ramIndex.schema, 'title').snippetFromDoc(doc)
+    expect(titleSnippet.fieldName).toBe('title')
+    expect(titleSnippet.score).toBeGreaterThan(0)
+
+    const best = SnippetGenerator.bestField(
+      searcher,
+      ramIndex.parseQuery('sea', ['title', 'body']),
+      ramIndex.schema,
+      doc,
+      ['body', 'title'],
+    )
+    expect(best?.fieldName).toBe('title')
+    expect(best?.fragment()).toContain('Sea')
+
+    expect(SnippetGenerator.bestField(searcher, query, ramIndex.schema, doc, [])).toBeNull()
+
+    // Spans are scored by their analyzed terms, here stemmed.
+    const schema = new SchemaBuilder()
+      .addTextField('summary', { stored: true })
+      .addTextField('content', { stored: true, tokenizerName: 'en_stem' })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ summary: 'Nothing to see', content: 'The Boats are sailing' }, schema))
+    writer.commit()
+    index.reload()
+    const stemmedQuery = index.parseQuery('boats', ['summary', 'content'])
+    const stemmedDoc = index.searcher().doc(index.searcher().search(stemmedQuery).hits[0].docAddress)
+    const stemmed = SnippetGenerator.bestField(index.searcher(), stemmedQuery, schema, stemmedDoc, [
+      'summary',
+      'content',
+    ])
+    expect(stemmed?.fieldName).toBe('content')
+    expect(stemmed?.score).toBeGreaterThan(0)
+  })
+
+  it('test_snippet_strip_html', () => {
+    const schema = new SchemaBuilder().addTextField('content', { stored: true }).build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    const html = '<div class="whale"><p>The <b>white</b> whale &amp; the sea</p><script>whale()</script></div>'
+    writer.addDocument(Document.fromDict({ content: html }))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const query = index.parseQuery('whale', ['content'])
+    const doc = searcher.doc(searcher.search(query).hits[0].docAddress)
+
+    const generator = SnippetGenerator.create(searcher, query, schema, 'content')
+    expect(generator.snippetFromDoc(doc).toHtml()).toContain('&lt;')
+
+    generator.setStripHtml(true)
+    const snippet = generator.snippetFromDoc(doc)
+    expect(snippet.fragment()).toBe('The white whale & the sea')
+    expect(snippet.highlighted()).toEqual([{ start: 10, end: 15 }])
+    expect(snippet.toHtml()).toBe('The white <b>whale</b> &amp; the sea')
+  })
+
+  it('test_snippet_analyzer', () => {
+    const schema = new SchemaBuilder().addTextField('content', { stored: true, tokenizerName: 'en_stem' }).build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ content: 'An e-mail about the boats' }))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const query = index.parseQuery('mail boat', ['content'])
+    const doc = searcher.doc(searcher.search(query).hits[0].docAddress)
 
-    // Get the document and generate snippet
-    const { docAddress } = result.hits[0]
-    const doc = searcher.doc(docAddress)
-    const snippet = snippetGenerator.snippetFromDoc(doc)
+    const generator = SnippetGenerator.create(searcher, query, schema, 'content')
+    expect(generator.snippetFromDoc(doc).toHtml()).toBe('An e-<b>mail</b> about the <b>boats</b>')
 
-    // Test snippet methods
-    expect(snippet).toBeDefined()
-    expect(typeof snippet.toHtml()).toBe('string')
-    expect(typeof snippet.fragment()).toBe('string')
-    expect(Array.isArray(snippet.highlighted())).toBe(true)
+    generator.setAnalyzer(new TextAnalyzerBuilder(TokenizerStatic.regex('[\\w-]+')).build())
+    const snippet = generator.snippetFromDoc(doc)
+    expect(snippet.toHtml()).toBe('An <b>e-mail</b> about the <b>boats</b>')
+    expect(snippet.score).toBeGreaterThan(0)
 
-    // Check that the snippet contains the search term
-    const htmlSnippet = snippet.toHtml()
-    expect(htmlSnippet.toLowerCase()).toContain('sea')
+    generator.setAnalyzer(null)
+    expect(generator.snippetFromDoc(doc).toHtml()).toBe('An e-<b>mail</b> about the <b>boats</b>')
   })
 })
 
@@ -1304,6 +3149,151 @@ describe('TestQuery', () => {
     expect((searchedDoc.toDict() as TestDoc).title).toEqual(['The Old Man and the Sea'])
   })
 
+  it('test_regex_query_options', () => {
+    const searcher = ramIndex.searcher()
+    expect(searcher.search(Query.regexQuery(ramIndex.schema, 'title', 'MA.')).count).toBe(0)
+    const insensitive = Query.regexQuery(ramIndex.schema, 'title', 'MA.', { caseInsensitive: true })
+    expect(searcher.search(insensitive).count).toBe(1)
+
+    const limited = { sizeLimit: 100_000 }
+    expect(searcher.search(Query.regexQuery(ramIndex.schema, 'title', 'm.n', limited)).count).toBe(2)
+    // Accepted by tantivy, with 31 states.
+    expect(searcher.search(Query.regexQuery(ramIndex.schema, 'title', '[a-z]{30}')).count).toBe(0)
+    expect(() => Query.regexQuery(ramIndex.schema, 'title', '[a-z]{30}', limited)).toThrow(/more than the limit/)
+  })
+
+  it('test_with_field_boosts', () => {
+    const searcher = ramIndex.searcher()
+    const query = Query.booleanQuery([
+      { occur: 1, query: Query.termQuery(ramIndex.schema, 'title', 'sea') }, // Should
+      { occur: 1, query: Query.termQuery(ramIndex.schema, 'body', 'fish') }, // Should
+    ])
+    const baseScore = searcher.search(query).hits[0].score!
+
+    const boosted = query.withFieldBoosts(ramIndex.schema, { title: 2 })
+    const titleScore = searcher.search(Query.termQuery(ramIndex.schema, 'title', 'sea')).hits[0].score!
+    expect(searcher.search(boosted).hits[0].score!).toBeCloseTo(baseScore + titleScore, 4)
+    expect(boosted.toString()).toContain('Boost')
+
+    expect(() => query.withFieldBoosts(ramIndex.schema, { nope: 2 })).toThrow()
+  })
+
+  it('test_geo_queries', () => {
+    const geoSchema = new SchemaBuilder().addTextField('name', { stored: true }).addGeoPointField('location').build()
+    const index = new Index(geoSchema)
+    const writer = index.writer(15_000_000, 1)
+    const cities: [string, number, number][] = [
+      ['paris', 48.8566, 2.3522],
+      ['london', 51.5074, -0.1278],
+      ['berlin', 52.52, 13.405],
+      ['suva', -18.1416, 178.4419],
+    ]
+    for (const [name, lat, lon] of cities) {
+      const doc = new Document()
+      doc.addText('name', name)
+      doc.addGeoPoint('location', lat, lon)
+      writer.addDocument(doc)
+    }
+    const chain = new Document()
+    chain.addText('name', 'chain')
+    chain.addGeoPoint('location', 52.52, 13.405)
+    chain.addGeoPoint('location', 48.8566, 2.3522)
+    writer.addDocument(chain)
+    writer.commit()
+    index.reload()
+
+    const searcher = index.searcher()
+    const names = (query: Query) =>
+      searcher
+        .search(query)
+        .hits.map((hit) => (searcher.doc(hit.docAddress).toDict() as { name: string[] }).name[0])
+        .sort()
+
+    expect(
+      names(Query.geoBoundingBoxQuery(geoSchema, 'location', { top: 55, left: -5, bottom: 45, right: 5 })),
+    ).toEqual(['chain', 'london', 'paris'])
+    // Boxes crossing the antimeridian
+    expect(
+      names(Query.geoBoundingBoxQuery(geoSchema, 'location', { top: 0, left: 170, bottom: -30, right: -170 })),
+    ).toEqual(['suva'])
+    expect(names(Query.geoDistanceQuery(geoSchema, 'location', { lat: 48.8566, lon: 2.3522 }, 400_000))).toEqual([
+      'chain',
+      'london',
+      'paris',
+    ])
+    expect(names(Query.geoDistanceQuery(geoSchema, 'location', { lat: 48.8566, lon: 2.3522 }, 300_000))).toEqual([
+      'chain',
+      'paris',
+    ])
+    // The latitude of a point and the longitude of another don't make a point.
+    expect(names(Query.geoDistanceQuery(geoSchema, 'location', { lat: 52.52, lon: 2.3522 }, 10_000))).toEqual([])
+    expect(() => Query.geoDistanceQuery(geoSchema, 'name', { lat: 0, lon: 0 }, 10)).toThrow()
+  })
+
+  it('test_nested_query', () => {
+    const nestedSchema = new SchemaBuilder()
+      .addTextField('name', { stored: true })
+      .addFloatField('price', { indexed: true })
+      .enableNestedDocuments()
+      .build()
+    const index = new Index(nestedSchema)
+    const writer = index.writer(15_000_000, 1)
+    const offer = (price: number) => Document.fromDict({ price }, nestedSchema)
+    writer.addNestedDocument(Document.fromDict({ name: 'phone' }, nestedSchema), [offer(300), offer(250)], 'offers')
+    writer.addNestedDocument(Document.fromDict({ name: 'case' }, nestedSchema), [offer(20), offer(400)], 'offers')
+    writer.commit()
+    index.reload()
+
+    const searcher = index.searcher()
+    const cheapOffer = Query.rangeQuery(nestedSchema, 'price', FieldType.F64, 0, 50, true, true)
+    const result = searcher.search(Query.nestedQuery(nestedSchema, 'offers', cheapOffer))
+    expect(result.hits.map((hit) => (searcher.doc(hit.docAddress).toDict() as { name: string[] }).name)).toEqual([
+      ['case'],
+    ])
+
+    expect(searcher.search(Query.nestedParentQuery(nestedSchema)).count).toBe(2)
+    expect(searcher.search(Query.nestedQuery(nestedSchema, 'variants', cheapOffer)).count).toBe(0)
+    expect(() => Query.nestedQuery(ramIndex.schema, 'offers', cheapOffer)).toThrow()
+  })
+
+  it('test_nested_delete_parent', () => {
+    const nestedSchema = new SchemaBuilder()
+      .addTextField('name', { stored: true })
+      .addFloatField('price', { indexed: true })
+      .enableNestedDocuments()
+      .build()
+    const index = new Index(nestedSchema)
+    const writer = index.writer(15_000_000, 1)
+    const offer = (price: number) => Document.fromDict({ price }, nestedSchema)
+    writer.addNestedDocument(Document.fromDict({ name: 'phone' }, nestedSchema), [offer(20), offer(300)], 'offers')
+    writer.commit()
+    writer.addNestedDocument(Document.fromDict({ name: 'case' }, nestedSchema), [offer(250), offer(400)], 'offers')
+    writer.commit()
+    writer.deleteDocumentsByTerm('name', 'phone')
+    writer.commit()
+    writer.waitMergingThreads()
+
+    // Once merged, children left behind would be joined to the next parent.
+    index.compact()
+    const searcher = index.searcher()
+    expect(searcher.numDocs).toBe(3)
+    const offers = (max: number) =>
+      Query.nestedQuery(nestedSchema, 'offers', Query.rangeQuery(nestedSchema, 'price', FieldType.F64, 0, max, true, true))
+    expect(searcher.search(offers(50)).count).toBe(0)
+    expect(searcher.search(offers(300)).count).toBe(1)
+
+    expect(() => index.writer({ uniqueField: 'name' })).toThrow("can't be used with nested documents")
+  })
+
+  it('test_prefix_query', () => {
+    const searcher = ramIndex.searcher()
+    expect(searcher.search(Query.prefixQuery(ramIndex.schema, 'title', 'm')).hits.length).toBe(3)
+    expect(searcher.search(Query.prefixQuery(ramIndex.schema, 'title', 'me')).hits.length).toBe(1)
+    // Metacharacters are matched literally
+    expect(searcher.search(Query.prefixQuery(ramIndex.schema, 'title', 'm.')).hits.length).toBe(0)
+    expect(() => Query.prefixQuery(ramIndex.schema, 'title', '')).toThrow()
+  })
+
   it('test_range_query_numerics', () => {
     const searcher = ramIndexNumericFields.searcher()
 
@@ -1318,6 +3308,61 @@ describe('TestQuery', () => {
     expect(result.hits.length).toBe(1)
   })
 
+  it('test_range_query_execution_hint', () => {
+    const schema = ramIndexNumericFields.schema
+    const searcher = ramIndexNumericFields.searcher()
+    const count = (options?: RangeQueryOptions) =>
+      searcher.search(Query.rangeQuery(schema, 'id', FieldType.I64, 1, 2, true, true, options)).count
+
+    expect(count()).toBe(2)
+    expect(count({ useFastField: true })).toBe(2)
+    expect(count({ useFastField: false })).toBe(2)
+
+    const slowSchema = new SchemaBuilder().addIntegerField('id', { indexed: true }).build()
+    expect(() => Query.rangeQuery(slowSchema, 'id', FieldType.I64, 1, 2, true, true, { useFastField: true })).toThrow(
+      'is not a fast field',
+    )
+    const columnarSchema = new SchemaBuilder().addIntegerField('id', { fast: true }).build()
+    expect(() =>
+      Query.rangeQuery(columnarSchema, 'id', FieldType.I64, 1, 2, true, true, { useFastField: false }),
+    ).toThrow('is not indexed')
+  })
+
+  it('test_range_query_large_integers', () => {
+    const schema = new SchemaBuilder()
+      .addUnsignedField('id', { indexed: true, fast: true })
+      .addIntegerField('offset', { indexed: true, fast: true })
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.addJson('{"id": 18446744073709551615, "offset": -9223372036854775808}')
+    writer.addJson('{"id": 9007199254740993, "offset": 9007199254740993}')
+    writer.addJson('{"id": 4294967296, "offset": 1}')
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const count = (query: Query) => searcher.search(query).count
+
+    // Beyond 32 bits, the bounds used to be clamped to 4294967295.
+    expect(count(Query.rangeQuery(schema, 'id', FieldType.U64, 4294967296, 4294967296))).toBe(1)
+    expect(count(Query.rangeQuery(schema, 'id', FieldType.U64, 2n ** 53n + 1n, 2n ** 64n - 1n))).toBe(2)
+    expect(count(Query.rangeQuery(schema, 'id', FieldType.U64, '18446744073709551615', '18446744073709551615'))).toBe(1)
+    expect(count(Query.termQuery(schema, 'id', 9007199254740993n))).toBe(1)
+    expect(count(Query.rangeQuery(schema, 'offset', FieldType.I64, -(2n ** 63n), 0))).toBe(1)
+    expect(count(Query.termQuery(schema, 'offset', '9007199254740993'))).toBe(1)
+
+    // Numbers that cannot be represented exactly are rejected instead of rounded.
+    expect(() => Query.termQuery(schema, 'id', 2 ** 60)).toThrow('is not a valid u64 integer')
+    expect(() => Query.termQuery(schema, 'id', -1)).toThrow('is not a valid u64 integer')
+    expect(() => Query.termQuery(schema, 'id', 2n ** 64n)).toThrow('is not a valid u64 integer')
+    expect(() => Query.termQuery(schema, 'offset', 'twelve')).toThrow('is not a valid i64 integer')
+
+    writer.deleteDocumentsByTerm('id', 18446744073709551615n)
+    writer.commit()
+    index.reload()
+    expect(index.searcher().numDocs).toBe(2)
+  })
+
   it('test_range_query_dates', () => {
     const searcher = ramIndexWithDateField.searcher()
 
@@ -1390,6 +3435,55 @@ describe('TestQuery', () => {
     expect(result.hits.length).toBe(1) // "The Old Man and the Sea" matches
   })
 
+  it('test_query_fingerprint', () => {
+    const schema = ramIndex.schema
+    const sea = Query.termQuery(schema, 'title', 'sea')
+    const man = Query.termQuery(schema, 'body', 'man')
+    const fingerprint = Query.booleanQuery([
+      { occur: 0, query: sea },
+      { occur: 1, query: man },
+    ]).fingerprint()
+    expect(fingerprint).toMatch(/^[0-9a-f]{16}$/)
+    expect(
+      Query.booleanQuery([
+        { occur: 1, query: man },
+        { occur: 0, query: sea },
+      ]).fingerprint(),
+    ).toBe(fingerprint)
+    expect(
+      Query.booleanQuery([
+        { occur: 1, query: sea },
+        { occur: 0, query: man },
+      ]).fingerprint(),
+    ).not.toBe(fingerprint)
+
+    expect(Query.termSetQuery(schema, 'title', ['sea', 'men']).fingerprint()).toBe(
+      Query.termSetQuery(schema, 'title', ['men', 'sea']).fingerprint(),
+    )
+    expect(sea.fingerprint()).toBe(Query.termQuery(schema, 'title', 'sea').fingerprint())
+    expect(sea.fingerprint()).not.toBe(Query.termQuery(schema, 'title', 'men').fingerprint())
+
+    const nested = (first: Query, second: Query) =>
+      Query.boostQuery(
+        Query.disjunctionMaxQuery([
+          Query.constScoreQuery(Query.booleanQuery([{ occur: 1, query: first }, { occur: 1, query: second }]), 1.5),
+          first.named('first'),
+        ]),
+        2.0,
+      )
+    expect(nested(sea, man).fingerprint()).not.toBe(nested(man, sea).fingerprint())
+    expect(
+      Query.boostQuery(
+        Query.disjunctionMaxQuery([
+          sea.named('first'),
+          Query.constScoreQuery(Query.booleanQuery([{ occur: 1, query: man }, { occur: 1, query: sea }]), 1.5),
+        ]),
+        2.0,
+      ).fingerprint(),
+    ).toBe(nested(sea, man).fingerprint())
+    expect(Query.boostQuery(sea, 2.0).fingerprint()).not.toBe(Query.boostQuery(sea, 3.0).fingerprint())
+  })
+
   it('test_boost_query', () => {
     const baseQuery = Query.termQuery(ramIndex.schema, 'title', 'sea')
     const boostedQuery = Query.boostQuery(baseQuery, 2.0)
@@ -1403,6 +3497,33 @@ describe('TestQuery', () => {
     expect(result.hits.length).toBe(baseResult.hits.length)
   })
 
+  it('test_similar_documents', () => {
+    const index = new Index(schema())
+    const writer = index.writer()
+    for (const title of ['Whale hunting at sea', 'Whale watching at sea', 'Sea shanties', 'Garden flowers']) {
+      writer.addDocument(Document.fromDict({ title }))
+    }
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const source = searcher.search(Query.termQuery(index.schema, 'title', 'hunting')).hits[0].docAddress
+    const titles = (hits: SearchHit[]) =>
+      hits.map((hit) => (searcher.doc(hit.docAddress).toDict() as TestDoc).title![0])
+
+    const options = { minDocFrequency: 1, minTermFrequency: 1 }
+    const hits = searcher.similarDocuments(source, options)
+    expect(titles(hits)).toEqual(['Whale watching at sea', 'Sea shanties'])
+    expect(titles(searcher.similarDocuments(source, { ...options, limit: 1 }))).toEqual(['Whale watching at sea'])
+    expect(titles(searcher.similarDocuments(source, { ...options, stopWords: ['whale', 'at'] })).sort()).toEqual([
+      'Sea shanties',
+      'Whale watching at sea',
+    ])
+    expect(searcher.similarDocuments(source, { ...options, stopWords: ['whale', 'at', 'sea'] })).toEqual([])
+    expect(searcher.similarDocuments(source, { ...options, fields: ['title'] }).length).toBe(2)
+    expect(searcher.similarDocuments(source)).toEqual([])
+    expect(() => searcher.similarDocuments(source, { fields: ['missing'] })).toThrow()
+  })
+
   it('test_const_score_query', () => {
     const baseQuery = Query.termQuery(ramIndex.schema, 'title', 'sea')
     const constQuery = Query.constScoreQuery(baseQuery, 1.5)
@@ -1423,6 +3544,28 @@ describe('TestQuery', () => {
     expect(result.hits.length).toBe(1) // Should match "The Old Man and the Sea"
   })
 
+  it('test_disjunction_max_query_boosts', () => {
+    const searcher = ramIndex.searcher()
+    const titleQuery = Query.termQuery(ramIndex.schema, 'title', 'sea')
+    const score = searcher.search(Query.disjunctionMaxQuery([titleQuery])).hits[0].score!
+
+    const boosted = Query.disjunctionMaxQuery([{ query: titleQuery, boost: 2 }])
+    expect(searcher.search(boosted).hits[0].score).toBeCloseTo(score * 2)
+    expect(() => Query.disjunctionMaxQuery([{ boost: 2 }])).toThrow('Missing')
+
+    const parsed = ramIndex.parseDisjunctionMaxQuery(
+      JSON.stringify({ queries: [{ query: 'title:sea', boost: 2 }], tieBreaker: 0 }),
+    )
+    expect(searcher.search(parsed).hits[0].score).toBeCloseTo(score * 2)
+    const withDefaultFields = ramIndex.parseDisjunctionMaxQuery(JSON.stringify({ queries: ['sea'] }), ['title'])
+    expect(searcher.search(withDefaultFields).hits.length).toBe(1)
+
+    expect(() => ramIndex.parseDisjunctionMaxQuery(JSON.stringify({ queries: [1] }))).toThrow(
+      'Invalid disjunction max query',
+    )
+    expect(() => ramIndex.parseDisjunctionMaxQuery(JSON.stringify({ queries: ['unknown:sea'] }))).toThrow()
+  })
+
   it('test_more_like_this_query', () => {
     const searcher = ramIndex.searcher()
 
@@ -1496,6 +3639,26 @@ describe('TestQuery', () => {
     }).toThrow('PhrasePrefixQuery requires at least two terms')
   })
 
+  it('test_span_near_query', () => {
+    const schema = new SchemaBuilder().addTextField('body', { stored: true }).build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ body: 'a patent for a claim' }, schema))
+    writer.addDocument(Document.fromDict({ body: 'claim a patent' }, schema))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+
+    expect(searcher.search(Query.spanNearQuery(schema, 'body', ['patent', 'claim'])).hits.length).toBe(0)
+    const query = Query.spanNearQuery(schema, 'body', ['patent', 'claim'], 3)
+    const result = searcher.search(query)
+    expect(result.hits.length).toBe(1)
+    expect(searcher.doc(result.hits[0].docAddress).toDict()).toEqual({ body: ['a patent for a claim'] })
+    // A phrase with the same slop also matches the words swapped around.
+    expect(searcher.search(Query.phraseQuery(schema, 'body', ['patent', 'claim'], 3)).hits.length).toBe(2)
+    expect(query.estimateCost(searcher).kind).toBe('span_near')
+  })
+
   it('test_regex_phrase_query', () => {
     // Match "old" followed by "man" using regex patterns with slop
     const query = Query.regexPhraseQuery(ramIndex.schema, 'body', ['old', 'ma.*'], 1)
@@ -1513,6 +3676,74 @@ describe('TestQuery', () => {
     }).toThrow('patterns must not be empty')
   })
 
+  it('test_query_visit', () => {
+    const index = ramIndex
+    const leaves: QueryLeaf[] = []
+    index.parseQuery('title:sea^2 -title:mice "old man"', ['title']).visit(index.schema, (leaf) => {
+      leaves.push(leaf)
+      return null
+    })
+    expect(leaves.map((leaf) => [leaf.kind, leaf.occur, leaf.field, leaf.terms, leaf.boost])).toEqual([
+      ['term', 'should', 'title', ['sea'], 2],
+      ['term', 'must_not', 'title', ['mice'], 1],
+      ['phrase', 'should', 'title', ['old', 'man'], 1],
+    ])
+
+    const searcher = index.searcher()
+    const expanded = index
+      .parseQuery('title:sea')
+      .visit(index.schema, (leaf) =>
+        leaf.terms[0] === 'sea' ? Query.termSetQuery(index.schema, 'title', ['sea', 'frankenstein']) : null,
+      )
+    expect(searcher.search(expanded).count).toBe(2)
+    const unchanged = index.parseQuery('title:sea^2').visit(index.schema, () => null)
+    expect(searcher.search(unchanged).hits[0].score).toBeCloseTo(
+      searcher.search(index.parseQuery('title:sea^2')).hits[0].score,
+    )
+  })
+
+  it('test_query_from_object', () => {
+    const index = ramIndexNumericFields
+    const searcher = index.searcher()
+    const ids = (object: unknown) =>
+      searcher
+        .search(Query.fromObject(index.schema, object))
+        .hits.map((hit) => (searcher.doc(hit.docAddress).toDict() as TestDoc).id![0])
+        .sort()
+
+    expect(ids({ term: { id: 1 } })).toEqual([1])
+    expect(ids({ terms: { id: [1, '2'] } })).toEqual([1, 2])
+    expect(ids({ range: { rating: { gt: 3.5 } } })).toEqual([2])
+    expect(ids({ range: { rating: { gte: 3.5, lt: 4.5 } } })).toEqual([1])
+    expect(ids({ all: {} })).toEqual([1, 2])
+    expect(
+      ids({
+        bool: {
+          should: [{ term: { body: 'fish' } }, { prefix: { body: 'sali' } }],
+          mustNot: [{ term: { is_good: false } }],
+        },
+      }),
+    ).toEqual([1])
+
+    const filtered = searcher.search(
+      Query.fromObject(index.schema, {
+        bool: { must: [{ term: { body: 'old' } }], filter: [{ range: { id: { lte: 1 } } }] },
+      }),
+    )
+    const plain = searcher.search(Query.fromObject(index.schema, { term: { body: 'old' } }))
+    expect(filtered.hits[0].score).toBeCloseTo(plain.hits[0].score)
+    const boosted = searcher.search(Query.fromObject(index.schema, { boost: { query: { term: { body: 'old' } }, boost: 2 } }))
+    expect(boosted.hits[0].score).toBeCloseTo(plain.hits[0].score * 2)
+
+    const dateQuery = Query.fromObject(ramIndexWithDateField.schema, { term: { date: '2021-01-01T00:00:00.500Z' } })
+    expect(ramIndexWithDateField.searcher().search(dateQuery).count).toBe(1)
+
+    expect(() => Query.fromObject(index.schema, { match: { body: 'old' } })).toThrow(/Invalid query object/)
+    expect(() => Query.fromObject(index.schema, { term: { id: 1, rating: 3.5 } })).toThrow(/exactly one field/)
+    expect(() => Query.fromObject(index.schema, { term: { id: 'one' } })).toThrow(/not a valid/)
+    expect(() => Query.fromObject(index.schema, { range: { body: { gt: 'a' } } })).toThrow(/not supported/)
+  })
+
   it('test_index_exists', () => {
     // Test basic index functionality
     expect(ramIndex).toBeDefined()
@@ -1534,6 +3765,44 @@ describe('TestQuery', () => {
       Query.rangeQuery(index.schema, 'nonexistent', FieldType.I64, 1, 10, true, true)
     }).toThrowErrorMatchingInlineSnapshot(`[Error: Field 'nonexistent' is not defined in the schema.]`)
   })
+
+  it('test_cached_filter', () => {
+    const index = createIndex()
+    const searcher = index.searcher()
+    const filter = index.cachedFilter(Query.termQuery(index.schema, 'title', 'sea'))
+    const query = Query.booleanQuery([
+      { occur: 0, query: Query.allQuery() }, // Must
+      { occur: 0, query: filter }, // Must
+    ])
+
+    expect(searcher.search(query, 10).hits.length).toBe(1)
+    expect(searcher.search(query, 10).hits.length).toBe(1)
+
+    const stats = index.filterCacheStats()
+    expect(stats.misses).toBe(searcher.numSegments)
+    expect(stats.hits).toBe(searcher.numSegments)
+    expect(stats.entries).toBe(searcher.numSegments)
+
+    // An equivalent filter shares the entries.
+    const same = index.cachedFilter(Query.termQuery(index.schema, 'title', 'sea'))
+    expect(searcher.search(same, 10).hits.length).toBe(1)
+    expect(index.filterCacheStats().hits).toBe(2 * searcher.numSegments)
+    expect(index.filterCacheStats().entries).toBe(searcher.numSegments)
+
+    // Another filter has its own entries.
+    const other = index.cachedFilter(Query.termQuery(index.schema, 'title', 'mice'))
+    const otherHits = searcher.search(other, 10).hits
+    expect(otherHits.length).toBe(1)
+    expect(otherHits[0].docAddress).not.toEqual(searcher.search(query, 10).hits[0].docAddress)
+    expect(index.filterCacheStats().entries).toBe(2 * searcher.numSegments)
+
+    index.clearFilterCache()
+    expect(index.filterCacheStats().entries).toBe(0)
+
+    index.configFilterCache(0)
+    expect(searcher.search(query, 10).hits.length).toBe(1)
+    expect(index.filterCacheStats().entries).toBe(0)
+  })
 })
 
 describe('TestTokenizers', () => {
@@ -1597,6 +3866,60 @@ describe('TestTokenizers', () => {
     expect(analyzer.analyze(docText)).toEqual(['fe', 'fer', 'er', 'err', 'rr', 'rro', 'ro', 'rou', 'ou', 'ous', 'us'])
   })
 
+  it('test_build_uax_url_email_tokenizer', () => {
+    const analyzer = new TextAnalyzerBuilder(TokenizerStatic.uaxUrlEmail()).filter(FilterStatic.lowercase()).build()
+    expect(analyzer.analyze('Mail <Jane.Doe@example.com>, see https://example.com/a.')).toEqual([
+      'mail',
+      'jane.doe@example.com',
+      'jane',
+      'doe',
+      'example',
+      'com',
+      'see',
+      'https://example.com/a',
+      'https',
+      'example',
+      'com',
+      'a',
+    ])
+
+    const schema = new SchemaBuilder().addTextField('body', { stored: true, tokenizerName: 'url_email' }).build()
+    const index = new Index(schema)
+    index.registerTokenizer(
+      'url_email',
+      new TextAnalyzerBuilder(TokenizerStatic.uaxUrlEmail()).filter(FilterStatic.lowercase()).build(),
+    )
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ body: 'Reported by user@example.com' }))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    expect(searcher.search(Query.termQuery(index.schema, 'body', 'user@example.com')).count).toBe(1)
+    expect(searcher.search(Query.termQuery(index.schema, 'body', 'example')).count).toBe(1)
+  })
+
+  it('test_build_path_hierarchy_tokenizer', () => {
+    const analyzer = new TextAnalyzerBuilder(TokenizerStatic.pathHierarchy()).build()
+    expect(analyzer.analyze('/usr/local/bin')).toEqual(['/usr', '/usr/local', '/usr/local/bin'])
+    expect(analyzer.analyze('docs//guide/')).toEqual(['docs', 'docs//guide'])
+    const dotted = new TextAnalyzerBuilder(TokenizerStatic.pathHierarchy('.')).build()
+    expect(dotted.analyze('com.example.app')).toEqual(['com', 'com.example', 'com.example.app'])
+    expect(() => new TextAnalyzerBuilder(TokenizerStatic.pathHierarchy(''))).toThrow('must not be empty')
+
+    const schema = new SchemaBuilder().addTextField('path', { stored: true, tokenizerName: 'path' }).build()
+    const index = new Index(schema)
+    index.registerTokenizer('path', analyzer)
+    const writer = index.writer()
+    for (const path of ['/src/lib.rs', '/src/index/mod.rs', '/tests/lib.rs']) {
+      writer.addDocument(Document.fromDict({ path }))
+    }
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    expect(searcher.search(Query.termQuery(index.schema, 'path', '/src')).count).toBe(2)
+    expect(searcher.search(Query.termQuery(index.schema, 'path', '/src/index')).count).toBe(1)
+  })
+
   it('test_build_tokenizer_w_stopword_filter', () => {
     const analyzer = new TextAnalyzerBuilder(TokenizerStatic.simple()).filter(FilterStatic.stopword('english')).build()
     const docText = 'the bad wolf buys an axe'
@@ -1612,6 +3935,182 @@ describe('TestTokenizers', () => {
     expect(analyzer.analyze(docText)).toEqual(['weird', 'way', 'test'])
   })
 
+  it('test_phrase_from_text', () => {
+    const analyzer = new TextAnalyzerBuilder(TokenizerStatic.simple())
+      .filter(FilterStatic.lowercase())
+      .filter(FilterStatic.stopword('english'))
+      .build()
+    const schema = new SchemaBuilder()
+      .addTextField('title')
+      .addTextField('content', { tokenizerName: 'stop_en' })
+      .build()
+    const index = new Index(schema)
+    index.registerTokenizer('stop_en', analyzer)
+
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ title: 'The Old Man', content: 'The Old Man and the Sea' }, schema))
+    writer.addDocument(Document.fromDict({ title: 'Man Old', content: 'old man, sea' }, schema))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+
+    // The stop words removed from the text leave their gaps in the phrase.
+    const query = Query.phraseFromText(schema, 'content', 'Man and the SEA', undefined, index)
+    expect(searcher.search(query).hits.length).toBe(1)
+    expect(searcher.search(Query.phraseFromText(schema, 'content', 'man sea', { slop: 3 }, index)).hits.length).toBe(2)
+
+    // The built-in tokenizers need no index.
+    expect(searcher.search(Query.phraseFromText(schema, 'title', 'OLD man')).hits.length).toBe(1)
+    expect(searcher.search(Query.phraseFromText(schema, 'title', 'Man')).hits.length).toBe(2)
+    expect(() => Query.phraseFromText(schema, 'content', 'old man')).toThrow(
+      "Tokenizer 'stop_en' of field 'content' is not registered.",
+    )
+    expect(() => Query.phraseFromText(schema, 'content', 'the', undefined, index)).toThrow('has no terms')
+  })
+
+  it('test_build_tokenizer_w_length_keep_and_trim_filters', () => {
+    const minLength = new TextAnalyzerBuilder(TokenizerStatic.simple()).filter(FilterStatic.minLength(3)).build()
+    expect(minLength.analyze('a big red fox')).toEqual(['big', 'red', 'fox'])
+
+    const keepWords = new TextAnalyzerBuilder(TokenizerStatic.simple())
+      .filter(FilterStatic.lowercase())
+      .filter(FilterStatic.keepWords(['red', 'fox']))
+      .build()
+    expect(keepWords.analyze('A big Red Fox')).toEqual(['red', 'fox'])
+
+    const trim = new TextAnalyzerBuilder(TokenizerStatic.regex('[^,]+')).filter(FilterStatic.trim()).build()
+    expect(trim.analyze(' new york , paris,  ,london')).toEqual(['new york', 'paris', 'london'])
+
+    // The highlighted ranges leave out the trimmed whitespace.
+    const schema = new SchemaBuilder().addTextField('cities', { stored: true, tokenizerName: 'trimmed' }).build()
+    const index = new Index(schema)
+    index.registerTokenizer('trimmed', trim)
+    const writer = index.writer()
+    writer.addDocument(Document.fromDict({ cities: ' new york , paris' }, schema))
+    writer.commit()
+    index.reload()
+    const searcher = index.searcher()
+    const query = Query.termQuery(schema, 'cities', 'paris')
+    const doc = searcher.doc(searcher.search(query).hits[0].docAddress)
+    const snippet = SnippetGenerator.create(searcher, query, schema, 'cities').snippetFromDoc(doc)
+    expect(snippet.highlighted()).toEqual([{ start: 12, end: 17 }])
+  })
+
+  it('test_build_tokenizer_w_char_filters', () => {
+    const html = new TextAnalyzerBuilder(TokenizerStatic.simple())
+      .filter(FilterStatic.lowercase())
+      .charFilter(CharFilterStatic.htmlStrip())
+      .build()
+    expect(html.analyze('<p>Hello <b>wor</b>ld</p><script>var x</script>fish &amp; chips')).toEqual([
+      'hello',
+      'world',
+      'fish',
+      'chips',
+    ])
+
+    const builder = new TextAnalyzerBuilder(TokenizerStatic.simple()).charFilter(
+      CharFilterStatic.patternReplace('(\\d+)-(\\d+)', '$1$2'),
+    )
+    expect(builder.toJSON()).toEqual({
+      tokenizer: { type: 'simple' },
+      charFilters: [{ type: 'patternReplace', pattern: '(\\d+)-(\\d+)', replacement: '$1$2' }],
+      filters: [],
+    })
+    expect(TextAnalyzer.fromJSON(builder.toJSON()).analyze('call 555-1234')).toEqual(['call', '5551234'])
+
+    const badPattern = CharFilterStatic.patternReplace('(', '')
+    expect(() => new TextAnalyzerBuilder(TokenizerStatic.simple()).charFilter(badPattern)).toThrow()
+  })
+
+  it('test_build_tokenizer_w_preserved_originals', () => {
+    const folding = new TextAnalyzerBuilder(TokenizerStatic.simple())
+      .filter(FilterStatic.lowercase())
+      .filter(FilterStatic.asciiFold(true))
+      .build()
+    expect(folding.analyze('Café au lait')).toEqual(['café', 'cafe', 'au', 'lait'])
+
+    const lowercasing = new TextAnalyzerBuilder(TokenizerStatic.simple()).filter(FilterStatic.lowercase(true)).build()
+    expect(lowercasing.analyze('Hello world')).toEqual(['Hello', 'hello', 'world'])
+
+    const schema = new SchemaBuilder().addTextField('title', { stored: true, tokenizerName: 'folding' }).build()
+    const index = new Index(schema)
+    index.registerTokenizer('folding', folding)
+    const writer = index.writer(15_000_000, 1)
+    writer.addDocument(Document.fromDict({ title: 'cafe' }, schema))
+    writer.addDocument(Document.fromDict({ title: 'café' }, schema))
+    writer.commit()
+    index.reload()
+
+    const searcher = index.searcher()
+    expect(searcher.search(index.parseQuery('cafe', ['title'])).count).toBe(2)
+    // A phrase of `café` and `cafe` at the same position.
+    expect(searcher.search(index.parseQuery('café', ['title'])).count).toBe(1)
+    expect(searcher.search(Query.termQuery(schema, 'title', 'café')).count).toBe(1)
+
+    const both = Query.booleanQuery([
+      { occur: 1, query: Query.termQuery(schema, 'title', 'café') }, // Should
+      { occur: 1, query: Query.termQuery(schema, 'title', 'cafe') }, // Should
+    ])
+    const hits = searcher.search(both).hits
+    expect((searcher.doc(hits[0].docAddress).toDict() as any).title).toEqual(['café'])
+  })
+
+  it('test_build_tokenizer_w_stemmer_variants', () => {
+    const stem = (filter: ReturnType<typeof FilterStatic.stemmer>) =>
+      new TextAnalyzerBuilder(TokenizerStatic.simple()).filter(filter).build().analyze('running cats')
+    const expected = stem(FilterStatic.stemmer('english'))
+    expect(expected).toEqual(['run', 'cat'])
+    expect(stem(FilterStatic.stemmer('english', 'porter2'))).toEqual(expected)
+    expect(stem(FilterStatic.stemmerCustom('porter2'))).toEqual(expected)
+    expect(stem(FilterStatic.stemmerCustom('English'))).toEqual(expected)
+
+    const stemWords = (filter: ReturnType<typeof FilterStatic.stemmer>) =>
+      new TextAnalyzerBuilder(TokenizerStatic.simple()).filter(filter).build().analyze('generously ponies houses')
+    expect(stemWords(FilterStatic.stemmer('english'))).toEqual(['generous', 'poni', 'hous'])
+    expect(stemWords(FilterStatic.stemmer('english', 'porter'))).toEqual(['gener', 'poni', 'hous'])
+    expect(stemWords(FilterStatic.stemmerCustom('porter'))).toEqual(['gener', 'poni', 'hous'])
+    expect(stemWords(FilterStatic.stemmer('english', 'light'))).toEqual(['generously', 'pony', 'house'])
+    expect(stemWords(FilterStatic.stemmerCustom('englishLight'))).toEqual(['generously', 'pony', 'house'])
+
+    expect(() => stem(FilterStatic.stemmer('french', 'porter2'))).toThrow()
+    expect(() => stem(FilterStatic.stemmer('french', 'light'))).toThrow()
+    expect(() => stem(FilterStatic.stemmerCustom('lovins'))).toThrow()
+  })
+
+  it('test_text_analyzer_for_language', () => {
+    const text = 'Die Häuser und die Bäume'
+    expect(TextAnalyzer.forLanguage('german').analyze(text)).toEqual(['die', 'haus', 'und', 'die', 'baum'])
+    expect(TextAnalyzer.forLanguage('german', { stopwords: true, asciiFold: true }).analyze(text)).toEqual([
+      'haus',
+      'baum',
+    ])
+    expect(TextAnalyzer.forLanguage('german', { stemmer: false }).analyze(text)).toEqual([
+      'die',
+      'häuser',
+      'und',
+      'die',
+      'bäume',
+    ])
+    expect(() => TextAnalyzer.forLanguage('klingon')).toThrow()
+  })
+
+  it('test_text_analyzer_json', () => {
+    const builder = new TextAnalyzerBuilder(TokenizerStatic.ngram(2, 3))
+      .filter(FilterStatic.lowercase())
+      .filter(FilterStatic.stemmer('english'))
+    const config = builder.toJSON()
+    expect(config).toEqual({
+      tokenizer: { type: 'ngram', minGram: 2, maxGram: 3, prefixOnly: false },
+      filters: [{ type: 'lowercase' }, { type: 'stemmer', language: 'english' }],
+    })
+
+    const restored = TextAnalyzer.fromJSON(JSON.parse(JSON.stringify(builder)))
+    expect(restored.analyze('FERROUS')).toEqual(builder.build().analyze('FERROUS'))
+
+    expect(TextAnalyzer.fromJSON({ tokenizer: { type: 'simple' } }).analyze('a b')).toEqual(['a', 'b'])
+    expect(() => TextAnalyzer.fromJSON({ tokenizer: { type: 'nope' } })).toThrow()
+  })
+
   it('test_delete_documents_by_query', () => {
     const schema = new SchemaBuilder().addTextField('id', { fast: true }).build()
     const index = new Index(schema)
@@ -1690,4 +4189,170 @@ describe('TestFacet', () => {
     const result = index.searcher().search(query)
     expect(result.hits.length).toBe(1)
   })
+
+  it('test_facet_validation', () => {
+    expect(Facet.isValid('/')).toBe(true)
+    expect(Facet.isValid('/electronics/phones')).toBe(true)
+    expect(Facet.isValid('/sizes/1\\/2')).toBe(true)
+    expect(Facet.isValid('electronics/phones')).toBe(false)
+    expect(Facet.isValid('/electronics//phones')).toBe(false)
+    expect(Facet.isValid('/electronics/')).toBe(false)
+    expect(Facet.isValid('/electronics\\')).toBe(false)
+    expect(Facet.isValid('')).toBe(false)
+
+    expect(Facet.normalize('electronics//phones/')).toBe('/electronics/phones')
+    expect(Facet.normalize('///')).toBe('/')
+    expect(Facet.normalize('')).toBe('/')
+    expect(Facet.normalize('/sizes/1\\/2')).toBe('/sizes/1\\/2')
+    expect(Facet.normalize('/tools\\')).toBe('/tools\\\\')
+    for (const path of ['electronics//phones/', '/sizes/1\\/2', '/tools\\', '//']) {
+      expect(Facet.isValid(Facet.normalize(path))).toBe(true)
+    }
+    expect(Facet.fromString(Facet.normalize('/sizes/1\\/2/')).toPath()).toEqual(['sizes', '1/2'])
+  })
+
+  it('test_facet_encoded', () => {
+    const facet = Facet.fromString('/electronics/phones')
+    const encoded = facet.encoded()
+    expect(Buffer.isBuffer(encoded)).toBe(true)
+    expect(Facet.fromEncoded(encoded).toPathStr()).toBe('/electronics/phones')
+    expect(Facet.fromEncoded([...encoded]).toPathStr()).toBe('/electronics/phones')
+    expect(Facet.root().encoded().length).toBe(0)
+
+    const facets = ['/ab', '/a/c', '/a', '/a/b'].map((path) => Facet.fromString(path))
+    const sorted = [...facets].sort((a, b) => a.compareTo(b)).map((f) => f.toPathStr())
+    expect(sorted).toEqual(['/a', '/a/b', '/a/c', '/ab'])
+    const byBytes = [...facets].sort((a, b) => Buffer.compare(a.encoded(), b.encoded())).map((f) => f.toPathStr())
+    expect(byBytes).toEqual(sorted)
+    expect(facet.compareTo(Facet.fromString('/electronics/phones'))).toBe(0)
+  })
+
+  it('test_search_faceted', () => {
+    const schema = new SchemaBuilder().addTextField('title', { stored: true }).addFacetField('category').build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    const categories = ['/books/scifi', '/books/scifi', '/books/poetry', '/music/jazz']
+    for (const category of categories) {
+      const doc = new Document()
+      doc.addText('title', 'product')
+      doc.addFacet('category', Facet.fromString(category))
+      writer.addDocument(doc)
+    }
+    writer.commit()
+    index.reload()
+
+    const result = index.searcher().searchFaceted(index.parseQuery('product', ['title']), {
+      facets: [
+        { field: 'category' },
+        { field: 'category', root: '/books', topK: 1 },
+      ],
+      limit: 2,
+    })
+    expect(result.hits.length).toBe(2)
+    expect(result.count).toBe(4)
+    expect(result.facets[0]).toEqual({
+      field: 'category',
+      root: '/',
+      counts: [
+        { facet: '/books', count: 3 },
+        { facet: '/music', count: 1 },
+      ],
+    })
+    expect(result.facets[1].counts).toEqual([{ facet: '/books/scifi', count: 2 }])
+
+    expect(() => index.searcher().searchFaceted(Query.allQuery(), { facets: [{ field: 'title' }] })).toThrow()
+  })
+
+  it('test_facet_navigator', () => {
+    const schema = new SchemaBuilder().addTextField('title').addFacetField('category').build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    const categories = ['/electronics/phones/android', '/electronics/phones/ios', '/electronics/tv', '/books/poetry']
+    for (const category of categories) {
+      const doc = new Document()
+      doc.addText('title', category.endsWith('ios') ? 'apple' : 'product')
+      doc.addFacet('category', Facet.fromString(category))
+      writer.addDocument(doc)
+    }
+    writer.commit()
+    index.reload()
+
+    const navigator = new FacetNavigator(index.searcher(), 'category')
+    const navigation = navigator.navigate('/electronics/phones')
+    expect(navigation.facet).toBe('/electronics/phones')
+    expect(navigation.count).toBe(2)
+    expect(navigation.breadcrumbs).toEqual([
+      { facet: '/', label: '', count: 4 },
+      { facet: '/electronics', label: 'electronics', count: 3 },
+      { facet: '/electronics/phones', label: 'phones', count: 2 },
+    ])
+    expect(navigation.children).toEqual([
+      { facet: '/electronics/phones/android', count: 1 },
+      { facet: '/electronics/phones/ios', count: 1 },
+    ])
+    expect(navigation.siblings).toEqual([
+      { facet: '/electronics/phones', count: 2 },
+      { facet: '/electronics/tv', count: 1 },
+    ])
+
+    const root = navigator.navigate('/', index.parseQuery('product', ['title']))
+    expect(root.count).toBe(3)
+    expect(root.siblings).toEqual([])
+    expect(root.children).toEqual([
+      { facet: '/books', count: 1 },
+      { facet: '/electronics', count: 2 },
+    ])
+    expect(() => new FacetNavigator(index.searcher(), 'title')).toThrow("Field 'title' is not a facet field.")
+  })
+
+  it('test_search_faceted_multi_select', () => {
+    const schema = new SchemaBuilder()
+      .addTextField('title', { stored: true })
+      .addFacetField('color')
+      .addFacetField('size')
+      .build()
+    const index = new Index(schema)
+    const writer = index.writer()
+    const products = [
+      ['/red', '/s'],
+      ['/red', '/m'],
+      ['/blue', '/m'],
+      ['/green', '/l'],
+    ]
+    for (const [color, size] of products) {
+      const doc = new Document()
+      doc.addText('title', 'shirt')
+      doc.addFacet('color', Facet.fromString(color))
+      doc.addFacet('size', Facet.fromString(size))
+      writer.addDocument(doc)
+    }
+    writer.commit()
+    index.reload()
+
+    const result = index
+      .searcher()
+      .searchFacetedMultiSelect(
+        index.parseQuery('shirt', ['title']),
+        [
+          { field: 'color', facets: ['/red', '/blue'] },
+          { field: 'size', facets: ['/m'] },
+        ],
+        { facets: [{ field: 'color' }, { field: 'size' }] },
+      )
+    expect(result.count).toBe(2)
+    expect(result.hits.length).toBe(2)
+    // The colors are counted among the 'm' shirts, the sizes among the red and blue ones.
+    expect(result.facets[0].counts).toEqual([
+      { facet: '/blue', count: 1 },
+      { facet: '/red', count: 1 },
+    ])
+    expect(result.facets[1].counts).toEqual([
+      { facet: '/m', count: 2 },
+      { facet: '/s', count: 1 },
+    ])
+
+    expect(() =>
+      index.searcher().searchFacetedMultiSelect(Query.allQuery(), [{ field: 'title', facets: ['/a'] }], { facets: [] }),
+    ).toThrow("Field 'title' is not a facet field.")
+  })
 })
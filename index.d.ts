@@ -5,11 +5,48 @@ export declare class AllButQueryForbiddenError {
   toString(): string
 }
 
+export declare class CharFilter {
+
+}
+
+export declare class CharFilterStatic {
+  /**
+   * Replaces every match of a regular expression.
+   *
+   * @param pattern - The regular expression.
+   * @param replacement - The replacement text, where `$1` or `${name}` refer
+   *   to the groups of the match.
+   */
+  static patternReplace(pattern: string, replacement: string): CharFilter
+  /**
+   * Removes HTML markup.
+   *
+   * Tags and comments are removed, along with the content of `script` and
+   * `style` elements. Numeric character references and the `&amp;`, `&lt;`,
+   * `&gt;`, `&quot;`, `&apos;` and `&nbsp;` entities are decoded. Tags other
+   * than inline ones (`b`, `em`, `span`...) are replaced by a space so that
+   * the words around them are not joined.
+   */
+  static htmlStrip(): CharFilter
+}
+
 /** The format for the date field is not RFC 3339 compliant. */
 export declare class DateFormatError {
   toString(): string
 }
 
+/**
+ * Iterator over the terms of a text field across all the segments of an
+ * index, in lexicographic order, see `Index.exportDictionary`.
+ *
+ * The terms are read a page at a time, after the last term read, so that
+ * the whole dictionary is never held in memory. Like `Searcher.docFreq`,
+ * the frequencies count the deleted documents until their segment is merged.
+ */
+export declare class DictionaryIterator {
+  [Symbol.iterator](): Iterator<DictionaryEntry, void, void>
+}
+
 /**
  * Tantivy's Document is the object that can be indexed and then searched for.
  *
@@ -42,18 +79,35 @@ export declare class DateFormatError {
 export declare class Document {
   /** Creates a new document. */
   constructor()
-  /** Extend the document with field values from a JavaScript object. */
-  extend(jsObj: object, schema?: Schema | undefined | null): void
-  /** Create a document from a JavaScript object. */
-  static fromDict(jsObj: object, schema?: Schema | undefined | null): Document
+  /**
+   * Extend the document with field values from a JavaScript object.
+   *
+   * The values are appended after the values already in the document, unless
+   * `overwrite` is true, in which case they replace the values of their field.
+   * See `fromDict` for `coerce`.
+   */
+  extend(jsObj: object, schema?: Schema | undefined | null, overwrite?: boolean | undefined | null, coerce?: boolean | undefined | null): void
+  /**
+   * Create a document from a JavaScript object.
+   *
+   * If a schema is given, the values are converted to the type of their
+   * field. The numbers given to numeric fields must then fit their type:
+   * negative numbers for unsigned fields, fractional numbers for integer
+   * fields and values that are not numbers, such as booleans, are rejected.
+   * If `coerce` is true, they are converted instead: negative numbers lose
+   * their sign, fractional numbers are truncated and booleans count as 0
+   * or 1.
+   */
+  static fromDict(jsObj: object, schema?: Schema | undefined | null, coerce?: boolean | undefined | null): Document
   /**
    * Returns a JavaScript object with the different field values.
    *
    * In tantivy, `Document` can hold multiple values for a single field.
    *
-   * For this reason, the object will associate a list of values for every field.
+   * For this reason, the object will associate a list of values for every field,
+   * unless `flatten` is set, see `ToDictOptions`.
    */
-  toDict(): object
+  toDict(options?: ToDictOptions | undefined | null): object
   /**
    * Add a text value to the document.
    *
@@ -82,6 +136,14 @@ export declare class Document {
    * @param value - The float that will be added to the document.
    */
   addFloat(fieldName: string, value: number): void
+  /**
+   * Add a geo point value to the document.
+   *
+   * @param fieldName - The geo point field for which we are adding the point.
+   * @param lat - The latitude, in degrees.
+   * @param lon - The longitude, in degrees.
+   */
+  addGeoPoint(fieldName: string, lat: number, lon: number): void
   /**
    * Add a boolean value to the document.
    *
@@ -109,6 +171,13 @@ export declare class Document {
    * @param bytes - The bytes (as Buffer or Uint8Array) that will be added to the document.
    */
   addBytes(fieldName: string, bytes: Uint8Array): void
+  /**
+   * Add a dense vector value to the document.
+   *
+   * @param fieldName - The vector field for which we are adding the vector.
+   * @param vector - The vector components, stored as 32-bit floats.
+   */
+  addVector(fieldName: string, vector: Array<number>): void
   /**
    * Add a JSON value to the document.
    *
@@ -127,6 +196,37 @@ export declare class Document {
    * @throws Raises an error if the IP address is invalid.
    */
   addIpAddr(fieldName: string, value: string): void
+  /**
+   * Remove all the values of a field.
+   *
+   * @param fieldName - The field to remove.
+   *
+   * @returns True if the document had values for this field.
+   */
+  remove(fieldName: string): boolean
+  /**
+   * Replace the values of a field.
+   *
+   * @param fieldName - The field for which we are setting the values.
+   * @param values - A single value or an array of values, converted like
+   *         the values given to `fromDict`.
+   * @param schema - If given, the values are converted to the type of the field.
+   * @param coerce - Convert the numbers not fitting a numeric field instead
+   *         of rejecting them, see `fromDict`.
+   *
+   * @throws Raises an error if the field is not in the schema, or if a value
+   *         cannot be converted.
+   */
+  set(fieldName: string, values: unknown, schema?: Schema | undefined | null, coerce?: boolean | undefined | null): void
+  /**
+   * Add all the values of another document to this document.
+   *
+   * The values of a field present in both documents are appended after the
+   * values already in this document.
+   *
+   * @param other - The document whose values are added.
+   */
+  merge(other: Document): void
   /** Returns the number of added fields that have been added to the document */
   get numFields(): number
   /** True if the document is empty, False otherwise. */
@@ -238,10 +338,26 @@ export declare class Explanation {
  * ```
  */
 export declare class Facet {
-  /** Creates a `Facet` from its binary representation. */
-  static fromEncoded(encodedBytes: Array<number>): Facet
+  /** Creates a `Facet` from its binary representation, see `encoded`. */
+  static fromEncoded(encodedBytes: Uint8Array | Array<number>): Facet
   /** Create a new instance of the "root facet" Equivalent to /. */
   static root(): Facet
+  /**
+   * The binary representation of the facet, as stored in the index.
+   *
+   * Comparing the encoded bytes of facets orders them like `compareTo`.
+   */
+  encoded(): Buffer
+  /**
+   * Compare the facet with another one, in the order of the index: a facet
+   * comes right before its subfacets, and sibling facets are sorted by
+   * name, e.g. `/a`, `/a/b`, `/a/c`, `/ab`.
+   *
+   * @param other - The facet to compare with.
+   * @returns A negative number if this facet comes first, a positive one if
+   *          the other does, 0 if they are equal.
+   */
+  compareTo(other: Facet): number
   /** Returns true if the facet is the root facet /. */
   get isRoot(): boolean
   /**
@@ -284,6 +400,29 @@ export declare class Facet {
    * @returns The facet path as a string
    */
   toString(): string
+  /**
+   * Check if a string is a well-formed facet path.
+   *
+   * A path starts with `/` and has no empty segment, e.g. `/a//b` and `/a/`
+   * are invalid, except the root facet `/`. A `/` or `\` within a segment
+   * must be escaped with `\`.
+   *
+   * @param path - The facet path to validate
+   * @returns True if the path is valid
+   */
+  static isValid(path: string): boolean
+  /**
+   * Normalize a facet path, typically given by a user, into a valid one.
+   *
+   * The leading `/` is added if missing, empty segments are dropped, such as
+   * the ones of trailing or repeated slashes, and a trailing `\` is
+   * escaped. For instance `electronics//phones/` becomes
+   * `/electronics/phones`.
+   *
+   * @param path - The facet path to normalize
+   * @returns The normalized facet path, `/` if it has no segment
+   */
+  static normalize(path: string): string
 }
 
 /** The format for the facet field is invalid. */
@@ -291,6 +430,35 @@ export declare class FacetFormatError {
   toString(): string
 }
 
+/**
+ * Drill-down navigation in the facets of a field.
+ *
+ * Example:
+ * ```javascript
+ * const navigator = new FacetNavigator(index.searcher(), "category");
+ * const { breadcrumbs, children } = navigator.navigate("/electronics/phones");
+ * ```
+ */
+export declare class FacetNavigator {
+  /**
+   * @param searcher - The searcher the counts are computed with.
+   * @param fieldName - A facet field.
+   */
+  constructor(searcher: Searcher, fieldName: string)
+  /**
+   * Count the children, the siblings and the ancestors of a facet, in a
+   * single pass over the matching documents.
+   *
+   * @param path - The current facet, e.g. '/electronics/phones'. '/' is the
+   *         root.
+   * @param query - Restrict the counts to the documents matching this query.
+   *         Defaults to all the documents.
+   *
+   * @returns FacetNavigation object.
+   */
+  navigate(path: string, query?: Query | undefined | null): FacetNavigation
+}
+
 /** The query references a field that is not in the schema. */
 export declare class FieldDoesNotExistError {
   /** The name of the field causing the error. */
@@ -317,17 +485,67 @@ export declare class Filter {
 export declare class FilterStatic {
   /** AlphaNumOnlyFilter */
   static alphanumOnly(): Filter
-  /** AsciiFoldingFilter */
-  static asciiFold(): Filter
-  static lowercase(): Filter
+  /**
+   * AsciiFoldingFilter
+   *
+   * @param preserveOriginal - If true, a token changed by the folding is
+   *   emitted unchanged, then folded, both at its position and offsets. The
+   *   folded term matches every spelling and the original term the exact one.
+   *   The query parser turns `café` into a phrase of both terms at the same
+   *   position, which only matches the exact spelling: query the folded term
+   *   to match every spelling.
+   */
+  static asciiFold(preserveOriginal?: boolean | undefined | null): Filter
+  /**
+   * LowerCaser
+   *
+   * @param preserveOriginal - If true, a token changed by the lowercasing is
+   *   emitted unchanged, then lowercased, both at its position and offsets.
+   */
+  static lowercase(preserveOriginal?: boolean | undefined | null): Filter
   /**
    * RemoveLongFilter
    *
    * @param lengthLimit - max character length of token.
    */
   static removeLong(lengthLimit: number): Filter
-  /** Stemmer */
-  static stemmer(language: string): Filter
+  /**
+   * Removes the tokens shorter than a length.
+   *
+   * @param length - min character length of token.
+   */
+  static minLength(length: number): Filter
+  /**
+   * Keeps only the tokens found in a list of words.
+   *
+   * @param words - the words to keep, compared to the tokens as they are
+   *   when reaching this filter (e.g. after lowercasing).
+   */
+  static keepWords(words: Array<string>): Filter
+  /**
+   * Removes the leading and trailing whitespace of the tokens.
+   *
+   * Tokens made only of whitespace are removed.
+   */
+  static trim(): Filter
+  /**
+   * Stemmer
+   *
+   * @param language - Stemmer language, one of the languages of `Filter.stopword()`.
+   * @param variant - The stemming algorithm to use for the language:
+   *   'snowball' (default) for every language, also named 'porter2' for
+   *   english. English also has 'porter', the original Porter stemmer, and
+   *   'light', which only removes plurals.
+   */
+  static stemmer(language: string, variant?: string | undefined | null): Filter
+  /**
+   * Stemmer, selected by the name of its algorithm.
+   *
+   * @param name - A language name, as accepted by `Filter.stemmer()`, for its
+   *   Snowball stemmer, or the name of an english stemming algorithm:
+   *   'porter', 'porter2' or 'englishLight'.
+   */
+  static stemmerCustom(name: string): Filter
   /**
    * StopWordFilter (builtin stop word list)
    *
@@ -381,6 +599,19 @@ export declare class FilterStatic {
  */
 export declare class Index {
   static open(path: string): Index
+  /**
+   * Open an index, checking that its schema is the expected one.
+   *
+   * Raises an error if the index cannot be opened, or if its schema differs
+   * from `expectedSchema`. The message of the latter names the fields
+   * missing from the index, the fields it has in excess and the fields
+   * whose type or options differ. `Schema.diff` returns the differences
+   * as structured data.
+   *
+   * @param path - The directory of the index.
+   * @param expectedSchema - The schema the index must have.
+   */
+  static openChecked(path: string, expectedSchema: Schema): Index
   constructor(schema: Schema, path?: string | undefined | null, reuse?: boolean | undefined | null)
   /**
    * Create a `IndexWriter` for the index.
@@ -399,9 +630,79 @@ export declare class Index {
    *         should use. If this value is 0, tantivy will choose
    *         automatically the number of threads.
    *
+   * The heap size can also be replaced by an object of options, see
+   * `IndexWriterOptions`, e.g. `writer({ uniqueField: 'id' })`.
+   *
+   * Raises error if there was an error while creating the writer.
+   */
+  writer(heapSize?: number | IndexWriterOptions | undefined | null, numThreads?: number | undefined | null): IndexWriter
+  /**
+   * Create a `SerializedWriter` for the index.
+   *
+   * For an index stored in a directory, the writer is shared by every
+   * `SerializedWriter` of that directory in the process, worker threads
+   * included, whose operations go through the same queue.
+   *
+   * @param options - Options of the writer, see `IndexWriterOptions`. They are
+   *         ignored when the writer of the directory is already open.
+   *
+   * Raises error if there was an error while creating the writer.
+   */
+  serializedWriter(options?: IndexWriterOptions | undefined | null): SerializedWriter
+  /**
+   * Create an `IndexSession` for the index, whose searches see the
+   * documents added before them without committing nor reloading.
+   *
+   * @param options - Options of the writer of the session, see
+   *         `IndexWriterOptions`.
+   *
+   * Raises error if there was an error while creating the writer.
+   */
+  session(options?: IndexWriterOptions | undefined | null): IndexSession
+  /**
+   * Create a `TenantIndex` for the index, restricting every addition,
+   * deletion and search to the documents of a tenant.
+   *
+   * @param tenantField - The field holding the tenant ids. It must be an
+   *         indexed text field using the `raw` tokenizer.
+   * @param options - Options of the writer of the tenant index, see
+   *         `IndexWriterOptions`.
+   *
    * Raises error if there was an error while creating the writer.
    */
-  writer(heapSize?: number | undefined | null, numThreads?: number | undefined | null): IndexWriter
+  tenantIndex(tenantField: string, options?: IndexWriterOptions | undefined | null): TenantIndex
+  /**
+   * Copy all the stored documents of this index into a new index with another schema.
+   *
+   * This is the way to "alter" the schema of an index: fields missing from
+   * the new schema are dropped, new fields are left empty. Only stored
+   * fields can be carried over. Tokenizers registered on this index are
+   * shared with the new one.
+   *
+   * @param newSchema - The schema of the new index.
+   * @param destPath - Path of the new index. If undefined, the new index is created in RAM.
+   * @param options - Reindexing options, see `ReindexOptions`.
+   * @param mapDocument - Optional callback receiving each document, after the
+   *         field renames, and returning the document to index, or null to skip it.
+   *
+   * @returns The new index.
+   */
+  reindexWithSchema(newSchema: Schema, destPath?: string | undefined | null, options?: ReindexOptions | undefined | null, mapDocument?: ((arg: Document) => Document | null | undefined) | undefined | null): Index
+  /**
+   * Merge the index down to a few segments, purging the deleted documents,
+   * then delete the files that are no longer used. This is the "optimize"
+   * of other search engines, best run when the index is not being written.
+   *
+   * The segments are merged on the calling thread, which blocks until the
+   * compaction is done. The reader is reloaded afterwards.
+   *
+   * @param options - See `CompactOptions`.
+   *
+   * @returns CompactResult object.
+   *
+   * Raises error if another writer is open on the index.
+   */
+  compact(options?: CompactOptions | undefined | null): CompactResult
   /**
    * Configure the index reader.
    *
@@ -418,6 +719,47 @@ export declare class Index {
    * The same searcher must be used for a given query, as it ensures the use of a consistent segment set.
    */
   searcher(): Searcher
+  /**
+   * Run a callback against a single searcher.
+   *
+   * Every search, aggregation and document fetch made through the searcher
+   * passed to the callback observes the same index generation, even if the
+   * index is reloaded in between.
+   *
+   * @param callback - Function receiving the searcher.
+   *
+   * @returns The value returned by the callback.
+   */
+  withSearcher(callback: (arg: Searcher) => unknown): unknown
+  /**
+   * The generation of the searchers currently handed out by `searcher()`.
+   *
+   * Compare it with `Searcher.generation` to detect a stale searcher.
+   */
+  get generation(): number
+  /**
+   * Whether the committed segments hold deleted documents, which keep
+   * taking space until their segment is merged. A maintenance job can use it
+   * to decide whether to `compact` the index.
+   */
+  hasPendingDeletes(): boolean
+  /**
+   * Export the terms of a text field with their frequencies, e.g. to build
+   * a spell checker or to analyze the vocabulary of the index.
+   *
+   * The terms are those of the searcher of the index at the time of the
+   * call, merged across the segments and in lexicographic order. They are
+   * read as the iterator is consumed.
+   *
+   * @param fieldName - The text field whose terms are exported.
+   * @param options - See `DictionaryOptions`.
+   *
+   * @returns An iterator of `DictionaryEntry`, e.g.
+   *          `for (const { term, docFreq } of index.exportDictionary('body')) { ... }`.
+   *
+   * Raises error if the field is not an indexed text field.
+   */
+  exportDictionary(fieldName: string, options?: DictionaryOptions | undefined | null): DictionaryIterator
   /**
    * Check if the given path contains an existing index.
    * @param path - The path where tantivy will search for an index.
@@ -427,8 +769,42 @@ export declare class Index {
    * Raises error if the directory cannot be opened.
    */
   static exists(path: string): boolean
+  /**
+   * Describe the index stored at a path without opening it, e.g. to tell
+   * whether an index written by another version of the binding can be
+   * opened, and how to migrate it otherwise.
+   *
+   * Only `meta.json` and the footers of the segment files are read, and the
+   * directory is not locked.
+   *
+   * @param path - The directory of the index.
+   *
+   * @returns See `IndexInspection`.
+   *
+   * Raises error if the directory holds no index.
+   */
+  static inspect(path: string): IndexInspection
   /** The schema of the current index. */
   get schema(): Schema
+  /**
+   * Check a JSON document against the schema, as `IndexWriter.addJson`
+   * would parse it, without adding it.
+   *
+   * Unlike `addJson`, which stops at the first invalid value, every value is
+   * checked. Fields missing from the schema are ignored, as when adding the
+   * document.
+   *
+   * @param json - The document, as a JSON object.
+   *
+   * @returns JsonValidationReport object.
+   */
+  validateJson(json: string): JsonValidationReport
+  /**
+   * The settings the index was created with.
+   *
+   * They don't include a sort field, tantivy 0.25 no longer sorts indexes.
+   */
+  settings(): IndexSettings
   /**
    * Update searchers so that they reflect the state of the last .commit().
    *
@@ -454,8 +830,37 @@ export declare class Index {
    *         `prefix` determines if terms which are prefixes of the given term match the query.
    *         `distance` determines the maximum Levenshtein distance between terms matching the query and the given term.
    *         `transpose_cost_one` determines if transpositions of neighbouring characters are counted only once against the Levenshtein distance.
+   *
+   * @param now - Timestamp in milliseconds that `now` resolves to in the date
+   *         math expressions on date fields, e.g. `date:[now-7d/d TO now]`.
+   *         Defaults to the current time.
+   *
+   * @param jsonPaths - A dictionary keyed on JSON default fields giving the paths
+   *         searched by the terms without field, see `setDefaultJsonPaths`.
+   *         Overrides the paths registered on the index for these fields.
+   *
+   * @param options - How quoted phrases are parsed, see `ParseQueryOptions`.
+   */
+  parseQuery(query: string, defaultFieldNames?: Array<string> | undefined | null, fieldBoosts?: Record<string, number> | undefined | null, fuzzyFields?: Record<string, [boolean, number, boolean]> | undefined | null, now?: number | undefined | null, jsonPaths?: Record<string, Array<string>> | undefined | null, options?: ParseQueryOptions | undefined | null): Query
+  /**
+   * Build a DisjunctionMaxQuery from its JSON description, e.g. from a
+   * relevance configuration file.
+   *
+   * Example:
+   * ```json
+   * {
+   *   "queries": ["title:whale", { "query": "body:whale", "boost": 0.5 }],
+   *   "tieBreaker": 0.1
+   * }
+   * ```
+   *
+   * @param json - The description of the query. Each subquery, following the
+   *         tantivy query language, is either a string or an object boosting
+   *         it. `tieBreaker` is optional, see `Query.disjunctionMaxQuery`.
+   * @param defaultFieldNames - A list of fields used to search if no
+   *         field is specified in a subquery.
    */
-  parseQuery(query: string, defaultFieldNames?: Array<string> | undefined | null, fieldBoosts?: Record<string, number> | undefined | null, fuzzyFields?: Record<string, [boolean, number, boolean]> | undefined | null): Query
+  parseDisjunctionMaxQuery(json: string, defaultFieldNames?: Array<string> | undefined | null): Query
   /**
    * Parse a query leniently.
    *
@@ -479,127 +884,513 @@ export declare class Index {
    *         `distance` determines the maximum Levenshtein distance between terms matching the query and the given term.
    *         `transpose_cost_one` determines if transpositions of neighbouring characters are counted only once against the Levenshtein distance.
    *
+   * @param now - Timestamp in milliseconds that `now` resolves to in the date
+   *         math expressions on date fields, e.g. `date:[now-7d/d TO now]`.
+   *         Defaults to the current time.
+   *
+   * @param jsonPaths - A dictionary keyed on JSON default fields giving the paths
+   *         searched by the terms without field, see `setDefaultJsonPaths`.
+   *         Overrides the paths registered on the index for these fields.
+   *
+   * @param options - How quoted phrases are parsed, see `ParseQueryOptions`.
+   *
    * Returns a tuple containing the parsed query and a list of error messages.
    */
-  parseQueryLenient(query: string, defaultFieldNames?: Array<string> | undefined | null, fieldBoosts?: Record<string, number> | undefined | null, fuzzyFields?: Record<string, [boolean, number, boolean]> | undefined | null): [Query, Array<string>]
+  parseQueryLenient(query: string, defaultFieldNames?: Array<string> | undefined | null, fieldBoosts?: Record<string, number> | undefined | null, fuzzyFields?: Record<string, [boolean, number, boolean]> | undefined | null, now?: number | undefined | null, jsonPaths?: Record<string, Array<string>> | undefined | null, options?: ParseQueryOptions | undefined | null): [Query, Array<string>]
   /**
-   * Register a custom text analyzer by name. (Confusingly,
-   * this is one of the places where Tantivy uses 'tokenizer' to refer to a
-   * TextAnalyzer instance.)
+   * Parse the text typed by an end user in a search box, the syntax it is
+   * not allowed to use being escaped so that it is searched as text, e.g.
+   * `title:sea^2` searches the words `title`, `sea` and `2` by default.
+   *
+   * The text never fails to parse: the syntax errors left, such as a
+   * parenthesis not closed, are recovered like `parseQueryLenient` does.
+   *
+   * @param text - The text typed by the user.
+   * @param defaultFieldNames - A list of fields used to search the words.
+   *         Defaults to all the indexed fields.
+   * @param options - The syntax allowed, see `UserQueryOptions`.
    */
-  registerTokenizer(name: string, analyzer: TextAnalyzer): void
-}
-
-/**
- * IndexWriter is the user entry-point to add documents to the index.
- *
- * To create an IndexWriter first create an Index and call the writer() method
- * on the index object.
- */
-export declare class IndexWriter {
+  parseUserQuery(text: string, defaultFieldNames?: Array<string> | undefined | null, options?: UserQueryOptions | undefined | null): Query
   /**
-   * Add a document to the index.
+   * Describe how a query is parsed, for debugging relevance.
    *
-   * If the indexing pipeline is full, this call may block.
+   * Every clause, boost and leaf of the query is described, the leaves with
+   * their query type, field and terms after analysis, as the parser would
+   * build them.
    *
-   * Returns an `opstamp`, which is an increasing integer that can be used
-   * by the client to align commits with its own document queue.
-   * The `opstamp` represents the number of documents that have been added
-   * since the creation of the index.
+   * @param query - the query, following the tantivy query language.
+   * @param options - Parsing options, see `DebugQueryOptions`.
+   *
+   * @returns The parsed query tree, as a JSON string.
    */
-  addDocument(doc: Document): bigint
+  debugQuery(query: string, options?: DebugQueryOptions | undefined | null): string
   /**
-   * Helper for the `add_document` method, but passing a json string.
+   * Register the paths of a JSON field searched by the terms without field.
    *
-   * If the indexing pipeline is full, this call may block.
+   * A JSON field among the default fields of `parseQuery` only matches the
+   * terms without field at the root of its objects. Once paths are
+   * registered, these terms search the paths of the field instead, e.g.
+   * after `setDefaultJsonPaths('attrs', ['color', 'size.unit'])` the query
+   * `red` matches `{ attrs: { color: 'red' } }`.
    *
-   * Returns an `opstamp`, which is an increasing integer that can be used
-   * by the client to align commits with its own document queue.
-   * The `opstamp` represents the number of documents that have been added
-   * since the creation of the index.
+   * @param fieldName - A JSON field of the schema.
+   * @param paths - Paths inside the field. An empty list removes the paths
+   *         registered for the field.
    */
-  addJson(json: string): bigint
+  setDefaultJsonPaths(fieldName: string, paths: Array<string>): void
   /**
-   * Commits all of the pending changes
+   * List the files making up the last commit, ready to be shipped to a replica.
    *
-   * A call to commit blocks. After it returns, all of the document that
-   * were added since the last commit are published and persisted.
+   * The returned list contains the files of every searchable segment followed
+   * by `meta.json`, which must be applied last on the replica.
    *
-   * In case of a crash or an hardware failure (as long as the hard disk is
-   * spared), it will be possible to resume indexing from this point.
+   * @param exclude - Paths of files the replica already holds. Segment files are
+   *         immutable, so they never need to be shipped twice.
    *
-   * Returns the `opstamp` of the last document that made it in the commit.
+   * Raises error if a file cannot be read from the index directory.
    */
-  commit(): bigint
+  listFilesForReplication(exclude?: Array<string> | undefined | null): Array<ReplicationFile>
   /**
-   * Rollback to the last commit
+   * Apply files produced by `listFilesForReplication()` on a primary index.
    *
-   * This cancels all of the update that happened before after the last
-   * commit. After calling rollback, the index is in the same state as it
-   * was after the last commit.
+   * Segment files are written first and `meta.json` is atomically replaced
+   * last, so readers never observe a commit whose segments are missing. The
+   * reader is reloaded once the files have been applied.
+   *
+   * @param files - The files shipped from the primary index.
+   *
+   * Raises error if a path is not a plain file name or if writing fails.
    */
-  rollback(): bigint
+  applyReplicatedFiles(files: Array<ReplicationFile>): void
   /**
-   * Detect and removes the files that are not used by the index anymore.
+   * Verify the checksums of the files of the searchable segments, stored in
+   * their footer, e.g. after restoring a backup or copying the index over
+   * the network.
    *
-   * Note: This is currently a no-op. Tantivy's garbage collection requires
-   * an async runtime. A future version may implement this properly.
+   * Every file is read in full, which takes a while on a large index.
+   *
+   * @returns The result of every file, sorted by path. The index is intact
+   *          if all of them are 'ok'.
+   *
+   * Raises error if the meta of the index can't be read.
    */
-  garbageCollectFiles(): void
-  /** Deletes all documents from the index. */
-  deleteAllDocuments(): void
+  verifyChecksums(): Array<ChecksumResult>
   /**
-   * The opstamp of the last successful commit.
+   * Configure the filter cache of the index.
    *
-   * This is the opstamp the index will rollback to if there is a failure
-   * like a power surge.
+   * The cache holds the documents matched by queries wrapped with
+   * `cachedFilter()`, per segment. Reconfiguring the cache clears it.
    *
-   * This is also the opstamp of the commit that is currently available
-   * for searchers.
+   * @param maxEntries - The maximum number of (query, segment) entries to
+   *         keep. Least recently used entries are evicted first. Defaults to 1024,
+   *         0 disables the cache.
+   * @param ttlMs - If given, entries older than this many milliseconds are
+   *         discarded.
    */
-  get commitOpstamp(): bigint
+  configFilterCache(maxEntries?: number | undefined | null, ttlMs?: number | undefined | null): void
   /**
-   * Delete all documents containing a given term.
+   * Wrap a query so that its matching documents are cached.
    *
-   * This method does not parse the given term and it expects the term to be
-   * already tokenized according to any tokenizers attached to the field. This
-   * can often result in surprising behaviour. For example, if you want to store
-   * UUIDs as text in a field, and those values have hyphens, and you use the
-   * default tokenizer which removes punctuation, you will not be able to delete
-   * a document added with particular UUID, by passing the same UUID to this
-   * method. In such workflows where deletions are required, particularly with
-   * string values, it is strongly recommended to use the
-   * "raw" tokenizer as this will match exactly. In situations where you do
-   * want tokenization to be applied, it is recommended to instead use the
-   * `delete_documents_by_query` method instead, which will delete documents
-   * matching the given query using the same query parser as used in search queries.
+   * This is meant for filter-style queries that are repeated across
+   * requests: the returned query gives the same constant score to every
+   * matching document, and is best used as a `Must` clause of a boolean query.
    *
-   * @param fieldName - The field name for which we want to filter deleted docs.
-   * @param fieldValue - JavaScript value with the value we want to filter.
+   * The filters with the same `Query.fingerprint` share their cached
+   * documents.
    *
-   * If the field_name is not on the schema raises error.
-   * If the field_value is not supported raises error.
+   * @param query - The filter query to cache.
    */
-  deleteDocumentsByTerm(fieldName: string, fieldValue: unknown): bigint
+  cachedFilter(query: Query): Query
+  /** Statistics of the filter cache. */
+  filterCacheStats(): FilterCacheStats
+  /** Remove every entry of the filter cache and reset its statistics. */
+  clearFilterCache(): void
   /**
-   * Delete all documents matching a given query.
+   * Register a custom text analyzer by name. (Confusingly,
+   * this is one of the places where Tantivy uses 'tokenizer' to refer to a
+   * TextAnalyzer instance.)
    *
-   * @param query - The query to filter the deleted documents.
+   * The analyzer can also be used as the normalizer of a fast text field,
+   * see `TextFieldOptions.fast`.
    *
-   * If the query is not valid raises error.
-   * If the query is not supported raises error.
+   * @param queryJsonFields - JSON fields whose text values are analyzed by
+   *         this analyzer when parsing queries, instead of the tokenizer the
+   *         fields were indexed with. The documents are indexed as before,
+   *         so a mismatch between the analyzer of the indexed values and the
+   *         one of the queries can be fixed without reindexing.
+   *
+   * Raises error if one of `queryJsonFields` is not an indexed JSON field.
    */
-  deleteDocumentsByQuery(query: Query): bigint
+  registerTokenizer(name: string, analyzer: TextAnalyzer, queryJsonFields?: Array<string> | undefined | null): void
   /**
-   * If there are some merging threads, blocks until they all finish
-   * their work and then drop the `IndexWriter`.
+   * Register the index to be shared with the worker threads of the process.
    *
-   * This will consume the `IndexWriter`. Further accesses to the
-   * object will result in an error.
+   * The handle is a number, which can be posted to a worker. The index
+   * created from it by `Index.fromHandle` shares the files, reader, filter
+   * cache and tokenizers of this one, instead of opening the index again.
+   * The handle must be released with `releaseHandle` once it is no longer
+   * needed.
+   *
+   * @returns A handle to pass to `Index.fromHandle` in another worker.
    */
-  waitMergingThreads(): void
-}
-
+  toHandle(): number
+  /**
+   * Create an index from a handle returned by `Index.toHandle`.
+   *
+   * Raises error if the handle is unknown or was released.
+   */
+  static fromHandle(handle: number): Index
+}
+
+/**
+ * An index writer and a reader whose searches see the writes made before
+ * them.
+ *
+ * Before a search, the pending operations are committed and the reader of
+ * the index is reloaded. Searches following each other without writes in
+ * between cost no more than on a plain searcher, but every search following
+ * a write costs a commit: this is meant for tests and small applications,
+ * while bulk indexing is best done with an `IndexWriter` committing in
+ * batches. The session is created by `Index.session()`.
+ *
+ * Example:
+ * ```javascript
+ * const session = index.session();
+ * session.add(Document.fromDict({ title: "The Old Man and the Sea" }));
+ * session.search(index.parseQuery("sea")).count; // 1
+ * ```
+ */
+export declare class IndexSession {
+  /**
+   * Add a document, see `IndexWriter.addDocument`.
+   *
+   * @param doc - The document to add.
+   *
+   * @returns The `opstamp` of the addition.
+   */
+  add(doc: Document): bigint
+  /**
+   * Delete the documents containing a term, see
+   * `IndexWriter.deleteDocumentsByTerm`.
+   *
+   * @param fieldName - The field name for which we want to filter deleted docs.
+   * @param fieldValue - JavaScript value with the value we want to filter.
+   *
+   * @returns The `opstamp` of the deletion.
+   */
+  deleteDocumentsByTerm(fieldName: string, fieldValue: Term | unknown): bigint
+  /**
+   * Delete the documents matching a query.
+   *
+   * @param query - The query to filter the deleted documents.
+   *
+   * @returns The `opstamp` of the deletion.
+   */
+  deleteDocumentsByQuery(query: Query): bigint
+  /**
+   * Commit the pending operations, if any, and reload the reader so that
+   * they are visible to the searchers of the index.
+   *
+   * Searches made through the session do it on their own.
+   */
+  refresh(): void
+  /**
+   * Cancel the operations made since the last refresh.
+   *
+   * @returns The `opstamp` of the last commit.
+   */
+  rollback(): bigint
+  /** A searcher seeing every operation made so far. */
+  searcher(): Searcher
+  /**
+   * Search the index, every operation made so far included, see
+   * `Searcher.search`.
+   *
+   * @returns SearchResult object.
+   */
+  search(query: Query, limit?: number | undefined | null, count?: boolean | undefined | null, orderByField?: string | undefined | null, offset?: number | undefined | null, order?: Order | undefined | null, options?: SearchOptions | undefined | null): SearchResult
+  /**
+   * Commit the pending operations and release the writer, see
+   * `IndexWriter.waitMergingThreads`. Further calls raise an error.
+   */
+  close(): void
+}
+
+/**
+ * IndexWriter is the user entry-point to add documents to the index.
+ *
+ * To create an IndexWriter first create an Index and call the writer() method
+ * on the index object.
+ */
+export declare class IndexWriter {
+  /**
+   * Add a document to the index.
+   *
+   * If the indexing pipeline is full, this call may block.
+   *
+   * Returns an `opstamp`, which is an increasing integer that can be used
+   * by the client to align commits with its own document queue.
+   * The `opstamp` represents the number of documents that have been added
+   * since the creation of the index.
+   */
+  addDocument(doc: Document): bigint
+  /**
+   * Add a parent document along with its nested child documents.
+   *
+   * The children are indexed right before the parent, as a single block, so
+   * they can be joined back to it with `Query.nestedQuery`. The schema must
+   * have been built with `SchemaBuilder.enableNestedDocuments()`. Deleting
+   * the parent deletes its children too.
+   *
+   * @param parent - The parent document.
+   * @param children - The child documents.
+   * @param path - Name of the nested path the children belong to.
+   *
+   * Returns the `opstamp` of the block.
+   */
+  addNestedDocument(parent: Document, children: Array<Document>, path: string): bigint
+  /**
+   * Add a document to the index, without failing on malformed values.
+   *
+   * Unknown fields are skipped, and values that don't match the type of
+   * their field are converted when possible (e.g. `"42"` for an integer
+   * field) or skipped otherwise.
+   *
+   * @param doc - The document to add.
+   *
+   * @returns The fields that were dropped or coerced.
+   */
+  addDocumentLenient(doc: Document): LenientAddReport
+  /**
+   * Add a batch of documents to the index, without failing on malformed values.
+   *
+   * See `addDocumentLenient`.
+   *
+   * @param docs - The documents to add.
+   *
+   * @returns One report per document, in the same order.
+   */
+  addDocumentsLenient(docs: Array<Document>): Array<LenientAddReport>
+  /**
+   * Add a batch of documents to the index, reporting the documents that
+   * can't be added instead of failing the whole batch.
+   *
+   * If the indexing pipeline is full, this call may block.
+   *
+   * @param docs - The documents to add.
+   * @param options - See `BatchOptions`.
+   *
+   * @returns One result per processed document, in the same order.
+   */
+  addDocuments(docs: Array<Document>, options?: BatchOptions | undefined | null): Array<BatchItemResult>
+  /**
+   * Helper for the `add_document` method, but passing a json string.
+   *
+   * If the indexing pipeline is full, this call may block.
+   *
+   * Returns an `opstamp`, which is an increasing integer that can be used
+   * by the client to align commits with its own document queue.
+   * The `opstamp` represents the number of documents that have been added
+   * since the creation of the index.
+   */
+  addJson(json: string): bigint
+  /**
+   * Add a batch of documents given as JSON strings, see `addJson`.
+   *
+   * The documents are all parsed before any is added, and then submitted to
+   * the writer at once, which is much faster than calling `addJson` for each
+   * of them.
+   *
+   * @param jsons - The documents, as JSON strings.
+   *
+   * @returns The `opstamp` of each document, in the same order.
+   *
+   * Raises error if a document can't be parsed, no document being added.
+   */
+  addJsonBatch(jsons: Array<string>): Array<bigint>
+  /**
+   * Commits all of the pending changes
+   *
+   * A call to commit blocks. After it returns, all of the document that
+   * were added since the last commit are published and persisted.
+   *
+   * In case of a crash or an hardware failure (as long as the hard disk is
+   * spared), it will be possible to resume indexing from this point.
+   *
+   * @param payload - Stored along with the commit in the meta file of the
+   *         index, e.g. the offset of the last message of a queue.
+   *
+   * Returns the `opstamp` of the last document that made it in the commit.
+   */
+  commit(payload?: string | undefined | null): bigint
+  /**
+   * Commit the pending changes like `commit`, after checking that the disk
+   * has room for them, rather than failing halfway through writing them.
+   *
+   * The space needed is estimated as the heap size of the writer, bounding
+   * the size of the buffered documents, plus the size of the largest merge
+   * the merge policy may start, plus `minFreeBytes`. For an index in RAM
+   * there is no check.
+   *
+   * @param options - See `CommitGuardOptions`.
+   *
+   * Returns the `opstamp` of the last document that made it in the commit.
+   *
+   * Raises an error whose `code` is 'ENOSPC', without committing, if the
+   * disk of the index has less space available than needed.
+   */
+  commitWithGuard(options?: CommitGuardOptions | undefined | null): bigint
+  /**
+   * The options the writer was created with, the heap size and the number of
+   * threads being the effective ones when they were left to their default.
+   */
+  options(): IndexWriterOptions
+  /**
+   * Call a callback after each successful commit of this writer, e.g. to
+   * acknowledge the offsets of a message queue once their documents are
+   * durable.
+   *
+   * The callback is called asynchronously, once `commit` has returned. It
+   * does not keep the process alive.
+   *
+   * @param callback - Called with a `CommitEvent`. If not given, the current
+   *         callback is removed.
+   */
+  onCommit(callback?: ((event: CommitEvent) => void) | undefined | null): void
+  /**
+   * Rollback to the last commit
+   *
+   * This cancels all of the update that happened before after the last
+   * commit. After calling rollback, the index is in the same state as it
+   * was after the last commit.
+   */
+  rollback(): bigint
+  /**
+   * Detect and removes the files that are not used by the index anymore.
+   *
+   * Note: This is currently a no-op. Tantivy's garbage collection requires
+   * an async runtime. A future version may implement this properly.
+   */
+  garbageCollectFiles(): void
+  /**
+   * Enable or disable the merges the writer schedules after each commit.
+   *
+   * A bulk load is faster with merges disabled: each commit adds a
+   * segment, and the index is merged once at the end with `Index.compact`,
+   * after `waitMergingThreads` released the writer. Merges are enabled by
+   * default.
+   *
+   * @param enabled - Whether the writer merges the segments.
+   */
+  setMergeOperationsEnabled(enabled: boolean): void
+  /** Deletes all documents from the index. */
+  deleteAllDocuments(): void
+  /**
+   * The opstamp of the last successful commit.
+   *
+   * This is the opstamp the index will rollback to if there is a failure
+   * like a power surge.
+   *
+   * This is also the opstamp of the commit that is currently available
+   * for searchers.
+   */
+  get commitOpstamp(): bigint
+  /**
+   * Delete all documents containing a given term.
+   *
+   * This method does not parse the given term and it expects the term to be
+   * already tokenized according to any tokenizers attached to the field. This
+   * can often result in surprising behaviour. For example, if you want to store
+   * UUIDs as text in a field, and those values have hyphens, and you use the
+   * default tokenizer which removes punctuation, you will not be able to delete
+   * a document added with particular UUID, by passing the same UUID to this
+   * method. In such workflows where deletions are required, particularly with
+   * string values, it is strongly recommended to use the
+   * "raw" tokenizer as this will match exactly. In situations where you do
+   * want tokenization to be applied, it is recommended to instead use the
+   * `delete_documents_by_query` method instead, which will delete documents
+   * matching the given query using the same query parser as used in search queries.
+   *
+   * @param fieldName - The field name for which we want to filter deleted docs.
+   * @param fieldValue - JavaScript value with the value we want to filter.
+   *
+   * If the field_name is not on the schema raises error.
+   * If the field_value is not supported raises error.
+   */
+  deleteDocumentsByTerm(fieldName: string, fieldValue: Term | unknown): bigint
+  /**
+   * Delete the documents containing any of the given terms, reporting the
+   * values that can't be converted to a term instead of failing the whole
+   * batch.
+   *
+   * See `deleteDocumentsByTerm`.
+   *
+   * @param fieldName - The field name for which we want to filter deleted docs.
+   * @param fieldValues - The values, or Terms, to filter.
+   * @param options - See `BatchOptions`.
+   *
+   * @returns One result per processed value, in the same order.
+   */
+  deleteDocumentsByTerms(fieldName: string, fieldValues: Array<Term | unknown>, options?: BatchOptions | undefined | null): Array<BatchItemResult>
+  /**
+   * Delete all documents matching a given query.
+   *
+   * @param query - The query to filter the deleted documents.
+   *
+   * If the query is not valid raises error.
+   * If the query is not supported raises error.
+   */
+  deleteDocumentsByQuery(query: Query): bigint
+  /**
+   * Delete all documents matching a query string, parsed like
+   * `Index.parseQuery` does, e.g. for retention scripts:
+   * `writer.deleteByQueryString('timestamp:[* TO now-30d]')`.
+   *
+   * @param queryText - The query, following the tantivy query language.
+   * @param defaultFields - A list of fields used to search if no field is
+   *         specified in the query. Defaults to all the indexed fields.
+   *
+   * @returns The `opstamp` of the deletion.
+   *
+   * If the query is not valid raises error.
+   */
+  deleteByQueryString(queryText: string, defaultFields?: Array<string> | undefined | null): bigint
+  /**
+   * Delete the documents whose expiry, the value of the date field given
+   * by `IndexWriterOptions.expiryField`, is before or at `now`. Like the
+   * other deletions, it is applied by the next commit.
+   *
+   * @param now - Timestamp in milliseconds. Defaults to the current time.
+   *
+   * @returns The number of committed documents which expired.
+   *
+   * Raises error if the writer has no expiry field.
+   */
+  purgeExpired(now?: number | undefined | null): number
+  /**
+   * If there are some merging threads, blocks until they all finish
+   * their work and then drop the `IndexWriter`.
+   *
+   * This will consume the `IndexWriter`. Further accesses to the
+   * object will result in an error.
+   */
+  waitMergingThreads(): void
+  /**
+   * Create a `Writable` stream adding the documents written to it, e.g.
+   * `await pipeline(fs.createReadStream('docs.ndjson'), writer.createWriteStream())`.
+   *
+   * The documents are submitted to the writer in batches, the stream
+   * waiting for a batch to be accepted before taking more data. The stream
+   * commits once it is ended, and fails on the first document that can't be
+   * added.
+   *
+   * @param options - See `WriteStreamOptions`.
+   *
+   * Raises error if the writer was consumed.
+   */
+  createWriteStream(options?: WriteStreamOptions | undefined | null): import('node:stream').Writable
+}
+
 /** The format for the ip field is invalid. */
 export declare class IpFormatError {
   toString(): string
@@ -620,10 +1411,35 @@ export declare class PhrasePrefixRequiresAtLeastTwoTermsError {
 /** Tantivy's Query */
 export declare class Query {
   toString(): string
+  /**
+   * A stable hash of the query tree, e.g. to key a cache of results or to
+   * count the most popular queries.
+   *
+   * Equivalent queries have the same fingerprint: the clauses of a boolean
+   * query, the subqueries of a disjunction max query and the terms of a term
+   * set query may come in any order, at any depth of boosted, constant
+   * score or named queries. Fields are identified by their position in the
+   * schema, so the fingerprint is only meaningful among the queries of an
+   * index. Regex, fuzzy and phrase queries, and the boosts of parsed
+   * queries, are hashed through their description, which may change with
+   * the version of tantivy.
+   *
+   * @returns 16 hexadecimal digits.
+   */
+  fingerprint(): string
+  /**
+   * Register the query to be shared with the worker threads, see
+   * `Index.toHandle`.
+   *
+   * @returns A handle to pass to `Query.fromHandle` in another worker.
+   */
+  toHandle(): number
+  /** Create a query from a handle returned by `Query.toHandle`. */
+  static fromHandle(handle: number): Query
   /** Construct a Tantivy's TermQuery */
-  static termQuery(schema: Schema, fieldName: string, fieldValue: unknown, indexOption?: string | undefined | null): Query
+  static termQuery(schema: Schema, fieldName: string, fieldValue: Term | unknown, indexOption?: string | undefined | null): Query
   /** Construct a Tantivy's TermSetQuery */
-  static termSetQuery(schema: Schema, fieldName: string, fieldValues: Array<unknown>): Query
+  static termSetQuery(schema: Schema, fieldName: string, fieldValues: Array<Term | unknown>): Query
   /** Construct a Tantivy's AllQuery */
   static allQuery(): Query
   /** Construct a Tantivy's EmptyQuery — matches no documents. Useful as a placeholder. */
@@ -638,41 +1454,158 @@ export declare class Query {
    */
   static existsQuery(schema: Schema, fieldName: string): Query
   /**
-   * Construct a Tantivy's FuzzyTermQuery
+   * Construct a Tantivy's FuzzyTermQuery
+   *
+   * # Arguments
+   *
+   * * `schema` - Schema of the target index.
+   * * `field_name` - Field name to be searched.
+   * * `text` - String representation of the query term.
+   * * `distance` - (Optional) Edit distance you are going to alow. When not specified, the default is 1.
+   * * `transposition_cost_one` - (Optional) If true, a transposition (swapping) cost will be 1; otherwise it will be 2. When not specified, the default is true.
+   * * `prefix` - (Optional) If true, prefix levenshtein distance is applied. When not specified, the default is false.
+   */
+  static fuzzyTermQuery(schema: Schema, fieldName: string, text: string, distance?: number | undefined | null, transpositionCostOne?: boolean | undefined | null, prefix?: boolean | undefined | null): Query
+  /**
+   * Construct a Tantivy's PhraseQuery with custom offsets and slop
+   *
+   * # Arguments
+   *
+   * * `schema` - Schema of the target index.
+   * * `field_name` - Field name to be searched.
+   * * `words` - Word list that constructs the phrase. A word can be a term text or a pair of term text and its offset in the phrase.
+   * * `slop` - (Optional) The number of gaps permitted between the words in the query phrase. Default is 0.
+   */
+  static phraseQuery(schema: Schema, fieldName: string, words: Array<unknown>, slop?: number | undefined | null): Query
+  /**
+   * Construct a Tantivy's PhraseQuery from text analyzed like the field
+   *
+   * The text is run through the tokenizer of the field, and each term is placed
+   * at the position of its token, so that the words dropped by the tokenizer,
+   * such as stop words, leave the same gaps as in the indexed documents. Text
+   * yielding a single term gives a TermQuery.
+   *
+   * # Arguments
+   *
+   * * `schema` - Schema of the target index.
+   * * `field_name` - Text field to be searched. It must be indexed with positions.
+   * * `text` - Text of the phrase.
+   * * `options` - (Optional) Slop of the phrase.
+   * * `index` - (Optional) Index providing the tokenizers, needed when the field uses
+   *   one registered by `Index.registerTokenizer`. Only the built-in ones are known otherwise.
+   */
+  static phraseFromText(schema: Schema, fieldName: string, text: string, options?: PhraseFromTextOptions | undefined | null, index?: Index | undefined | null): Query
+  /**
+   * Construct an ordered proximity query
+   *
+   * Matches the documents where the words appear in the given order, with at most
+   * `slop` other words between the first and the last one. Unlike the slop of a
+   * PhraseQuery, which lets the words swap places, the order is always kept:
+   * `['patent', 'claim']` with a slop of 2 matches "patent for a claim" but not
+   * "claim a patent".
+   *
+   * # Arguments
+   *
+   * * `schema` - Schema of the target index.
+   * * `field_name` - Field name to be searched. It must be indexed with positions.
+   * * `words` - Words that must appear in order.
+   * * `slop` - (Optional) The number of other words permitted within the span. Default is 0.
+   */
+  static spanNearQuery(schema: Schema, fieldName: string, words: Array<string>, slop?: number | undefined | null): Query
+  /**
+   * Construct a Tantivy's BooleanQuery
+   *
+   * Every subquery is given as `{ occur, query, name? }`. A clause with a
+   * name is reported in `SearchHit.matchedQueries` and by
+   * `Searcher.matchedClauses` when it matches, see `Query.named`.
+   */
+  static booleanQuery(subqueries: Array<object>): Query
+  /**
+   * Construct a Tantivy's DisjunctionMaxQuery
+   *
+   * @param subqueries - Queries, or `{ query: Query, boost?: number }`
+   *         objects boosting their query.
+   * @param tieBreaker - Weight of the scores of the subqueries other than the
+   *         best matching one. Defaults to 0.
+   */
+  static disjunctionMaxQuery(subqueries: Array<Query | object>, tieBreaker?: number | undefined | null): Query
+  /** Construct a Tantivy's BoostQuery */
+  static boostQuery(query: Query, boost: number): Query
+  /**
+   * Construct a Tantivy's RegexQuery
+   *
+   * # Arguments
+   *
+   * * `schema` - Schema of the target index.
+   * * `field_name` - Field to be searched.
+   * * `regex_pattern` - Regular expression that the whole term must match.
+   * * `options` - Flags of the pattern and limit of its size.
+   */
+  static regexQuery(schema: Schema, fieldName: string, regexPattern: string, options?: RegexQueryOptions | undefined | null): Query
+  /**
+   * Construct a query matching the geo points inside a bounding box
+   *
+   * # Arguments
+   *
+   * * `schema` - Schema of the target index.
+   * * `field_name` - Geo point field to be searched.
+   * * `bounding_box` - The box, in degrees. It crosses the antimeridian if `left` is
+   *   greater than `right`.
+   */
+  static geoBoundingBoxQuery(schema: Schema, fieldName: string, boundingBox: GeoBoundingBox): Query
+  /**
+   * Construct a query matching the geo points within a distance of a point
+   *
+   * # Arguments
+   *
+   * * `schema` - Schema of the target index.
+   * * `field_name` - Geo point field to be searched.
+   * * `center` - The point the distance is computed from.
+   * * `radius` - The maximum distance, in meters.
+   *
+   * A document with several points matches if any of them is within the radius.
+   */
+  static geoDistanceQuery(schema: Schema, fieldName: string, center: GeoPoint, radius: number): Query
+  /**
+   * Construct a block join query over nested documents
+   *
+   * Matches the parent documents having at least one child, under the given
+   * path, that matches `child_query`. The children must have been indexed with
+   * `IndexWriter.addNestedDocument`.
    *
    * # Arguments
    *
-   * * `schema` - Schema of the target index.
-   * * `field_name` - Field name to be searched.
-   * * `text` - String representation of the query term.
-   * * `distance` - (Optional) Edit distance you are going to alow. When not specified, the default is 1.
-   * * `transposition_cost_one` - (Optional) If true, a transposition (swapping) cost will be 1; otherwise it will be 2. When not specified, the default is true.
-   * * `prefix` - (Optional) If true, prefix levenshtein distance is applied. When not specified, the default is false.
+   * * `schema` - Schema of the target index, with nested documents enabled.
+   * * `path` - Nested path of the children.
+   * * `child_query` - Query the children are matched against.
+   * * `score_mode` - (Optional) How the scores of the matching children are combined:
+   *   'max', 'avg', 'sum' or 'none'. Default is 'max'.
    */
-  static fuzzyTermQuery(schema: Schema, fieldName: string, text: string, distance?: number | undefined | null, transpositionCostOne?: boolean | undefined | null, prefix?: boolean | undefined | null): Query
+  static nestedQuery(schema: Schema, path: string, childQuery: Query, scoreMode?: string | undefined | null): Query
   /**
-   * Construct a Tantivy's PhraseQuery with custom offsets and slop
+   * Construct a query matching all the parent documents of nested blocks
+   *
+   * Child documents are regular documents of the index, combine other queries
+   * with this one to leave them out of the results.
+   */
+  static nestedParentQuery(schema: Schema): Query
+  /**
+   * Construct a query matching all terms starting with the given prefix
+   *
+   * The prefix is matched literally, regex metacharacters are escaped.
+   * Unlike a prefix `FuzzyTermQuery`, no edit distance is applied.
    *
    * # Arguments
    *
    * * `schema` - Schema of the target index.
    * * `field_name` - Field name to be searched.
-   * * `words` - Word list that constructs the phrase. A word can be a term text or a pair of term text and its offset in the phrase.
-   * * `slop` - (Optional) The number of gaps permitted between the words in the query phrase. Default is 0.
+   * * `prefix` - Prefix of the indexed terms to match. Must not be empty.
    */
-  static phraseQuery(schema: Schema, fieldName: string, words: Array<unknown>, slop?: number | undefined | null): Query
-  /** Construct a Tantivy's BooleanQuery */
-  static booleanQuery(subqueries: Array<object>): Query
-  /** Construct a Tantivy's DisjunctionMaxQuery */
-  static disjunctionMaxQuery(subqueries: Array<Query>, tieBreaker?: number | undefined | null): Query
-  /** Construct a Tantivy's BoostQuery */
-  static boostQuery(query: Query, boost: number): Query
-  /** Construct a Tantivy's RegexQuery */
-  static regexQuery(schema: Schema, fieldName: string, regexPattern: string): Query
+  static prefixQuery(schema: Schema, fieldName: string, prefix: string): Query
   static moreLikeThisQuery(docAddress: DocAddress, minDocFrequency?: number | undefined | null, maxDocFrequency?: number | undefined | null, minTermFrequency?: number | undefined | null, maxQueryTerms?: number | undefined | null, minWordLength?: number | undefined | null, maxWordLength?: number | undefined | null, boostFactor?: number | undefined | null, stopWords?: Array<string> | undefined | null): Query
   /** Construct a Tantivy's ConstScoreQuery */
   static constScoreQuery(query: Query, score: number): Query
-  static rangeQuery(schema: Schema, fieldName: string, fieldType: FieldType, lowerBound: unknown, upperBound: unknown, includeLower?: boolean | undefined | null, includeUpper?: boolean | undefined | null): Query
+  static rangeQuery(schema: Schema, fieldName: string, fieldType: FieldType, lowerBound: Term | unknown, upperBound: Term | unknown, includeLower?: boolean | undefined | null, includeUpper?: boolean | undefined | null, options?: RangeQueryOptions | undefined | null): Query
   /**
    * Construct a Tantivy's PhrasePrefixQuery
    *
@@ -698,6 +1631,71 @@ export declare class Query {
    * @param maxExpansions - (Optional) Maximum number of terms each regex can expand to.
    */
   static regexPhraseQuery(schema: Schema, fieldName: string, patterns: Array<string>, slop?: number | undefined | null, maxExpansions?: number | undefined | null): Query
+  /**
+   * Construct a query from its description as data, e.g. received as JSON
+   * from a client, sparing the injection concerns of query strings.
+   *
+   * Every query is an object with a single key naming its kind:
+   * - `{ bool: { must?, should?, mustNot?, filter? } }`, lists of queries,
+   *   the `filter` ones being required without contributing to the score.
+   * - `{ term: { field: value } }` and `{ terms: { field: [values] } }`, the
+   *   text values not being tokenized.
+   * - `{ range: { field: { gt?, gte?, lt?, lte? } } }` on numeric, date and
+   *   IP address fields.
+   * - `{ prefix: { field: prefix } }`, `{ exists: { field } }`, `{ all: {} }`
+   *   and `{ boost: { query, boost } }`.
+   *
+   * Dates are seconds timestamps, like in `Query.termQuery`, or RFC 3339
+   * strings. Integers beyond `Number.MAX_SAFE_INTEGER` are given as strings.
+   *
+   * # Arguments
+   *
+   * * `schema` - Schema of the target index.
+   * * `object` - The description of the query.
+   */
+  static fromObject(schema: Schema, object: any): Query
+  /**
+   * Apply per-field boosts to this query.
+   *
+   * Boolean queries are walked recursively and every sub-query targeting a
+   * single field listed in `boosts` is wrapped in a BoostQuery, like
+   * `Index.parseQuery` does with its `fieldBoosts` argument.
+   *
+   * # Arguments
+   *
+   * * `schema` - Schema of the target index.
+   * * `boosts` - Boost factor per field name.
+   */
+  withFieldBoosts(schema: Schema, boosts: Record<string, number>): Query
+  /**
+   * Walk the leaves of this query, e.g. to expand synonyms or enforce a
+   * policy on a query parsed from user input.
+   *
+   * Boolean and named queries are walked into, any other query is a leaf,
+   * including a boosted boolean query. The callback may return a query
+   * replacing the leaf, which keeps the boost of the leaf.
+   *
+   * # Arguments
+   *
+   * * `schema` - Schema of the target index, naming the fields of the leaves.
+   * * `callback` - Function receiving each leaf, see `QueryLeaf`, and
+   *   returning the query replacing it, or nothing to keep it.
+   *
+   * Returns the query with its leaves replaced.
+   */
+  visit(schema: Schema, callback: (arg: QueryLeaf) => Query | null | undefined): Query
+  /**
+   * Give a name to this query, reported by `Searcher.matchedClauses` for
+   * the documents it matches.
+   *
+   * The name has no effect on matching nor on scoring, so named queries can
+   * be used as clauses of a boolean query to tell which of them matched.
+   *
+   * # Arguments
+   *
+   * * `name` - Name of the query.
+   */
+  named(name: string): Query
   /**
    * Explain how this query matches a given document.
    *
@@ -712,6 +1710,20 @@ export declare class Query {
    * * `Explanation` - An object containing detailed scoring information
    */
   explain(searcher: Searcher, docAddress: DocAddress): Explanation
+  /**
+   * Estimate how many documents this query matches, without executing it.
+   *
+   * The estimate is computed per clause from the document frequencies of
+   * the terms, so applications can reject or rewrite expensive queries,
+   * such as huge lists of alternatives or regexes, before searching.
+   *
+   * # Arguments
+   * * `searcher` - The searcher whose term statistics are used
+   *
+   * # Returns
+   * * `QueryCost` - The estimated cost of the query and of its clauses
+   */
+  estimateCost(searcher: Searcher): QueryCost
 }
 
 /**
@@ -741,8 +1753,36 @@ export declare class Schema {
   getFieldType(fieldName: string): FieldType
   /** Check if a field exists in the schema */
   hasField(fieldName: string): boolean
+  /**
+   * Compare this schema with another one.
+   *
+   * Fields are matched by name, `added` and `removed` being relative to
+   * this schema.
+   *
+   * @param other - The new schema.
+   */
+  diff(other: Schema): SchemaDiff
+  /**
+   * Check if phrase queries can be run against a field, that is whether the
+   * field is a text or JSON field indexed with positions.
+   *
+   * Phrase queries on other fields are rejected by `Index.parseQuery` and the
+   * phrase factories of `Query` with a "does not have positions indexed" error.
+   *
+   * @param fieldName - The name of the field.
+   */
+  supportsPhraseQueries(fieldName: string): boolean
   /** Get a string representation of the schema */
   toString(): string
+  /**
+   * Register the schema to be shared with the worker threads, see
+   * `Index.toHandle`.
+   *
+   * @returns A handle to pass to `Schema.fromHandle` in another worker.
+   */
+  toHandle(): number
+  /** Create a schema from a handle returned by `Schema.toHandle`. */
+  static fromHandle(handle: number): Schema
 }
 
 /**
@@ -777,6 +1817,18 @@ export declare class SchemaBuilder {
    * @returns Self for method chaining
    */
   addTextField(name: string, options?: TextFieldOptions | undefined | null): this
+  /**
+   * Add a keyword field to the schema.
+   *
+   * A keyword field is a text field indexed as a single untokenized term,
+   * for identifiers and tags matched exactly, e.g. with
+   * `IndexWriter.deleteDocumentsByTerm`.
+   *
+   * @param name - The name of the field
+   * @param options - Keyword field options
+   * @returns Self for method chaining
+   */
+  addKeywordField(name: string, options?: KeywordFieldOptions | undefined | null): this
   /**
    * Add a signed integer field to the schema.
    *
@@ -784,127 +1836,489 @@ export declare class SchemaBuilder {
    * @param options - Numeric field options
    * @returns Self for method chaining
    */
-  addIntegerField(name: string, options?: NumericFieldOptions | undefined | null): this
+  addIntegerField(name: string, options?: NumericFieldOptions | undefined | null): this
+  /**
+   * Add an unsigned integer field to the schema.
+   *
+   * @param name - The name of the field
+   * @param options - Numeric field options
+   * @returns Self for method chaining
+   */
+  addUnsignedField(name: string, options?: NumericFieldOptions | undefined | null): this
+  /**
+   * Add a float field to the schema.
+   *
+   * @param name - The name of the field
+   * @param options - Numeric field options
+   * @returns Self for method chaining
+   */
+  addFloatField(name: string, options?: NumericFieldOptions | undefined | null): this
+  /**
+   * Add a boolean field to the schema.
+   *
+   * @param name - The name of the field
+   * @param options - Numeric field options
+   * @returns Self for method chaining
+   */
+  addBooleanField(name: string, options?: NumericFieldOptions | undefined | null): this
+  /**
+   * Add a date field to the schema.
+   *
+   * @param name - The name of the field
+   * @param options - Numeric field options
+   * @returns Self for method chaining
+   */
+  addDateField(name: string, options?: NumericFieldOptions | undefined | null): this
+  /**
+   * Add a JSON field to the schema.
+   *
+   * @param name - The name of the field
+   * @param options - JSON field options
+   * @returns Self for method chaining
+   */
+  addJsonField(name: string, options?: TextFieldOptions | undefined | null): this
+  /**
+   * Add a JSON field to the schema that is only stored.
+   *
+   * The values are returned with the documents but neither tokenized nor
+   * indexed, which suits opaque payloads such as metadata or render blobs.
+   * Same as `addJsonField(name, { stored: true, indexed: false })`.
+   *
+   * @param name - The name of the field
+   * @returns Self for method chaining
+   */
+  addStoredJsonField(name: string): this
+  /**
+   * Add a facet field to the schema.
+   *
+   * @param name - The name of the field
+   * @returns Self for method chaining
+   */
+  addFacetField(name: string): this
+  /**
+   * Add a bytes field to the schema.
+   *
+   * @param name - The name of the field
+   * @param options - Bytes field options
+   * @returns Self for method chaining
+   */
+  addBytesField(name: string, options?: BytesFieldOptions | undefined | null): this
+  /**
+   * Add an IP address field to the schema.
+   *
+   * @param name - The name of the field
+   * @param options - IP address field options
+   * @returns Self for method chaining
+   */
+  addIpAddrField(name: string, options?: IpAddrFieldOptions | undefined | null): this
+  /**
+   * Add a dense vector field to the schema.
+   *
+   * Vectors are stored as little-endian f32 values in a fast bytes field,
+   * to be ranked with `Searcher.knn`.
+   *
+   * @param name - The name of the field
+   * @param options - Bytes field options
+   * @returns Self for method chaining
+   */
+  addVectorField(name: string, options?: BytesFieldOptions | undefined | null): this
+  /**
+   * Add a geo point field to the schema.
+   *
+   * The point is stored as two float fast fields, `<name>.lat` and
+   * `<name>.lon`, which are always fast.
+   *
+   * @param name - The name of the field
+   * @param options - Numeric field options, applied to both coordinates
+   * @returns Self for method chaining
+   */
+  addGeoPointField(name: string, options?: NumericFieldOptions | undefined | null): this
+  /**
+   * Enable nested documents for this schema.
+   *
+   * Adds the internal `_nested` field used to mark the parent and child
+   * documents indexed with `IndexWriter.addNestedDocument`.
+   *
+   * @returns Self for method chaining
+   */
+  enableNestedDocuments(): this
+  /**
+   * Build the final schema.
+   *
+   * After calling this method, the SchemaBuilder can no longer be used.
+   *
+   * @returns The built schema
+   */
+  build(): Schema
+}
+
+/**
+ * Tantivy's Searcher class
+ *
+ * A Searcher is used to search the index given a prepared Query.
+ */
+export declare class Searcher {
+  /**
+   * Search the index with the given query and collect results.
+   *
+   * @param query - The query that will be used for the search.
+   * @param limit - The maximum number of search results to
+   *         return. Defaults to 10.
+   * @param count - Should the number of documents that match
+   *         the query be returned as well. Defaults to true.
+   * @param orderByField - A schema field that the results
+   *         should be ordered by. The field must be declared as a fast field
+   *         when building the schema. The `order` of the hits is the value
+   *         of the field, dates being milliseconds timestamps.
+   * @param offset - The offset from which the results have
+   *         to be returned.
+   * @param order - The order in which the results
+   *         should be sorted. If not specified, defaults to descending.
+   * @param options - Additional search options, see `SearchOptions`.
+   *
+   * @returns SearchResult object.
+   *
+   * @throws ValueError if there was an error with the search.
+   */
+  search(query: Query, limit?: number | undefined | null, count?: boolean | undefined | null, orderByField?: string | undefined | null, offset?: number | undefined | null, order?: Order | undefined | null, options?: SearchOptions | undefined | null): SearchResult
+  /**
+   * Search a single segment of the index, e.g. to split a search over a
+   * large index between worker threads, each searching some of the
+   * segments listed by `segments`, and to merge their hits.
+   *
+   * Scores are computed with the statistics of the whole index, so that
+   * the hits of different segments can be compared.
+   *
+   * @param segmentOrd - The ordinal of the segment, see `SegmentInfo.segmentOrd`.
+   * @param query - The query that will be used for the search.
+   * @param limit - The maximum number of search results to return.
+   *         Defaults to 10.
+   *
+   * @returns SearchResult object, counting the matching documents of the
+   *          segment.
+   */
+  searchSegment(segmentOrd: number, query: Query, limit?: number | undefined | null): SearchResult
+  /**
+   * Search the index and fetch the stored documents of the hits.
+   *
+   * @param query - The query that will be used for the search.
+   * @param options - Search options, see `SearchDocsOptions`.
+   *
+   * @returns SearchDocsResult object.
+   */
+  searchDocs(query: Query, options?: SearchDocsOptions | undefined | null): SearchDocsResult
+  /**
+   * Search the index and count facets of the matching documents, in a single pass.
+   *
+   * @param query - The query that will be used for the search.
+   * @param options - The facets to count and the page of hits to return,
+   *         see `SearchFacetedOptions`.
+   *
+   * @returns SearchFacetedResult object.
+   */
+  searchFaceted(query: Query, options: SearchFacetedOptions): SearchFacetedResult
+  /**
+   * Search the index with facet filters and count the facets of the
+   * matching documents, the "multi-select" way, in a single pass.
+   *
+   * The hits and the count are those of the documents matching `query` and
+   * every filter. The facets of a dimension are counted without the filters
+   * of that dimension, so that the other values of the dimension can still
+   * be selected: with 'red' selected, the count of 'blue' is the number of
+   * documents that selecting 'blue' as well would add.
+   *
+   * @param query - The query selecting the documents, before any filter.
+   * @param filters - The facets selected in every dimension, see `FacetFilter`.
+   *         Filters of different fields must all match.
+   * @param options - The facets to count and the page of hits to return,
+   *         see `SearchFacetedOptions`.
+   *
+   * @returns SearchFacetedResult object.
+   */
+  searchFacetedMultiSelect(query: Query, filters: Array<FacetFilter>, options: SearchFacetedOptions): SearchFacetedResult
+  /**
+   * Run a search described by a single request object: the page of hits,
+   * possibly ordered by a field, along with their documents and snippets,
+   * the facet counts and the aggregations.
+   *
+   * The hits, the count, the facets and the aggregations scoped to the
+   * query are collected in a single pass over the matching documents.
+   *
+   * @param request - See `SearchRequest`.
+   *
+   * @returns SearchResponse object.
+   */
+  execute(request: SearchRequest): SearchResponse
+  /**
+   * Compute aggregations over the documents matching a query.
+   *
+   * Fields of JSON fast fields are aggregated by their path, e.g.
+   * `{ colors: { terms: { field: 'attributes.color' } } }`.
+   *
+   * A top-level aggregation flagged with `global: true` is computed over
+   * every document of the index instead, e.g. to show "N of M" counts next to
+   * the ones of the query:
+   * `{ colors: { terms: { field: 'color' } }, allColors: { terms: { field: 'color' }, global: true } }`.
+   *
+   * @param query - The query selecting the documents, or null to aggregate
+   *         every document.
+   * @param agg - The aggregation request, in Elasticsearch's format.
+   *
+   * @returns A JSON string with the results of the aggregations.
+   */
+  aggregate(query: Query | undefined | null, agg: unknown): string
+  /**
+   * Find the k nearest neighbors of a vector.
+   *
+   * Vectors are compared by brute force, among the documents matching the
+   * filter. Documents whose vector has another number of dimensions than
+   * `queryVector` are ignored.
+   *
+   * @param fieldName - A field declared with `SchemaBuilder.addVectorField`.
+   * @param queryVector - The vector to search the neighbors of.
+   * @param k - The number of neighbors to return.
+   * @param filter - Restrict the search to the documents matching this query.
+   * @param metric - The similarity used as score: 'cosine' (default), 'dot' or 'l2'.
+   *
+   * @returns SearchResult object, the hits being ordered by decreasing similarity.
+   */
+  knn(fieldName: string, queryVector: Array<number>, k: number, filter?: Query | undefined | null, metric?: string | undefined | null): SearchResult
+  /**
+   * Compute aggregations over several filtered subsets of the matching documents.
+   *
+   * All the buckets are filled in a single pass over the documents matching
+   * `query`, which is cheaper than one search per filter.
+   *
+   * @param query - The query selecting the documents.
+   * @param filters - The buckets, as an object mapping a bucket name to a Query.
+   * @param subAggs - Optional aggregation request (in the format accepted by
+   *         `aggregate`) computed in every bucket.
+   *
+   * @returns A JSON string mapping every bucket name to its `doc_count` and the
+   *         results of the sub-aggregations.
+   */
+  aggregateFilters(query: Query, filters: object, subAggs?: unknown | undefined | null): string
+  /**
+   * Count the distinct values of a fast field among the documents matching a query.
+   *
+   * The count is exact. This is cheaper than a terms aggregation when the
+   * cardinality of the field is small.
+   *
+   * @param query - The query selecting the documents.
+   * @param fieldName - A field declared as fast in the schema.
+   * @param limit - If given, the most frequent values (at most `limit` of them)
+   *         are returned along with their document counts.
+   *
+   * @returns DistinctCounts object.
+   */
+  distinctCounts(query: Query, fieldName: string, limit?: number | undefined | null): DistinctCounts
+  /**
+   * The bounds and a few statistics of a numeric fast field over the
+   * documents matching a query, e.g. to set the bounds of a range slider.
+   *
+   * The values are read from the fast field column while collecting, which
+   * is cheaper than a stats aggregation.
+   *
+   * @param fieldName - A fast field of type integer, unsigned, float, boolean or date.
+   * @param query - The query selecting the documents, or null for every document.
+   *
+   * @returns FieldMinMax object.
+   */
+  fieldMinMax(fieldName: string, query?: Query | undefined | null): FieldMinMax
+  /**
+   * Read the value of a numeric fast field for a batch of documents.
+   *
+   * This is meant to enrich a page of hits (timestamps, prices...) without
+   * fetching the stored documents. The column of every segment is opened once.
+   *
+   * @param fieldName - A fast field of type integer, unsigned, float, boolean or date.
+   * @param docAddresses - The addresses of the documents, e.g. taken from `SearchResult.hits`.
+   *
+   * @returns A Float64Array holding the value of every document, in the order
+   *         of `docAddresses`. Dates are milliseconds timestamps and booleans
+   *         are 0 or 1. Documents without a value get `NaN`, for documents
+   *         with several values the first one is returned.
+   */
+  fastFieldValuesForDocs(fieldName: string, docAddresses: Array<DocAddress>): Float64Array
+  /**
+   * Read the term ordinal of a text fast field for a batch of documents,
+   * e.g. to group or deduplicate hits on small integers rather than on
+   * strings.
+   *
+   * Ordinals are shared by all the segments of this searcher and follow
+   * the sorted order of the terms. They are only stable within this
+   * searcher, and the first call for a field reads its whole dictionary.
+   *
+   * @param fieldName - A text fast field.
+   * @param docAddresses - The addresses of the documents, e.g. taken from `SearchResult.hits`.
+   *
+   * @returns An Int32Array holding the ordinal of every document, in the
+   *         order of `docAddresses`. Documents without a value get -1, for
+   *         documents with several values the first one is returned.
+   */
+  termOrds(fieldName: string, docAddresses: Array<DocAddress>): Int32Array
   /**
-   * Add an unsigned integer field to the schema.
+   * The term of a text fast field having the given ordinal, see `termOrds`.
    *
-   * @param name - The name of the field
-   * @param options - Numeric field options
-   * @returns Self for method chaining
+   * @param fieldName - A text fast field.
+   * @param ord - An ordinal returned by `termOrds` for this field.
    */
-  addUnsignedField(name: string, options?: NumericFieldOptions | undefined | null): this
+  ordToTerm(fieldName: string, ord: number): string
   /**
-   * Add a float field to the schema.
+   * Map a document address to the single integer id used by `SearchHit.globalDoc`.
    *
-   * @param name - The name of the field
-   * @param options - Numeric field options
-   * @returns Self for method chaining
+   * Global ids are only stable within this searcher.
+   *
+   * @param docAddress - The address of the document.
    */
-  addFloatField(name: string, options?: NumericFieldOptions | undefined | null): this
+  globalDoc(docAddress: DocAddress): number
   /**
-   * Add a boolean field to the schema.
+   * Map a global document id, as found in `SearchHit.globalDoc`, back to its address.
    *
-   * @param name - The name of the field
-   * @param options - Numeric field options
-   * @returns Self for method chaining
+   * @param globalDoc - The global id of the document, obtained from this searcher.
    */
-  addBooleanField(name: string, options?: NumericFieldOptions | undefined | null): this
+  docAddressForGlobalDoc(globalDoc: number): DocAddress
   /**
-   * Add a date field to the schema.
+   * The names of the sub-queries, named with `Query.named`, matching a
+   * document, in the order they appear in the query.
    *
-   * @param name - The name of the field
-   * @param options - Numeric field options
-   * @returns Self for method chaining
+   * Each named sub-query is checked on its own, so a named clause of a
+   * boolean query is reported even if another clause excludes the document.
+   *
+   * @param query - The query containing named sub-queries.
+   * @param docAddress - The address of the document.
    */
-  addDateField(name: string, options?: NumericFieldOptions | undefined | null): this
+  matchedClauses(query: Query, docAddress: DocAddress): Array<string>
+  /** Returns the overall number of documents in the index. */
+  get numDocs(): number
+  /** Returns the number of segments in the index. */
+  get numSegments(): number
   /**
-   * Add a JSON field to the schema.
+   * Returns the number of deleted documents still held by the segments of
+   * the index, until they are merged, see `Index.compact`.
+   */
+  get numDeletedDocs(): number
+  /** The document counts of every segment, in the order of the segments. */
+  segments(): Array<SegmentInfo>
+  /**
+   * The index generation this searcher is pinned to.
    *
-   * @param name - The name of the field
-   * @param options - JSON field options
-   * @returns Self for method chaining
+   * All calls made on this searcher observe this generation. A searcher is
+   * stale when `Index.generation` has moved past it.
    */
-  addJsonField(name: string, options?: TextFieldOptions | undefined | null): this
+  get generation(): number
   /**
-   * Add a facet field to the schema.
+   * An identifier of the content this searcher sees, e.g. to key the
+   * results cached by the application.
    *
-   * @param name - The name of the field
-   * @returns Self for method chaining
+   * It is a hash of the segments of the searcher and of the opstamps of
+   * their deletes: unlike `generation`, it is the same in every process
+   * and after a restart, and it only changes when a commit or a merge
+   * changes the segments, not on every reload.
+   *
+   * @returns 16 hexadecimal digits.
    */
-  addFacetField(name: string): this
+  generationId(): string
   /**
-   * Add a bytes field to the schema.
+   * Return the overall number of documents containing
+   * the given term.
+   */
+  docFreq(fieldName: string, fieldValue: Term | unknown): number
+  /**
+   * Search the documents similar to a document, such as related articles,
+   * with a MoreLikeThisQuery built from its stored fields.
    *
-   * @param name - The name of the field
-   * @param options - Bytes field options
-   * @returns Self for method chaining
+   * @param docAddress - The address of the document.
+   * @param options - See `SimilarDocumentsOptions`.
+   *
+   * @returns The hits, by decreasing score, the document itself excluded.
    */
-  addBytesField(name: string, options?: BytesFieldOptions | undefined | null): this
+  similarDocuments(docAddress: DocAddress, options?: SimilarDocumentsOptions | undefined | null): Array<SearchHit>
   /**
-   * Add an IP address field to the schema.
+   * Fetches a document from Tantivy's store given a DocAddress.
    *
-   * @param name - The name of the field
-   * @param options - IP address field options
-   * @returns Self for method chaining
+   * @param docAddress - The DocAddress that is associated with
+   *         the document that we wish to fetch.
+   *
+   * @returns The Document, raises ValueError if the document can't be found.
    */
-  addIpAddrField(name: string, options?: IpAddrFieldOptions | undefined | null): this
+  doc(docAddress: DocAddress): Document
   /**
-   * Build the final schema.
+   * Fetches a document as a plain object, without creating a `Document`.
    *
-   * After calling this method, the SchemaBuilder can no longer be used.
+   * A field having a single value is mapped to that value, and a field
+   * having several values to their list. Dates are milliseconds timestamps
+   * and bytes are arrays of numbers.
    *
-   * @returns The built schema
+   * @param docAddress - The DocAddress that is associated with
+   *         the document that we wish to fetch.
+   * @param options - See `DocObjectOptions`.
+   *
+   * @returns The object, raises ValueError if the document can't be found.
    */
-  build(): Schema
+  docObject(docAddress: DocAddress, options?: DocObjectOptions | undefined | null): any
 }
 
 /**
- * Tantivy's Searcher class
+ * An index writer whose operations are queued and run one at a time, in
+ * the order they were made.
  *
- * A Searcher is used to search the index given a prepared Query.
+ * Every method returns a promise resolved with the `opstamp` of the
+ * operation once it has run, so that concurrent requests of a server can
+ * write to the index without coordinating. The writer is created by
+ * `Index.serializedWriter()`.
  */
-export declare class Searcher {
+export declare class SerializedWriter {
   /**
-   * Search the index with the given query and collect results.
+   * Queue the addition of a document.
    *
-   * @param query - The query that will be used for the search.
-   * @param limit - The maximum number of search results to
-   *         return. Defaults to 10.
-   * @param count - Should the number of documents that match
-   *         the query be returned as well. Defaults to true.
-   * @param orderByField - A schema field that the results
-   *         should be ordered by. The field must be declared as a fast field
-   *         when building the schema. Note, this only works for unsigned
-   *         fields.
-   * @param offset - The offset from which the results have
-   *         to be returned.
-   * @param order - The order in which the results
-   *         should be sorted. If not specified, defaults to descending.
+   * With a unique field, see `IndexWriterOptions.uniqueField`, the documents
+   * having the same value are deleted first.
    *
-   * @returns SearchResult object.
+   * @param doc - The document to add.
    *
-   * @throws ValueError if there was an error with the search.
+   * @returns A promise resolved with the `opstamp` of the addition.
    */
-  search(query: Query, limit?: number | undefined | null, count?: boolean | undefined | null, orderByField?: string | undefined | null, offset?: number | undefined | null, order?: Order | undefined | null): SearchResult
-  aggregate(query: Query, agg: unknown): string
-  /** Returns the overall number of documents in the index. */
-  get numDocs(): number
-  /** Returns the number of segments in the index. */
-  get numSegments(): number
+  addDocument(doc: Document): Promise<bigint>
   /**
-   * Return the overall number of documents containing
-   * the given term.
+   * Queue the deletion of the documents containing a term, see
+   * `IndexWriter.deleteDocumentsByTerm`.
+   *
+   * @param fieldName - The field name for which we want to filter deleted docs.
+   * @param fieldValue - JavaScript value with the value we want to filter.
+   *
+   * @returns A promise resolved with the `opstamp` of the deletion.
    */
-  docFreq(fieldName: string, fieldValue: unknown): number
+  deleteDocumentsByTerm(fieldName: string, fieldValue: Term | unknown): Promise<bigint>
   /**
-   * Fetches a document from Tantivy's store given a DocAddress.
+   * Queue the deletion of the documents matching a query.
    *
-   * @param docAddress - The DocAddress that is associated with
-   *         the document that we wish to fetch.
+   * @param query - The query to filter the deleted documents.
    *
-   * @returns The Document, raises ValueError if the document can't be found.
+   * @returns A promise resolved with the `opstamp` of the deletion.
    */
-  doc(docAddress: DocAddress): Document
+  deleteDocumentsByQuery(query: Query): Promise<bigint>
+  /**
+   * Queue a commit of the operations queued before it.
+   *
+   * @returns A promise resolved with the `opstamp` of the last operation
+   *         that made it in the commit.
+   */
+  commit(): Promise<bigint>
+  /**
+   * Queue a rollback of the operations queued since the last commit.
+   *
+   * @returns A promise resolved with the `opstamp` of the last commit.
+   */
+  rollback(): Promise<bigint>
+  /**
+   * Release this writer.
+   *
+   * The queued operations still run. The underlying index writer is dropped,
+   * releasing the lock of the index, once every `SerializedWriter` sharing it
+   * is closed or garbage collected. Further calls raise an error.
+   */
+  close(): void
 }
 
 /**
@@ -917,12 +2331,59 @@ export declare class Snippet {
   toHtml(): string
   highlighted(): Array<Range>
   fragment(): string
+  /** The field the snippet was generated from. */
+  get fieldName(): string
+  /**
+   * Relevance of the fragment: the sum of the weights of the highlighted
+   * terms, rarer terms weighing more. 0 if nothing is highlighted.
+   */
+  get score(): number
 }
 
 export declare class SnippetGenerator {
   static create(searcher: Searcher, query: Query, schema: Schema, fieldName: string): SnippetGenerator
+  /**
+   * Generate a snippet for each of several fields of a document, and return
+   * the one with the highest score.
+   *
+   * @param searcher - The searcher the query is run on.
+   * @param query - The query whose terms are highlighted.
+   * @param schema - The schema of the index.
+   * @param doc - The document to generate the snippets from.
+   * @param fieldNames - The candidate fields. On equal scores, the first
+   *         field wins.
+   * @param maxNumChars - Maximum number of characters of the snippets.
+   *
+   * @returns The best snippet, or undefined if no field is given.
+   */
+  static bestField(searcher: Searcher, query: Query, schema: Schema, doc: Document, fieldNames: Array<string>, maxNumChars?: number | undefined | null): Snippet | null
   snippetFromDoc(doc: Document): Snippet
   setMaxNumChars(maxNumChars: number): void
+  /**
+   * Split the text into highlighted spans with another analyzer than the
+   * one the field is indexed with, e.g. one without stemming or keeping
+   * hyphenated words whole, so that the highlighted spans are exactly the
+   * words of the text.
+   *
+   * A span is highlighted if the analyzer of the field turns it into one of
+   * the terms of the query, so `running` is still highlighted for a query
+   * on `run` when the field is stemmed.
+   *
+   * @param analyzer - The analyzer of the spans, or null to use the one of
+   *         the field again.
+   */
+  setAnalyzer(analyzer?: TextAnalyzer | undefined | null): void
+  /**
+   * Strip the HTML markup of the field before computing the snippets.
+   *
+   * Tags, comments and the content of `script` and `style` elements are
+   * removed and entities are decoded, so that fragments never cut through a
+   * tag and `toHtml` escapes the text only once. The fragments and the
+   * highlighted ranges are then those of the text without markup.
+   *
+   * @param strip - Whether to strip the markup. Defaults to false.
+   */
+  setStripHtml(strip: boolean): void
 }
 
 /** Error in the query syntax. */
@@ -931,6 +2392,138 @@ export declare class SyntaxError {
   toString(): string
 }
 
+/**
+ * An index shared by several tenants, each of them only seeing its own
+ * documents.
+ *
+ * Every document added is given the tenant id in the tenant field, and
+ * every search and deletion is restricted to the documents of the tenant,
+ * so that a query forgetting the tenant can't leak nor delete the documents
+ * of another one. With a unique field, see `IndexWriterOptions.uniqueField`,
+ * the documents are only deduplicated within their tenant. The tenant index
+ * is created by `Index.tenantIndex()`.
+ *
+ * Example:
+ * ```javascript
+ * const tenants = index.tenantIndex("tenant");
+ * tenants.add("acme", Document.fromDict({ title: "The Old Man and the Sea" }));
+ * tenants.commit();
+ * tenants.search("acme", index.parseQuery("sea")).count; // 1
+ * tenants.search("globex", index.parseQuery("sea")).count; // 0
+ * ```
+ */
+export declare class TenantIndex {
+  /**
+   * Add a document of a tenant, see `IndexWriter.addDocument`.
+   *
+   * @param tenantId - The tenant owning the document.
+   * @param doc - The document to add. It should not have a value of the
+   *         tenant field, unless it is the tenant id.
+   *
+   * @returns The `opstamp` of the addition.
+   */
+  add(tenantId: string, doc: Document): bigint
+  /**
+   * Delete the documents of a tenant containing a term, see
+   * `IndexWriter.deleteDocumentsByTerm`.
+   *
+   * @param tenantId - The tenant owning the documents.
+   * @param fieldName - The field name for which we want to filter deleted docs.
+   * @param fieldValue - JavaScript value with the value we want to filter.
+   *
+   * @returns The `opstamp` of the deletion.
+   */
+  deleteDocumentsByTerm(tenantId: string, fieldName: string, fieldValue: Term | unknown): bigint
+  /**
+   * Delete the documents of a tenant matching a query.
+   *
+   * @param tenantId - The tenant owning the documents.
+   * @param query - The query to filter the deleted documents.
+   *
+   * @returns The `opstamp` of the deletion.
+   */
+  deleteDocumentsByQuery(tenantId: string, query: Query): bigint
+  /**
+   * Delete all the documents of a tenant.
+   *
+   * @param tenantId - The tenant whose documents are deleted.
+   *
+   * @returns The `opstamp` of the deletion.
+   */
+  deleteTenant(tenantId: string): bigint
+  /**
+   * Commit the pending operations and reload the reader so that they are
+   * visible to the searches, see `IndexWriter.commit`.
+   *
+   * @returns The `opstamp` of the commit.
+   */
+  commit(): bigint
+  /**
+   * Search the documents of a tenant, see `Searcher.search`.
+   *
+   * @param tenantId - The tenant owning the documents.
+   * @param query - The query, restricted to the documents of the tenant
+   *         without changing their scores.
+   *
+   * @returns SearchResult object.
+   */
+  search(tenantId: string, query: Query, limit?: number | undefined | null, count?: boolean | undefined | null, orderByField?: string | undefined | null, offset?: number | undefined | null, order?: Order | undefined | null, options?: SearchOptions | undefined | null): SearchResult
+  /**
+   * Commit the pending operations and release the writer, see
+   * `IndexWriter.waitMergingThreads`. Further calls raise an error.
+   */
+  close(): void
+}
+
+/**
+ * A term, the unit the index is made of: a field and a value as it was
+ * indexed.
+ *
+ * Term queries, term set queries, range queries, deletions and document
+ * frequencies accept a Term in place of a value. The value is then used as
+ * is, while plain JavaScript values are converted according to the type of
+ * the field.
+ *
+ * Example:
+ * ```javascript
+ * const term = Term.fromText(schema, "title", "Whale");
+ * const query = Query.termQuery(schema, "title", term);
+ * ```
+ */
+export declare class Term {
+  /**
+   * Create the term of a text field, without tokenizing nor normalizing the
+   * text.
+   *
+   * @param schema - The schema of the index.
+   * @param fieldName - A text field.
+   * @param text - The text of the term, as indexed.
+   */
+  static fromText(schema: Schema, fieldName: string, text: string): Term
+  /**
+   * Create the term of a value at a path of a JSON field.
+   *
+   * Strings are used as is, without tokenizing nor normalizing them. Integers
+   * are indexed as 64-bit integers and other numbers as floats, like the
+   * numbers of the documents.
+   *
+   * @param schema - The schema of the index.
+   * @param fieldName - A JSON field.
+   * @param path - The path of the value in the JSON object, e.g. `user.name`.
+   * @param value - A string, a number or a boolean.
+   */
+  static fromJsonPath(schema: Schema, fieldName: string, path: string, value: unknown): Term
+  /**
+   * Create the term of a bytes field.
+   *
+   * @param schema - The schema of the index.
+   * @param fieldName - A bytes field.
+   * @param bytes - The bytes (as Buffer or Uint8Array) of the term.
+   */
+  static fromBytes(schema: Schema, fieldName: string, bytes: Uint8Array): Term
+  toString(): string
+}
+
 /**
  * Tantivy's TextAnalyzer
  *
@@ -938,6 +2531,31 @@ export declare class SyntaxError {
  * Use the `TextAnalyzerBuilder` class instead.
  */
 export declare class TextAnalyzer {
+  /**
+   * Build a TextAnalyzer from its declarative description.
+   *
+   * @param config - An object as returned by `TextAnalyzerBuilder.toJSON()`:
+   *   `{ tokenizer: { type: 'ngram', minGram: 2, maxGram: 3, prefixOnly: false },
+   *   filters: [{ type: 'lowercase' }, { type: 'stemmer', language: 'english' }] }`.
+   *   Character filters go in an optional `charFilters` list. The types are
+   *   the names of the `Tokenizer`, `CharFilter` and `Filter` factories, the
+   *   other properties their parameters.
+   * @returns - TextAnalyzer with tokenizer and filters baked in.
+   */
+  static fromJSON(config: any): TextAnalyzer
+  /**
+   * Build the usual analyzer for a language.
+   *
+   * The text is split with the simple tokenizer, long tokens are removed and
+   * tokens are lowercased, then stop words are removed, tokens are stemmed and
+   * folded to ASCII as selected by `options`. Without options, this is the
+   * analyzer registered as `<lang>_stem` (e.g. `de_stem`) on every index.
+   *
+   * @param language - A language supported by `Filter.stemmer()`.
+   * @param options - The optional steps of the analyzer.
+   * @returns - TextAnalyzer with tokenizer and filters baked in.
+   */
+  static forLanguage(language: string, options?: LanguageAnalyzerOptions | undefined | null): TextAnalyzer
   /**
    * Tokenize a string
    * @param text - text to tokenize.
@@ -971,6 +2589,24 @@ export declare class TextAnalyzerBuilder {
    * Note: The builder is _not_ mutated in-place.
    */
   filter(filter: Filter): TextAnalyzerBuilder
+  /**
+   * Add a character filter, applied to the text before it is tokenized.
+   *
+   * @param charFilter - a CharFilter object.
+   * @returns - A new instance of the builder
+   *
+   * Note: The builder is _not_ mutated in-place. Character filters run
+   * before the tokenizer, in the order they are added, whatever the
+   * position of the call among the `filter()` calls.
+   */
+  charFilter(charFilter: CharFilter): TextAnalyzerBuilder
+  /**
+   * Describe the tokenizer and filters of this builder.
+   *
+   * The description is plain JSON, it can be stored in a configuration file
+   * and turned back into an analyzer with `TextAnalyzer.fromJSON()`.
+   */
+  toJSON(): any
   /**
    * Build final TextAnalyzer object.
    *
@@ -997,6 +2633,24 @@ export declare class TokenizerStatic {
   static facet(): Tokenizer
   /** Regextokenizer */
   static regex(pattern: string): Tokenizer
+  /**
+   * Tokenizer keeping the emails and the URLs whole
+   *
+   * Splits the text like `simple()`, but also emits each email or URL as a
+   * single token, e.g. `user@example.com` gives `user@example.com`, `user`,
+   * `example` and `com`.
+   */
+  static uaxUrlEmail(): Tokenizer
+  /**
+   * Tokenizer emitting the ancestors of a path
+   *
+   * `/a/b/c` gives `/a`, `/a/b` and `/a/b/c`, so that a term query on a
+   * directory, e.g. `/a/b`, matches every path under it.
+   *
+   * @param delimiter - The separator of the segments of the paths. Defaults
+   *         to '/'.
+   */
+  static pathHierarchy(delimiter?: string | undefined | null): Tokenizer
   /**
    * NgramTokenizer
    *
@@ -1007,27 +2661,158 @@ export declare class TokenizerStatic {
   static ngram(minGram?: number | undefined | null, maxGram?: number | undefined | null, prefixOnly?: boolean | undefined | null): Tokenizer
 }
 
-/** The tokenizer for the given field is unknown. */
-export declare class UnknownTokenizerError {
-  tokenizer(): string
-  field(): string
-  toString(): string
+/** The tokenizer for the given field is unknown. */
+export declare class UnknownTokenizerError {
+  tokenizer(): string
+  field(): string
+  toString(): string
+}
+
+/** This query is unsupported. */
+export declare class UnsupportedQueryError {
+  get innerMessage(): string
+  toString(): string
+}
+
+/** Bytes field options */
+/** The outcome of an item of a batch, see `BatchOptions`. */
+export interface BatchItemResult {
+  /** True if the item was applied. */
+  ok: boolean
+  /** The `opstamp` of the item, if it was applied. */
+  opstamp?: bigint
+  /** Why the item was rejected, if it was not applied. */
+  error?: string
+}
+
+/** Options of the batch APIs of `IndexWriter`, such as `addDocuments`. */
+export interface BatchOptions {
+  /**
+   * Stop at the first item that fails, leaving the following ones
+   * unprocessed and out of the results. Defaults to false, every item being
+   * processed.
+   */
+  failFast?: boolean
+}
+
+export interface BytesFieldOptions {
+  /** Store the field value (can be retrieved from search results) */
+  stored?: boolean
+  /** Index the field (enables searching) */
+  indexed?: boolean
+  /** Fast field access (column-oriented storage) */
+  fast?: boolean
+}
+
+/** The verification of the checksum of an index file, see `Index.verifyChecksums`. */
+export interface ChecksumResult {
+  /** Path of the file, relative to the index directory. */
+  path: string
+  /** Id of the segment the file belongs to. */
+  segmentId: string
+  /**
+   * 'ok', 'corrupted' when the checksum of the content doesn't match the one
+   * of its footer, 'missing', or 'unreadable' when the file or its footer
+   * can't be read.
+   */
+  status: string
+  /** The reason why the file can't be read. */
+  error?: string
+}
+
+/** Options of `Index.debugQuery`, with the meaning they have in `Index.parseQuery`. */
+/** A successful commit, see `IndexWriter.onCommit`. */
+export interface CommitEvent {
+  /** The `opstamp` returned by the commit. */
+  opstamp: bigint
+  /** The payload given to the commit, if any. */
+  payload?: string
+}
+
+/** Options of `IndexWriter.commitWithGuard`. */
+export interface CommitGuardOptions {
+  /**
+   * Number of bytes that must remain free on the disk once the commit and
+   * the merges it may trigger are written. Defaults to 0.
+   */
+  minFreeBytes?: number
+  /** The payload of the commit, see `IndexWriter.commit`. */
+  payload?: string
+}
+
+/** Options of `Index.compact`. */
+export interface CompactOptions {
+  /** The number of segments to merge the index down to. Defaults to 1. */
+  targetSegments?: number
+  /** Called after each step of the compaction. */
+  onProgress?: ((arg: CompactProgress) => void)
+}
+
+/** A step of `Index.compact`, see `CompactOptions.onProgress`. */
+export interface CompactProgress {
+  /** The step: 'merge' or 'garbageCollect'. */
+  step: string
+  /** Number of steps done, this one included. */
+  completed: number
+  /** Number of steps of the compaction. */
+  total: number
+}
+
+/** The outcome of `Index.compact`. */
+export interface CompactResult {
+  /** Number of searchable segments before the compaction. */
+  segmentsBefore: number
+  /** Number of searchable segments after the compaction. */
+  segmentsAfter: number
+  /** Number of index files deleted, those of the merged segments. */
+  filesDeleted: number
+  /**
+   * Size of the index files before the compaction minus their size after,
+   * in bytes.
+   */
+  bytesReclaimed: number
+}
+
+export interface DebugQueryOptions {
+  defaultFieldNames?: Array<string>
+  fieldBoosts?: Record<string, number>
+  fuzzyFields?: Record<string, [boolean, number, boolean]>
+  now?: number
+  jsonPaths?: Record<string, Array<string>>
+}
+
+/** A term of the dictionary of a field, see `Index.exportDictionary`. */
+export interface DictionaryEntry {
+  term: string
+  /** Number of documents containing the term. */
+  docFreq: number
+  /**
+   * Number of occurrences of the term in all the documents, or undefined
+   * if the field doesn't record the term frequencies.
+   */
+  totalTermFreq?: number
+}
+
+/** Options of `Index.exportDictionary`. */
+export interface DictionaryOptions {
+  /** Skip the terms found in fewer documents. */
+  minDocFreq?: number
+  /** Maximum number of terms to export. */
+  limit?: number
 }
 
-/** This query is unsupported. */
-export declare class UnsupportedQueryError {
-  get innerMessage(): string
-  toString(): string
+/** Object holding the result of a distinct count. */
+export interface DistinctCounts {
+  /** Exact number of distinct values among the matching documents. */
+  distinct: number
+  /** The most frequent values, only filled if a `limit` was given. */
+  values: Array<DistinctValue>
 }
 
-/** Bytes field options */
-export interface BytesFieldOptions {
-  /** Store the field value (can be retrieved from search results) */
-  stored?: boolean
-  /** Index the field (enables searching) */
-  indexed?: boolean
-  /** Fast field access (column-oriented storage) */
-  fast?: boolean
+/** A distinct fast field value and the number of matching documents holding it. */
+export interface DistinctValue {
+  value: any
+  count: number
 }
 
 /**
@@ -1043,6 +2828,119 @@ export interface DocAddress {
   doc: number
 }
 
+/** Options of `Searcher.docObject`. */
+export interface DocObjectOptions {
+  /** If given, only these stored fields are included in the object. */
+  fields?: Array<string>
+}
+
+/** A level of the path of a `FacetNavigation`. */
+export interface FacetBreadcrumb {
+  /** The facet of the level, e.g. '/electronics/phones'. */
+  facet: string
+  /** The last segment of the facet, e.g. 'phones', or '' for the root. */
+  label: string
+  /** Number of matching documents under the facet. */
+  count: number
+}
+
+/** A facet and the number of matching documents under it. */
+export interface FacetCount {
+  facet: string
+  count: number
+}
+
+/** The counts of the children of a facet among the matching documents. */
+export interface FacetCounts {
+  field: string
+  root: string
+  /**
+   * The children of `root`, by decreasing count if `topK` was given and in
+   * facet order otherwise.
+   */
+  counts: Array<FacetCount>
+}
+
+/** The facets selected in a dimension of `Searcher.searchFacetedMultiSelect`. */
+export interface FacetFilter {
+  /** A facet field. */
+  field: string
+  /**
+   * The selected facets, e.g. ['/color/red', '/color/blue']. Documents
+   * under any of them match.
+   */
+  facets: Array<string>
+}
+
+/**
+ * Everything needed to render a facet in a drill-down menu, see
+ * `FacetNavigator.navigate`.
+ */
+export interface FacetNavigation {
+  /** The current facet. */
+  facet: string
+  /** Number of matching documents under the current facet. */
+  count: number
+  /** The levels from the root to the current facet, both included. */
+  breadcrumbs: Array<FacetBreadcrumb>
+  /** The children of the current facet, in facet order. */
+  children: Array<FacetCount>
+  /**
+   * The children of the parent of the current facet, the current facet
+   * included, in facet order. Empty at the root.
+   */
+  siblings: Array<FacetCount>
+}
+
+/** A facet count block requested from `Searcher.searchFaceted`. */
+export interface FacetRequest {
+  /** A facet field. */
+  field: string
+  /** The facet whose children are counted, e.g. '/category'. Defaults to '/'. */
+  root?: string
+  /** If given, only the `topK` most frequent children are returned. */
+  topK?: number
+}
+
+/** A field whose type or options differ between two schemas. */
+export interface FieldChange {
+  name: string
+  /**
+   * The differing options, as paths such as `stored` or `indexing.tokenizer`,
+   * or `type` when the type of the field changed.
+   */
+  options: Array<string>
+  /** The field in the old schema, in the format of `Schema.toJSON`. */
+  before: any
+  /** The field in the new schema, in the format of `Schema.toJSON`. */
+  after: any
+}
+
+/** A field value that was altered while adding a document leniently. */
+export interface FieldIssue {
+  fieldName: string
+  reason: string
+}
+
+/**
+ * Statistics of a numeric fast field, see `Searcher.fieldMinMax`.
+ *
+ * Dates are milliseconds timestamps and booleans are 0 or 1. Every value of
+ * a document with several values is counted.
+ */
+export interface FieldMinMax {
+  /** The smallest value, if any matching document has a value. */
+  min?: number
+  /** The largest value, if any matching document has a value. */
+  max?: number
+  /** Number of values of the matching documents. */
+  count: number
+  /** Sum of the values of the matching documents. */
+  sum: number
+  /** Mean of the values of the matching documents, if there are any. */
+  mean?: number
+}
+
 /** Tantivy's FieldType */
 export declare const enum FieldType {
   Str = 0,
@@ -1057,9 +2955,127 @@ export declare const enum FieldType {
   IpAddr = 9
 }
 
+/** Statistics of the filter cache of an index. */
+export interface FilterCacheStats {
+  /** Number of segment lookups answered from the cache. */
+  hits: number
+  /** Number of segment lookups that had to execute the filter. */
+  misses: number
+  /** Number of entries dropped because the cache was full or expired. */
+  evictions: number
+  /** Number of entries currently held. */
+  entries: number
+}
+
+/** Options of `fuseResults`. */
+export interface FuseOptions {
+  /**
+   * 'rrf' (reciprocal rank fusion, default) or 'linear' (weighted sum of
+   * min-max normalized scores).
+   */
+  method?: string
+  /** Weight of each list. Defaults to 1 for both. */
+  weights?: Array<number>
+  /** Rank constant of the reciprocal rank fusion. Defaults to 60. */
+  rrfK?: number
+  /** Maximum number of hits to return. Defaults to all of them. */
+  limit?: number
+}
+
+/**
+ * Fuse two ranked lists of hits, e.g. keyword and vector search hits, into one.
+ *
+ * Hits are identified by their document address, so both lists must come
+ * from the same searcher. The fused score is stored in `score`.
+ *
+ * @param listA - The first list of hits, best first.
+ * @param listB - The second list of hits, best first.
+ * @param options - Fusion options, see `FuseOptions`.
+ *
+ * @returns The fused hits, best first.
+ */
+export declare function fuseResults(listA: Array<SearchHit>, listB: Array<SearchHit>, options?: FuseOptions | undefined | null): Array<SearchHit>
+
+/**
+ * A geographic bounding box, in degrees.
+ *
+ * If `left` is greater than `right`, the box crosses the antimeridian.
+ */
+export interface GeoBoundingBox {
+  top: number
+  left: number
+  bottom: number
+  right: number
+}
+
+/** A geographic point, in degrees. */
+export interface GeoPoint {
+  lat: number
+  lon: number
+}
+
 /** Get the version of the library */
 export declare function getVersion(): string
 
+/** What `Index.inspect` finds about an index, without opening it. */
+export interface IndexInspection {
+  /**
+   * The index format version of the oldest segment, undefined for an index
+   * without segments.
+   */
+  indexFormatVersion?: number
+  /** The version of tantivy which wrote the oldest segment, such as '0.22.0'. */
+  tantivyVersion?: string
+  /** The schema, as stored in `meta.json`. */
+  schema: any
+  /** Number of segments of the last commit. */
+  numSegments: number
+  /** Number of documents of the last commit, the deleted ones excluded. */
+  numDocs: number
+  /** Whether this version of the binding can open the index. */
+  compatible: boolean
+  /** Why the index can't be opened, and how to migrate it. */
+  advice?: string
+}
+
+/**
+ * The settings of an index, see `Index.settings`.
+ *
+ * There is no sort field: tantivy 0.25 dropped the sorting of indexes, its
+ * `IndexSettings` only configure the doc store.
+ */
+export interface IndexSettings {
+  /**
+   * The compression of the doc store: 'none', 'lz4' or 'zstd', possibly
+   * followed by its options, e.g. 'zstd(compression_level=5)'.
+   */
+  docstoreCompression: string
+  /** The size of the blocks of the doc store, in bytes, before compression. */
+  docstoreBlocksize: number
+  /** True if the blocks of the doc store are compressed on a dedicated thread. */
+  docstoreCompressDedicatedThread: boolean
+}
+
+/** Options of `Index.writer`. */
+export interface IndexWriterOptions {
+  /** The total target heap memory usage of the writer, see `Index.writer`. */
+  heapSize?: number
+  /** The number of threads of the writer, see `Index.writer`. */
+  numThreads?: number
+  /**
+   * A field identifying the documents. Adding a document first deletes the
+   * documents having the same value in this field, so that at most one live
+   * document has a given value. The field must be indexed, with the `raw`
+   * tokenizer for a text field. It can't be used with nested documents.
+   */
+  uniqueField?: string
+  /**
+   * A date field giving the expiry of the documents, see
+   * `IndexWriter.purgeExpired`. The field must be indexed or fast.
+   */
+  expiryField?: string
+}
+
 /** IP address field options */
 export interface IpAddrFieldOptions {
   /** Store the field value (can be retrieved from search results) */
@@ -1070,6 +3086,109 @@ export interface IpAddrFieldOptions {
   fast?: boolean
 }
 
+/** The outcome of `Index.validateJson`. */
+export interface JsonValidationReport {
+  /** True if the document can be added as is by `IndexWriter.addJson`. */
+  valid: boolean
+  /** Set if the string is not a JSON object, the fields being left unchecked. */
+  jsonError?: string
+  /**
+   * One issue per value that doesn't match the type of its field, sorted by
+   * field name.
+   */
+  errors: Array<FieldIssue>
+}
+
+/** Keyword field options */
+export interface KeywordFieldOptions {
+  /** Store the field value (can be retrieved from search results) */
+  stored?: boolean
+  /** Fast field access (column-oriented storage) */
+  fast?: boolean
+}
+
+/** Options of `TextAnalyzer.forLanguage` */
+export interface LanguageAnalyzerOptions {
+  /** Remove the builtin stop words of the language (default: false) */
+  stopwords?: boolean
+  /** Fold the tokens to ASCII, after stemming (default: false) */
+  asciiFold?: boolean
+  /** Stem the tokens (default: true) */
+  stemmer?: boolean
+  /** Remove the tokens longer than this many characters (default: 40) */
+  lengthLimit?: number
+}
+
+/** A bucket of the search latency histogram. */
+export interface LatencyBucket {
+  /**
+   * Upper bound of the bucket, in milliseconds. The last bucket is unbounded
+   * (`Infinity`).
+   */
+  le: number
+  /**
+   * Number of searches that took at most `le` milliseconds. Like the buckets
+   * of a Prometheus histogram, the counts are cumulative.
+   */
+  count: number
+}
+
+/** What happened to the fields of a document added leniently. */
+export interface LenientAddReport {
+  /** Values that were left out of the indexed document. */
+  dropped: Array<FieldIssue>
+  /** Values that were converted to the type of their field. */
+  coerced: Array<FieldIssue>
+}
+
+/** A log record of Tantivy or of the binding, see `setLogHandler`. */
+export interface LogRecord {
+  /** 'error', 'warn', 'info', 'debug' or 'trace'. */
+  level: string
+  /** The module that emitted the record, e.g. `tantivy::indexer::segment_updater`. */
+  target: string
+  message: string
+}
+
+/** Cumulative counters of the process, see `metrics`. */
+export interface Metrics {
+  /**
+   * Number of documents added by the index writers, including the child
+   * documents of nested documents.
+   */
+  documentsAdded: number
+  /** Number of commits. */
+  commits: number
+  /** Number of segment merges scheduled by the index writers. */
+  merges: number
+  /** Number of searches executed, including aggregations and knn searches. */
+  searches: number
+  /** Histogram of the search latencies. */
+  searchLatencyBuckets: Array<LatencyBucket>
+  /** Total time spent searching, in milliseconds. */
+  searchLatencySumMs: number
+  /** Number of documents fetched from the doc store cache. */
+  docStoreCacheHits: number
+  /** Number of documents whose block had to be read and decompressed. */
+  docStoreCacheMisses: number
+  /**
+   * `docStoreCacheHits / (docStoreCacheHits + docStoreCacheMisses)`, 0 before
+   * the first document is fetched.
+   */
+  docStoreCacheHitRate: number
+}
+
+/**
+ * Snapshot the counters of the process, to be exported to a monitoring
+ * system such as Prometheus.
+ *
+ * The counters are cumulative since the process started, over every index
+ * and worker thread, and never reset.
+ *
+ * @returns Metrics object.
+ */
+export declare function metrics(): Metrics
+
 /** Numeric field options (for integers, floats, dates) */
 export interface NumericFieldOptions {
   /** Store the field value (can be retrieved from search results) */
@@ -1095,15 +3214,380 @@ export declare const enum Order {
   Desc = 1
 }
 
+/** Options of `Index.parseQuery` and `Index.parseQueryLenient`. */
+export interface ParseQueryOptions {
+  /**
+   * Slop of the quoted phrases not giving one, as if they were followed by
+   * `~slop`, so that `"old sea"` also matches `old grey sea` (default: 0).
+   */
+  phraseSlop?: number
+  /**
+   * Search the quoted phrases ending with `*`, such as `"old man and the se*"`,
+   * as phrase prefixes, like `"old man and the se"*` (default: false).
+   */
+  phrasePrefix?: boolean
+}
+
+/** Options of `Query.phraseFromText` */
+export interface PhraseFromTextOptions {
+  /** The number of gaps permitted between the words of the phrase (default: 0) */
+  slop?: number
+}
+
+/** Estimated cost of a query, see `Query.estimateCost`. */
+export interface QueryCost {
+  /**
+   * Kind of the query: 'bool', 'term', 'term_set', 'phrase', 'phrase_prefix',
+   * 'span_near', 'regex', 'regex_phrase', 'fuzzy', 'range', 'exists', 'all', 'empty' or 'other'.
+   */
+  kind: string
+  /** Occurrence of the query in its parent boolean query: 'must', 'should' or 'must_not'. */
+  occur?: string
+  /** The field the query targets, if it targets a single one. */
+  field?: string
+  /** Number of terms whose document frequency was looked up. */
+  terms: number
+  /**
+   * Approximate number of matching documents. It is an upper bound, and the
+   * queries walking the term dictionary (regex, fuzzy, range, exists...) are
+   * estimated at the number of documents of the index.
+   */
+  estimatedDocs: number
+  /** Costs of the clauses of a boolean query. */
+  clauses?: Array<QueryCost>
+}
+
+/** A leaf of a query tree, see `Query.visit`. */
+export interface QueryLeaf {
+  /**
+   * Kind of the query: 'term', 'term_set', 'phrase', 'phrase_prefix',
+   * 'span_near', 'regex', 'regex_phrase', 'fuzzy', 'range', 'exists', 'all',
+   * 'empty', 'bool' for a boosted boolean query, or 'other'.
+   */
+  kind: string
+  /** Occurrence of the leaf in its parent boolean query: 'must', 'should' or 'must_not'. */
+  occur?: string
+  /** The field the query targets, if it targets a single one. */
+  field?: string
+  /** The terms searched, as they were analyzed. */
+  terms: Array<any>
+  /** The boost of the leaf, 1 if it is not boosted. */
+  boost: number
+}
+
 export interface Range {
   start: number
   end: number
 }
 
+/** Options of `Query.regexQuery` */
+/** Options of `Query.rangeQuery` */
+export interface RangeQueryOptions {
+  /**
+   * Match the documents by scanning the fast field (true) or by walking the
+   * terms of the inverted index (false). By default, the fast field is used
+   * if the field has one. Scanning the fast field is best for broad ranges,
+   * the inverted index for selective ones.
+   */
+  useFastField?: boolean
+}
+
+export interface RegexQueryOptions {
+  /** Match letters regardless of their case (default: false) */
+  caseInsensitive?: boolean
+  /** Let `.` match line breaks too (default: false) */
+  dotAll?: boolean
+  /**
+   * Maximum size, in bytes, of the automaton running the query, each of its
+   * states taking 4 KiB on 64-bit platforms. Larger patterns are rejected,
+   * use it when the pattern comes from user input. Tantivy always rejects
+   * patterns compiling to more than 10 MiB or 1000 automaton states.
+   */
+  sizeLimit?: number
+}
+
+/** Options of `Index.reindexWithSchema`. */
+export interface ReindexOptions {
+  /** Renames applied to the fields of every document, from old to new name. */
+  fieldMap?: Record<string, string>
+  /** Memory budget of the writer of the new index, in bytes. */
+  heapSize?: number
+}
+
+/**
+ * Release a handle created by `Index.toHandle`, `Schema.toHandle` or
+ * `Query.toHandle`.
+ *
+ * The objects created from the handle remain valid. A handle holds its
+ * object until it is released, so each handle should be released once every
+ * worker has created its object from it.
+ *
+ * @param handle - The handle to release.
+ *
+ * @returns Whether the handle was registered.
+ */
+export declare function releaseHandle(handle: number): boolean
+
+/** A single index file shipped from a primary index to a replica. */
+export interface ReplicationFile {
+  /** Path of the file, relative to the index directory. */
+  path: string
+  /** Content of the file, without the footer holding its checksum. */
+  data: Buffer
+}
+
+/**
+ * The hits passed to `RescoreOptions.callback`, column by column: the
+ * values of a hit are at the same index in every array.
+ */
+export interface RescoreBatch {
+  /** The BM25 scores of the hits, in decreasing order. */
+  scores: Float64Array
+  /** The global ids of the hits, see `SearchHit.globalDoc`. */
+  globalDocs: Uint32Array
+  /**
+   * The values of every fast field of `RescoreOptions.fields`, as returned
+   * by `Searcher.fastFieldValuesForDocs`.
+   */
+  fields: Record<string, Float64Array>
+}
+
+/** Options of `SearchOptions.rescore`. */
+export interface RescoreOptions {
+  /**
+   * Number of the best hits by BM25 score to rescore (default: 100). The
+   * hits beyond them, if the page reaches past them, follow in BM25 order.
+   */
+  topN?: number
+  /** Fast fields whose values are passed to the callback, see `RescoreBatch.fields`. */
+  fields?: Array<string>
+  /**
+   * Called once with the hits to rescore, it returns their new scores, in
+   * the order of the batch.
+   */
+  callback: ((arg: RescoreBatch) => Float64Array | Array<number>)
+}
+
+/** Differences between two schemas, see `Schema.diff`. */
+export interface SchemaDiff {
+  /** Names of the fields only in the new schema. */
+  added: Array<string>
+  /** Names of the fields only in the old schema. */
+  removed: Array<string>
+  /** Fields in both schemas whose type or options differ. */
+  changed: Array<FieldChange>
+}
+
+/** A search hit along with its stored document. */
+export interface SearchDocHit {
+  score?: number
+  docAddress: DocAddress
+  globalDoc: number
+  /**
+   * The stored document, in the format of `Document.toDict`. Bytes values
+   * are arrays of numbers.
+   */
+  doc: any
+}
+
+/** Options of `Searcher.searchDocs`. */
+export interface SearchDocsOptions {
+  /** The maximum number of hits to return. Defaults to 10. */
+  limit?: number
+  /** The offset from which the hits have to be returned. */
+  offset?: number
+  /**
+   * Should the number of matching documents be returned as well.
+   * Defaults to true.
+   */
+  count?: boolean
+  /** If given, only these stored fields are included in the documents. */
+  fields?: Array<string>
+  /** See `SearchOptions.scoring`. */
+  scoring?: boolean
+  /** See `SearchOptions.tieBreak`. */
+  tieBreak?: string
+  /** See `SearchOptions.profile`. */
+  profile?: boolean
+  /** See `SearchOptions.normalizeScores`. */
+  normalizeScores?: string
+  /** See `SearchOptions.minScore`. */
+  minScore?: number
+}
+
+/** Object holding the result of `Searcher.searchDocs`. */
+export interface SearchDocsResult {
+  hits: Array<SearchDocHit>
+  /** How many documents matched the query, if `count` was not disabled. */
+  count?: number
+  /**
+   * Timings of the search, only available if `SearchDocsOptions.profile`
+   * was set.
+   */
+  profile?: SearchProfile
+}
+
+/** Options of `Searcher.searchFaceted`. */
+export interface SearchFacetedOptions {
+  facets: Array<FacetRequest>
+  /** The maximum number of hits to return. Defaults to 10. */
+  limit?: number
+  /** The offset from which the hits have to be returned. */
+  offset?: number
+  /** See `SearchOptions.profile`. */
+  profile?: boolean
+}
+
+/** Object holding the result of `Searcher.searchFaceted`. */
+export interface SearchFacetedResult {
+  hits: Array<SearchHit>
+  /** How many documents matched the query. */
+  count: number
+  /** One block per requested facet, in the order of the requests. */
+  facets: Array<FacetCounts>
+  /**
+   * Timings of the search, only available if `SearchFacetedOptions.profile`
+   * was set.
+   */
+  profile?: SearchProfile
+}
+
 export interface SearchHit {
   score?: number
   order?: number
   docAddress: DocAddress
+  /**
+   * Single integer id of the document, only stable for the searcher that
+   * returned the hit. See `Searcher.docAddressForGlobalDoc`.
+   */
+  globalDoc: number
+  /**
+   * Names of the sub-queries matching the document, see `Query.named`.
+   * Only set if the query has named sub-queries.
+   */
+  matchedQueries?: Array<string>
+}
+
+/** Additional options of a search. */
+export interface SearchOptions {
+  /**
+   * If false, relevance scoring is skipped: matching documents are
+   * collected in index order and all get a constant score of 1.
+   * Defaults to true. Ignored when ordering by a field.
+   */
+  scoring?: boolean
+  /**
+   * How hits with the same score (or the same value of the ordering field)
+   * are ordered: 'docAddress' (default) returns them by ascending document
+   * address, so that pages are stable as long as the searcher is the same.
+   * 'none' leaves their order unspecified.
+   */
+  tieBreak?: string
+  /** If true, the result holds the timings of the search, see `SearchProfile`. */
+  profile?: boolean
+  /**
+   * Rescale the scores of the hits into [0, 1], over all the matching
+   * documents rather than the returned page: 'max' divides them by the
+   * highest score, 'minmax' maps the lowest score to 0 and the highest to 1,
+   * and 'none' (default) leaves them as is. When all the documents have the
+   * same score, 'minmax' gives them 1. Ignored when ordering by a field.
+   */
+  normalizeScores?: string
+  /**
+   * Discard the documents scoring below this BM25 score while collecting
+   * them, so that they are neither returned nor counted. When ordering by a
+   * field, the documents are still scored to be filtered. Can't be used
+   * along with `scoring: false`.
+   */
+  minScore?: number
+  /**
+   * Rescore the best hits by BM25 score with a JavaScript function, see
+   * `RescoreOptions`. Can't be used when ordering by a field, along with
+   * `scoring: false` or with `normalizeScores`.
+   */
+  rescore?: RescoreOptions
+}
+
+/** Timings of a search, in milliseconds. Parsing the query is not included. */
+export interface SearchProfile {
+  /**
+   * Time spent matching, scoring and collecting the documents, including
+   * the aggregations computed in the same pass, such as facet counts.
+   */
+  collectMs: number
+  /**
+   * Time spent fetching the stored documents of the hits, for
+   * `Searcher.searchDocs`.
+   */
+  fetchMs?: number
+  /** One entry per segment, in the order of the segments. */
+  segments: Array<SegmentProfile>
+}
+
+/** Everything a search returns, see `Searcher.execute`. */
+export interface SearchRequest {
+  /** The query selecting the documents. */
+  query: Query
+  /** The maximum number of hits to return. Defaults to 10. */
+  limit?: number
+  /** The offset from which the hits have to be returned. */
+  offset?: number
+  /** Order the hits by a field rather than by score. */
+  sort?: SearchSort
+  /** The facets to count, see `Searcher.searchFaceted`. */
+  facets?: Array<FacetRequest>
+  /**
+   * Aggregations computed over the matching documents, as an object or a
+   * JSON string in the format accepted by `Searcher.aggregate`.
+   */
+  aggregations?: any
+  /** Text fields highlighted in every hit, see `SnippetGenerator`. */
+  snippetFields?: Array<string>
+  /**
+   * The stored fields returned with every hit, or true to return all of
+   * them. By default, the documents are not fetched.
+   */
+  retrieveFields?: boolean | Array<string>
+  /**
+   * Stop collecting the matching documents once the search has run for this
+   * long. The response then only holds the documents collected so far.
+   */
+  timeoutMs?: number
+}
+
+/** The result of `Searcher.execute`. */
+export interface SearchResponse {
+  hits: Array<SearchResponseHit>
+  /** How many documents matched the query. */
+  count: number
+  /** One block per requested facet, in the order of the requests. */
+  facets: Array<FacetCounts>
+  /** The results of the aggregations, if any were requested. */
+  aggregations?: any
+  /** True if the timeout stopped the collection of the matching documents. */
+  timedOut: boolean
+}
+
+/** A hit of `Searcher.execute`. */
+export interface SearchResponseHit {
+  /** The score of the hit, when the hits are ordered by score. */
+  score?: number
+  /** The value of the sort field, when the hits are ordered by a field. */
+  order?: number
+  docAddress: DocAddress
+  globalDoc: number
+  /**
+   * Names of the sub-queries matching the document, see `Query.named`.
+   * Only set if the query has named sub-queries.
+   */
+  matchedQueries?: Array<string>
+  /**
+   * The stored document, in the format of `Document.toDict`, if
+   * `retrieveFields` was given.
+   */
+  doc?: any
+  /** The HTML snippet of every field of `snippetFields`. */
+  snippets?: Record<string, string>
 }
 
 /** Object holding a results successful search. */
@@ -1114,16 +3598,170 @@ export interface SearchResult {
    * to true during the search.
    */
   count?: number
+  /** Timings of the search, only available if `SearchOptions.profile` was set. */
+  profile?: SearchProfile
+}
+
+/** The field the hits of a `SearchRequest` are ordered by. */
+export interface SearchSort {
+  /**
+   * A numeric or date fast field. The `order` of the hits is its value,
+   * dates being milliseconds timestamps.
+   */
+  field: string
+  /** Defaults to descending. */
+  order?: Order
+  /**
+   * Where the documents without a value for the field go: 'first', 'last'
+   * (default) or, given a number, where a document holding this value
+   * would, dates being milliseconds timestamps. Hits placed first or last
+   * have no `order`.
+   */
+  missing?: string | number
+}
+
+/** The document counts of a segment, see `Searcher.segments`. */
+export interface SegmentInfo {
+  segmentOrd: number
+  /** The id of the segment, which its files are named after. */
+  segmentId: string
+  /** Number of documents of the segment, deleted ones included. */
+  maxDoc: number
+  /** Number of live documents of the segment. */
+  numDocs: number
+  /**
+   * Number of deleted documents of the segment, whose space is reclaimed
+   * when the segment is merged.
+   */
+  numDeletedDocs: number
+}
+
+/** Timings of a search in a segment. */
+export interface SegmentProfile {
+  segmentOrd: number
+  /** Number of documents of the segment, deleted ones included. */
+  maxDoc: number
+  /**
+   * Number of documents of the segment matching the query, which the
+   * collectors were fed.
+   */
+  docsCollected: number
+  /** Time spent matching, scoring and collecting the documents of the segment. */
+  collectMs: number
+}
+
+/**
+ * Forward the log records of Tantivy, such as merges, garbage collection of
+ * the index files and warnings, to a callback.
+ *
+ * The callback is called asynchronously, on the main thread, with the
+ * records emitted by any thread, including the indexing and merging threads.
+ * It does not keep the process alive.
+ *
+ * @param callback - Called with each `LogRecord`. If not given, records are
+ *         no longer forwarded.
+ * @param level - The most verbose level forwarded: 'error', 'warn', 'info'
+ *         (default), 'debug' or 'trace'.
+ */
+export declare function setLogHandler(callback?: ((record: LogRecord) => void) | undefined | null, level?: string | undefined | null): void
+
+/** Options of `Searcher.similarDocuments`. */
+export interface SimilarDocumentsOptions {
+  /** The number of similar documents to return. Defaults to 10. */
+  limit?: number
+  /**
+   * The stored fields the terms are taken from. Defaults to every stored
+   * field.
+   */
+  fields?: Array<string>
+  /** Words that are never used as terms. */
+  stopWords?: Array<string>
+  /** Ignore the terms found in fewer documents. Defaults to 5. */
+  minDocFrequency?: number
+  /** Ignore the terms occurring fewer times in the document. Defaults to 2. */
+  minTermFrequency?: number
+  /** The maximum number of terms of the query. Defaults to 25. */
+  maxQueryTerms?: number
 }
 
 /** Text field indexing options */
 export interface TextFieldOptions {
   /** Store the field value (can be retrieved from search results) */
   stored?: boolean
-  /** Fast field access (column-oriented storage) */
-  fast?: boolean
+  /**
+   * Index the field (enables searching, default: true). A field that is
+   * neither indexed nor fast is only stored, e.g. for opaque payloads.
+   */
+  indexed?: boolean
+  /**
+   * Fast field access (column-oriented storage). `true` normalizes the
+   * fast values with the tokenizer of the field. A string names the
+   * normalizer instead: 'raw' keeps the text as is, 'lowercase' lowercases
+   * it, e.g. for case-insensitive aggregations, and any other name is a
+   * tokenizer registered with `Index.registerTokenizer`.
+   */
+  fast?: boolean | string
   /** Tokenizer name to use (default: "default") */
   tokenizerName?: string
   /** Index record option: "basic", "freq", or "position" (default: "position") */
   indexOption?: string
 }
+
+/** Options of `Document.toDict`. */
+export interface ToDictOptions {
+  /**
+   * Map the fields having a single value to that value instead of a list,
+   * the fields having several values still being mapped to their list
+   * (default: false).
+   */
+  flatten?: boolean
+}
+
+/**
+ * The syntax allowed in the text of `Index.parseUserQuery`, the rest being
+ * searched as plain text.
+ */
+export interface UserQueryOptions {
+  /** Field prefixes, such as `title:sea` (default: false). */
+  allowFields?: boolean
+  /** Ranges, such as `[a TO b]` or `>3` (default: false). */
+  allowRanges?: boolean
+  /** Boosts, such as `sea^2` (default: false). */
+  allowBoosts?: boolean
+  /**
+   * `+`, `-`, parentheses and the `AND`, `OR` and `NOT` operators
+   * (default: true).
+   */
+  allowOperators?: boolean
+  /**
+   * Quoted phrases, with their slop or prefix, such as `"old man"~1`. A
+   * quote left open is searched as text (default: true).
+   */
+  allowPhrases?: boolean
+  /**
+   * Replace the characters of the syntax not allowed by spaces instead of
+   * escaping them (default: false).
+   */
+  strip?: boolean
+}
+
+/** Options of `IndexWriter.createWriteStream`. */
+export interface WriteStreamOptions {
+  /**
+   * What is written to the stream: 'ndjson' for text holding a JSON
+   * document per line, e.g. read from a file, or 'document' for `Document`
+   * objects. Defaults to 'ndjson'.
+   */
+  format?: string
+  /**
+   * The number of documents submitted to the writer at once, like
+   * `addJsonBatch` does. It is also the high water mark of a 'document'
+   * stream. Defaults to 1000.
+   */
+  batchSize?: number
+  /**
+   * Commit every time this many documents were written. The stream
+   * commits anyway once it is ended.
+   */
+  commitEvery?: number
+}
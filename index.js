@@ -509,6 +509,8 @@ if (!nativeBinding) {
 
 module.exports = nativeBinding
 module.exports.AllButQueryForbiddenError = nativeBinding.AllButQueryForbiddenError
+module.exports.CharFilter = nativeBinding.CharFilter
+module.exports.CharFilterStatic = nativeBinding.CharFilterStatic
 module.exports.DateFormatError = nativeBinding.DateFormatError
 module.exports.Document = nativeBinding.Document
 module.exports.ExpectedBase64Error = nativeBinding.ExpectedBase64Error
@@ -518,6 +520,7 @@ module.exports.ExpectedIntError = nativeBinding.ExpectedIntError
 module.exports.Explanation = nativeBinding.Explanation
 module.exports.Facet = nativeBinding.Facet
 module.exports.FacetFormatError = nativeBinding.FacetFormatError
+module.exports.FacetNavigator = nativeBinding.FacetNavigator
 module.exports.FieldDoesNotExistError = nativeBinding.FieldDoesNotExistError
 module.exports.FieldDoesNotHavePositionsIndexedError = nativeBinding.FieldDoesNotHavePositionsIndexedError
 module.exports.FieldNotIndexedError = nativeBinding.FieldNotIndexedError
@@ -533,9 +536,11 @@ module.exports.RangeMustNotHavePhraseError = nativeBinding.RangeMustNotHavePhras
 module.exports.Schema = nativeBinding.Schema
 module.exports.SchemaBuilder = nativeBinding.SchemaBuilder
 module.exports.Searcher = nativeBinding.Searcher
+module.exports.SerializedWriter = nativeBinding.SerializedWriter
 module.exports.Snippet = nativeBinding.Snippet
 module.exports.SnippetGenerator = nativeBinding.SnippetGenerator
 module.exports.SyntaxError = nativeBinding.SyntaxError
+module.exports.Term = nativeBinding.Term
 module.exports.TextAnalyzer = nativeBinding.TextAnalyzer
 module.exports.TextAnalyzerBuilder = nativeBinding.TextAnalyzerBuilder
 module.exports.Tokenizer = nativeBinding.Tokenizer
@@ -543,6 +548,10 @@ module.exports.TokenizerStatic = nativeBinding.TokenizerStatic
 module.exports.UnknownTokenizerError = nativeBinding.UnknownTokenizerError
 module.exports.UnsupportedQueryError = nativeBinding.UnsupportedQueryError
 module.exports.FieldType = nativeBinding.FieldType
+module.exports.fuseResults = nativeBinding.fuseResults
 module.exports.getVersion = nativeBinding.getVersion
+module.exports.metrics = nativeBinding.metrics
 module.exports.Occur = nativeBinding.Occur
 module.exports.Order = nativeBinding.Order
+module.exports.releaseHandle = nativeBinding.releaseHandle
+module.exports.setLogHandler = nativeBinding.setLogHandler
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use napi_derive::napi;
+use tantivy as tv;
+use tantivy::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::{DocId, DocSet, Score, SegmentReader, TERMINATED};
+
+const DEFAULT_MAX_ENTRIES: usize = 1024;
+
+/// Statistics of the filter cache of an index.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct FilterCacheStats {
+  /// Number of segment lookups answered from the cache.
+  pub hits: u32,
+  /// Number of segment lookups that had to execute the filter.
+  pub misses: u32,
+  /// Number of entries dropped because the cache was full or expired.
+  pub evictions: u32,
+  /// Number of entries currently held.
+  pub entries: u32,
+}
+
+struct CacheEntry {
+  docs: Arc<Vec<DocId>>,
+  inserted: Instant,
+  last_used: u64,
+}
+
+/// Cache of the documents matched by filter queries, per segment.
+///
+/// Segments are immutable, so an entry keyed by the fingerprint of the
+/// filter and the segment id stays valid for every searcher generation containing that
+/// segment. Deleted documents are filtered out by the collectors.
+pub(crate) struct FilterCache {
+  max_entries: usize,
+  ttl: Option<Duration>,
+  entries: HashMap<(u64, tv::index::SegmentId), CacheEntry>,
+  tick: u64,
+  stats: FilterCacheStats,
+}
+
+impl Default for FilterCache {
+  fn default() -> Self {
+    FilterCache {
+      max_entries: DEFAULT_MAX_ENTRIES,
+      ttl: None,
+      entries: HashMap::new(),
+      tick: 0,
+      stats: FilterCacheStats::default(),
+    }
+  }
+}
+
+impl FilterCache {
+  pub(crate) fn configure(&mut self, max_entries: Option<u32>, ttl_ms: Option<u32>) {
+    self.max_entries = max_entries.map_or(DEFAULT_MAX_ENTRIES, |n| n as usize);
+    self.ttl = ttl_ms.map(|ms| Duration::from_millis(ms as u64));
+    self.clear();
+  }
+
+  pub(crate) fn clear(&mut self) {
+    self.entries.clear();
+    self.stats = FilterCacheStats::default();
+  }
+
+  pub(crate) fn stats(&self) -> FilterCacheStats {
+    FilterCacheStats {
+      entries: self.entries.len() as u32,
+      ..self.stats.clone()
+    }
+  }
+
+  fn get(&mut self, key: &(u64, tv::index::SegmentId)) -> Option<Arc<Vec<DocId>>> {
+    self.tick += 1;
+    let expired = match (self.entries.get(key), self.ttl) {
+      (Some(entry), Some(ttl)) => entry.inserted.elapsed() > ttl,
+      _ => false,
+    };
+    if expired {
+      self.entries.remove(key);
+      self.stats.evictions += 1;
+    }
+    match self.entries.get_mut(key) {
+      Some(entry) => {
+        entry.last_used = self.tick;
+        self.stats.hits += 1;
+        Some(entry.docs.clone())
+      }
+      None => {
+        self.stats.misses += 1;
+        None
+      }
+    }
+  }
+
+  fn insert(&mut self, key: (u64, tv::index::SegmentId), docs: Arc<Vec<DocId>>) {
+    if self.max_entries == 0 {
+      return;
+    }
+    while self.entries.len() >= self.max_entries {
+      let oldest = self
+        .entries
+        .iter()
+        .min_by_key(|(_, entry)| entry.last_used)
+        .map(|(key, _)| *key);
+      match oldest {
+        Some(oldest) => {
+          self.entries.remove(&oldest);
+          self.stats.evictions += 1;
+        }
+        None => break,
+      }
+    }
+    self.entries.insert(
+      key,
+      CacheEntry {
+        docs,
+        inserted: Instant::now(),
+        last_used: self.tick,
+      },
+    );
+  }
+}
+
+/// A filter query whose matching documents are cached per segment.
+///
+/// Matching documents all get the same constant score.
+pub(crate) struct CachedFilterQuery {
+  key: u64,
+  query: Box<dyn Query>,
+  cache: Arc<Mutex<FilterCache>>,
+}
+
+impl Clone for CachedFilterQuery {
+  fn clone(&self) -> Self {
+    CachedFilterQuery {
+      key: self.key,
+      query: self.query.box_clone(),
+      cache: self.cache.clone(),
+    }
+  }
+}
+
+impl CachedFilterQuery {
+  /// A cached filter, whose documents are shared with the equivalent
+  /// filters, see `Query.fingerprint`.
+  pub(crate) fn new(query: Box<dyn Query>, cache: Arc<Mutex<FilterCache>>) -> Self {
+    let key = crate::fingerprint::fingerprint(query.as_ref());
+    CachedFilterQuery { key, query, cache }
+  }
+
+  pub(crate) fn query(&self) -> &dyn Query {
+    self.query.as_ref()
+  }
+}
+
+impl fmt::Debug for CachedFilterQuery {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "CachedFilter(key={:016x}, {:?})", self.key, self.query)
+  }
+}
+
+impl Query for CachedFilterQuery {
+  fn weight(&self, enable_scoring: EnableScoring<'_>) -> tv::Result<Box<dyn Weight>> {
+    let schema = enable_scoring.schema();
+    let inner = self
+      .query
+      .weight(EnableScoring::disabled_from_schema(schema))?;
+    Ok(Box::new(CachedFilterWeight {
+      key: self.key,
+      inner,
+      cache: self.cache.clone(),
+    }))
+  }
+
+  fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a tv::Term, bool)) {
+    self.query.query_terms(visitor);
+  }
+}
+
+struct CachedFilterWeight {
+  key: u64,
+  inner: Box<dyn Weight>,
+  cache: Arc<Mutex<FilterCache>>,
+}
+
+impl CachedFilterWeight {
+  fn matching_docs(&self, reader: &SegmentReader) -> tv::Result<Arc<Vec<DocId>>> {
+    let key = (self.key, reader.segment_id());
+    if let Some(docs) = self.cache.lock().unwrap().get(&key) {
+      return Ok(docs);
+    }
+
+    let mut scorer = self.inner.scorer(reader, 1.0)?;
+    let mut docs = Vec::new();
+    let mut doc = scorer.doc();
+    while doc != TERMINATED {
+      docs.push(doc);
+      doc = scorer.advance();
+    }
+    let docs = Arc::new(docs);
+    self.cache.lock().unwrap().insert(key, docs.clone());
+    Ok(docs)
+  }
+}
+
+impl Weight for CachedFilterWeight {
+  fn scorer(&self, reader: &SegmentReader, boost: Score) -> tv::Result<Box<dyn Scorer>> {
+    Ok(Box::new(ConstScoreDocSet::new(
+      self.matching_docs(reader)?,
+      boost,
+    )))
+  }
+
+  fn explain(&self, reader: &SegmentReader, doc: DocId) -> tv::Result<Explanation> {
+    let mut scorer = self.scorer(reader, 1.0)?;
+    if scorer.seek(doc) != doc {
+      return Err(tv::TantivyError::InvalidArgument(format!(
+        "Document #({doc}) does not match"
+      )));
+    }
+    Ok(Explanation::new("CachedFilter", scorer.score()))
+  }
+}
+
+/// Scorer over a precomputed, sorted list of documents, all with the same score.
+pub(crate) struct ConstScoreDocSet {
+  docs: Arc<Vec<DocId>>,
+  cursor: usize,
+  score: Score,
+}
+
+impl ConstScoreDocSet {
+  pub(crate) fn new(docs: Arc<Vec<DocId>>, score: Score) -> Self {
+    ConstScoreDocSet {
+      docs,
+      cursor: 0,
+      score,
+    }
+  }
+}
+
+impl DocSet for ConstScoreDocSet {
+  fn advance(&mut self) -> DocId {
+    if self.cursor < self.docs.len() {
+      self.cursor += 1;
+    }
+    self.doc()
+  }
+
+  fn doc(&self) -> DocId {
+    self.docs.get(self.cursor).copied().unwrap_or(TERMINATED)
+  }
+
+  fn size_hint(&self) -> u32 {
+    self.docs.len() as u32
+  }
+}
+
+impl Scorer for ConstScoreDocSet {
+  fn score(&mut self) -> Score {
+    self.score
+  }
+}
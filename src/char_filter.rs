@@ -0,0 +1,365 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+/// Character filters, applied to the text before it is tokenized.
+///
+/// ## Example
+///
+/// ```javascript
+/// analyzer = new TextAnalyzerBuilder(Tokenizer.simple())
+///     .charFilter(CharFilter.htmlStrip())
+///     .filter(Filter.lowercase())
+///     .build()
+/// ```
+///
+/// The offsets of the tokens refer to the original text, so snippets
+/// highlight the right characters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all_fields = "camelCase")]
+pub(crate) enum CharFilterType {
+  #[serde(rename = "patternReplace")]
+  PatternReplace {
+    pattern: String,
+    replacement: String,
+  },
+  #[serde(rename = "htmlStrip")]
+  HtmlStrip,
+}
+
+#[napi]
+#[derive(Debug, Clone)]
+pub struct CharFilter {
+  pub(crate) inner: CharFilterType,
+}
+
+#[napi]
+pub struct CharFilterStatic;
+
+#[napi]
+impl CharFilterStatic {
+  /// Replaces every match of a regular expression.
+  ///
+  /// @param pattern - The regular expression.
+  /// @param replacement - The replacement text, where `$1` or `${name}` refer
+  ///   to the groups of the match.
+  #[napi]
+  pub fn pattern_replace(pattern: String, replacement: String) -> CharFilter {
+    CharFilter {
+      inner: CharFilterType::PatternReplace {
+        pattern,
+        replacement,
+      },
+    }
+  }
+
+  /// Removes HTML markup.
+  ///
+  /// Tags and comments are removed, along with the content of `script` and
+  /// `style` elements. Numeric character references and the `&amp;`, `&lt;`,
+  /// `&gt;`, `&quot;`, `&apos;` and `&nbsp;` entities are decoded. Tags other
+  /// than inline ones (`b`, `em`, `span`...) are replaced by a space so that
+  /// the words around them are not joined.
+  #[napi]
+  pub fn html_strip() -> CharFilter {
+    CharFilter {
+      inner: CharFilterType::HtmlStrip,
+    }
+  }
+}
+
+/// A character filter ready to be applied.
+#[derive(Clone)]
+pub(crate) enum CompiledCharFilter {
+  PatternReplace { regex: Regex, replacement: String },
+  HtmlStrip,
+}
+
+impl CompiledCharFilter {
+  pub(crate) fn new(char_filter: &CharFilterType) -> Result<Self> {
+    Ok(match char_filter {
+      CharFilterType::PatternReplace {
+        pattern,
+        replacement,
+      } => CompiledCharFilter::PatternReplace {
+        regex: Regex::new(pattern)
+          .map_err(|e| Error::from_reason(format!("Invalid regex pattern: {}", e)))?,
+        replacement: replacement.clone(),
+      },
+      CharFilterType::HtmlStrip => CompiledCharFilter::HtmlStrip,
+    })
+  }
+
+  fn apply(&self, text: &str, out: &mut Rewriter) {
+    match self {
+      CompiledCharFilter::PatternReplace { regex, replacement } => {
+        let mut copied = 0;
+        let mut expanded = String::new();
+        for captures in regex.captures_iter(text) {
+          let span = captures.get(0).map_or(0..0, |m| m.range());
+          expanded.clear();
+          captures.expand(replacement, &mut expanded);
+          out.copy(text, copied..span.start);
+          out.replace(&expanded, span.clone());
+          copied = span.end;
+        }
+        out.copy(text, copied..text.len());
+      }
+      CompiledCharFilter::HtmlStrip => strip_html(text, out),
+    }
+  }
+}
+
+/// Maps the byte offsets of a filtered text back to the original text.
+#[derive(Clone, Default)]
+struct OffsetMap {
+  /// Original offset of the start of the source of every filtered byte.
+  starts: Vec<usize>,
+  /// Original offset of the end of the source of every filtered byte.
+  ends: Vec<usize>,
+  /// Length of the original text.
+  len: usize,
+}
+
+impl OffsetMap {
+  fn start(&self, offset: usize) -> usize {
+    self.starts.get(offset).copied().unwrap_or(self.len)
+  }
+
+  fn end(&self, offset: usize) -> usize {
+    match offset.checked_sub(1) {
+      Some(last) => self.ends.get(last).copied().unwrap_or(self.len),
+      None => 0,
+    }
+  }
+}
+
+/// Output of a character filter, with the source of every byte.
+#[derive(Default)]
+struct Rewriter {
+  text: String,
+  starts: Vec<usize>,
+  ends: Vec<usize>,
+}
+
+impl Rewriter {
+  fn copy(&mut self, text: &str, range: Range<usize>) {
+    self.text.push_str(&text[range.clone()]);
+    for offset in range {
+      self.starts.push(offset);
+      self.ends.push(offset + 1);
+    }
+  }
+
+  fn replace(&mut self, replacement: &str, span: Range<usize>) {
+    self.text.push_str(replacement);
+    for _ in 0..replacement.len() {
+      self.starts.push(span.start);
+      self.ends.push(span.end);
+    }
+  }
+
+  /// The offset map from the rewritten text to the original one, `previous`
+  /// mapping the text given to the filter to the original one.
+  fn offset_map(&self, previous: Option<&OffsetMap>, len: usize) -> OffsetMap {
+    match previous {
+      None => OffsetMap {
+        starts: self.starts.clone(),
+        ends: self.ends.clone(),
+        len,
+      },
+      Some(previous) => OffsetMap {
+        starts: self.starts.iter().map(|&s| previous.start(s)).collect(),
+        ends: self.ends.iter().map(|&e| previous.end(e)).collect(),
+        len: previous.len,
+      },
+    }
+  }
+}
+
+const INLINE_TAGS: &[&str] = &[
+  "a", "abbr", "b", "bdi", "bdo", "cite", "code", "data", "del", "dfn", "em", "font", "i", "ins",
+  "kbd", "mark", "q", "s", "samp", "small", "span", "strike", "strong", "sub", "sup", "time", "tt",
+  "u", "var",
+];
+
+fn strip_html(text: &str, out: &mut Rewriter) {
+  let bytes = text.as_bytes();
+  let mut copied = 0;
+  let mut i = 0;
+  while i < bytes.len() {
+    let replaced = match bytes[i] {
+      b'<' => html_tag(text, i).map(|(end, replacement)| (end, replacement.to_string())),
+      b'&' => html_entity(text, i),
+      _ => None,
+    };
+    match replaced {
+      Some((end, replacement)) => {
+        out.copy(text, copied..i);
+        out.replace(&replacement, i..end);
+        copied = end;
+        i = end;
+      }
+      None => i += 1,
+    }
+  }
+  out.copy(text, copied..text.len());
+}
+
+/// The text of an HTML document, as the `html_strip` filter rewrites it.
+pub(crate) fn html_text(html: &str) -> String {
+  let mut out = Rewriter::default();
+  strip_html(html, &mut out);
+  out.text
+}
+
+/// The end of the tag starting at `start`, and the text replacing it.
+fn html_tag(text: &str, start: usize) -> Option<(usize, &'static str)> {
+  let rest = &text[start..];
+  if let Some(comment) = rest.strip_prefix("<!--") {
+    let end = comment
+      .find("-->")
+      .map_or(text.len(), |pos| start + 4 + pos + 3);
+    return Some((end, ""));
+  }
+  let after = rest[1..].chars().next()?;
+  if !(after.is_ascii_alphabetic() || matches!(after, '/' | '!' | '?')) {
+    // A lone `<`, as in `a < b`.
+    return None;
+  }
+  let end = start + rest.find('>')? + 1;
+  let closing = after == '/';
+  let name: String = rest[1..]
+    .trim_start_matches('/')
+    .chars()
+    .take_while(char::is_ascii_alphanumeric)
+    .collect::<String>()
+    .to_ascii_lowercase();
+  if !closing && (name == "script" || name == "style") {
+    let closing_tag = format!("</{}", name);
+    let end = text[end..]
+      .to_ascii_lowercase()
+      .find(&closing_tag)
+      .and_then(|pos| {
+        let close = end + pos;
+        text[close..].find('>').map(|gt| close + gt + 1)
+      })
+      .unwrap_or(text.len());
+    return Some((end, " "));
+  }
+  if INLINE_TAGS.contains(&name.as_str()) {
+    Some((end, ""))
+  } else {
+    Some((end, " "))
+  }
+}
+
+/// The end of the character entity starting at `start`, and its character.
+fn html_entity(text: &str, start: usize) -> Option<(usize, String)> {
+  let rest = &text[start + 1..];
+  let semicolon = rest.char_indices().take(12).find(|(_, c)| *c == ';')?.0;
+  let name = &rest[..semicolon];
+  let decoded = match name {
+    "amp" => '&',
+    "lt" => '<',
+    "gt" => '>',
+    "quot" => '"',
+    "apos" => '\'',
+    "nbsp" => ' ',
+    _ => {
+      let code = match name.strip_prefix('#')? {
+        hex if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok()?,
+        decimal => decimal.parse().ok()?,
+      };
+      char::from_u32(code)?
+    }
+  };
+  Some((start + 1 + semicolon + 1, decoded.to_string()))
+}
+
+/// Wraps a tokenizer so that it tokenizes the text rewritten by character filters.
+#[derive(Clone)]
+pub(crate) struct CharFilterTokenizer<T> {
+  tokenizer: T,
+  char_filters: Arc<Vec<CompiledCharFilter>>,
+  text: String,
+  offsets: OffsetMap,
+}
+
+impl<T> CharFilterTokenizer<T> {
+  pub(crate) fn new(tokenizer: T, char_filters: Vec<CompiledCharFilter>) -> Self {
+    CharFilterTokenizer {
+      tokenizer,
+      char_filters: Arc::new(char_filters),
+      text: String::new(),
+      offsets: OffsetMap::default(),
+    }
+  }
+}
+
+impl<T: Tokenizer> Tokenizer for CharFilterTokenizer<T> {
+  type TokenStream<'a> = CharFilterTokenStream<'a, T::TokenStream<'a>>;
+
+  fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+    let mut filtered: Option<(String, OffsetMap)> = None;
+    for char_filter in self.char_filters.iter() {
+      let input = filtered.as_ref().map_or(text, |(text, _)| text.as_str());
+      let mut out = Rewriter::default();
+      char_filter.apply(input, &mut out);
+      let offsets = out.offset_map(filtered.as_ref().map(|(_, offsets)| offsets), text.len());
+      filtered = Some((out.text, offsets));
+    }
+    let (filtered_text, offsets) = filtered.unwrap_or_else(|| {
+      let offsets = OffsetMap {
+        starts: (0..text.len()).collect(),
+        ends: (1..=text.len()).collect(),
+        len: text.len(),
+      };
+      (text.to_string(), offsets)
+    });
+    self.text = filtered_text;
+    self.offsets = offsets;
+
+    let CharFilterTokenizer {
+      tokenizer,
+      text,
+      offsets,
+      ..
+    } = self;
+    CharFilterTokenStream {
+      tail: tokenizer.token_stream(text),
+      offsets,
+    }
+  }
+}
+
+pub(crate) struct CharFilterTokenStream<'a, T> {
+  tail: T,
+  offsets: &'a OffsetMap,
+}
+
+impl<T: TokenStream> TokenStream for CharFilterTokenStream<'_, T> {
+  fn advance(&mut self) -> bool {
+    if !self.tail.advance() {
+      return false;
+    }
+    let token = self.tail.token_mut();
+    let offset_from = self.offsets.start(token.offset_from);
+    token.offset_to = self.offsets.end(token.offset_to).max(offset_from);
+    token.offset_from = offset_from;
+    true
+  }
+
+  fn token(&self) -> &Token {
+    self.tail.token()
+  }
+
+  fn token_mut(&mut self) -> &mut Token {
+    self.tail.token_mut()
+  }
+}
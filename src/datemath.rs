@@ -0,0 +1,188 @@
+use chrono::{DateTime, Datelike, Duration, Months, SecondsFormat, TimeZone, Timelike, Utc};
+use tantivy as tv;
+
+/// Where a date math expression appears in a query.
+enum Position {
+  /// `field:now/d`, the resolved date must be quoted.
+  Term,
+  /// `field:[now-7d TO now]` or `field:>now-1h`.
+  RangeBound,
+}
+
+/// Replace the date math expressions on date fields of a query, such as
+/// `date:[now-7d TO now]` or `date:>=now/d`, with the dates they resolve to.
+///
+/// Expressions are `now` followed by any number of additions or subtractions
+/// (`+1d`, `-2h`) and roundings down (`/d`), using the units `y`, `M`, `w`,
+/// `d`, `h` (or `H`), `m` and `s`. Expressions that cannot be resolved are
+/// left untouched, for the query parser to report.
+pub(crate) fn resolve_date_math(
+  query: &str,
+  schema: &tv::schema::Schema,
+  now: DateTime<Utc>,
+) -> String {
+  let mut resolved = String::with_capacity(query.len());
+  let mut in_quotes = false;
+  let mut escaped = false;
+  let mut i = 0;
+  while let Some(c) = query[i..].chars().next() {
+    if in_quotes {
+      if escaped {
+        escaped = false;
+      } else if c == '\\' {
+        escaped = true;
+      } else if c == '"' {
+        in_quotes = false;
+      }
+    } else if c == '"' {
+      in_quotes = true;
+    } else if query[i..].starts_with("now") {
+      let len = expression_len(&query[i..]);
+      let ends_value = query[i + len..]
+        .chars()
+        .next()
+        .is_none_or(|next| next.is_whitespace() || matches!(next, ')' | ']' | '}' | '^'));
+      let date = position(&query[..i], schema)
+        .filter(|_| ends_value)
+        .and_then(|position| Some((position, evaluate(&query[i + 3..i + len], now)?)));
+      if let Some((position, date)) = date {
+        let date = date.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+        match position {
+          Position::Term => resolved.push_str(&format!("\"{date}\"")),
+          Position::RangeBound => resolved.push_str(&date),
+        }
+        i += len;
+        continue;
+      }
+    }
+    resolved.push(c);
+    i += c.len_utf8();
+  }
+  resolved
+}
+
+/// Length of the date math expression at the start of `text`, which starts with `now`.
+fn expression_len(text: &str) -> usize {
+  let bytes = text.as_bytes();
+  let mut len = 3;
+  loop {
+    match bytes.get(len) {
+      Some(b'+' | b'-') => {
+        let digits = bytes[len + 1..]
+          .iter()
+          .take_while(|b| b.is_ascii_digit())
+          .count();
+        match bytes.get(len + 1 + digits) {
+          Some(&unit) if digits > 0 && is_unit(unit) => len += digits + 2,
+          _ => return len,
+        }
+      }
+      Some(b'/') => match bytes.get(len + 1) {
+        Some(&unit) if is_unit(unit) => len += 2,
+        _ => return len,
+      },
+      _ => return len,
+    }
+  }
+}
+
+fn is_unit(unit: u8) -> bool {
+  matches!(unit, b'y' | b'M' | b'w' | b'd' | b'h' | b'H' | b'm' | b's')
+}
+
+/// Find whether the text preceding a `now` puts it in the value of a date field.
+fn position(before: &str, schema: &tv::schema::Schema) -> Option<Position> {
+  let before = before.trim_end();
+  let (before, position) = if let Some(before) = before.strip_suffix(':') {
+    (before, Position::Term)
+  } else if let Some(before) = [">=", "<=", ">", "<"]
+    .iter()
+    .find_map(|op| before.strip_suffix(op))
+  {
+    (before.trim_end().strip_suffix(':')?, Position::RangeBound)
+  } else {
+    let before = match before.strip_suffix("TO") {
+      // The upper bound, skip the lower bound.
+      Some(lower) if lower.ends_with(char::is_whitespace) => {
+        let lower = lower.trim_end();
+        lower.trim_end_matches(|c: char| !c.is_whitespace() && c != '[' && c != '{')
+      }
+      _ => before,
+    };
+    let before = before.strip_suffix(['[', '{'])?.trim_end();
+    (before.strip_suffix(':')?, Position::RangeBound)
+  };
+
+  let field_name = before
+    .rsplit(|c: char| c.is_whitespace() || c == '(')
+    .next()?
+    .trim_start_matches(['+', '-']);
+  let field = schema.get_field(field_name).ok()?;
+  match schema.get_field_entry(field).field_type().value_type() {
+    tv::schema::Type::Date => Some(position),
+    _ => None,
+  }
+}
+
+/// Apply the operations following `now` in a date math expression.
+fn evaluate(operations: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+  let mut date = now;
+  let mut rest = operations;
+  while let Some(op) = rest.chars().next() {
+    if op == '/' {
+      let unit = rest[1..].chars().next()?;
+      date = round_down(date, unit)?;
+      rest = &rest[2..];
+    } else {
+      let digits = rest[1..].chars().take_while(char::is_ascii_digit).count();
+      let amount: i64 = rest[1..1 + digits].parse().ok()?;
+      let amount = if op == '-' { -amount } else { amount };
+      let unit = rest[1 + digits..].chars().next()?;
+      date = add(date, amount, unit)?;
+      rest = &rest[2 + digits..];
+    }
+  }
+  Some(date)
+}
+
+fn add(date: DateTime<Utc>, amount: i64, unit: char) -> Option<DateTime<Utc>> {
+  let months = match unit {
+    'y' => amount.checked_mul(12)?,
+    'M' => amount,
+    _ => {
+      let duration = match unit {
+        'w' => Duration::try_weeks(amount)?,
+        'd' => Duration::try_days(amount)?,
+        'h' | 'H' => Duration::try_hours(amount)?,
+        'm' => Duration::try_minutes(amount)?,
+        _ => Duration::try_seconds(amount)?,
+      };
+      return date.checked_add_signed(duration);
+    }
+  };
+  let abs_months = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+  if months >= 0 {
+    date.checked_add_months(abs_months)
+  } else {
+    date.checked_sub_months(abs_months)
+  }
+}
+
+fn round_down(date: DateTime<Utc>, unit: char) -> Option<DateTime<Utc>> {
+  let day = |date: DateTime<Utc>| {
+    Utc
+      .with_ymd_and_hms(date.year(), date.month(), date.day(), 0, 0, 0)
+      .single()
+  };
+  match unit {
+    'y' => Utc.with_ymd_and_hms(date.year(), 1, 1, 0, 0, 0).single(),
+    'M' => Utc
+      .with_ymd_and_hms(date.year(), date.month(), 1, 0, 0, 0)
+      .single(),
+    'w' => day(date - Duration::days(date.weekday().num_days_from_monday() as i64)),
+    'd' => day(date),
+    'h' | 'H' => Some(day(date)? + Duration::hours(date.hour() as i64)),
+    'm' => Some(day(date)? + Duration::minutes((date.hour() * 60 + date.minute()) as i64)),
+    _ => date.with_nanosecond(0),
+  }
+}
@@ -0,0 +1,176 @@
+use std::collections::VecDeque;
+
+use napi::bindgen_prelude::*;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use tantivy as tv;
+use tantivy::postings::Postings;
+use tantivy::schema::{FieldType, IndexRecordOption};
+use tantivy::termdict::TermMerger;
+use tantivy::{DocSet, TERMINATED};
+
+use crate::to_napi_error;
+
+/// Number of terms read from the term dictionaries at once.
+const PAGE_SIZE: usize = 1024;
+
+/// Options of `Index.exportDictionary`.
+#[napi(object)]
+#[derive(Default)]
+pub struct DictionaryOptions {
+  /// Skip the terms found in fewer documents.
+  pub min_doc_freq: Option<u32>,
+  /// Maximum number of terms to export.
+  pub limit: Option<u32>,
+}
+
+/// A term of the dictionary of a field, see `Index.exportDictionary`.
+#[napi(object, object_from_js = false)]
+pub struct DictionaryEntry {
+  pub term: String,
+  /// Number of documents containing the term.
+  pub doc_freq: u32,
+  /// Number of occurrences of the term in all the documents, or undefined
+  /// if the field doesn't record the term frequencies.
+  pub total_term_freq: Option<i64>,
+}
+
+/// Iterator over the terms of a text field across all the segments of an
+/// index, in lexicographic order, see `Index.exportDictionary`.
+///
+/// The terms are read a page at a time, after the last term read, so that
+/// the whole dictionary is never held in memory. Like `Searcher.docFreq`,
+/// the frequencies count the deleted documents until their segment is merged.
+#[napi(iterator)]
+pub struct DictionaryIterator {
+  searcher: tv::Searcher,
+  field: tv::schema::Field,
+  with_freqs: bool,
+  min_doc_freq: u32,
+  remaining: Option<u32>,
+  /// The last term read, the next page starts after it.
+  last_term: Option<Vec<u8>>,
+  exhausted: bool,
+  page: VecDeque<DictionaryEntry>,
+}
+
+impl DictionaryIterator {
+  pub(crate) fn new(
+    searcher: tv::Searcher,
+    field_name: &str,
+    options: DictionaryOptions,
+  ) -> Result<Self> {
+    let schema = searcher.schema();
+    let field = crate::get_field(schema, field_name)?;
+    let indexing = match schema.get_field_entry(field).field_type() {
+      FieldType::Str(options) => options.get_indexing_options(),
+      _ => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Field '{}' is not a text field.", field_name),
+        ))
+      }
+    };
+    let Some(indexing) = indexing else {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Field '{}' is not indexed.", field_name),
+      ));
+    };
+    let with_freqs = indexing.index_option().has_freq();
+    let mut iterator = DictionaryIterator {
+      searcher,
+      field,
+      with_freqs,
+      min_doc_freq: options.min_doc_freq.unwrap_or(0),
+      remaining: options.limit,
+      last_term: None,
+      exhausted: false,
+      page: VecDeque::new(),
+    };
+    // Fail now rather than on the first iteration.
+    iterator.read_page().map_err(to_napi_error)?;
+    Ok(iterator)
+  }
+
+  /// Read the next page of terms from the dictionaries of the segments.
+  fn read_page(&mut self) -> tv::Result<()> {
+    let inverted_indexes = self
+      .searcher
+      .segment_readers()
+      .iter()
+      .map(|segment_reader| segment_reader.inverted_index(self.field))
+      .collect::<tv::Result<Vec<_>>>()?;
+    let streams = inverted_indexes
+      .iter()
+      .map(|inverted_index| {
+        let range = inverted_index.terms().range();
+        match &self.last_term {
+          Some(last_term) => range.gt(last_term),
+          None => range,
+        }
+        .into_stream()
+      })
+      .collect::<std::io::Result<Vec<_>>>()?;
+
+    let mut merger = TermMerger::new(streams);
+    let mut num_terms = 0;
+    while num_terms < PAGE_SIZE && merger.advance() {
+      num_terms += 1;
+      self.last_term = Some(merger.key().to_vec());
+      let term_infos: Vec<_> = merger.current_segment_ords_and_term_infos().collect();
+      let doc_freq: u32 = term_infos
+        .iter()
+        .map(|(_, term_info)| term_info.doc_freq)
+        .sum();
+      if doc_freq < self.min_doc_freq {
+        continue;
+      }
+      let total_term_freq = if self.with_freqs {
+        let mut total_term_freq = 0i64;
+        for (segment_ord, term_info) in &term_infos {
+          let mut postings = inverted_indexes[*segment_ord]
+            .read_postings_from_terminfo(term_info, IndexRecordOption::WithFreqs)?;
+          while postings.doc() != TERMINATED {
+            total_term_freq += postings.term_freq() as i64;
+            postings.advance();
+          }
+        }
+        Some(total_term_freq)
+      } else {
+        None
+      };
+      self.page.push_back(DictionaryEntry {
+        term: String::from_utf8_lossy(merger.key()).into_owned(),
+        doc_freq,
+        total_term_freq,
+      });
+    }
+    self.exhausted = num_terms < PAGE_SIZE;
+    Ok(())
+  }
+}
+
+#[napi]
+impl Generator for DictionaryIterator {
+  type Yield = Result<DictionaryEntry>;
+  type Next = ();
+  type Return = ();
+
+  fn next(&mut self, _value: Option<()>) -> Option<Self::Yield> {
+    if self.remaining == Some(0) {
+      return None;
+    }
+    while self.page.is_empty() && !self.exhausted {
+      if let Err(e) = self.read_page() {
+        self.exhausted = true;
+        return Some(Err(to_napi_error(e)));
+      }
+    }
+    let entry = self.page.pop_front()?;
+    if let Some(remaining) = &mut self.remaining {
+      *remaining -= 1;
+    }
+    Some(Ok(entry))
+  }
+}
@@ -165,11 +165,14 @@ pub(crate) fn extract_value(value: &Unknown) -> Result<Value> {
   }
 }
 
-// Simplified schema-aware value extraction (similar to Python)
+// Simplified schema-aware value extraction (similar to Python). Numbers not
+// fitting a numeric field are rejected, unless `coerce` is true, see
+// `Document.fromDict`.
 pub(crate) fn extract_value_for_type(
   value: &Unknown,
   tv_type: tv::schema::Type,
   field_name: &str,
+  coerce: bool,
 ) -> Result<Value> {
   let error_msg = |type_name: &str| {
     format!(
@@ -184,27 +187,39 @@ pub(crate) fn extract_value_for_type(
       Ok(Value::Str(s))
     }
     tv::schema::Type::U64 => {
-      // Reject strings but allow number coercion
-      if matches!(value.get_type()?, ValueType::String) {
-        return Err(Error::new(Status::InvalidArg, error_msg("U64")));
+      // Strings are rejected, even when coercing
+      match value.get_type()? {
+        ValueType::String => Err(Error::new(Status::InvalidArg, error_msg("U64"))),
+        ValueType::Number | ValueType::BigInt if !coerce => {
+          Ok(Value::U64(crate::value_to_u64(field_name, value)?))
+        }
+        _ if !coerce => Err(Error::new(Status::InvalidArg, error_msg("U64"))),
+        _ => {
+          let n = value.coerce_to_number()?.get_double()?;
+          Ok(Value::U64(n.abs() as u64))
+        }
       }
-      let n = value.coerce_to_number()?.get_double()?;
-      Ok(Value::U64(n.abs() as u64))
     }
-    tv::schema::Type::I64 => {
-      if matches!(value.get_type()?, ValueType::String) {
-        return Err(Error::new(Status::InvalidArg, error_msg("I64")));
+    tv::schema::Type::I64 => match value.get_type()? {
+      ValueType::String => Err(Error::new(Status::InvalidArg, error_msg("I64"))),
+      ValueType::Number | ValueType::BigInt if !coerce => {
+        Ok(Value::I64(crate::value_to_i64(field_name, value)?))
       }
-      let n = value.coerce_to_number()?.get_double()?;
-      Ok(Value::I64(n as i64))
-    }
-    tv::schema::Type::F64 => {
-      if matches!(value.get_type()?, ValueType::String) {
-        return Err(Error::new(Status::InvalidArg, error_msg("F64")));
+      _ if !coerce => Err(Error::new(Status::InvalidArg, error_msg("I64"))),
+      _ => {
+        let n = value.coerce_to_number()?.get_double()?;
+        Ok(Value::I64(n as i64))
       }
-      let n = value.coerce_to_number()?.get_double()?;
-      Ok(Value::F64(n))
-    }
+    },
+    tv::schema::Type::F64 => match value.get_type()? {
+      ValueType::String => Err(Error::new(Status::InvalidArg, error_msg("F64"))),
+      ValueType::Number => Ok(Value::F64(value.coerce_to_number()?.get_double()?)),
+      _ if !coerce => Err(Error::new(Status::InvalidArg, error_msg("F64"))),
+      _ => {
+        let n = value.coerce_to_number()?.get_double()?;
+        Ok(Value::F64(n))
+      }
+    },
     tv::schema::Type::Bool => {
       let b = value.coerce_to_bool()?;
       Ok(Value::Bool(b))
@@ -269,6 +284,86 @@ pub(crate) fn extract_value_for_type(
   }
 }
 
+/// Outcome of fitting a document value to the type of its field.
+pub(crate) enum Coercion {
+  /// The value already has the type of the field.
+  Kept,
+  /// The value was converted to the type of the field.
+  Coerced(Value),
+  /// The value can't be converted to the type of the field.
+  Dropped,
+}
+
+/// Fit a value to the given field type, converting it when it is lossless.
+pub(crate) fn coerce_value(value: &Value, tv_type: tv::schema::Type) -> Coercion {
+  use tv::schema::Type;
+
+  let coerced = match (tv_type, value) {
+    (Type::Str, Value::Str(_) | Value::PreTokStr(_))
+    | (Type::U64, Value::U64(_))
+    | (Type::I64, Value::I64(_))
+    | (Type::F64, Value::F64(_))
+    | (Type::Bool, Value::Bool(_))
+    | (Type::Date, Value::Date(_))
+    | (Type::Facet, Value::Facet(_))
+    | (Type::Bytes, Value::Bytes(_))
+    | (Type::Json, Value::Object(_))
+    | (Type::IpAddr, Value::IpAddr(_)) => return Coercion::Kept,
+
+    (Type::Str, Value::U64(_) | Value::I64(_) | Value::F64(_) | Value::Bool(_)) => {
+      Some(Value::Str(value_to_string(value)))
+    }
+    (Type::U64, Value::I64(n)) => u64::try_from(*n).ok().map(Value::U64),
+    (Type::U64, Value::F64(n)) if n.fract() == 0.0 && *n >= 0.0 && *n <= u64::MAX as f64 => {
+      Some(Value::U64(*n as u64))
+    }
+    (Type::U64, Value::Str(s)) => s.trim().parse().ok().map(Value::U64),
+    (Type::I64, Value::U64(n)) => i64::try_from(*n).ok().map(Value::I64),
+    (Type::I64, Value::F64(n))
+      if n.fract() == 0.0 && *n >= i64::MIN as f64 && *n <= i64::MAX as f64 =>
+    {
+      Some(Value::I64(*n as i64))
+    }
+    (Type::I64, Value::Str(s)) => s.trim().parse().ok().map(Value::I64),
+    (Type::F64, Value::U64(n)) => Some(Value::F64(*n as f64)),
+    (Type::F64, Value::I64(n)) => Some(Value::F64(*n as f64)),
+    (Type::F64, Value::Str(s)) => s
+      .trim()
+      .parse::<f64>()
+      .ok()
+      .filter(|n| n.is_finite())
+      .map(Value::F64),
+    (Type::Bool, Value::Str(s)) => match s.trim() {
+      "true" => Some(Value::Bool(true)),
+      "false" => Some(Value::Bool(false)),
+      _ => None,
+    },
+    (Type::Bool, Value::U64(n)) if *n <= 1 => Some(Value::Bool(*n == 1)),
+    (Type::Bool, Value::I64(n)) if *n == 0 || *n == 1 => Some(Value::Bool(*n == 1)),
+    (Type::Date, Value::I64(millis)) => {
+      Some(Value::Date(tv::DateTime::from_timestamp_millis(*millis)))
+    }
+    (Type::Date, Value::U64(millis)) => i64::try_from(*millis)
+      .ok()
+      .map(|millis| Value::Date(tv::DateTime::from_timestamp_millis(millis))),
+    (Type::Date, Value::Str(s)) => chrono::DateTime::parse_from_rfc3339(s.trim())
+      .ok()
+      .map(|dt| Value::Date(tv::DateTime::from_timestamp_millis(dt.timestamp_millis()))),
+    (Type::Facet, Value::Str(s)) => tv::schema::Facet::from_text(s).ok().map(Value::Facet),
+    (Type::IpAddr, Value::Str(s)) => IpAddr::from_str(s.trim()).ok().map(|ip_addr| {
+      Value::IpAddr(match ip_addr {
+        IpAddr::V4(addr) => addr.to_ipv6_mapped(),
+        IpAddr::V6(addr) => addr,
+      })
+    }),
+    _ => None,
+  };
+  match coerced {
+    Some(value) => Coercion::Coerced(value),
+    None => Coercion::Dropped,
+  }
+}
+
 fn extract_value_single_or_list(value: &Unknown) -> Result<Vec<Value>> {
   // Check if it's a string first, since strings are array-like in JavaScript
   if matches!(value.get_type()?, ValueType::String) {
@@ -294,6 +389,7 @@ fn extract_value_single_or_list_for_type(
   value: &Unknown,
   field_type: &tv::schema::FieldType,
   field_name: &str,
+  coerce: bool,
 ) -> Result<Vec<Value>> {
   // Check if it's a string first, since strings are array-like in JavaScript
   if matches!(value.get_type()?, ValueType::String) {
@@ -301,6 +397,7 @@ fn extract_value_single_or_list_for_type(
       value,
       field_type.value_type(),
       field_name,
+      coerce,
     )?]);
   }
 
@@ -324,6 +421,7 @@ fn extract_value_single_or_list_for_type(
         &item,
         field_type.value_type(),
         field_name,
+        coerce,
       )?);
     }
     return Ok(values);
@@ -333,6 +431,7 @@ fn extract_value_single_or_list_for_type(
     value,
     field_type.value_type(),
     field_name,
+    coerce,
   )?])
 }
 
@@ -417,6 +516,14 @@ fn value_to_serde_json(value: &Value) -> serde_json::Value {
   }
 }
 
+/// Like `value_to_serde_json`, but with dates in milliseconds like `toDict`.
+fn value_to_dict_json(value: &Value) -> serde_json::Value {
+  match value {
+    Value::Date(d) => serde_json::Value::from(d.into_timestamp_secs() as f64 * 1000.0),
+    _ => value_to_serde_json(value),
+  }
+}
+
 fn value_to_string(value: &Value) -> String {
   match value {
     Value::Null => format!("{:?}", value),
@@ -649,6 +756,16 @@ impl<'a> From<&'a Value> for BorrowedSerdeValue<'a> {
   }
 }
 
+/// Options of `Document.toDict`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ToDictOptions {
+  /// Map the fields having a single value to that value instead of a list,
+  /// the fields having several values still being mapped to their list
+  /// (default: false).
+  pub flatten: Option<bool>,
+}
+
 /// Tantivy's Document is the object that can be indexed and then searched for.
 ///
 /// Documents are fundamentally a collection of unordered tuples
@@ -744,16 +861,54 @@ impl Document {
   }
 
   /// Extend the document with field values from a JavaScript object.
+  ///
+  /// The values are appended after the values already in the document, unless
+  /// `overwrite` is true, in which case they replace the values of their field.
+  /// See `fromDict` for `coerce`.
   #[napi]
-  pub fn extend(&mut self, env: Env, js_obj: Object, schema: Option<&Schema>) -> Result<()> {
-    Document::extract_js_values_from_object(env, &js_obj, schema, &mut self.field_values)
+  pub fn extend(
+    &mut self,
+    env: Env,
+    js_obj: Object,
+    schema: Option<&Schema>,
+    overwrite: Option<bool>,
+    coerce: Option<bool>,
+  ) -> Result<()> {
+    Document::extract_js_values_from_object(
+      env,
+      &js_obj,
+      schema,
+      overwrite.unwrap_or(false),
+      coerce.unwrap_or(false),
+      &mut self.field_values,
+    )
   }
 
   /// Create a document from a JavaScript object.
+  ///
+  /// If a schema is given, the values are converted to the type of their
+  /// field. The numbers given to numeric fields must then fit their type:
+  /// negative numbers for unsigned fields, fractional numbers for integer
+  /// fields and values that are not numbers, such as booleans, are rejected.
+  /// If `coerce` is true, they are converted instead: negative numbers lose
+  /// their sign, fractional numbers are truncated and booleans count as 0
+  /// or 1.
   #[napi(factory)]
-  pub fn from_dict(env: Env, js_obj: Object, schema: Option<&Schema>) -> Result<Document> {
+  pub fn from_dict(
+    env: Env,
+    js_obj: Object,
+    schema: Option<&Schema>,
+    coerce: Option<bool>,
+  ) -> Result<Document> {
     let mut field_values: BTreeMap<String, Vec<Value>> = BTreeMap::new();
-    Document::extract_js_values_from_object(env, &js_obj, schema, &mut field_values)?;
+    Document::extract_js_values_from_object(
+      env,
+      &js_obj,
+      schema,
+      true,
+      coerce.unwrap_or(false),
+      &mut field_values,
+    )?;
     Ok(Document { field_values })
   }
 
@@ -761,11 +916,17 @@ impl Document {
   ///
   /// In tantivy, `Document` can hold multiple values for a single field.
   ///
-  /// For this reason, the object will associate a list of values for every field.
+  /// For this reason, the object will associate a list of values for every field,
+  /// unless `flatten` is set, see `ToDictOptions`.
   #[napi]
-  pub fn to_dict(&self, env: Env) -> Result<Object<'_>> {
+  pub fn to_dict(&self, env: Env, options: Option<ToDictOptions>) -> Result<Object<'_>> {
+    let flatten = options.and_then(|options| options.flatten).unwrap_or(false);
     let mut obj = Object::new(&env)?;
     for (key, values) in &self.field_values {
+      if let ([value], true) = (values.as_slice(), flatten) {
+        obj.set_named_property(key, value_to_js(env, value)?)?;
+        continue;
+      }
       let mut js_values = env.create_array(values.len() as u32)?;
       for (i, v) in values.iter().enumerate() {
         let js_value = value_to_js(env, v)?;
@@ -812,6 +973,17 @@ impl Document {
     self.add_value(field_name, value);
   }
 
+  /// Add a geo point value to the document.
+  ///
+  /// @param fieldName - The geo point field for which we are adding the point.
+  /// @param lat - The latitude, in degrees.
+  /// @param lon - The longitude, in degrees.
+  #[napi]
+  pub fn add_geo_point(&mut self, field_name: String, lat: f64, lon: f64) {
+    self.add_value(crate::geo::lat_field_name(&field_name), lat);
+    self.add_value(crate::geo::lon_field_name(&field_name), lon);
+  }
+
   /// Add a boolean value to the document.
   ///
   /// @param fieldName - The field name for which we are adding the value.
@@ -850,6 +1022,15 @@ impl Document {
     self.add_value(field_name, bytes.to_vec());
   }
 
+  /// Add a dense vector value to the document.
+  ///
+  /// @param fieldName - The vector field for which we are adding the vector.
+  /// @param vector - The vector components, stored as 32-bit floats.
+  #[napi]
+  pub fn add_vector(&mut self, field_name: String, vector: Vec<f64>) {
+    self.add_value(field_name, crate::vector::encode_vector(&vector));
+  }
+
   /// Add a JSON value to the document.
   ///
   /// @param fieldName - The field for which we are adding the JSON.
@@ -882,6 +1063,70 @@ impl Document {
     Ok(())
   }
 
+  /// Remove all the values of a field.
+  ///
+  /// @param fieldName - The field to remove.
+  ///
+  /// @returns True if the document had values for this field.
+  #[napi]
+  pub fn remove(&mut self, field_name: String) -> bool {
+    self.field_values.remove(&field_name).is_some()
+  }
+
+  /// Replace the values of a field.
+  ///
+  /// @param fieldName - The field for which we are setting the values.
+  /// @param values - A single value or an array of values, converted like
+  ///         the values given to `fromDict`.
+  /// @param schema - If given, the values are converted to the type of the field.
+  /// @param coerce - Convert the numbers not fitting a numeric field instead
+  ///         of rejecting them, see `fromDict`.
+  ///
+  /// @throws Raises an error if the field is not in the schema, or if a value
+  ///         cannot be converted.
+  #[napi]
+  pub fn set(
+    &mut self,
+    field_name: String,
+    values: Unknown,
+    schema: Option<&Schema>,
+    coerce: Option<bool>,
+  ) -> Result<()> {
+    let value_list = if let Some(schema) = schema {
+      let field = crate::get_field(&schema.inner, &field_name)?;
+      let field_type = schema.inner.get_field_entry(field).field_type();
+      extract_value_single_or_list_for_type(
+        &values,
+        field_type,
+        &field_name,
+        coerce.unwrap_or(false),
+      )?
+    } else {
+      extract_value_single_or_list(&values)?
+    };
+    self.field_values.insert(field_name, value_list);
+    Ok(())
+  }
+
+  /// Add all the values of another document to this document.
+  ///
+  /// The values of a field present in both documents are appended after the
+  /// values already in this document.
+  ///
+  /// @param other - The document whose values are added.
+  #[napi]
+  pub fn merge(&mut self, other: &Document) {
+    // Copy first, `other` may be this very document.
+    let other_values = other.field_values.clone();
+    for (field_name, values) in other_values {
+      self
+        .field_values
+        .entry(field_name)
+        .or_default()
+        .extend(values);
+    }
+  }
+
   /// Returns the number of added fields that have been added to the document
   #[napi(getter)]
   pub fn num_fields(&self) -> u32 {
@@ -948,6 +1193,8 @@ impl Document {
     _env: Env,
     js_object: &Object,
     schema: Option<&Schema>,
+    overwrite: bool,
+    coerce: bool,
     out_field_values: &mut BTreeMap<String, Vec<Value>>,
   ) -> Result<()> {
     let keys = js_object.get_property_names()?;
@@ -968,16 +1215,53 @@ impl Document {
       };
 
       let value_list = if let Some(ref field_type) = field_type {
-        extract_value_single_or_list_for_type(&js_value, field_type, &key)?
+        extract_value_single_or_list_for_type(&js_value, field_type, &key, coerce)?
       } else {
         extract_value_single_or_list(&js_value)?
       };
 
-      out_field_values.insert(key, value_list);
+      if overwrite {
+        out_field_values.insert(key, value_list);
+      } else {
+        out_field_values.entry(key).or_default().extend(value_list);
+      }
     }
     Ok(())
   }
 
+  /// Convert the document to the JSON equivalent of `toDict`, keeping only
+  /// `fields` if given.
+  pub(crate) fn to_json_dict(&self, fields: Option<&[String]>) -> serde_json::Value {
+    let map: serde_json::Map<String, serde_json::Value> = self
+      .field_values
+      .iter()
+      .filter(|(key, _)| fields.is_none_or(|fields| fields.contains(key)))
+      .map(|(key, values)| {
+        let values = values.iter().map(value_to_dict_json).collect();
+        (key.clone(), serde_json::Value::Array(values))
+      })
+      .collect();
+    serde_json::Value::Object(map)
+  }
+
+  /// Like `to_json_dict`, but the fields having a single value are mapped to
+  /// that value instead of a list.
+  pub(crate) fn to_json_object(&self, fields: Option<&[String]>) -> serde_json::Value {
+    let map: serde_json::Map<String, serde_json::Value> = self
+      .field_values
+      .iter()
+      .filter(|(key, _)| fields.is_none_or(|fields| fields.contains(key)))
+      .map(|(key, values)| {
+        let value = match values.as_slice() {
+          [value] => value_to_dict_json(value),
+          values => serde_json::Value::Array(values.iter().map(value_to_dict_json).collect()),
+        };
+        (key.clone(), value)
+      })
+      .collect();
+    serde_json::Value::Object(map)
+  }
+
   pub fn iter_values_for_field<'a>(&'a self, field: &str) -> impl Iterator<Item = &'a Value> + 'a {
     self
       .field_values
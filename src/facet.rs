@@ -1,5 +1,7 @@
+use napi::bindgen_prelude::{Buffer, Either};
 use napi::{Error, Result, Status};
 use napi_derive::napi;
+use std::cmp::Ordering;
 use tantivy::schema::Facet as TantivyFacet;
 
 /// Represents a facet in Tantivy.
@@ -25,9 +27,13 @@ pub struct Facet {
 
 #[napi]
 impl Facet {
-  /// Creates a `Facet` from its binary representation.
+  /// Creates a `Facet` from its binary representation, see `encoded`.
   #[napi(factory)]
-  pub fn from_encoded(encoded_bytes: Vec<u8>) -> Result<Facet> {
+  pub fn from_encoded(encoded_bytes: Either<&[u8], Vec<u8>>) -> Result<Facet> {
+    let encoded_bytes = match encoded_bytes {
+      Either::A(bytes) => bytes.to_vec(),
+      Either::B(bytes) => bytes,
+    };
     let inner = TantivyFacet::from_encoded(encoded_bytes)
       .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid encoded facet: {}", e)))?;
     Ok(Facet { inner })
@@ -41,6 +47,30 @@ impl Facet {
     }
   }
 
+  /// The binary representation of the facet, as stored in the index.
+  ///
+  /// Comparing the encoded bytes of facets orders them like `compareTo`.
+  #[napi]
+  pub fn encoded(&self) -> Buffer {
+    self.inner.encoded_str().as_bytes().to_vec().into()
+  }
+
+  /// Compare the facet with another one, in the order of the index: a facet
+  /// comes right before its subfacets, and sibling facets are sorted by
+  /// name, e.g. `/a`, `/a/b`, `/a/c`, `/ab`.
+  ///
+  /// @param other - The facet to compare with.
+  /// @returns A negative number if this facet comes first, a positive one if
+  ///          the other does, 0 if they are equal.
+  #[napi]
+  pub fn compare_to(&self, other: &Facet) -> i32 {
+    match self.inner.cmp(&other.inner) {
+      Ordering::Less => -1,
+      Ordering::Equal => 0,
+      Ordering::Greater => 1,
+    }
+  }
+
   /// Returns true if the facet is the root facet /.
   #[napi(getter)]
   pub fn is_root(&self) -> bool {
@@ -107,11 +137,74 @@ impl Facet {
   pub fn to_string(&self) -> String {
     self.inner.to_string()
   }
+
+  /// Check if a string is a well-formed facet path.
+  ///
+  /// A path starts with `/` and has no empty segment, e.g. `/a//b` and `/a/`
+  /// are invalid, except the root facet `/`. A `/` or `\` within a segment
+  /// must be escaped with `\`.
+  ///
+  /// @param path - The facet path to validate
+  /// @returns True if the path is valid
+  #[napi]
+  pub fn is_valid(path: String) -> bool {
+    let Some(rest) = path.strip_prefix('/') else {
+      return false;
+    };
+    let (segments, complete) = split_segments(rest);
+    complete && (path == "/" || segments.iter().all(|segment| is_valid_segment(segment)))
+  }
+
+  /// Normalize a facet path, typically given by a user, into a valid one.
+  ///
+  /// The leading `/` is added if missing, empty segments are dropped, such as
+  /// the ones of trailing or repeated slashes, and a trailing `\` is
+  /// escaped. For instance `electronics//phones/` becomes
+  /// `/electronics/phones`.
+  ///
+  /// @param path - The facet path to normalize
+  /// @returns The normalized facet path, `/` if it has no segment
+  #[napi]
+  pub fn normalize(path: String) -> String {
+    let (segments, _) = split_segments(path.strip_prefix('/').unwrap_or(&path));
+    let segments: Vec<String> = segments
+      .into_iter()
+      .map(|segment| segment.replace(FACET_SEP_CHAR, ""))
+      .filter(|segment| !segment.is_empty())
+      // Unlike tantivy, which prints `\` as `\/`, escape it as `\\`.
+      .map(|segment| segment.replace('\\', "\\\\").replace('/', "\\/"))
+      .collect();
+    format!("/{}", segments.join("/"))
+  }
 }
 
-impl Facet {
-  #[allow(dead_code)]
-  pub(crate) fn new(facet: TantivyFacet) -> Self {
-    Self { inner: facet }
+/// Tantivy's separator of the segments of an encoded facet, which the
+/// segments can't contain.
+const FACET_SEP_CHAR: char = '\u{0}';
+
+fn is_valid_segment(segment: &str) -> bool {
+  !segment.is_empty() && !segment.contains(FACET_SEP_CHAR)
+}
+
+/// Split the segments of a facet path without its leading slash, unescaping
+/// them. A trailing `\` is kept as is, and reported by returning false.
+fn split_segments(path: &str) -> (Vec<String>, bool) {
+  let mut segments = Vec::new();
+  let mut segment = String::new();
+  let mut escaped = false;
+  for c in path.chars() {
+    match (escaped, c) {
+      (false, '\\') => escaped = true,
+      (false, '/') => segments.push(std::mem::take(&mut segment)),
+      (_, c) => {
+        segment.push(c);
+        escaped = false;
+      }
+    }
+  }
+  if escaped {
+    segment.push('\\');
   }
+  segments.push(segment);
+  (segments, !escaped)
 }
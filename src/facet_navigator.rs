@@ -0,0 +1,157 @@
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use tantivy as tv;
+use tantivy::collector::{Count, FacetCollector, MultiCollector};
+
+use crate::{query::Query, searcher::FacetCount, searcher::Searcher};
+
+/// A level of the path of a `FacetNavigation`.
+#[napi(object, object_from_js = false)]
+pub struct FacetBreadcrumb {
+  /// The facet of the level, e.g. '/electronics/phones'.
+  pub facet: String,
+  /// The last segment of the facet, e.g. 'phones', or '' for the root.
+  pub label: String,
+  /// Number of matching documents under the facet.
+  pub count: u32,
+}
+
+/// Everything needed to render a facet in a drill-down menu, see
+/// `FacetNavigator.navigate`.
+#[napi(object, object_from_js = false)]
+pub struct FacetNavigation {
+  /// The current facet.
+  pub facet: String,
+  /// Number of matching documents under the current facet.
+  pub count: u32,
+  /// The levels from the root to the current facet, both included.
+  pub breadcrumbs: Vec<FacetBreadcrumb>,
+  /// The children of the current facet, in facet order.
+  pub children: Vec<FacetCount>,
+  /// The children of the parent of the current facet, the current facet
+  /// included, in facet order. Empty at the root.
+  pub siblings: Vec<FacetCount>,
+}
+
+/// Drill-down navigation in the facets of a field.
+///
+/// Example:
+/// ```javascript
+/// const navigator = new FacetNavigator(index.searcher(), "category");
+/// const { breadcrumbs, children } = navigator.navigate("/electronics/phones");
+/// ```
+#[napi]
+pub struct FacetNavigator {
+  searcher: Searcher,
+  field_name: String,
+}
+
+#[napi]
+impl FacetNavigator {
+  /// @param searcher - The searcher the counts are computed with.
+  /// @param fieldName - A facet field.
+  #[napi(constructor)]
+  pub fn new(searcher: &Searcher, field_name: String) -> Result<Self> {
+    crate::searcher::check_facet_field(searcher.inner.schema(), &field_name)?;
+    Ok(FacetNavigator {
+      searcher: Searcher::new(searcher.inner.clone()),
+      field_name,
+    })
+  }
+
+  /// Count the children, the siblings and the ancestors of a facet, in a
+  /// single pass over the matching documents.
+  ///
+  /// @param path - The current facet, e.g. '/electronics/phones'. '/' is the
+  ///         root.
+  /// @param query - Restrict the counts to the documents matching this query.
+  ///         Defaults to all the documents.
+  ///
+  /// @returns FacetNavigation object.
+  #[napi]
+  pub fn navigate(&self, path: String, query: Option<&Query>) -> Result<FacetNavigation> {
+    let facet = tv::schema::Facet::from_text(&path)
+      .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+    // The path of the root is a single empty label.
+    let path = if facet.is_root() {
+      Vec::new()
+    } else {
+      facet.to_path()
+    };
+    // The facets from the root to the current one.
+    let ancestors: Vec<tv::schema::Facet> = (0..=path.len())
+      .map(|depth| tv::schema::Facet::from_path(path.iter().take(depth)))
+      .collect();
+
+    // A facet collector can't count a facet along with its ancestors, so
+    // every level gets its own.
+    let mut multicollector = MultiCollector::new();
+    let count_handle = multicollector.add_collector(Count);
+    let facet_handles: Vec<_> = ancestors
+      .iter()
+      .map(|ancestor| {
+        let mut facet_collector = FacetCollector::for_field(&self.field_name);
+        facet_collector.add_facet(ancestor.clone());
+        multicollector.add_collector(facet_collector)
+      })
+      .collect();
+    let all_query = tv::query::AllQuery;
+    let query = query.map_or(&all_query as &dyn tv::query::Query, |query| query.get());
+    let (mut multifruit, _) = self.searcher.collect(query, &multicollector, false)?;
+    let total = count_handle.extract(&mut multifruit) as u64;
+    // The children of every level, with their counts.
+    let levels: Vec<Vec<(tv::schema::Facet, u64)>> = ancestors
+      .iter()
+      .zip(facet_handles)
+      .map(|(ancestor, handle)| {
+        handle
+          .extract(&mut multifruit)
+          .get(ancestor.clone())
+          .map(|(facet, count)| (facet.clone(), count))
+          .collect()
+      })
+      .collect();
+
+    let facet_counts = |children: &[(tv::schema::Facet, u64)]| -> Vec<FacetCount> {
+      children
+        .iter()
+        .map(|(facet, count)| FacetCount {
+          facet: facet.to_string(),
+          count: *count as u32,
+        })
+        .collect()
+    };
+    // The count of a level is found among the children of the level above.
+    let count_at = |depth: usize| match depth {
+      0 => total,
+      depth => levels[depth - 1]
+        .iter()
+        .find(|(child, _)| *child == ancestors[depth])
+        .map_or(0, |(_, count)| *count),
+    };
+
+    let breadcrumbs = ancestors
+      .iter()
+      .enumerate()
+      .map(|(depth, ancestor)| FacetBreadcrumb {
+        facet: ancestor.to_string(),
+        label: ancestor
+          .to_path()
+          .last()
+          .map_or_else(String::new, |label| label.to_string()),
+        count: count_at(depth) as u32,
+      })
+      .collect();
+    let depth = ancestors.len() - 1;
+    Ok(FacetNavigation {
+      facet: facet.to_string(),
+      count: count_at(depth) as u32,
+      breadcrumbs,
+      children: facet_counts(&levels[depth]),
+      siblings: match depth {
+        0 => Vec::new(),
+        depth => facet_counts(&levels[depth - 1]),
+      },
+    })
+  }
+}
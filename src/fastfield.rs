@@ -0,0 +1,241 @@
+use napi::{Error, Result, Status};
+use std::collections::{hash_map::Entry, HashMap};
+use tantivy as tv;
+use tantivy::columnar::{BytesColumn, Column, ColumnType, MonotonicallyMappableToU64, StrColumn};
+use tantivy::{DocId, SegmentReader};
+
+/// Read access to a fast field column of a single segment.
+///
+/// Values are read in tantivy's internal `u64` representation (term ordinals
+/// for text and bytes columns) and only converted to JSON on demand, so
+/// collectors can work on cheap integer keys.
+pub(crate) struct FastFieldReader {
+  column: Column<u64>,
+  column_type: ColumnType,
+  str_column: Option<StrColumn>,
+  bytes_column: Option<BytesColumn>,
+}
+
+impl FastFieldReader {
+  /// Open the fast field column for `field_name` in the given segment.
+  ///
+  /// Returns `None` if the segment holds no value for the field.
+  pub(crate) fn open(segment_reader: &SegmentReader, field_name: &str) -> tv::Result<Option<Self>> {
+    let fast_fields = segment_reader.fast_fields();
+    let Some((column, column_type)) = fast_fields.u64_lenient(field_name)? else {
+      return Ok(None);
+    };
+    if column_type == ColumnType::IpAddr {
+      return Err(tv::TantivyError::InvalidArgument(format!(
+        "Fast field '{field_name}' of type IpAddr is not supported."
+      )));
+    }
+    let str_column = match column_type {
+      ColumnType::Str => fast_fields.str(field_name)?,
+      _ => None,
+    };
+    let bytes_column = match column_type {
+      ColumnType::Bytes => fast_fields.bytes(field_name)?,
+      _ => None,
+    };
+    Ok(Some(FastFieldReader {
+      column,
+      column_type,
+      str_column,
+      bytes_column,
+    }))
+  }
+
+  /// Raw values of the given document, in the column's `u64` representation.
+  pub(crate) fn raw_values(&self, doc: DocId) -> impl Iterator<Item = u64> + '_ {
+    self.column.values_for_doc(doc)
+  }
+
+  /// Convert a raw column value into its JSON representation.
+  ///
+  /// Dates are returned as milliseconds timestamps, like `Document.toDict()`.
+  pub(crate) fn to_json(&self, raw: u64) -> tv::Result<serde_json::Value> {
+    Ok(match self.column_type {
+      ColumnType::U64 => serde_json::Value::from(raw),
+      ColumnType::I64 => serde_json::Value::from(i64::from_u64(raw)),
+      ColumnType::F64 => serde_json::Value::from(f64::from_u64(raw)),
+      ColumnType::Bool => serde_json::Value::from(bool::from_u64(raw)),
+      ColumnType::DateTime => {
+        serde_json::Value::from(tv::DateTime::from_u64(raw).into_timestamp_millis())
+      }
+      ColumnType::Str => {
+        let mut text = String::new();
+        if let Some(str_column) = &self.str_column {
+          str_column.ord_to_str(raw, &mut text)?;
+        }
+        serde_json::Value::from(text)
+      }
+      ColumnType::Bytes => {
+        let mut bytes = Vec::new();
+        if let Some(bytes_column) = &self.bytes_column {
+          bytes_column.ord_to_bytes(raw, &mut bytes)?;
+        }
+        serde_json::Value::from(bytes)
+      }
+      ColumnType::IpAddr => serde_json::Value::Null,
+    })
+  }
+
+  /// Convert a raw column value into a number, for numeric, boolean and date columns.
+  ///
+  /// Dates are returned as milliseconds timestamps. Returns `None` for text
+  /// and bytes columns, whose raw values are term ordinals.
+  pub(crate) fn to_f64(&self, raw: u64) -> Option<f64> {
+    match self.column_type {
+      ColumnType::U64 => Some(raw as f64),
+      ColumnType::I64 => Some(i64::from_u64(raw) as f64),
+      ColumnType::F64 => Some(f64::from_u64(raw)),
+      ColumnType::Bool => Some(if bool::from_u64(raw) { 1.0 } else { 0.0 }),
+      ColumnType::DateTime => Some(tv::DateTime::from_u64(raw).into_timestamp_millis() as f64),
+      ColumnType::Str | ColumnType::Bytes | ColumnType::IpAddr => None,
+    }
+  }
+}
+
+/// Ordinals of the terms of a text fast field, shared by all the segments of
+/// a searcher.
+///
+/// The ordinals of a column are local to its segment, so the dictionaries of
+/// the segments are merged: the ordinal of a term is its rank among the
+/// terms of every segment.
+pub(crate) struct TermOrdinals {
+  terms: Vec<String>,
+  /// The ordinal of every local ordinal, per segment.
+  segment_ords: Vec<Vec<u32>>,
+}
+
+impl TermOrdinals {
+  pub(crate) fn build(searcher: &tv::Searcher, field_name: &str) -> tv::Result<Self> {
+    let mut segment_terms = Vec::with_capacity(searcher.segment_readers().len());
+    for segment_reader in searcher.segment_readers() {
+      let mut terms = Vec::new();
+      if let Some(str_column) = segment_reader.fast_fields().str(field_name)? {
+        let mut stream = str_column.dictionary().stream()?;
+        while stream.advance() {
+          terms.push(String::from_utf8_lossy(stream.key()).into_owned());
+        }
+      }
+      segment_terms.push(terms);
+    }
+    let mut terms: Vec<String> = segment_terms.iter().flatten().cloned().collect();
+    terms.sort_unstable();
+    terms.dedup();
+    let segment_ords = segment_terms
+      .iter()
+      .map(|local_terms| {
+        local_terms
+          .iter()
+          .map(|term| terms.binary_search(term).unwrap_or_default() as u32)
+          .collect()
+      })
+      .collect();
+    Ok(TermOrdinals {
+      terms,
+      segment_ords,
+    })
+  }
+
+  /// The ordinal of the first term of every document, if it has one. The
+  /// column of every segment is opened once.
+  pub(crate) fn doc_ords(
+    &self,
+    searcher: &tv::Searcher,
+    field_name: &str,
+    doc_addresses: &[tv::DocAddress],
+  ) -> tv::Result<Vec<Option<u32>>> {
+    let mut columns: HashMap<u32, Option<StrColumn>> = HashMap::new();
+    let mut ords = Vec::with_capacity(doc_addresses.len());
+    for doc_address in doc_addresses {
+      let column = match columns.entry(doc_address.segment_ord) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => {
+          let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+          entry.insert(segment_reader.fast_fields().str(field_name)?)
+        }
+      };
+      let local_ord = column
+        .as_ref()
+        .and_then(|column| column.term_ords(doc_address.doc_id).next());
+      ords
+        .push(local_ord.map(|local_ord| {
+          self.segment_ords[doc_address.segment_ord as usize][local_ord as usize]
+        }));
+    }
+    Ok(ords)
+  }
+
+  /// The term of an ordinal.
+  pub(crate) fn term(&self, ord: u32) -> Option<&str> {
+    self.terms.get(ord as usize).map(String::as_str)
+  }
+}
+
+/// Check that `field_name` is declared as a fast field in the schema.
+pub(crate) fn check_fast_field(schema: &tv::schema::Schema, field_name: &str) -> Result<()> {
+  let field = crate::get_field(schema, field_name)?;
+  if !schema.get_field_entry(field).is_fast() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Field '{}' is not a fast field.", field_name),
+    ));
+  }
+  Ok(())
+}
+
+/// Check that `field_name` is a fast field of type integer, unsigned, float,
+/// boolean or date.
+pub(crate) fn check_numeric_fast_field(
+  schema: &tv::schema::Schema,
+  field_name: &str,
+) -> Result<()> {
+  check_fast_field(schema, field_name)?;
+  let field = crate::get_field(schema, field_name)?;
+  match schema.get_field_entry(field).field_type().value_type() {
+    tv::schema::Type::U64
+    | tv::schema::Type::I64
+    | tv::schema::Type::F64
+    | tv::schema::Type::Bool
+    | tv::schema::Type::Date => Ok(()),
+    _ => Err(Error::new(
+      Status::InvalidArg,
+      format!("Fast field '{}' is not numeric.", field_name),
+    )),
+  }
+}
+
+/// Convert a value of a numeric fast field, read as `u64` like tantivy does
+/// to order by any numeric field, into a number. Dates are returned as
+/// milliseconds timestamps, the values of text fields are term ordinals.
+pub(crate) fn sort_value_to_f64(value_type: tv::schema::Type, raw: u64) -> f64 {
+  match value_type {
+    tv::schema::Type::I64 => i64::from_u64(raw) as f64,
+    tv::schema::Type::F64 => f64::from_u64(raw),
+    tv::schema::Type::Date => tv::DateTime::from_u64(raw).into_timestamp_millis() as f64,
+    _ => raw as f64,
+  }
+}
+
+/// The `u64` representation of a number given for a numeric fast field, see
+/// `sort_value_to_f64`, or None if the number doesn't fit the type.
+pub(crate) fn f64_to_sort_value(value_type: tv::schema::Type, value: f64) -> Option<u64> {
+  let is_integer = value.fract() == 0.0;
+  match value_type {
+    tv::schema::Type::U64 if is_integer && value >= 0.0 && value < u64::MAX as f64 => {
+      Some(value as u64)
+    }
+    tv::schema::Type::I64 if is_integer && value >= i64::MIN as f64 && value < i64::MAX as f64 => {
+      Some((value as i64).to_u64())
+    }
+    tv::schema::Type::F64 if !value.is_nan() => Some(value.to_u64()),
+    tv::schema::Type::Date if is_integer => {
+      Some(tv::DateTime::from_timestamp_millis(value as i64).to_u64())
+    }
+    tv::schema::Type::Bool if value == 0.0 || value == 1.0 => Some(value as u64),
+    _ => None,
+  }
+}
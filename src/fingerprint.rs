@@ -0,0 +1,161 @@
+use std::ops::Bound;
+
+use tantivy as tv;
+use tantivy::query::{AllQuery, BooleanQuery, EmptyQuery, Query, TermQuery, TermSetQuery};
+
+use crate::cache::CachedFilterQuery;
+use crate::geo::GeoDistanceQuery;
+use crate::named::NamedQuery;
+use crate::nested::NestedQuery;
+use crate::query::{BoostedQuery, ConstScoredQuery, DisjunctionQuery, HintedRangeQuery};
+use crate::query_cost::occur_name;
+use crate::span_near::SpanNearQuery;
+
+/// A hash of a query identifying it up to the order of its commutative
+/// parts, see `Query.fingerprint`.
+pub(crate) fn fingerprint(query: &dyn Query) -> u64 {
+  crate::fnv1a(canonical_form(query).bytes())
+}
+
+/// A description of a query in which the clauses of boolean queries, the
+/// disjuncts of disjunction max queries and the terms of term set queries
+/// are sorted.
+///
+/// Terms are written as their serialized bytes, and strings are prefixed by
+/// their length, so that two different queries can't be described the same.
+/// Tantivy keeps the parts of some queries private, such as regex, fuzzy or
+/// phrase queries and the boosts built by its query parser: those are only
+/// known through their debug output.
+fn canonical_form(query: &dyn Query) -> String {
+  if let Some(boolean_query) = query.downcast_ref::<BooleanQuery>() {
+    let clauses = boolean_query.clauses().iter().map(|(occur, subquery)| {
+      format!(
+        "{}:{}",
+        occur_name(*occur),
+        canonical_form(subquery.as_ref())
+      )
+    });
+    return format!(
+      "bool({};{})",
+      boolean_query.get_minimum_number_should_match(),
+      sorted(clauses)
+    );
+  }
+  if let Some(boosted_query) = query.downcast_ref::<BoostedQuery>() {
+    return format!(
+      "boost({};{})",
+      boosted_query.boost(),
+      canonical_form(boosted_query.query())
+    );
+  }
+  if let Some(const_query) = query.downcast_ref::<ConstScoredQuery>() {
+    return format!(
+      "const({};{})",
+      const_query.score(),
+      canonical_form(const_query.query())
+    );
+  }
+  if let Some(dismax_query) = query.downcast_ref::<DisjunctionQuery>() {
+    let disjuncts = dismax_query
+      .disjuncts()
+      .iter()
+      .map(|disjunct| canonical_form(disjunct.as_ref()));
+    return format!(
+      "dismax({};{})",
+      dismax_query.tie_breaker(),
+      sorted(disjuncts)
+    );
+  }
+  if let Some(named_query) = query.downcast_ref::<NamedQuery>() {
+    return format!(
+      "named({};{})",
+      string(named_query.name()),
+      canonical_form(named_query.query())
+    );
+  }
+  if let Some(cached_query) = query.downcast_ref::<CachedFilterQuery>() {
+    return format!("cached({})", canonical_form(cached_query.query()));
+  }
+  if let Some(nested_query) = query.downcast_ref::<NestedQuery>() {
+    return format!(
+      "nested({};{};{:?};{})",
+      nested_query.field().field_id(),
+      string(nested_query.path()),
+      nested_query.score_mode(),
+      canonical_form(nested_query.child())
+    );
+  }
+  if let Some(term_query) = query.downcast_ref::<TermQuery>() {
+    return format!("term({})", term_bytes(term_query.term()));
+  }
+  if query.downcast_ref::<TermSetQuery>().is_some() {
+    let mut terms = Vec::new();
+    query.query_terms(&mut |term, _| terms.push(term_bytes(term)));
+    terms.sort_unstable();
+    terms.dedup();
+    return format!("terms({})", terms.join(","));
+  }
+  if let Some(range_query) = query.downcast_ref::<HintedRangeQuery>() {
+    let (lower_bound, upper_bound) = range_query.bounds();
+    return format!(
+      "range({};{};{})",
+      if range_query.use_fast_field() {
+        "fast"
+      } else {
+        "inverted"
+      },
+      bound(lower_bound),
+      bound(upper_bound)
+    );
+  }
+  if let Some(span_near_query) = query.downcast_ref::<SpanNearQuery>() {
+    let terms: Vec<String> = span_near_query.terms().iter().map(term_bytes).collect();
+    return format!("span_near({};{})", span_near_query.slop(), terms.join(","));
+  }
+  if let Some(geo_query) = query.downcast_ref::<GeoDistanceQuery>() {
+    let (lat_field, lon_field) = geo_query.fields();
+    return format!(
+      "geo_distance({};{};{};{};{})",
+      string(lat_field),
+      string(lon_field),
+      geo_query.center().lat,
+      geo_query.center().lon,
+      geo_query.radius()
+    );
+  }
+  if query.downcast_ref::<AllQuery>().is_some() {
+    return "all".to_string();
+  }
+  if query.downcast_ref::<EmptyQuery>().is_some() {
+    return "empty".to_string();
+  }
+  format!("debug({})", string(&format!("{:?}", query)))
+}
+
+/// The forms of commutative parts, sorted and joined.
+fn sorted(forms: impl Iterator<Item = String>) -> String {
+  let mut forms: Vec<String> = forms.collect();
+  forms.sort_unstable();
+  forms.join(",")
+}
+
+fn string(text: &str) -> String {
+  format!("{}:{}", text.len(), text)
+}
+
+/// The serialized bytes of a term, field and type included, in hexadecimal.
+fn term_bytes(term: &tv::Term) -> String {
+  term
+    .serialized_term()
+    .iter()
+    .map(|byte| format!("{:02x}", byte))
+    .collect()
+}
+
+fn bound(bound: &Bound<tv::Term>) -> String {
+  match bound {
+    Bound::Included(term) => format!("[{}", term_bytes(term)),
+    Bound::Excluded(term) => format!("({}", term_bytes(term)),
+    Bound::Unbounded => "*".to_string(),
+  }
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::searcher::{DocAddress, SearchHit};
+
+const DEFAULT_RRF_K: f64 = 60.0;
+
+/// Options of `fuseResults`.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct FuseOptions {
+  /// 'rrf' (reciprocal rank fusion, default) or 'linear' (weighted sum of
+  /// min-max normalized scores).
+  pub method: Option<String>,
+  /// Weight of each list. Defaults to 1 for both.
+  pub weights: Option<Vec<f64>>,
+  /// Rank constant of the reciprocal rank fusion. Defaults to 60.
+  pub rrf_k: Option<f64>,
+  /// Maximum number of hits to return. Defaults to all of them.
+  pub limit: Option<u32>,
+}
+
+/// Fuse two ranked lists of hits, e.g. keyword and vector search hits, into one.
+///
+/// Hits are identified by their document address, so both lists must come
+/// from the same searcher. The fused score is stored in `score`.
+///
+/// @param listA - The first list of hits, best first.
+/// @param listB - The second list of hits, best first.
+/// @param options - Fusion options, see `FuseOptions`.
+///
+/// @returns The fused hits, best first.
+#[napi]
+pub fn fuse_results(
+  list_a: Vec<SearchHit>,
+  list_b: Vec<SearchHit>,
+  options: Option<FuseOptions>,
+) -> Result<Vec<SearchHit>> {
+  let options = options.unwrap_or_default();
+  let weights = options.weights.unwrap_or_else(|| vec![1.0, 1.0]);
+  if weights.len() != 2 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "Expected one weight per list.",
+    ));
+  }
+  let linear = match options.method.as_deref().unwrap_or("rrf") {
+    "rrf" => false,
+    "linear" => true,
+    _ => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Invalid fusion method, valid choices are: 'rrf' and 'linear'",
+      ))
+    }
+  };
+  let rrf_k = options.rrf_k.unwrap_or(DEFAULT_RRF_K);
+
+  // Fused hits, in first seen order so that ties are broken deterministically.
+  let mut fused: Vec<(SearchHit, f64)> = Vec::new();
+  let mut positions: BTreeMap<DocAddress, usize> = BTreeMap::new();
+  for (list, weight) in [list_a, list_b].into_iter().zip(weights) {
+    let normalize = min_max(&list);
+    for (rank, hit) in list.into_iter().enumerate() {
+      let contribution = if linear {
+        weight * normalize(hit.score.unwrap_or(0.0))
+      } else {
+        weight / (rrf_k + rank as f64 + 1.0)
+      };
+      match positions.get(&hit.doc_address) {
+        Some(&position) => fused[position].1 += contribution,
+        None => {
+          positions.insert(hit.doc_address.clone(), fused.len());
+          fused.push((hit, contribution));
+        }
+      }
+    }
+  }
+
+  fused.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+  if let Some(limit) = options.limit {
+    fused.truncate(limit as usize);
+  }
+  Ok(
+    fused
+      .into_iter()
+      .map(|(hit, score)| SearchHit {
+        score: Some(score),
+        order: None,
+        ..hit
+      })
+      .collect(),
+  )
+}
+
+/// Min-max normalization of the scores of a list of hits.
+fn min_max(hits: &[SearchHit]) -> impl Fn(f64) -> f64 {
+  let scores = hits.iter().filter_map(|hit| hit.score);
+  let min = scores.clone().fold(f64::INFINITY, f64::min);
+  let max = scores.fold(f64::NEG_INFINITY, f64::max);
+  move |score| {
+    if max > min {
+      (score - min) / (max - min)
+    } else {
+      1.0
+    }
+  }
+}
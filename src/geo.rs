@@ -0,0 +1,307 @@
+use std::ops::Bound;
+use std::sync::Arc;
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use tantivy as tv;
+use tantivy::query::{
+  BooleanQuery, EnableScoring, Explanation, Occur, Query, RangeQuery, Scorer, Weight,
+};
+use tantivy::{DocId, DocSet, Score, SegmentReader, TERMINATED};
+
+use crate::cache::ConstScoreDocSet;
+
+/// Mean earth radius, in meters.
+const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// A geographic point, in degrees.
+#[napi(object)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GeoPoint {
+  pub lat: f64,
+  pub lon: f64,
+}
+
+/// A geographic bounding box, in degrees.
+///
+/// If `left` is greater than `right`, the box crosses the antimeridian.
+#[napi(object)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GeoBoundingBox {
+  pub top: f64,
+  pub left: f64,
+  pub bottom: f64,
+  pub right: f64,
+}
+
+/// Name of the fast field holding the latitude of a geo point field.
+pub(crate) fn lat_field_name(field_name: &str) -> String {
+  format!("{field_name}.lat")
+}
+
+/// Name of the fast field holding the longitude of a geo point field.
+pub(crate) fn lon_field_name(field_name: &str) -> String {
+  format!("{field_name}.lon")
+}
+
+/// Check that `field_name` was declared with `SchemaBuilder.addGeoPointField`.
+fn check_geo_field(schema: &tv::schema::Schema, field_name: &str) -> Result<()> {
+  for name in [lat_field_name(field_name), lon_field_name(field_name)] {
+    let is_geo = schema.get_field(&name).is_ok_and(|field| {
+      let entry = schema.get_field_entry(field);
+      entry.is_fast() && entry.field_type().value_type() == tv::schema::Type::F64
+    });
+    if !is_geo {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Field '{}' is not a geo point field.", field_name),
+      ));
+    }
+  }
+  Ok(())
+}
+
+fn check_point(lat: f64, lon: f64) -> Result<()> {
+  if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Invalid geo point ({}, {}).", lat, lon),
+    ));
+  }
+  Ok(())
+}
+
+fn f64_range_query(
+  schema: &tv::schema::Schema,
+  field_name: &str,
+  lower: f64,
+  upper: f64,
+) -> Result<Box<dyn Query>> {
+  let field = crate::get_field(schema, field_name)?;
+  Ok(Box::new(RangeQuery::new(
+    Bound::Included(tv::Term::from_field_f64(field, lower)),
+    Bound::Included(tv::Term::from_field_f64(field, upper)),
+  )))
+}
+
+/// Build a query matching the geo points inside a bounding box.
+pub(crate) fn bounding_box_query(
+  schema: &tv::schema::Schema,
+  field_name: &str,
+  bounding_box: &GeoBoundingBox,
+) -> Result<Box<dyn Query>> {
+  check_geo_field(schema, field_name)?;
+  check_point(bounding_box.top, bounding_box.left)?;
+  check_point(bounding_box.bottom, bounding_box.right)?;
+  if bounding_box.bottom > bounding_box.top {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "The bottom of the bounding box must not be above its top.",
+    ));
+  }
+
+  let lon_name = lon_field_name(field_name);
+  let lat_query = f64_range_query(
+    schema,
+    &lat_field_name(field_name),
+    bounding_box.bottom,
+    bounding_box.top,
+  )?;
+  let lon_query = if bounding_box.left <= bounding_box.right {
+    f64_range_query(schema, &lon_name, bounding_box.left, bounding_box.right)?
+  } else {
+    Box::new(BooleanQuery::new(vec![
+      (
+        Occur::Should,
+        f64_range_query(schema, &lon_name, bounding_box.left, 180.0)?,
+      ),
+      (
+        Occur::Should,
+        f64_range_query(schema, &lon_name, -180.0, bounding_box.right)?,
+      ),
+    ]))
+  };
+  Ok(Box::new(BooleanQuery::new(vec![
+    (Occur::Must, lat_query),
+    (Occur::Must, lon_query),
+  ])))
+}
+
+/// Build a query matching the geo points within `radius` meters of `center`.
+pub(crate) fn distance_query(
+  schema: &tv::schema::Schema,
+  field_name: &str,
+  center: &GeoPoint,
+  radius: f64,
+) -> Result<Box<dyn Query>> {
+  check_geo_field(schema, field_name)?;
+  check_point(center.lat, center.lon)?;
+  if radius.is_nan() || radius < 0.0 {
+    return Err(Error::new(
+      Status::InvalidArg,
+      "The radius must be a positive number of meters.",
+    ));
+  }
+
+  // Prefilter with the bounding box of the circle, then check the exact
+  // distance of the remaining points.
+  let delta_lat = (radius / EARTH_RADIUS_METERS).to_degrees();
+  let top = center.lat + delta_lat;
+  let bottom = center.lat - delta_lat;
+  let bounding_box = if top >= 90.0 || bottom <= -90.0 {
+    GeoBoundingBox {
+      top: top.min(90.0),
+      left: -180.0,
+      bottom: bottom.max(-90.0),
+      right: 180.0,
+    }
+  } else {
+    let delta_lon = (radius / (EARTH_RADIUS_METERS * center.lat.to_radians().cos())).to_degrees();
+    if delta_lon >= 180.0 {
+      GeoBoundingBox {
+        top,
+        left: -180.0,
+        bottom,
+        right: 180.0,
+      }
+    } else {
+      GeoBoundingBox {
+        top,
+        left: wrap_lon(center.lon - delta_lon),
+        bottom,
+        right: wrap_lon(center.lon + delta_lon),
+      }
+    }
+  };
+
+  Ok(Box::new(GeoDistanceQuery {
+    bounding_box: bounding_box_query(schema, field_name, &bounding_box)?,
+    lat_field: lat_field_name(field_name),
+    lon_field: lon_field_name(field_name),
+    center: center.clone(),
+    radius,
+  }))
+}
+
+fn wrap_lon(lon: f64) -> f64 {
+  if lon > 180.0 {
+    lon - 360.0
+  } else if lon < -180.0 {
+    lon + 360.0
+  } else {
+    lon
+  }
+}
+
+/// Great-circle distance between two points, in meters.
+fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+  let d_lat = (lat2 - lat1).to_radians();
+  let d_lon = (lon2 - lon1).to_radians();
+  let a = (d_lat / 2.0).sin().powi(2)
+    + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+  2.0 * EARTH_RADIUS_METERS * a.sqrt().asin()
+}
+
+/// Matches the documents having a geo point within a radius of a center.
+pub(crate) struct GeoDistanceQuery {
+  bounding_box: Box<dyn Query>,
+  lat_field: String,
+  lon_field: String,
+  center: GeoPoint,
+  radius: f64,
+}
+
+impl GeoDistanceQuery {
+  /// The latitude and longitude fields.
+  pub(crate) fn fields(&self) -> (&str, &str) {
+    (&self.lat_field, &self.lon_field)
+  }
+
+  pub(crate) fn center(&self) -> &GeoPoint {
+    &self.center
+  }
+
+  pub(crate) fn radius(&self) -> f64 {
+    self.radius
+  }
+}
+
+impl Clone for GeoDistanceQuery {
+  fn clone(&self) -> Self {
+    GeoDistanceQuery {
+      bounding_box: self.bounding_box.box_clone(),
+      lat_field: self.lat_field.clone(),
+      lon_field: self.lon_field.clone(),
+      center: self.center.clone(),
+      radius: self.radius,
+    }
+  }
+}
+
+impl std::fmt::Debug for GeoDistanceQuery {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(
+      f,
+      "GeoDistance(center=({}, {}), radius={})",
+      self.center.lat, self.center.lon, self.radius
+    )
+  }
+}
+
+impl Query for GeoDistanceQuery {
+  fn weight(&self, enable_scoring: EnableScoring<'_>) -> tv::Result<Box<dyn Weight>> {
+    let schema = enable_scoring.schema();
+    Ok(Box::new(GeoDistanceWeight {
+      bounding_box: self
+        .bounding_box
+        .weight(EnableScoring::disabled_from_schema(schema))?,
+      query: self.clone(),
+    }))
+  }
+}
+
+struct GeoDistanceWeight {
+  bounding_box: Box<dyn Weight>,
+  query: GeoDistanceQuery,
+}
+
+impl Weight for GeoDistanceWeight {
+  fn scorer(&self, reader: &SegmentReader, boost: Score) -> tv::Result<Box<dyn Scorer>> {
+    let fast_fields = reader.fast_fields();
+    let lat_column = fast_fields.column_opt::<f64>(&self.query.lat_field)?;
+    let lon_column = fast_fields.column_opt::<f64>(&self.query.lon_field)?;
+    let mut docs = Vec::new();
+    if let (Some(lat_column), Some(lon_column)) = (lat_column, lon_column) {
+      let mut candidates = self.bounding_box.scorer(reader, 1.0)?;
+      let mut doc = candidates.doc();
+      while doc != TERMINATED {
+        // The coordinates of the points of a document are stored in the
+        // order they were added, so that the nth latitude and the nth
+        // longitude make a point.
+        let within_radius = lat_column
+          .values_for_doc(doc)
+          .zip(lon_column.values_for_doc(doc))
+          .any(|(lat, lon)| {
+            haversine_distance(self.query.center.lat, self.query.center.lon, lat, lon)
+              <= self.query.radius
+          });
+        if within_radius {
+          docs.push(doc);
+        }
+        doc = candidates.advance();
+      }
+    }
+    Ok(Box::new(ConstScoreDocSet::new(Arc::new(docs), boost)))
+  }
+
+  fn explain(&self, reader: &SegmentReader, doc: DocId) -> tv::Result<Explanation> {
+    let mut scorer = self.scorer(reader, 1.0)?;
+    if scorer.seek(doc) != doc {
+      return Err(tv::TantivyError::InvalidArgument(format!(
+        "Document #({doc}) does not match"
+      )));
+    }
+    Ok(Explanation::new("GeoDistance", scorer.score()))
+  }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use tantivy as tv;
+
+use crate::cache::FilterCache;
+
+/// The state of an `Index` shared by the instances created from its handle.
+#[derive(Clone)]
+pub(crate) struct SharedIndex {
+  pub(crate) index: tv::Index,
+  pub(crate) reader: tv::IndexReader,
+  pub(crate) filter_cache: Arc<Mutex<FilterCache>>,
+  pub(crate) path: Option<PathBuf>,
+  pub(crate) json_paths: HashMap<String, Vec<String>>,
+  pub(crate) query_tokenizers: HashMap<String, String>,
+}
+
+/// An object registered by `toHandle`.
+pub(crate) enum Shared {
+  Index(Box<SharedIndex>),
+  Schema(tv::schema::Schema),
+  Query(Box<dyn tv::query::Query>),
+}
+
+impl Shared {
+  fn kind(&self) -> &'static str {
+    match self {
+      Shared::Index(_) => "Index",
+      Shared::Schema(_) => "Schema",
+      Shared::Query(_) => "Query",
+    }
+  }
+}
+
+/// The registered objects, by handle. The registry is global to the process,
+/// so that the worker threads share it.
+fn registry() -> &'static Mutex<HashMap<u32, Shared>> {
+  static REGISTRY: OnceLock<Mutex<HashMap<u32, Shared>>> = OnceLock::new();
+  REGISTRY.get_or_init(Default::default)
+}
+
+static NEXT_HANDLE: AtomicU32 = AtomicU32::new(1);
+
+/// Register an object, returning its handle.
+pub(crate) fn register(shared: Shared) -> u32 {
+  let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+  registry().lock().unwrap().insert(handle, shared);
+  handle
+}
+
+/// Look up the object of a handle, `get` returning `None` when the object is
+/// not of the expected kind.
+pub(crate) fn resolve<T>(
+  handle: u32,
+  expected: &str,
+  get: impl FnOnce(&Shared) -> Option<T>,
+) -> Result<T> {
+  let registry = registry().lock().unwrap();
+  let shared = registry.get(&handle).ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!("Unknown handle {handle}, it may have been released."),
+    )
+  })?;
+  let kind = shared.kind();
+  get(shared).ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!("Handle {handle} is a handle of a {kind}, not of a {expected}."),
+    )
+  })
+}
+
+/// Release a handle created by `Index.toHandle`, `Schema.toHandle` or
+/// `Query.toHandle`.
+///
+/// The objects created from the handle remain valid. A handle holds its
+/// object until it is released, so each handle should be released once every
+/// worker has created its object from it.
+///
+/// @param handle - The handle to release.
+///
+/// @returns Whether the handle was registered.
+#[napi]
+pub fn release_handle(handle: u32) -> bool {
+  registry().lock().unwrap().remove(&handle).is_some()
+}
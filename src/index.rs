@@ -1,16 +1,298 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use napi::{Error, Result, Status};
 use napi_derive::napi;
 
 use crate::{
-  document::Document, query::Query, schema::Schema, searcher::Searcher, to_napi_error,
+  cache::{CachedFilterQuery, FilterCache, FilterCacheStats},
+  dictionary::{DictionaryIterator, DictionaryOptions},
+  document::Document,
+  handle::{self, SharedIndex},
+  inspect::IndexInspection,
+  json_paths::JsonPathsParser,
+  query::Query,
+  query_debug::{DebugQueryOptions, QueryDebugger},
+  schema::{Schema, SchemaDiff},
+  searcher::Searcher,
+  serialized_writer::SerializedWriter,
+  session::IndexSession,
+  tenant::TenantIndex,
+  term::Term,
+  to_napi_error,
   tokenizer::TextAnalyzer,
 };
 use tantivy as tv;
+use tantivy::directory::{Directory, TerminatingWrite};
 
 const RELOAD_POLICY: &str = "commit";
+const META_FILEPATH: &str = "meta.json";
+
+/// A single index file shipped from a primary index to a replica.
+#[napi(object)]
+pub struct ReplicationFile {
+  /// Path of the file, relative to the index directory.
+  pub path: String,
+  /// Content of the file, without the footer holding its checksum.
+  pub data: Buffer,
+}
+
+/// The verification of the checksum of an index file, see `Index.verifyChecksums`.
+#[napi(object, object_from_js = false)]
+pub struct ChecksumResult {
+  /// Path of the file, relative to the index directory.
+  pub path: String,
+  /// Id of the segment the file belongs to.
+  pub segment_id: String,
+  /// 'ok', 'corrupted' when the checksum of the content doesn't match the one
+  /// of its footer, 'missing', or 'unreadable' when the file or its footer
+  /// can't be read.
+  pub status: String,
+  /// The reason why the file can't be read.
+  pub error: Option<String>,
+}
+
+/// Options of `Index.reindexWithSchema`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ReindexOptions {
+  /// Renames applied to the fields of every document, from old to new name.
+  pub field_map: Option<HashMap<String, String>>,
+  /// Memory budget of the writer of the new index, in bytes.
+  pub heap_size: Option<u32>,
+}
+
+/// Options of `IndexWriter.commitWithGuard`.
+#[napi(object)]
+#[derive(Default)]
+pub struct CommitGuardOptions {
+  /// Number of bytes that must remain free on the disk once the commit and
+  /// the merges it may trigger are written. Defaults to 0.
+  pub min_free_bytes: Option<f64>,
+  /// The payload of the commit, see `IndexWriter.commit`.
+  pub payload: Option<String>,
+}
+
+/// Code of the error raised by `IndexWriter.commitWithGuard` when the disk is
+/// short of space, the one Node.js gives to the writes failing for this reason.
+const INSUFFICIENT_DISK_SPACE: &str = "ENOSPC";
+
+/// Options of `Index.writer`.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct IndexWriterOptions {
+  /// The total target heap memory usage of the writer, see `Index.writer`.
+  pub heap_size: Option<u32>,
+  /// The number of threads of the writer, see `Index.writer`.
+  pub num_threads: Option<u32>,
+  /// A field identifying the documents. Adding a document first deletes the
+  /// documents having the same value in this field, so that at most one live
+  /// document has a given value. The field must be indexed, with the `raw`
+  /// tokenizer for a text field. It can't be used with nested documents.
+  pub unique_field: Option<String>,
+  /// A date field giving the expiry of the documents, see
+  /// `IndexWriter.purgeExpired`. The field must be indexed or fast.
+  pub expiry_field: Option<String>,
+}
+
+/// Options of `Index.compact`.
+#[napi(object, object_to_js = false)]
+#[derive(Default)]
+pub struct CompactOptions<'env> {
+  /// The number of segments to merge the index down to. Defaults to 1.
+  pub target_segments: Option<u32>,
+  /// Called after each step of the compaction.
+  pub on_progress: Option<Function<'env, CompactProgress, ()>>,
+}
+
+/// A step of `Index.compact`, see `CompactOptions.onProgress`.
+#[napi(object, object_from_js = false)]
+pub struct CompactProgress {
+  /// The step: 'merge' or 'garbageCollect'.
+  pub step: String,
+  /// Number of steps done, this one included.
+  pub completed: u32,
+  /// Number of steps of the compaction.
+  pub total: u32,
+}
+
+/// The outcome of `Index.compact`.
+#[napi(object, object_from_js = false)]
+pub struct CompactResult {
+  /// Number of searchable segments before the compaction.
+  pub segments_before: u32,
+  /// Number of searchable segments after the compaction.
+  pub segments_after: u32,
+  /// Number of index files deleted, those of the merged segments.
+  pub files_deleted: u32,
+  /// Size of the index files before the compaction minus their size after,
+  /// in bytes.
+  pub bytes_reclaimed: f64,
+}
+
+/// Options of `Index.parseQuery` and `Index.parseQueryLenient`.
+#[napi(object)]
+#[derive(Default)]
+pub struct ParseQueryOptions {
+  /// Slop of the quoted phrases not giving one, as if they were followed by
+  /// `~slop`, so that `"old sea"` also matches `old grey sea` (default: 0).
+  pub phrase_slop: Option<u32>,
+  /// Search the quoted phrases ending with `*`, such as `"old man and the se*"`,
+  /// as phrase prefixes, like `"old man and the se"*` (default: false).
+  pub phrase_prefix: Option<bool>,
+}
+
+/// The syntax allowed in the text of `Index.parseUserQuery`, the rest being
+/// searched as plain text.
+#[napi(object)]
+#[derive(Default)]
+pub struct UserQueryOptions {
+  /// Field prefixes, such as `title:sea` (default: false).
+  pub allow_fields: Option<bool>,
+  /// Ranges, such as `[a TO b]` or `>3` (default: false).
+  pub allow_ranges: Option<bool>,
+  /// Boosts, such as `sea^2` (default: false).
+  pub allow_boosts: Option<bool>,
+  /// `+`, `-`, parentheses and the `AND`, `OR` and `NOT` operators
+  /// (default: true).
+  pub allow_operators: Option<bool>,
+  /// Quoted phrases, with their slop or prefix, such as `"old man"~1`. A
+  /// quote left open is searched as text (default: true).
+  pub allow_phrases: Option<bool>,
+  /// Replace the characters of the syntax not allowed by spaces instead of
+  /// escaping them (default: false).
+  pub strip: Option<bool>,
+}
+
+/// The settings of an index, see `Index.settings`.
+///
+/// There is no sort field: tantivy 0.25 dropped the sorting of indexes, its
+/// `IndexSettings` only configure the doc store.
+#[napi(object, object_from_js = false)]
+pub struct IndexSettings {
+  /// The compression of the doc store: 'none', 'lz4' or 'zstd', possibly
+  /// followed by its options, e.g. 'zstd(compression_level=5)'.
+  pub docstore_compression: String,
+  /// The size of the blocks of the doc store, in bytes, before compression.
+  pub docstore_blocksize: u32,
+  /// True if the blocks of the doc store are compressed on a dedicated thread.
+  pub docstore_compress_dedicated_thread: bool,
+}
+
+/// A field value that was altered while adding a document leniently.
+#[napi(object)]
+#[derive(Clone)]
+pub struct FieldIssue {
+  pub field_name: String,
+  pub reason: String,
+}
+
+/// What happened to the fields of a document added leniently.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct LenientAddReport {
+  /// Values that were left out of the indexed document.
+  pub dropped: Vec<FieldIssue>,
+  /// Values that were converted to the type of their field.
+  pub coerced: Vec<FieldIssue>,
+}
+
+/// The JSON description of a query, see `Index.parseDisjunctionMaxQuery`.
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct DisjunctionMaxSpec {
+  queries: Vec<DisjunctionMaxSubquery>,
+  tie_breaker: Option<f64>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum DisjunctionMaxSubquery {
+  Query(String),
+  Boosted { query: String, boost: Option<f64> },
+}
+
+/// Options of the batch APIs of `IndexWriter`, such as `addDocuments`.
+#[napi(object)]
+#[derive(Default)]
+pub struct BatchOptions {
+  /// Stop at the first item that fails, leaving the following ones
+  /// unprocessed and out of the results. Defaults to false, every item being
+  /// processed.
+  pub fail_fast: Option<bool>,
+}
+
+/// The outcome of an item of a batch, see `BatchOptions`.
+#[napi(object, object_from_js = false)]
+pub struct BatchItemResult {
+  /// True if the item was applied.
+  pub ok: bool,
+  /// The `opstamp` of the item, if it was applied.
+  pub opstamp: Option<u64>,
+  /// Why the item was rejected, if it was not applied.
+  pub error: Option<String>,
+}
+
+/// Apply each item of a batch, collecting one result per item.
+fn run_batch<T>(
+  items: Vec<T>,
+  options: Option<BatchOptions>,
+  mut apply: impl FnMut(T) -> Result<u64>,
+) -> Vec<BatchItemResult> {
+  let fail_fast = options.unwrap_or_default().fail_fast.unwrap_or(false);
+  let mut results = Vec::with_capacity(items.len());
+  for item in items {
+    match apply(item) {
+      Ok(opstamp) => results.push(BatchItemResult {
+        ok: true,
+        opstamp: Some(opstamp),
+        error: None,
+      }),
+      Err(e) => {
+        results.push(BatchItemResult {
+          ok: false,
+          opstamp: None,
+          error: Some(e.reason),
+        });
+        if fail_fast {
+          break;
+        }
+      }
+    }
+  }
+  results
+}
+
+/// The outcome of `Index.validateJson`.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct JsonValidationReport {
+  /// True if the document can be added as is by `IndexWriter.addJson`.
+  pub valid: bool,
+  /// Set if the string is not a JSON object, the fields being left unchecked.
+  pub json_error: Option<String>,
+  /// One issue per value that doesn't match the type of its field, sorted by
+  /// field name.
+  pub errors: Vec<FieldIssue>,
+}
+
+/// A successful commit, see `IndexWriter.onCommit`.
+#[napi(object, object_from_js = false)]
+pub struct CommitEvent {
+  /// The `opstamp` returned by the commit.
+  pub opstamp: u64,
+  /// The payload given to the commit, if any.
+  pub payload: Option<String>,
+}
+
+/// A JS callback called after each commit. Being weak, it does not keep the
+/// process alive.
+type CommitCallback = ThreadsafeFunction<CommitEvent, (), CommitEvent, Status, false, true>;
 
 /// IndexWriter is the user entry-point to add documents to the index.
 ///
@@ -19,11 +301,21 @@ const RELOAD_POLICY: &str = "commit";
 #[napi]
 pub struct IndexWriter {
   inner_index_writer: Option<tv::IndexWriter>,
-  schema: tv::schema::Schema,
+  pub(crate) schema: tv::schema::Schema,
+  pub(crate) unique_field: Option<tv::schema::Field>,
+  expiry_field: Option<tv::schema::Field>,
+  on_commit: Option<CommitCallback>,
+  options: IndexWriterOptions,
+  /// The JSON paths registered on the index, see `Index.setDefaultJsonPaths`.
+  json_paths: Arc<Mutex<HashMap<String, Vec<String>>>>,
+  /// The query tokenizers registered on the index, see `Index.registerTokenizer`.
+  query_tokenizers: Arc<Mutex<HashMap<String, String>>>,
+  /// Canonical path of the index directory, for an index stored on disk.
+  path: Option<PathBuf>,
 }
 
 impl IndexWriter {
-  fn inner(&self) -> Result<&tv::IndexWriter> {
+  pub(crate) fn inner(&self) -> Result<&tv::IndexWriter> {
     self.inner_index_writer.as_ref().ok_or_else(|| {
       Error::new(
         Status::GenericFailure,
@@ -41,7 +333,130 @@ impl IndexWriter {
     })
   }
 
-  fn take_inner(&mut self) -> Result<tv::IndexWriter> {
+  fn convert_document(&self, doc: &Document) -> Result<tv::TantivyDocument> {
+    convert_document(&self.schema, doc)
+  }
+
+  /// Estimate the bytes written by a commit: the documents buffered by the
+  /// writer, at most its heap size, and the largest merge its merge policy
+  /// may start with the committed segments.
+  fn estimated_commit_bytes(&self) -> Result<(u64, u64)> {
+    let inner = self.inner()?;
+    let buffered_bytes = self.options.heap_size.unwrap_or(0) as u64;
+    let index = inner.index();
+    let segment_metas = index.searchable_segment_metas().map_err(to_napi_error)?;
+    let segment_bytes: HashMap<tv::index::SegmentId, u64> = segment_metas
+      .iter()
+      .map(|segment_meta| {
+        let num_bytes = segment_meta
+          .list_files()
+          .iter()
+          .filter_map(|path| index.directory().open_read(path).ok())
+          .map(|file| file.num_bytes().get_bytes())
+          .sum();
+        (segment_meta.id(), num_bytes)
+      })
+      .collect();
+    let merge_bytes = inner
+      .get_merge_policy()
+      .compute_merge_candidates(&segment_metas)
+      .iter()
+      .map(|candidate| {
+        candidate
+          .0
+          .iter()
+          .filter_map(|segment_id| segment_bytes.get(segment_id))
+          .sum::<u64>()
+      })
+      .max()
+      .unwrap_or(0);
+    Ok((buffered_bytes, merge_bytes))
+  }
+
+  /// Convert a document, dropping unknown fields and coercing or dropping
+  /// the values that don't match the type of their field.
+  fn convert_document_lenient(&self, doc: &Document) -> (tv::TantivyDocument, LenientAddReport) {
+    let mut tantivy_doc = tv::TantivyDocument::default();
+    let mut report = LenientAddReport::default();
+    for (field_name, values) in &doc.field_values {
+      let Ok(field) = self.schema.get_field(field_name) else {
+        report.dropped.push(FieldIssue {
+          field_name: field_name.clone(),
+          reason: "unknown field".to_string(),
+        });
+        continue;
+      };
+      let value_type = self.schema.get_field_entry(field).field_type().value_type();
+      for value in values {
+        match crate::document::coerce_value(value, value_type) {
+          crate::document::Coercion::Kept => tantivy_doc.add_field_value(field, value),
+          crate::document::Coercion::Coerced(coerced) => {
+            report.coerced.push(FieldIssue {
+              field_name: field_name.clone(),
+              reason: format!("converted {:?} to {:?}", value, value_type),
+            });
+            tantivy_doc.add_field_value(field, &coerced);
+          }
+          crate::document::Coercion::Dropped => report.dropped.push(FieldIssue {
+            field_name: field_name.clone(),
+            reason: format!("{:?} is not a valid {:?} value", value, value_type),
+          }),
+        }
+      }
+    }
+    (tantivy_doc, report)
+  }
+
+  /// Add a document, after deleting the documents having its value of the
+  /// unique field if the writer has one.
+  fn add(&self, doc: tv::TantivyDocument) -> Result<u64> {
+    if self.unique_field.is_none() {
+      let opstamp = self.inner()?.add_document(doc).map_err(to_napi_error)?;
+      crate::metrics::record_documents_added(1);
+      return Ok(opstamp);
+    }
+    let mut operations = Vec::with_capacity(2);
+    self.push_add_operations(doc, &mut operations)?;
+    self.run(operations)
+  }
+
+  /// Add a document, the unique field of the writer, if any, only
+  /// deduplicating the documents matching `scope`, see `TenantIndex`.
+  pub(crate) fn add_scoped(
+    &self,
+    doc: tv::TantivyDocument,
+    scope: Box<dyn tv::query::Query>,
+  ) -> Result<u64> {
+    let writer = self.inner()?;
+    if let Some(field) = self.unique_field {
+      let term = unique_term(&self.schema, field, &doc)?;
+      let unique = tv::query::TermQuery::new(term, tv::schema::IndexRecordOption::Basic);
+      writer
+        .delete_query(Box::new(tv::query::BooleanQuery::intersection(vec![
+          Box::new(unique),
+          scope,
+        ])))
+        .map_err(to_napi_error)?;
+    }
+    let opstamp = writer.add_document(doc).map_err(to_napi_error)?;
+    crate::metrics::record_documents_added(1);
+    Ok(opstamp)
+  }
+
+  pub(crate) fn run(&self, operations: Vec<tv::indexer::UserOperation>) -> Result<u64> {
+    crate::metrics::record_operations(&operations);
+    self.inner()?.run(operations).map_err(to_napi_error)
+  }
+
+  pub(crate) fn push_add_operations(
+    &self,
+    doc: tv::TantivyDocument,
+    operations: &mut Vec<tv::indexer::UserOperation>,
+  ) -> Result<()> {
+    push_add_operations(&self.schema, self.unique_field, doc, operations)
+  }
+
+  pub(crate) fn take_inner(&mut self) -> Result<tv::IndexWriter> {
     self.inner_index_writer.take().ok_or_else(|| {
       Error::new(
         Status::GenericFailure,
@@ -63,11 +478,101 @@ impl IndexWriter {
   /// since the creation of the index.
   #[napi]
   pub fn add_document(&mut self, doc: &Document) -> Result<u64> {
-    let named_doc = tantivy::schema::NamedFieldDocument(doc.field_values.clone());
-    let doc =
-      tantivy::schema::document::TantivyDocument::convert_named_doc(&self.schema, named_doc)
-        .map_err(to_napi_error)?;
-    self.inner()?.add_document(doc).map_err(to_napi_error)
+    let doc = self.convert_document(doc)?;
+    self.add(doc)
+  }
+
+  /// Add a parent document along with its nested child documents.
+  ///
+  /// The children are indexed right before the parent, as a single block, so
+  /// they can be joined back to it with `Query.nestedQuery`. The schema must
+  /// have been built with `SchemaBuilder.enableNestedDocuments()`. Deleting
+  /// the parent deletes its children too.
+  ///
+  /// @param parent - The parent document.
+  /// @param children - The child documents.
+  /// @param path - Name of the nested path the children belong to.
+  ///
+  /// Returns the `opstamp` of the block.
+  #[napi]
+  pub fn add_nested_document(
+    &mut self,
+    parent: &Document,
+    children: Vec<&Document>,
+    path: String,
+  ) -> Result<u64> {
+    crate::nested::check_path(&path)?;
+    let field = crate::nested::nested_field(&self.schema)?;
+
+    let mut operations = Vec::with_capacity(children.len() + 1);
+    for child in children {
+      let mut doc = self.convert_document(child)?;
+      doc.add_text(field, &path);
+      operations.push(tv::indexer::UserOperation::Add(doc));
+    }
+    let mut doc = self.convert_document(parent)?;
+    doc.add_text(field, crate::nested::PARENT_MARKER);
+    operations.push(tv::indexer::UserOperation::Add(doc));
+
+    self.run(operations)
+  }
+
+  /// Add a document to the index, without failing on malformed values.
+  ///
+  /// Unknown fields are skipped, and values that don't match the type of
+  /// their field are converted when possible (e.g. `"42"` for an integer
+  /// field) or skipped otherwise.
+  ///
+  /// @param doc - The document to add.
+  ///
+  /// @returns The fields that were dropped or coerced.
+  #[napi]
+  pub fn add_document_lenient(&mut self, doc: &Document) -> Result<LenientAddReport> {
+    let (doc, report) = self.convert_document_lenient(doc);
+    self.add(doc)?;
+    Ok(report)
+  }
+
+  /// Add a batch of documents to the index, without failing on malformed values.
+  ///
+  /// See `addDocumentLenient`.
+  ///
+  /// @param docs - The documents to add.
+  ///
+  /// @returns One report per document, in the same order.
+  #[napi]
+  pub fn add_documents_lenient(&mut self, docs: Vec<&Document>) -> Result<Vec<LenientAddReport>> {
+    let mut operations = Vec::with_capacity(docs.len());
+    let mut reports = Vec::with_capacity(docs.len());
+    for doc in docs {
+      let (doc, report) = self.convert_document_lenient(doc);
+      self.push_add_operations(doc, &mut operations)?;
+      reports.push(report);
+    }
+    self.run(operations)?;
+    Ok(reports)
+  }
+
+  /// Add a batch of documents to the index, reporting the documents that
+  /// can't be added instead of failing the whole batch.
+  ///
+  /// If the indexing pipeline is full, this call may block.
+  ///
+  /// @param docs - The documents to add.
+  /// @param options - See `BatchOptions`.
+  ///
+  /// @returns One result per processed document, in the same order.
+  #[napi]
+  pub fn add_documents(
+    &mut self,
+    docs: Vec<&Document>,
+    options: Option<BatchOptions>,
+  ) -> Result<Vec<BatchItemResult>> {
+    self.inner()?;
+    Ok(run_batch(docs, options, |doc| {
+      let doc = self.convert_document(doc)?;
+      self.add(doc)
+    }))
   }
 
   /// Helper for the `add_document` method, but passing a json string.
@@ -82,8 +587,50 @@ impl IndexWriter {
   pub fn add_json(&mut self, json: String) -> Result<u64> {
     let doc = tantivy::schema::document::TantivyDocument::parse_json(&self.schema, &json)
       .map_err(to_napi_error)?;
-    let opstamp = self.inner()?.add_document(doc);
-    opstamp.map_err(to_napi_error)
+    self.add(doc)
+  }
+
+  /// Add a batch of documents given as JSON strings, see `addJson`.
+  ///
+  /// The documents are all parsed before any is added, and then submitted to
+  /// the writer at once, which is much faster than calling `addJson` for each
+  /// of them.
+  ///
+  /// @param jsons - The documents, as JSON strings.
+  ///
+  /// @returns The `opstamp` of each document, in the same order.
+  ///
+  /// Raises error if a document can't be parsed, no document being added.
+  #[napi]
+  pub fn add_json_batch(&mut self, jsons: Vec<String>) -> Result<Vec<u64>> {
+    self.inner()?;
+    let mut operations = Vec::with_capacity(jsons.len());
+    let mut add_positions = Vec::with_capacity(jsons.len());
+    for (index, json) in jsons.iter().enumerate() {
+      let doc = tantivy::schema::document::TantivyDocument::parse_json(&self.schema, json)
+        .map_err(|e| {
+          Error::new(
+            Status::InvalidArg,
+            format!("Invalid JSON document at index {}: {}", index, e),
+          )
+        })?;
+      self.push_add_operations(doc, &mut operations)?;
+      add_positions.push(operations.len() - 1);
+    }
+    if operations.is_empty() {
+      return Ok(Vec::new());
+    }
+    // The operations of a batch get the consecutive opstamps preceding the
+    // one of the batch, which is returned.
+    let num_operations = operations.len() as u64;
+    let batch_opstamp = self.run(operations)?;
+    let first_opstamp = batch_opstamp - num_operations;
+    Ok(
+      add_positions
+        .into_iter()
+        .map(|position| first_opstamp + position as u64)
+        .collect(),
+    )
   }
 
   /// Commits all of the pending changes
@@ -94,10 +641,84 @@ impl IndexWriter {
   /// In case of a crash or an hardware failure (as long as the hard disk is
   /// spared), it will be possible to resume indexing from this point.
   ///
+  /// @param payload - Stored along with the commit in the meta file of the
+  ///         index, e.g. the offset of the last message of a queue.
+  ///
+  /// Returns the `opstamp` of the last document that made it in the commit.
+  #[napi]
+  pub fn commit(&mut self, payload: Option<String>) -> Result<u64> {
+    let mut prepared_commit = self.inner_mut()?.prepare_commit().map_err(to_napi_error)?;
+    if let Some(payload) = &payload {
+      prepared_commit.set_payload(payload);
+    }
+    let opstamp = prepared_commit.commit().map_err(to_napi_error)?;
+    crate::metrics::record_commit();
+    if let Some(callback) = &self.on_commit {
+      callback.call(
+        CommitEvent { opstamp, payload },
+        ThreadsafeFunctionCallMode::NonBlocking,
+      );
+    }
+    Ok(opstamp)
+  }
+
+  /// Commit the pending changes like `commit`, after checking that the disk
+  /// has room for them, rather than failing halfway through writing them.
+  ///
+  /// The space needed is estimated as the heap size of the writer, bounding
+  /// the size of the buffered documents, plus the size of the largest merge
+  /// the merge policy may start, plus `minFreeBytes`. For an index in RAM
+  /// there is no check.
+  ///
+  /// @param options - See `CommitGuardOptions`.
+  ///
   /// Returns the `opstamp` of the last document that made it in the commit.
+  ///
+  /// Raises an error whose `code` is 'ENOSPC', without committing, if the
+  /// disk of the index has less space available than needed.
+  #[napi]
+  pub fn commit_with_guard(&mut self, options: Option<CommitGuardOptions>) -> Result<u64, String> {
+    let generic_error = |e: Error| Error::new(e.status.to_string(), e.reason.clone());
+    let options = options.unwrap_or_default();
+    if let Some(path) = &self.path {
+      let (buffered_bytes, merge_bytes) = self.estimated_commit_bytes().map_err(generic_error)?;
+      let min_free_bytes = options.min_free_bytes.unwrap_or(0.0).max(0.0) as u64;
+      let required_bytes = buffered_bytes + merge_bytes + min_free_bytes;
+      let available_bytes =
+        fs4::available_space(path).map_err(|e| generic_error(to_napi_error(e)))?;
+      if available_bytes < required_bytes {
+        return Err(Error::new(
+          INSUFFICIENT_DISK_SPACE.to_string(),
+          format!(
+            "Not enough disk space to commit: {} bytes available, {} required \
+             ({} for the buffered documents, {} for merges, {} to keep free).",
+            available_bytes, required_bytes, buffered_bytes, merge_bytes, min_free_bytes
+          ),
+        ));
+      }
+    }
+    self.commit(options.payload).map_err(generic_error)
+  }
+
+  /// The options the writer was created with, the heap size and the number of
+  /// threads being the effective ones when they were left to their default.
+  #[napi]
+  pub fn options(&self) -> IndexWriterOptions {
+    self.options.clone()
+  }
+
+  /// Call a callback after each successful commit of this writer, e.g. to
+  /// acknowledge the offsets of a message queue once their documents are
+  /// durable.
+  ///
+  /// The callback is called asynchronously, once `commit` has returned. It
+  /// does not keep the process alive.
+  ///
+  /// @param callback - Called with a `CommitEvent`. If not given, the current
+  ///         callback is removed.
   #[napi]
-  pub fn commit(&mut self) -> Result<u64> {
-    self.inner_mut()?.commit().map_err(to_napi_error)
+  pub fn on_commit(&mut self, callback: Option<CommitCallback>) {
+    self.on_commit = callback;
   }
 
   /// Rollback to the last commit
@@ -120,6 +741,29 @@ impl IndexWriter {
     Ok(())
   }
 
+  /// Enable or disable the merges the writer schedules after each commit.
+  ///
+  /// A bulk load is faster with merges disabled: each commit adds a
+  /// segment, and the index is merged once at the end with `Index.compact`,
+  /// after `waitMergingThreads` released the writer. Merges are enabled by
+  /// default.
+  ///
+  /// @param enabled - Whether the writer merges the segments.
+  #[napi]
+  pub fn set_merge_operations_enabled(&self, enabled: bool) -> Result<()> {
+    let policy: Arc<dyn tv::indexer::MergePolicy> = if enabled {
+      Arc::new(tv::indexer::LogMergePolicy::default())
+    } else {
+      Arc::new(tv::indexer::NoMergePolicy)
+    };
+    self
+      .inner()?
+      .set_merge_policy(Box::new(crate::metrics::CountingMergePolicy {
+        inner: policy,
+      }));
+    Ok(())
+  }
+
   /// Deletes all documents from the index.
   #[napi]
   pub fn delete_all_documents(&mut self) -> Result<()> {
@@ -166,10 +810,39 @@ impl IndexWriter {
   pub fn delete_documents_by_term(
     &mut self,
     field_name: String,
-    field_value: Unknown,
+    field_value: Either<&Term, Unknown>,
   ) -> Result<u64> {
-    let term = crate::make_term(&self.schema, &field_name, field_value)?;
-    Ok(self.inner()?.delete_term(term))
+    let term = Term::resolve(&self.schema, &field_name, field_value, |value| {
+      crate::make_term(&self.schema, &field_name, value)
+    })?;
+    crate::nested::delete_term(self.inner()?, &self.schema, term).map_err(to_napi_error)
+  }
+
+  /// Delete the documents containing any of the given terms, reporting the
+  /// values that can't be converted to a term instead of failing the whole
+  /// batch.
+  ///
+  /// See `deleteDocumentsByTerm`.
+  ///
+  /// @param fieldName - The field name for which we want to filter deleted docs.
+  /// @param fieldValues - The values, or Terms, to filter.
+  /// @param options - See `BatchOptions`.
+  ///
+  /// @returns One result per processed value, in the same order.
+  #[napi]
+  pub fn delete_documents_by_terms(
+    &mut self,
+    field_name: String,
+    field_values: Vec<Either<&Term, Unknown>>,
+    options: Option<BatchOptions>,
+  ) -> Result<Vec<BatchItemResult>> {
+    let writer = self.inner()?;
+    Ok(run_batch(field_values, options, |field_value| {
+      let term = Term::resolve(&self.schema, &field_name, field_value, |value| {
+        crate::make_term(&self.schema, &field_name, value)
+      })?;
+      crate::nested::delete_term(writer, &self.schema, term).map_err(to_napi_error)
+    }))
   }
 
   /// Delete all documents matching a given query.
@@ -180,10 +853,84 @@ impl IndexWriter {
   /// If the query is not supported raises error.
   #[napi]
   pub fn delete_documents_by_query(&mut self, query: &Query) -> Result<u64> {
-    self
-      .inner()?
-      .delete_query(query.inner.box_clone())
-      .map_err(to_napi_error)
+    let query = crate::nested::deletion_query(&self.schema, query.inner.box_clone());
+    self.inner()?.delete_query(query).map_err(to_napi_error)
+  }
+
+  /// Delete all documents matching a query string, parsed like
+  /// `Index.parseQuery` does, e.g. for retention scripts:
+  /// `writer.deleteByQueryString('timestamp:[* TO now-30d]')`.
+  ///
+  /// @param queryText - The query, following the tantivy query language.
+  /// @param defaultFields - A list of fields used to search if no field is
+  ///         specified in the query. Defaults to all the indexed fields.
+  ///
+  /// @returns The `opstamp` of the deletion.
+  ///
+  /// If the query is not valid raises error.
+  #[napi]
+  pub fn delete_by_query_string(
+    &mut self,
+    query_text: String,
+    default_fields: Option<Vec<String>>,
+  ) -> Result<u64> {
+    let writer = self.inner()?;
+    let parser = prepare_query_parser(
+      writer.index(),
+      &self.json_paths,
+      &self.query_tokenizers,
+      default_fields,
+      None,
+      None,
+      None,
+    )?;
+    let query_text =
+      crate::datemath::resolve_date_math(&query_text, &self.schema, chrono::Utc::now());
+    let query = parser.parse_query(&query_text).map_err(to_napi_error)?;
+    let query = crate::nested::deletion_query(&self.schema, query);
+    writer.delete_query(query).map_err(to_napi_error)
+  }
+
+  /// Delete the documents whose expiry, the value of the date field given
+  /// by `IndexWriterOptions.expiryField`, is before or at `now`. Like the
+  /// other deletions, it is applied by the next commit.
+  ///
+  /// @param now - Timestamp in milliseconds. Defaults to the current time.
+  ///
+  /// @returns The number of committed documents which expired.
+  ///
+  /// Raises error if the writer has no expiry field.
+  #[napi]
+  pub fn purge_expired(&mut self, now: Option<i64>) -> Result<u32> {
+    let field = self.expiry_field.ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "The writer has no expiry field, see `IndexWriterOptions.expiryField`.",
+      )
+    })?;
+    let now = match now {
+      Some(now) => tv::DateTime::from_timestamp_millis(now),
+      None => tv::DateTime::from_timestamp_millis(chrono::Utc::now().timestamp_millis()),
+    };
+    let query = tv::query::RangeQuery::new(
+      Bound::Unbounded,
+      Bound::Included(tv::Term::from_field_date_for_search(field, now)),
+    );
+    let writer = self.inner()?;
+    let reader: tv::IndexReader = writer
+      .index()
+      .reader_builder()
+      .reload_policy(tv::ReloadPolicy::Manual)
+      .try_into()
+      .map_err(to_napi_error)?;
+    let count = reader
+      .searcher()
+      .search(&query, &tv::collector::Count)
+      .map_err(to_napi_error)?;
+    writer
+      .delete_query(crate::nested::deletion_query(&self.schema, Box::new(query)))
+      .map_err(to_napi_error)?;
+    Ok(count as u32)
   }
 
   /// If there are some merging threads, blocks until they all finish
@@ -214,26 +961,90 @@ impl IndexWriter {
 pub struct Index {
   pub(crate) index: tv::Index,
   reader: tv::IndexReader,
+  filter_cache: Arc<Mutex<FilterCache>>,
+  /// Canonical path of the index directory, for an index stored on disk.
+  pub(crate) path: Option<PathBuf>,
+  /// Paths searched by the terms without field, by JSON field name.
+  json_paths: Arc<Mutex<HashMap<String, Vec<String>>>>,
+  /// Tokenizers analyzing the text values of queries, by JSON field name.
+  query_tokenizers: Arc<Mutex<HashMap<String, String>>>,
 }
 
 #[napi]
 impl Index {
   #[napi(factory)]
   pub fn open(path: String) -> Result<Index> {
-    let index = tv::Index::open_in_dir(&path).map_err(to_napi_error)?;
+    let index = tv::Index::open_in_dir(&path).map_err(|e| {
+      // Tell why an index written by another version can't be opened.
+      match crate::inspect::inspect(&path) {
+        Ok(IndexInspection {
+          advice: Some(advice),
+          ..
+        }) => Error::new(Status::GenericFailure, format!("{} {}", e, advice)),
+        _ => to_napi_error(e),
+      }
+    })?;
 
-    Index::register_custom_text_analyzers(&index);
+    Index::register_custom_text_analyzers(index.tokenizers());
+    Index::register_fast_field_normalizers(index.fast_field_tokenizer());
 
     let reader = index.reader().map_err(to_napi_error)?;
-    Ok(Index { index, reader })
+    Ok(Index {
+      index,
+      reader,
+      filter_cache: Default::default(),
+      path: std::fs::canonicalize(&path).ok(),
+      json_paths: Default::default(),
+      query_tokenizers: Default::default(),
+    })
+  }
+
+  /// Open an index, checking that its schema is the expected one.
+  ///
+  /// Raises an error if the index cannot be opened, or if its schema differs
+  /// from `expectedSchema`. The message of the latter names the fields
+  /// missing from the index, the fields it has in excess and the fields
+  /// whose type or options differ. `Schema.diff` returns the differences
+  /// as structured data.
+  ///
+  /// @param path - The directory of the index.
+  /// @param expectedSchema - The schema the index must have.
+  #[napi(factory)]
+  pub fn open_checked(path: String, expected_schema: &Schema) -> Result<Index> {
+    let index = Index::open(path.clone())?;
+    let diff = SchemaDiff::between(&index.index.schema(), &expected_schema.inner);
+    if !diff.is_empty() {
+      let changed = diff
+        .changed
+        .iter()
+        .map(|change| format!("`{}` differs", change.name));
+      let added = diff
+        .added
+        .iter()
+        .map(|name| format!("`{}` is missing", name));
+      let removed = diff
+        .removed
+        .iter()
+        .map(|name| format!("`{}` is not expected", name));
+      let differences: Vec<String> = changed.chain(added).chain(removed).collect();
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!(
+          "Schema of the index at `{}` does not match the expected schema: {}",
+          path,
+          differences.join(", ")
+        ),
+      ));
+    }
+    Ok(index)
   }
 
   #[napi(constructor)]
   pub fn new(schema: &Schema, path: Option<String>, reuse: Option<bool>) -> Result<Self> {
     let reuse = reuse.unwrap_or(true);
-    let index = match path {
+    let index = match &path {
       Some(p) => {
-        let directory = tantivy::directory::MmapDirectory::open(&p).map_err(to_napi_error)?;
+        let directory = tantivy::directory::MmapDirectory::open(p).map_err(to_napi_error)?;
         if reuse {
           tv::Index::open_or_create(directory, schema.inner.clone())
         } else {
@@ -248,10 +1059,18 @@ impl Index {
       None => tv::Index::create_in_ram(schema.inner.clone()),
     };
 
-    Index::register_custom_text_analyzers(&index);
+    Index::register_custom_text_analyzers(index.tokenizers());
+    Index::register_fast_field_normalizers(index.fast_field_tokenizer());
 
     let reader = index.reader().map_err(to_napi_error)?;
-    Ok(Index { index, reader })
+    Ok(Index {
+      index,
+      reader,
+      filter_cache: Default::default(),
+      path: path.and_then(|path| std::fs::canonicalize(path).ok()),
+      json_paths: Default::default(),
+      query_tokenizers: Default::default(),
+    })
   }
 
   /// Create a `IndexWriter` for the index.
@@ -270,20 +1089,176 @@ impl Index {
   ///         should use. If this value is 0, tantivy will choose
   ///         automatically the number of threads.
   ///
+  /// The heap size can also be replaced by an object of options, see
+  /// `IndexWriterOptions`, e.g. `writer({ uniqueField: 'id' })`.
+  ///
   /// Raises error if there was an error while creating the writer.
   #[napi]
-  pub fn writer(&self, heap_size: Option<u32>, num_threads: Option<u32>) -> Result<IndexWriter> {
-    let heap_size = heap_size.unwrap_or(128_000_000) as usize;
-    let num_threads = num_threads.unwrap_or(0) as usize;
-    let writer = match num_threads {
-      0 => self.index.writer(heap_size),
-      _ => self.index.writer_with_num_threads(num_threads, heap_size),
-    }
-    .map_err(to_napi_error)?;
+  pub fn writer(
+    &self,
+    heap_size: Option<Either<u32, IndexWriterOptions>>,
+    num_threads: Option<u32>,
+  ) -> Result<IndexWriter> {
+    let mut options = match heap_size {
+      Some(Either::A(heap_size)) => IndexWriterOptions {
+        heap_size: Some(heap_size),
+        ..Default::default()
+      },
+      Some(Either::B(options)) => options,
+      None => IndexWriterOptions::default(),
+    };
+    options.num_threads = options.num_threads.or(num_threads);
+    self.create_writer(options)
+  }
+
+  /// Create a `SerializedWriter` for the index.
+  ///
+  /// For an index stored in a directory, the writer is shared by every
+  /// `SerializedWriter` of that directory in the process, worker threads
+  /// included, whose operations go through the same queue.
+  ///
+  /// @param options - Options of the writer, see `IndexWriterOptions`. They are
+  ///         ignored when the writer of the directory is already open.
+  ///
+  /// Raises error if there was an error while creating the writer.
+  #[napi]
+  pub fn serialized_writer(&self, options: Option<IndexWriterOptions>) -> Result<SerializedWriter> {
+    SerializedWriter::open(self, options.unwrap_or_default())
+  }
+
+  /// Create an `IndexSession` for the index, whose searches see the
+  /// documents added before them without committing nor reloading.
+  ///
+  /// @param options - Options of the writer of the session, see
+  ///         `IndexWriterOptions`.
+  ///
+  /// Raises error if there was an error while creating the writer.
+  #[napi]
+  pub fn session(&self, options: Option<IndexWriterOptions>) -> Result<IndexSession> {
+    let writer = self.create_writer(options.unwrap_or_default())?;
+    Ok(IndexSession::new(writer, self.reader.clone()))
+  }
+
+  /// Create a `TenantIndex` for the index, restricting every addition,
+  /// deletion and search to the documents of a tenant.
+  ///
+  /// @param tenantField - The field holding the tenant ids. It must be an
+  ///         indexed text field using the `raw` tokenizer.
+  /// @param options - Options of the writer of the tenant index, see
+  ///         `IndexWriterOptions`.
+  ///
+  /// Raises error if there was an error while creating the writer.
+  #[napi]
+  pub fn tenant_index(
+    &self,
+    tenant_field: String,
+    options: Option<IndexWriterOptions>,
+  ) -> Result<TenantIndex> {
+    let tenant_field = crate::tenant::check_tenant_field(&self.index.schema(), &tenant_field)?;
+    let writer = self.create_writer(options.unwrap_or_default())?;
+    Ok(TenantIndex::new(writer, self.reader.clone(), tenant_field))
+  }
+
+  /// Copy all the stored documents of this index into a new index with another schema.
+  ///
+  /// This is the way to "alter" the schema of an index: fields missing from
+  /// the new schema are dropped, new fields are left empty. Only stored
+  /// fields can be carried over. Tokenizers registered on this index are
+  /// shared with the new one.
+  ///
+  /// @param newSchema - The schema of the new index.
+  /// @param destPath - Path of the new index. If undefined, the new index is created in RAM.
+  /// @param options - Reindexing options, see `ReindexOptions`.
+  /// @param mapDocument - Optional callback receiving each document, after the
+  ///         field renames, and returning the document to index, or null to skip it.
+  ///
+  /// @returns The new index.
+  #[napi]
+  pub fn reindex_with_schema<'env>(
+    &self,
+    new_schema: &Schema,
+    dest_path: Option<String>,
+    options: Option<ReindexOptions>,
+    map_document: Option<Function<'env, Document, Option<ClassInstance<'env, Document>>>>,
+  ) -> Result<Index> {
+    let options = options.unwrap_or_default();
+    let field_map = options.field_map.unwrap_or_default();
+
+    let mut new_index = Index::new(new_schema, dest_path, Some(false))?;
+    new_index
+      .index
+      .set_tokenizers(self.index.tokenizers().clone());
+    new_index
+      .index
+      .set_fast_field_tokenizers(self.index.fast_field_tokenizer().clone());
+    let mut writer = new_index.create_writer(IndexWriterOptions {
+      heap_size: options.heap_size,
+      ..Default::default()
+    })?;
+
     let schema = self.index.schema();
-    Ok(IndexWriter {
-      inner_index_writer: Some(writer),
-      schema,
+    let searcher = self.reader.searcher();
+    for segment_reader in searcher.segment_readers() {
+      let store_reader = segment_reader.get_store_reader(1).map_err(to_napi_error)?;
+      for doc in store_reader.iter::<tv::TantivyDocument>(segment_reader.alive_bitset()) {
+        let doc = doc.map_err(to_napi_error)?;
+        let named_doc = tv::Document::to_named_doc(&doc, &schema);
+        let mut document = Document {
+          field_values: Default::default(),
+        };
+        for (field_name, values) in named_doc.0 {
+          let field_name = field_map.get(&field_name).cloned().unwrap_or(field_name);
+          document
+            .field_values
+            .entry(field_name)
+            .or_default()
+            .extend(values);
+        }
+        match &map_document {
+          Some(map_document) => {
+            if let Some(mapped) = map_document.call(document)? {
+              writer.add_document(&mapped)?;
+            }
+          }
+          None => {
+            writer.add_document(&document)?;
+          }
+        }
+      }
+    }
+
+    writer.commit(None)?;
+    new_index.reload()?;
+    Ok(new_index)
+  }
+
+  /// Merge the index down to a few segments, purging the deleted documents,
+  /// then delete the files that are no longer used. This is the "optimize"
+  /// of other search engines, best run when the index is not being written.
+  ///
+  /// The segments are merged on the calling thread, which blocks until the
+  /// compaction is done. The reader is reloaded afterwards.
+  ///
+  /// @param options - See `CompactOptions`.
+  ///
+  /// @returns CompactResult object.
+  ///
+  /// Raises error if another writer is open on the index.
+  #[napi]
+  pub fn compact<'env>(&self, options: Option<CompactOptions<'env>>) -> Result<CompactResult> {
+    let options = options.unwrap_or_default();
+    let target_segments = options.target_segments.unwrap_or(1);
+    if target_segments == 0 {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "The target number of segments must be at least 1.",
+      ));
+    }
+    self.compact_segments(target_segments as usize, |progress| {
+      match &options.on_progress {
+        Some(on_progress) => on_progress.call(progress),
+        None => Ok(()),
+      }
     })
   }
 
@@ -333,23 +1308,105 @@ impl Index {
   /// The same searcher must be used for a given query, as it ensures the use of a consistent segment set.
   #[napi]
   pub fn searcher(&self) -> Searcher {
-    Searcher {
-      inner: self.reader.searcher(),
-    }
+    Searcher::new(self.reader.searcher())
   }
 
-  /// Check if the given path contains an existing index.
-  /// @param path - The path where tantivy will search for an index.
+  /// Run a callback against a single searcher.
   ///
-  /// Returns True if an index exists at the given path, False otherwise.
+  /// Every search, aggregation and document fetch made through the searcher
+  /// passed to the callback observes the same index generation, even if the
+  /// index is reloaded in between.
   ///
-  /// Raises error if the directory cannot be opened.
+  /// @param callback - Function receiving the searcher.
+  ///
+  /// @returns The value returned by the callback.
+  #[napi]
+  pub fn with_searcher<'env>(
+    &self,
+    callback: Function<'env, Searcher, Unknown<'env>>,
+  ) -> Result<Unknown<'env>> {
+    callback.call(self.searcher())
+  }
+
+  /// The generation of the searchers currently handed out by `searcher()`.
+  ///
+  /// Compare it with `Searcher.generation` to detect a stale searcher.
+  #[napi(getter)]
+  pub fn generation(&self) -> u32 {
+    self.reader.searcher().generation().generation_id() as u32
+  }
+
+  /// Whether the committed segments hold deleted documents, which keep
+  /// taking space until their segment is merged. A maintenance job can use it
+  /// to decide whether to `compact` the index.
+  #[napi]
+  pub fn has_pending_deletes(&self) -> Result<bool> {
+    Ok(
+      self
+        .index
+        .searchable_segment_metas()
+        .map_err(to_napi_error)?
+        .iter()
+        .any(|segment_meta| segment_meta.has_deletes()),
+    )
+  }
+
+  /// Export the terms of a text field with their frequencies, e.g. to build
+  /// a spell checker or to analyze the vocabulary of the index.
+  ///
+  /// The terms are those of the searcher of the index at the time of the
+  /// call, merged across the segments and in lexicographic order. They are
+  /// read as the iterator is consumed.
+  ///
+  /// @param fieldName - The text field whose terms are exported.
+  /// @param options - See `DictionaryOptions`.
+  ///
+  /// @returns An iterator of `DictionaryEntry`, e.g.
+  ///          `for (const { term, docFreq } of index.exportDictionary('body')) { ... }`.
+  ///
+  /// Raises error if the field is not an indexed text field.
+  #[napi]
+  pub fn export_dictionary(
+    &self,
+    field_name: String,
+    options: Option<DictionaryOptions>,
+  ) -> Result<DictionaryIterator> {
+    DictionaryIterator::new(
+      self.reader.searcher(),
+      &field_name,
+      options.unwrap_or_default(),
+    )
+  }
+
+  /// Check if the given path contains an existing index.
+  /// @param path - The path where tantivy will search for an index.
+  ///
+  /// Returns True if an index exists at the given path, False otherwise.
+  ///
+  /// Raises error if the directory cannot be opened.
   #[napi]
   pub fn exists(path: String) -> Result<bool> {
     let directory = tantivy::directory::MmapDirectory::open(&path).map_err(to_napi_error)?;
     tv::Index::exists(&directory).map_err(to_napi_error)
   }
 
+  /// Describe the index stored at a path without opening it, e.g. to tell
+  /// whether an index written by another version of the binding can be
+  /// opened, and how to migrate it otherwise.
+  ///
+  /// Only `meta.json` and the footers of the segment files are read, and the
+  /// directory is not locked.
+  ///
+  /// @param path - The directory of the index.
+  ///
+  /// @returns See `IndexInspection`.
+  ///
+  /// Raises error if the directory holds no index.
+  #[napi]
+  pub fn inspect(path: String) -> Result<IndexInspection> {
+    crate::inspect::inspect(&path)
+  }
+
   /// The schema of the current index.
   #[napi(getter)]
   pub fn schema(&self) -> Schema {
@@ -357,6 +1414,73 @@ impl Index {
     Schema { inner: schema }
   }
 
+  /// Check a JSON document against the schema, as `IndexWriter.addJson`
+  /// would parse it, without adding it.
+  ///
+  /// Unlike `addJson`, which stops at the first invalid value, every value is
+  /// checked. Fields missing from the schema are ignored, as when adding the
+  /// document.
+  ///
+  /// @param json - The document, as a JSON object.
+  ///
+  /// @returns JsonValidationReport object.
+  #[napi]
+  pub fn validate_json(&self, json: String) -> JsonValidationReport {
+    let json_obj: serde_json::Map<String, serde_json::Value> = match serde_json::from_str(&json) {
+      Ok(json_obj) => json_obj,
+      Err(e) => {
+        return JsonValidationReport {
+          valid: false,
+          json_error: Some(e.to_string()),
+          errors: Vec::new(),
+        }
+      }
+    };
+    let schema = self.index.schema();
+    let mut errors = Vec::new();
+    for (field_name, json_value) in json_obj {
+      let Ok(field) = schema.get_field(&field_name) else {
+        continue;
+      };
+      let field_type = schema.get_field_entry(field).field_type();
+      let json_items = match json_value {
+        serde_json::Value::Array(json_items) => json_items,
+        json_value => vec![json_value],
+      };
+      for json_item in json_items {
+        if let Err(e) = field_type.value_from_json(json_item) {
+          errors.push(FieldIssue {
+            field_name: field_name.clone(),
+            reason: e.to_string(),
+          });
+        }
+      }
+    }
+    JsonValidationReport {
+      valid: errors.is_empty(),
+      json_error: None,
+      errors,
+    }
+  }
+
+  /// The settings the index was created with.
+  ///
+  /// They don't include a sort field, tantivy 0.25 no longer sorts indexes.
+  #[napi]
+  pub fn settings(&self) -> Result<IndexSettings> {
+    let settings = self.index.settings();
+    let docstore_compression =
+      serde_json::to_value(settings.docstore_compression).map_err(to_napi_error)?;
+    Ok(IndexSettings {
+      docstore_compression: docstore_compression
+        .as_str()
+        .unwrap_or_default()
+        .to_string(),
+      docstore_blocksize: settings.docstore_blocksize as u32,
+      docstore_compress_dedicated_thread: settings.docstore_compress_dedicated_thread,
+    })
+  }
+
   /// Update searchers so that they reflect the state of the last .commit().
   ///
   /// If you set up the the reload policy to be on 'commit' (which is the
@@ -383,21 +1507,98 @@ impl Index {
   ///         `prefix` determines if terms which are prefixes of the given term match the query.
   ///         `distance` determines the maximum Levenshtein distance between terms matching the query and the given term.
   ///         `transpose_cost_one` determines if transpositions of neighbouring characters are counted only once against the Levenshtein distance.
+  ///
+  /// @param now - Timestamp in milliseconds that `now` resolves to in the date
+  ///         math expressions on date fields, e.g. `date:[now-7d/d TO now]`.
+  ///         Defaults to the current time.
+  ///
+  /// @param jsonPaths - A dictionary keyed on JSON default fields giving the paths
+  ///         searched by the terms without field, see `setDefaultJsonPaths`.
+  ///         Overrides the paths registered on the index for these fields.
+  ///
+  /// @param options - How quoted phrases are parsed, see `ParseQueryOptions`.
   #[napi]
+  #[allow(clippy::too_many_arguments)]
   pub fn parse_query(
     &self,
     query: String,
     default_field_names: Option<Vec<String>>,
     field_boosts: Option<HashMap<String, f64>>,
     fuzzy_fields: Option<HashMap<String, (bool, u8, bool)>>,
+    now: Option<i64>,
+    json_paths: Option<HashMap<String, Vec<String>>>,
+    options: Option<ParseQueryOptions>,
   ) -> Result<Query> {
-    let parser = self.prepare_query_parser(default_field_names, field_boosts, fuzzy_fields)?;
+    let mut parser = prepare_query_parser(
+      &self.index,
+      &self.json_paths,
+      &self.query_tokenizers,
+      default_field_names,
+      field_boosts,
+      fuzzy_fields,
+      json_paths,
+    )?;
+    set_phrase_options(&mut parser, options.unwrap_or_default());
+    let query = self.resolve_date_math(&query, now)?;
 
     let query = parser.parse_query(&query).map_err(to_napi_error)?;
 
     Ok(Query { inner: query })
   }
 
+  /// Build a DisjunctionMaxQuery from its JSON description, e.g. from a
+  /// relevance configuration file.
+  ///
+  /// Example:
+  /// ```json
+  /// {
+  ///   "queries": ["title:whale", { "query": "body:whale", "boost": 0.5 }],
+  ///   "tieBreaker": 0.1
+  /// }
+  /// ```
+  ///
+  /// @param json - The description of the query. Each subquery, following the
+  ///         tantivy query language, is either a string or an object boosting
+  ///         it. `tieBreaker` is optional, see `Query.disjunctionMaxQuery`.
+  /// @param defaultFieldNames - A list of fields used to search if no
+  ///         field is specified in a subquery.
+  #[napi]
+  pub fn parse_disjunction_max_query(
+    &self,
+    json: String,
+    default_field_names: Option<Vec<String>>,
+  ) -> Result<Query> {
+    let spec: DisjunctionMaxSpec = serde_json::from_str(&json).map_err(|e| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Invalid disjunction max query: {}", e),
+      )
+    })?;
+    let parser = prepare_query_parser(
+      &self.index,
+      &self.json_paths,
+      &self.query_tokenizers,
+      default_field_names,
+      None,
+      None,
+      None,
+    )?;
+    let subqueries = spec
+      .queries
+      .into_iter()
+      .map(|subquery| {
+        let (query, boost) = match subquery {
+          DisjunctionMaxSubquery::Query(query) => (query, None),
+          DisjunctionMaxSubquery::Boosted { query, boost } => (query, boost),
+        };
+        let query = self.resolve_date_math(&query, None)?;
+        let query = parser.parse_query(&query).map_err(to_napi_error)?;
+        Ok(crate::query::boosted(query, boost))
+      })
+      .collect::<Result<Vec<_>>>()?;
+    Ok(crate::query::disjunction_max(subqueries, spec.tie_breaker))
+  }
+
   /// Parse a query leniently.
   ///
   /// This variant parses invalid query on a best effort basis. If some part of the query can't
@@ -420,16 +1621,40 @@ impl Index {
   ///         `distance` determines the maximum Levenshtein distance between terms matching the query and the given term.
   ///         `transpose_cost_one` determines if transpositions of neighbouring characters are counted only once against the Levenshtein distance.
   ///
+  /// @param now - Timestamp in milliseconds that `now` resolves to in the date
+  ///         math expressions on date fields, e.g. `date:[now-7d/d TO now]`.
+  ///         Defaults to the current time.
+  ///
+  /// @param jsonPaths - A dictionary keyed on JSON default fields giving the paths
+  ///         searched by the terms without field, see `setDefaultJsonPaths`.
+  ///         Overrides the paths registered on the index for these fields.
+  ///
+  /// @param options - How quoted phrases are parsed, see `ParseQueryOptions`.
+  ///
   /// Returns a tuple containing the parsed query and a list of error messages.
   #[napi]
+  #[allow(clippy::too_many_arguments)]
   pub fn parse_query_lenient(
     &self,
     query: String,
     default_field_names: Option<Vec<String>>,
     field_boosts: Option<HashMap<String, f64>>,
     fuzzy_fields: Option<HashMap<String, (bool, u8, bool)>>,
+    now: Option<i64>,
+    json_paths: Option<HashMap<String, Vec<String>>>,
+    options: Option<ParseQueryOptions>,
   ) -> Result<(Query, Vec<String>)> {
-    let parser = self.prepare_query_parser(default_field_names, field_boosts, fuzzy_fields)?;
+    let mut parser = prepare_query_parser(
+      &self.index,
+      &self.json_paths,
+      &self.query_tokenizers,
+      default_field_names,
+      field_boosts,
+      fuzzy_fields,
+      json_paths,
+    )?;
+    set_phrase_options(&mut parser, options.unwrap_or_default());
+    let query = self.resolve_date_math(&query, now)?;
 
     let (query, errors) = parser.parse_query_lenient(&query);
     let error_messages: Vec<String> = errors.into_iter().map(|err| format!("{:?}", err)).collect();
@@ -437,30 +1662,614 @@ impl Index {
     Ok((Query { inner: query }, error_messages))
   }
 
+  /// Parse the text typed by an end user in a search box, the syntax it is
+  /// not allowed to use being escaped so that it is searched as text, e.g.
+  /// `title:sea^2` searches the words `title`, `sea` and `2` by default.
+  ///
+  /// The text never fails to parse: the syntax errors left, such as a
+  /// parenthesis not closed, are recovered like `parseQueryLenient` does.
+  ///
+  /// @param text - The text typed by the user.
+  /// @param defaultFieldNames - A list of fields used to search the words.
+  ///         Defaults to all the indexed fields.
+  /// @param options - The syntax allowed, see `UserQueryOptions`.
+  #[napi]
+  pub fn parse_user_query(
+    &self,
+    text: String,
+    default_field_names: Option<Vec<String>>,
+    options: Option<UserQueryOptions>,
+  ) -> Result<Query> {
+    let parser = prepare_query_parser(
+      &self.index,
+      &self.json_paths,
+      &self.query_tokenizers,
+      default_field_names,
+      None,
+      None,
+      None,
+    )?;
+    let text = crate::user_query::sanitize(&text, &options.unwrap_or_default());
+    let (query, _errors) = parser.parse_query_lenient(&text);
+    Ok(Query { inner: query })
+  }
+
+  /// Describe how a query is parsed, for debugging relevance.
+  ///
+  /// Every clause, boost and leaf of the query is described, the leaves with
+  /// their query type, field and terms after analysis, as the parser would
+  /// build them.
+  ///
+  /// @param query - the query, following the tantivy query language.
+  /// @param options - Parsing options, see `DebugQueryOptions`.
+  ///
+  /// @returns The parsed query tree, as a JSON string.
+  #[napi]
+  pub fn debug_query(&self, query: String, options: Option<DebugQueryOptions>) -> Result<String> {
+    let options = options.unwrap_or_default();
+    let schema = self.index.schema();
+    let field_boosts = options.field_boosts.unwrap_or_default();
+    let fuzzy_fields = options.fuzzy_fields.unwrap_or_default();
+    for field_name in field_boosts.keys().chain(fuzzy_fields.keys()) {
+      crate::get_field(&schema, field_name)?;
+    }
+    // Boosts and fuzzy fields are reported by the debugger, see `QueryDebugger`.
+    let parser = prepare_query_parser(
+      &self.index,
+      &self.json_paths,
+      &self.query_tokenizers,
+      options.default_field_names,
+      None,
+      None,
+      options.json_paths,
+    )?;
+    let query = self.resolve_date_math(&query, options.now)?;
+
+    let ast = tv::query_grammar::parse_query(&query)
+      .map_err(|_| Error::new(Status::InvalidArg, "Syntax error in query"))?;
+    let ast = parser.expand(ast);
+    let debugger = QueryDebugger {
+      parser: &parser.parser,
+      schema: &schema,
+      field_boosts: &field_boosts,
+      fuzzy_fields: &fuzzy_fields,
+    };
+    let description = debugger.describe(ast).map_err(to_napi_error)?;
+    serde_json::to_string(&description).map_err(to_napi_error)
+  }
+
+  /// Register the paths of a JSON field searched by the terms without field.
+  ///
+  /// A JSON field among the default fields of `parseQuery` only matches the
+  /// terms without field at the root of its objects. Once paths are
+  /// registered, these terms search the paths of the field instead, e.g.
+  /// after `setDefaultJsonPaths('attrs', ['color', 'size.unit'])` the query
+  /// `red` matches `{ attrs: { color: 'red' } }`.
+  ///
+  /// @param fieldName - A JSON field of the schema.
+  /// @param paths - Paths inside the field. An empty list removes the paths
+  ///         registered for the field.
+  #[napi]
+  pub fn set_default_json_paths(&self, field_name: String, paths: Vec<String>) -> Result<()> {
+    check_json_field(&self.index.schema(), &field_name)?;
+    let mut json_paths = self.json_paths.lock().unwrap();
+    if paths.is_empty() {
+      json_paths.remove(&field_name);
+    } else {
+      json_paths.insert(field_name, paths);
+    }
+    Ok(())
+  }
+
+  /// List the files making up the last commit, ready to be shipped to a replica.
+  ///
+  /// The returned list contains the files of every searchable segment followed
+  /// by `meta.json`, which must be applied last on the replica.
+  ///
+  /// @param exclude - Paths of files the replica already holds. Segment files are
+  ///         immutable, so they never need to be shipped twice.
+  ///
+  /// Raises error if a file cannot be read from the index directory.
+  #[napi]
+  pub fn list_files_for_replication(
+    &self,
+    exclude: Option<Vec<String>>,
+  ) -> Result<Vec<ReplicationFile>> {
+    let exclude: HashSet<PathBuf> = exclude
+      .unwrap_or_default()
+      .into_iter()
+      .map(PathBuf::from)
+      .collect();
+    let directory = self.index.directory();
+
+    let mut segment_files: Vec<PathBuf> = self
+      .index
+      .searchable_segment_metas()
+      .map_err(to_napi_error)?
+      .iter()
+      .flat_map(segment_files)
+      .filter(|path| !exclude.contains(path))
+      .collect();
+    segment_files.sort();
+
+    let mut files = Vec::with_capacity(segment_files.len() + 1);
+    for path in segment_files {
+      let data = directory
+        .open_read(&path)
+        .map_err(to_napi_error)?
+        .read_bytes()
+        .map_err(to_napi_error)?;
+      files.push(ReplicationFile {
+        path: path.to_string_lossy().into_owned(),
+        data: data.as_slice().to_vec().into(),
+      });
+    }
+
+    let meta = directory
+      .atomic_read(Path::new(META_FILEPATH))
+      .map_err(to_napi_error)?;
+    files.push(ReplicationFile {
+      path: META_FILEPATH.to_string(),
+      data: meta.into(),
+    });
+    Ok(files)
+  }
+
+  /// Apply files produced by `listFilesForReplication()` on a primary index.
+  ///
+  /// Segment files are written first and `meta.json` is atomically replaced
+  /// last, so readers never observe a commit whose segments are missing. The
+  /// reader is reloaded once the files have been applied.
+  ///
+  /// @param files - The files shipped from the primary index.
+  ///
+  /// Raises error if a path is not a plain file name or if writing fails.
+  #[napi]
+  pub fn apply_replicated_files(&self, files: Vec<ReplicationFile>) -> Result<()> {
+    let directory = self.index.directory();
+    let mut meta = None;
+
+    for file in &files {
+      let path = Path::new(&file.path);
+      if path.file_name().map(Path::new) != Some(path) {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Invalid replication file path: '{}'", file.path),
+        ));
+      }
+      if file.path == META_FILEPATH {
+        meta = Some(file);
+        continue;
+      }
+      // Segment files never change, a file already there is complete.
+      if directory.exists(path).map_err(to_napi_error)? {
+        continue;
+      }
+      // The files are shipped without their footer, which is added back
+      // when they are written.
+      let mut writer = directory.open_write(path).map_err(to_napi_error)?;
+      writer.write_all(&file.data).map_err(to_napi_error)?;
+      writer.terminate().map_err(to_napi_error)?;
+    }
+
+    if let Some(meta) = meta {
+      directory
+        .atomic_write(Path::new(META_FILEPATH), &meta.data)
+        .map_err(to_napi_error)?;
+      self.reader.reload().map_err(to_napi_error)?;
+    }
+    Ok(())
+  }
+
+  /// Verify the checksums of the files of the searchable segments, stored in
+  /// their footer, e.g. after restoring a backup or copying the index over
+  /// the network.
+  ///
+  /// Every file is read in full, which takes a while on a large index.
+  ///
+  /// @returns The result of every file, sorted by path. The index is intact
+  ///          if all of them are 'ok'.
+  ///
+  /// Raises error if the meta of the index can't be read.
+  #[napi]
+  pub fn verify_checksums(&self) -> Result<Vec<ChecksumResult>> {
+    let directory = self.index.directory();
+    let mut results = Vec::new();
+    for segment_meta in self
+      .index
+      .searchable_segment_metas()
+      .map_err(to_napi_error)?
+    {
+      for path in segment_files(&segment_meta) {
+        let (status, error) = match directory.validate_checksum(&path) {
+          Ok(true) => ("ok", None),
+          Ok(false) => ("corrupted", None),
+          Err(tv::directory::error::OpenReadError::FileDoesNotExist(_)) => ("missing", None),
+          Err(e) => ("unreadable", Some(e.to_string())),
+        };
+        results.push(ChecksumResult {
+          path: path.to_string_lossy().into_owned(),
+          segment_id: segment_meta.id().uuid_string(),
+          status: status.to_string(),
+          error,
+        });
+      }
+    }
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(results)
+  }
+
+  /// Configure the filter cache of the index.
+  ///
+  /// The cache holds the documents matched by queries wrapped with
+  /// `cachedFilter()`, per segment. Reconfiguring the cache clears it.
+  ///
+  /// @param maxEntries - The maximum number of (query, segment) entries to
+  ///         keep. Least recently used entries are evicted first. Defaults to 1024,
+  ///         0 disables the cache.
+  /// @param ttlMs - If given, entries older than this many milliseconds are
+  ///         discarded.
+  #[napi]
+  pub fn config_filter_cache(&self, max_entries: Option<u32>, ttl_ms: Option<u32>) {
+    self
+      .filter_cache
+      .lock()
+      .unwrap()
+      .configure(max_entries, ttl_ms);
+  }
+
+  /// Wrap a query so that its matching documents are cached.
+  ///
+  /// This is meant for filter-style queries that are repeated across
+  /// requests: the returned query gives the same constant score to every
+  /// matching document, and is best used as a `Must` clause of a boolean query.
+  ///
+  /// The filters with the same `Query.fingerprint` share their cached
+  /// documents.
+  ///
+  /// @param query - The filter query to cache.
+  #[napi]
+  pub fn cached_filter(&self, query: &Query) -> Query {
+    Query {
+      inner: Box::new(CachedFilterQuery::new(
+        query.inner.box_clone(),
+        self.filter_cache.clone(),
+      )),
+    }
+  }
+
+  /// Statistics of the filter cache.
+  #[napi]
+  pub fn filter_cache_stats(&self) -> FilterCacheStats {
+    self.filter_cache.lock().unwrap().stats()
+  }
+
+  /// Remove every entry of the filter cache and reset its statistics.
+  #[napi]
+  pub fn clear_filter_cache(&self) {
+    self.filter_cache.lock().unwrap().clear();
+  }
+
   /// Register a custom text analyzer by name. (Confusingly,
   /// this is one of the places where Tantivy uses 'tokenizer' to refer to a
   /// TextAnalyzer instance.)
   ///
+  /// The analyzer can also be used as the normalizer of a fast text field,
+  /// see `TextFieldOptions.fast`.
+  ///
+  /// @param queryJsonFields - JSON fields whose text values are analyzed by
+  ///         this analyzer when parsing queries, instead of the tokenizer the
+  ///         fields were indexed with. The documents are indexed as before,
+  ///         so a mismatch between the analyzer of the indexed values and the
+  ///         one of the queries can be fixed without reindexing.
+  ///
+  /// Raises error if one of `queryJsonFields` is not an indexed JSON field.
   // Implementation notes: Skipped indirection of TokenizerManager.
   #[napi]
-  pub fn register_tokenizer(&self, name: String, analyzer: &TextAnalyzer) {
+  pub fn register_tokenizer(
+    &self,
+    name: String,
+    analyzer: &TextAnalyzer,
+    query_json_fields: Option<Vec<String>>,
+  ) -> Result<()> {
+    let schema = self.index.schema();
+    for field_name in query_json_fields.iter().flatten() {
+      check_json_field(&schema, field_name)?;
+      if !schema
+        .get_field_entry(crate::get_field(&schema, field_name)?)
+        .is_indexed()
+      {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Field `{field_name}` is not set as indexed in the schema."),
+        ));
+      }
+    }
     self
       .index
       .tokenizers()
       .register(&name, analyzer.analyzer.clone());
+    self
+      .index
+      .fast_field_tokenizer()
+      .register(&name, analyzer.analyzer.clone());
+    let mut query_tokenizers = self.query_tokenizers.lock().unwrap();
+    for field_name in query_json_fields.unwrap_or_default() {
+      query_tokenizers.insert(field_name, name.clone());
+    }
+    Ok(())
+  }
+
+  /// Register the index to be shared with the worker threads of the process.
+  ///
+  /// The handle is a number, which can be posted to a worker. The index
+  /// created from it by `Index.fromHandle` shares the files, reader, filter
+  /// cache and tokenizers of this one, instead of opening the index again.
+  /// The handle must be released with `releaseHandle` once it is no longer
+  /// needed.
+  ///
+  /// @returns A handle to pass to `Index.fromHandle` in another worker.
+  #[napi]
+  pub fn to_handle(&self) -> u32 {
+    handle::register(handle::Shared::Index(Box::new(SharedIndex {
+      index: self.index.clone(),
+      reader: self.reader.clone(),
+      filter_cache: self.filter_cache.clone(),
+      path: self.path.clone(),
+      json_paths: self.json_paths.lock().unwrap().clone(),
+      query_tokenizers: self.query_tokenizers.lock().unwrap().clone(),
+    })))
+  }
+
+  /// Create an index from a handle returned by `Index.toHandle`.
+  ///
+  /// Raises error if the handle is unknown or was released.
+  #[napi(factory)]
+  pub fn from_handle(handle: u32) -> Result<Index> {
+    let shared = handle::resolve(handle, "Index", |shared| match shared {
+      handle::Shared::Index(index) => Some(index.as_ref().clone()),
+      _ => None,
+    })?;
+    Ok(Index {
+      index: shared.index,
+      reader: shared.reader,
+      filter_cache: shared.filter_cache,
+      path: shared.path,
+      json_paths: Arc::new(Mutex::new(shared.json_paths)),
+      query_tokenizers: Arc::new(Mutex::new(shared.query_tokenizers)),
+    })
   }
 }
 
 impl Index {
-  fn prepare_query_parser(
-    &self,
-    default_field_names: Option<Vec<String>>,
-    field_boosts: Option<HashMap<String, f64>>,
-    fuzzy_fields: Option<HashMap<String, (bool, u8, bool)>>,
-  ) -> Result<tv::query::QueryParser> {
+  pub(crate) fn create_writer(&self, options: IndexWriterOptions) -> Result<IndexWriter> {
     let schema = self.index.schema();
+    let unique_field = options
+      .unique_field
+      .as_deref()
+      .map(|field_name| check_unique_field(&schema, field_name))
+      .transpose()?;
+    let expiry_field = options
+      .expiry_field
+      .as_deref()
+      .map(|field_name| check_expiry_field(&schema, field_name))
+      .transpose()?;
+    let heap_size = options.heap_size.unwrap_or(128_000_000) as usize;
+    let num_threads = match options.num_threads.unwrap_or(0) as usize {
+      0 => default_num_threads(heap_size),
+      num_threads => num_threads,
+    };
+    let writer = self
+      .index
+      .writer_with_num_threads(num_threads, heap_size)
+      .map_err(to_napi_error)?;
+    writer.set_merge_policy(Box::new(crate::metrics::CountingMergePolicy {
+      inner: writer.get_merge_policy(),
+    }));
+    Ok(IndexWriter {
+      inner_index_writer: Some(writer),
+      schema,
+      unique_field,
+      expiry_field,
+      on_commit: None,
+      json_paths: self.json_paths.clone(),
+      query_tokenizers: self.query_tokenizers.clone(),
+      path: self.path.clone(),
+      options: IndexWriterOptions {
+        heap_size: Some(heap_size as u32),
+        num_threads: Some(num_threads as u32),
+        unique_field: options.unique_field,
+        expiry_field: options.expiry_field,
+      },
+    })
+  }
+
+  fn compact_segments(
+    &self,
+    target_segments: usize,
+    mut on_progress: impl FnMut(CompactProgress) -> Result<()>,
+  ) -> Result<CompactResult> {
+    // The files of a segment are kept as long as its meta is alive, so the
+    // metas are dropped before merging.
+    let (segments_before, groups) = {
+      let segments = self
+        .index
+        .searchable_segment_metas()
+        .map_err(to_napi_error)?;
+      (segments.len(), merge_groups(&segments, target_segments))
+    };
+    let total = groups.len() as u32 + 1;
+    let files_before = self.managed_files();
+
+    let mut writer = self.create_writer(IndexWriterOptions::default())?;
+    let inner = writer.inner_mut()?;
+    // The merges are ours to choose.
+    inner.set_merge_policy(Box::new(tv::indexer::NoMergePolicy));
+    for (idx, group) in groups.iter().enumerate() {
+      inner.merge(group).wait().map_err(to_napi_error)?;
+      on_progress(CompactProgress {
+        step: "merge".to_string(),
+        completed: idx as u32 + 1,
+        total,
+      })?;
+    }
+    // The writer collects the files of a merged segment right away, unless a
+    // searcher still uses it.
+    self.reader.reload().map_err(to_napi_error)?;
+    inner
+      .garbage_collect_files()
+      .wait()
+      .map_err(to_napi_error)?;
+    writer.wait_merging_threads()?;
+    on_progress(CompactProgress {
+      step: "garbageCollect".to_string(),
+      completed: total,
+      total,
+    })?;
+
+    let files_after = self.managed_files();
+    let size = |files: &HashMap<PathBuf, u64>| files.values().sum::<u64>();
+    Ok(CompactResult {
+      segments_before: segments_before as u32,
+      segments_after: self
+        .index
+        .searchable_segment_metas()
+        .map_err(to_napi_error)?
+        .len() as u32,
+      files_deleted: files_before
+        .keys()
+        .filter(|path| !files_after.contains_key(*path))
+        .count() as u32,
+      bytes_reclaimed: size(&files_before).saturating_sub(size(&files_after)) as f64,
+    })
+  }
+
+  /// The files of the index, with their size in bytes.
+  fn managed_files(&self) -> HashMap<PathBuf, u64> {
+    let directory = self.index.directory();
+    directory
+      .list_managed_files()
+      .into_iter()
+      .filter_map(|path| {
+        let num_bytes = directory.open_read(&path).ok()?.num_bytes().get_bytes();
+        Some((path, num_bytes))
+      })
+      .collect()
+  }
+
+  fn resolve_date_math(&self, query: &str, now: Option<i64>) -> Result<String> {
+    let now = match now {
+      Some(now) => chrono::DateTime::from_timestamp_millis(now)
+        .ok_or_else(|| Error::new(Status::InvalidArg, format!("Invalid timestamp {}.", now)))?,
+      None => chrono::Utc::now(),
+    };
+    Ok(crate::datemath::resolve_date_math(
+      query,
+      &self.index.schema(),
+      now,
+    ))
+  }
+
+  pub(crate) fn register_custom_text_analyzers(tokenizers: &tv::tokenizer::TokenizerManager) {
+    let analyzers = [
+      ("ar_stem", tantivy::tokenizer::Language::Arabic),
+      ("da_stem", tantivy::tokenizer::Language::Danish),
+      ("nl_stem", tantivy::tokenizer::Language::Dutch),
+      ("fi_stem", tantivy::tokenizer::Language::Finnish),
+      ("fr_stem", tantivy::tokenizer::Language::French),
+      ("de_stem", tantivy::tokenizer::Language::German),
+      ("el_stem", tantivy::tokenizer::Language::Greek),
+      ("hu_stem", tantivy::tokenizer::Language::Hungarian),
+      ("it_stem", tantivy::tokenizer::Language::Italian),
+      ("no_stem", tantivy::tokenizer::Language::Norwegian),
+      ("pt_stem", tantivy::tokenizer::Language::Portuguese),
+      ("ro_stem", tantivy::tokenizer::Language::Romanian),
+      ("ru_stem", tantivy::tokenizer::Language::Russian),
+      ("es_stem", tantivy::tokenizer::Language::Spanish),
+      ("sv_stem", tantivy::tokenizer::Language::Swedish),
+      ("ta_stem", tantivy::tokenizer::Language::Tamil),
+      ("tr_stem", tantivy::tokenizer::Language::Turkish),
+    ];
+
+    for (name, lang) in &analyzers {
+      let an =
+        tantivy::tokenizer::TextAnalyzer::builder(tantivy::tokenizer::SimpleTokenizer::default())
+          .filter(tantivy::tokenizer::RemoveLongFilter::limit(40))
+          .filter(tantivy::tokenizer::LowerCaser)
+          .filter(tantivy::tokenizer::Stemmer::new(*lang))
+          .build();
+      tokenizers.register(name, an);
+    }
+  }
+
+  /// Register the normalizers of the fast text fields that tantivy doesn't
+  /// provide.
+  pub(crate) fn register_fast_field_normalizers(tokenizers: &tv::tokenizer::TokenizerManager) {
+    let lowercase =
+      tantivy::tokenizer::TextAnalyzer::builder(tantivy::tokenizer::RawTokenizer::default())
+        .filter(tantivy::tokenizer::LowerCaser)
+        .build();
+    tokenizers.register("lowercase", lowercase);
+  }
+}
+
+/// The files of a segment. The delete file is only listed once the segment
+/// has deletes, as it is not written before.
+fn segment_files(segment_meta: &tv::index::SegmentMeta) -> impl Iterator<Item = PathBuf> {
+  let delete_file = (!segment_meta.has_deletes())
+    .then(|| segment_meta.relative_path(tv::index::SegmentComponent::Delete));
+  segment_meta
+    .list_files()
+    .into_iter()
+    .filter(move |path| Some(path) != delete_file.as_ref())
+}
 
-    let default_fields = if let Some(default_field_names) = default_field_names {
+/// Split the segments into at most `target_segments` groups of similar numbers
+/// of documents, each group being merged into a single segment. Segments left
+/// alone are merged only if they hold deleted documents, to purge them.
+fn merge_groups(
+  segments: &[tv::index::SegmentMeta],
+  target_segments: usize,
+) -> Vec<Vec<tv::index::SegmentId>> {
+  let mut segments: Vec<&tv::index::SegmentMeta> = segments.iter().collect();
+  segments.sort_by_key(|segment| std::cmp::Reverse(segment.num_docs()));
+  // Every segment goes to the group with the fewest documents so far.
+  let mut groups: Vec<(u64, Vec<&tv::index::SegmentMeta>)> = Vec::new();
+  for segment in segments {
+    if groups.len() < target_segments {
+      groups.push((segment.num_docs() as u64, vec![segment]));
+      continue;
+    }
+    let group = groups
+      .iter_mut()
+      .min_by_key(|(num_docs, _)| *num_docs)
+      .expect("at least one group");
+    group.0 += segment.num_docs() as u64;
+    group.1.push(segment);
+  }
+  groups
+    .into_iter()
+    .filter(|(_, group)| group.len() > 1 || group[0].has_deletes())
+    .map(|(_, group)| group.iter().map(|segment| segment.id()).collect())
+    .collect()
+}
+
+/// A query parser searching the given fields, all the indexed ones by
+/// default, with the JSON paths registered on the index and the given ones,
+/// and the query tokenizers registered on the index.
+fn prepare_query_parser(
+  index: &tv::Index,
+  registered_json_paths: &Mutex<HashMap<String, Vec<String>>>,
+  query_tokenizers: &Mutex<HashMap<String, String>>,
+  default_field_names: Option<Vec<String>>,
+  field_boosts: Option<HashMap<String, f64>>,
+  fuzzy_fields: Option<HashMap<String, (bool, u8, bool)>>,
+  json_paths: Option<HashMap<String, Vec<String>>>,
+) -> Result<JsonPathsParser> {
+  let schema = index.schema();
+
+  let mut default_fields: Vec<tv::schema::Field> =
+    if let Some(default_field_names) = default_field_names {
       default_field_names
         .iter()
         .map(|field_name| {
@@ -490,66 +2299,251 @@ impl Index {
         .collect()
     };
 
-    let mut parser = tv::query::QueryParser::for_index(&self.index, default_fields);
-
-    // Set field boosts if provided
-    if let Some(field_boosts) = field_boosts {
-      for (field_name, boost) in field_boosts {
-        let field = schema.get_field(&field_name).map_err(|_err| {
-          Error::new(
-            Status::InvalidArg,
-            format!("Field `{field_name}` is not defined in the schema."),
-          )
-        })?;
-        parser.set_field_boost(field, boost as tv::Score);
+  let mut all_json_paths = registered_json_paths.lock().unwrap().clone();
+  for (field_name, paths) in json_paths.unwrap_or_default() {
+    check_json_field(&schema, &field_name)?;
+    all_json_paths.insert(field_name, paths);
+  }
+  let mut paths = Vec::new();
+  default_fields.retain(|field| {
+    let field_name = schema.get_field_name(*field);
+    match all_json_paths.get(field_name) {
+      Some(json_paths) if !json_paths.is_empty() => {
+        paths.extend(json_paths.iter().map(|path| format!("{field_name}.{path}")));
+        false
       }
+      _ => true,
     }
+  });
+  let has_default_fields = !default_fields.is_empty();
 
-    // Set fuzzy fields if provided
-    if let Some(fuzzy_fields) = fuzzy_fields {
-      for (field_name, (prefix, distance, transpose_cost_one)) in fuzzy_fields {
-        let field = schema.get_field(&field_name).map_err(|_err| {
-          Error::new(
-            Status::InvalidArg,
-            format!("Field `{field_name}` is not defined in the schema."),
-          )
-        })?;
-        parser.set_field_fuzzy(field, prefix, distance, transpose_cost_one);
-      }
+  let mut parser = tv::query::QueryParser::new(
+    with_query_tokenizers(&schema, &query_tokenizers.lock().unwrap()),
+    default_fields,
+    index.tokenizers().clone(),
+  );
+
+  // Set field boosts if provided
+  if let Some(field_boosts) = field_boosts {
+    for (field_name, boost) in field_boosts {
+      let field = schema.get_field(&field_name).map_err(|_err| {
+        Error::new(
+          Status::InvalidArg,
+          format!("Field `{field_name}` is not defined in the schema."),
+        )
+      })?;
+      parser.set_field_boost(field, boost as tv::Score);
     }
+  }
 
-    Ok(parser)
+  // Set fuzzy fields if provided
+  if let Some(fuzzy_fields) = fuzzy_fields {
+    for (field_name, (prefix, distance, transpose_cost_one)) in fuzzy_fields {
+      let field = schema.get_field(&field_name).map_err(|_err| {
+        Error::new(
+          Status::InvalidArg,
+          format!("Field `{field_name}` is not defined in the schema."),
+        )
+      })?;
+      parser.set_field_fuzzy(field, prefix, distance, transpose_cost_one);
+    }
   }
 
-  fn register_custom_text_analyzers(index: &tv::Index) {
-    let analyzers = [
-      ("ar_stem", tantivy::tokenizer::Language::Arabic),
-      ("da_stem", tantivy::tokenizer::Language::Danish),
-      ("nl_stem", tantivy::tokenizer::Language::Dutch),
-      ("fi_stem", tantivy::tokenizer::Language::Finnish),
-      ("fr_stem", tantivy::tokenizer::Language::French),
-      ("de_stem", tantivy::tokenizer::Language::German),
-      ("el_stem", tantivy::tokenizer::Language::Greek),
-      ("hu_stem", tantivy::tokenizer::Language::Hungarian),
-      ("it_stem", tantivy::tokenizer::Language::Italian),
-      ("no_stem", tantivy::tokenizer::Language::Norwegian),
-      ("pt_stem", tantivy::tokenizer::Language::Portuguese),
-      ("ro_stem", tantivy::tokenizer::Language::Romanian),
-      ("ru_stem", tantivy::tokenizer::Language::Russian),
-      ("es_stem", tantivy::tokenizer::Language::Spanish),
-      ("sv_stem", tantivy::tokenizer::Language::Swedish),
-      ("ta_stem", tantivy::tokenizer::Language::Tamil),
-      ("tr_stem", tantivy::tokenizer::Language::Turkish),
-    ];
+  Ok(JsonPathsParser {
+    parser,
+    paths,
+    has_default_fields,
+    phrase_slop: 0,
+    phrase_prefix: false,
+  })
+}
 
-    for (name, lang) in &analyzers {
-      let an =
-        tantivy::tokenizer::TextAnalyzer::builder(tantivy::tokenizer::SimpleTokenizer::default())
-          .filter(tantivy::tokenizer::RemoveLongFilter::limit(40))
-          .filter(tantivy::tokenizer::LowerCaser)
-          .filter(tantivy::tokenizer::Stemmer::new(*lang))
-          .build();
-      index.tokenizers().register(name, an);
+/// The schema the query parser sees, where the JSON fields given a query
+/// tokenizer are indexed with it. The fields keep their order, and so the
+/// queries built search the fields of `schema`.
+fn with_query_tokenizers(
+  schema: &tv::schema::Schema,
+  query_tokenizers: &HashMap<String, String>,
+) -> tv::schema::Schema {
+  if query_tokenizers.is_empty() {
+    return schema.clone();
+  }
+  let mut builder = tv::schema::Schema::builder();
+  for (_, field_entry) in schema.fields() {
+    let tokenizer = query_tokenizers.get(field_entry.name());
+    let field_entry = match (field_entry.field_type(), tokenizer) {
+      (tv::schema::FieldType::JsonObject(options), Some(tokenizer)) => {
+        match options.get_text_indexing_options() {
+          Some(indexing) => tv::schema::FieldEntry::new_json(
+            field_entry.name().to_string(),
+            options
+              .clone()
+              .set_indexing_options(indexing.clone().set_tokenizer(tokenizer)),
+          ),
+          None => field_entry.clone(),
+        }
+      }
+      _ => field_entry.clone(),
+    };
+    builder.add_field(field_entry);
+  }
+  builder.build()
+}
+
+fn set_phrase_options(parser: &mut JsonPathsParser, options: ParseQueryOptions) {
+  parser.phrase_slop = options.phrase_slop.unwrap_or(0);
+  parser.phrase_prefix = options.phrase_prefix.unwrap_or(false);
+}
+
+fn check_json_field(schema: &tv::schema::Schema, field_name: &str) -> Result<()> {
+  let field = crate::get_field(schema, field_name)?;
+  if !matches!(
+    schema.get_field_entry(field).field_type(),
+    tv::schema::FieldType::JsonObject(_)
+  ) {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Field `{field_name}` is not a JSON field."),
+    ));
+  }
+  Ok(())
+}
+
+pub(crate) fn convert_document(
+  schema: &tv::schema::Schema,
+  doc: &Document,
+) -> Result<tv::TantivyDocument> {
+  let named_doc = tantivy::schema::NamedFieldDocument(doc.field_values.clone());
+  tantivy::schema::document::TantivyDocument::convert_named_doc(schema, named_doc)
+    .map_err(to_napi_error)
+}
+
+/// The number of indexing threads picked by `tantivy::Index::writer`: one per
+/// core, up to 8, as long as each one gets at least 15MB of the heap.
+fn default_num_threads(heap_size: usize) -> usize {
+  const MAX_NUM_THREADS: usize = 8;
+  const MIN_HEAP_SIZE_PER_THREAD: usize = 15_000_000;
+  let num_threads = std::thread::available_parallelism()
+    .map_or(1, |parallelism| parallelism.get())
+    .min(MAX_NUM_THREADS);
+  if heap_size / num_threads < MIN_HEAP_SIZE_PER_THREAD {
+    (heap_size / MIN_HEAP_SIZE_PER_THREAD).max(1)
+  } else {
+    num_threads
+  }
+}
+
+/// Push the operations adding a document, preceded by the deletion of the
+/// documents having its value of the unique field if there is one.
+pub(crate) fn push_add_operations(
+  schema: &tv::schema::Schema,
+  unique_field: Option<tv::schema::Field>,
+  doc: tv::TantivyDocument,
+  operations: &mut Vec<tv::indexer::UserOperation>,
+) -> Result<()> {
+  if let Some(field) = unique_field {
+    let term = unique_term(schema, field, &doc)?;
+    operations.push(tv::indexer::UserOperation::Delete(term));
+  }
+  operations.push(tv::indexer::UserOperation::Add(doc));
+  Ok(())
+}
+
+/// Check that a field can identify the documents, see `IndexWriterOptions.uniqueField`.
+fn check_unique_field(schema: &tv::schema::Schema, field_name: &str) -> Result<tv::schema::Field> {
+  let field = crate::get_field(schema, field_name)?;
+  if crate::nested::nested_field(schema).is_ok() {
+    // Replacing a parent by its key would leave its children behind.
+    return Err(Error::new(
+      Status::InvalidArg,
+      "A unique field can't be used with nested documents.",
+    ));
+  }
+  let field_entry = schema.get_field_entry(field);
+  if !field_entry.is_indexed() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Unique field `{field_name}` is not set as indexed in the schema."),
+    ));
+  }
+  match field_entry.field_type() {
+    tv::schema::FieldType::Str(options) => {
+      let tokenizer = options
+        .get_indexing_options()
+        .map(|indexing| indexing.tokenizer());
+      if tokenizer != Some("raw") {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Unique field `{field_name}` must use the `raw` tokenizer."),
+        ));
+      }
+    }
+    tv::schema::FieldType::Facet(_) | tv::schema::FieldType::JsonObject(_) => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Field `{field_name}` cannot be a unique field."),
+      ));
     }
+    _ => {}
+  }
+  Ok(field)
+}
+
+/// Check that a field can give the expiry of the documents, see
+/// `IndexWriterOptions.expiryField`.
+fn check_expiry_field(schema: &tv::schema::Schema, field_name: &str) -> Result<tv::schema::Field> {
+  let field = crate::get_field(schema, field_name)?;
+  let field_entry = schema.get_field_entry(field);
+  if field_entry.field_type().value_type() != tv::schema::Type::Date {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Expiry field `{field_name}` is not a date field."),
+    ));
+  }
+  if !field_entry.is_indexed() && !field_entry.is_fast() {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Expiry field `{field_name}` must be indexed or fast."),
+    ));
   }
+  Ok(field)
+}
+
+/// The term identifying a document by its value of the unique field.
+fn unique_term(
+  schema: &tv::schema::Schema,
+  field: tv::schema::Field,
+  doc: &tv::TantivyDocument,
+) -> Result<tv::Term> {
+  use tv::schema::Value;
+
+  let value = doc.get_first(field);
+  let term = value.and_then(|value| match schema.get_field_entry(field).field_type() {
+    tv::schema::FieldType::Str(_) => value
+      .as_str()
+      .map(|text| tv::Term::from_field_text(field, text)),
+    tv::schema::FieldType::U64(_) => value.as_u64().map(|v| tv::Term::from_field_u64(field, v)),
+    tv::schema::FieldType::I64(_) => value.as_i64().map(|v| tv::Term::from_field_i64(field, v)),
+    tv::schema::FieldType::F64(_) => value.as_f64().map(|v| tv::Term::from_field_f64(field, v)),
+    tv::schema::FieldType::Bool(_) => value.as_bool().map(|v| tv::Term::from_field_bool(field, v)),
+    tv::schema::FieldType::Date(_) => value
+      .as_datetime()
+      .map(|v| tv::Term::from_field_date_for_search(field, v)),
+    tv::schema::FieldType::Bytes(_) => value
+      .as_bytes()
+      .map(|v| tv::Term::from_field_bytes(field, v)),
+    tv::schema::FieldType::IpAddr(_) => value
+      .as_ip_addr()
+      .map(|v| tv::Term::from_field_ip_addr(field, v)),
+    _ => None,
+  });
+  term.ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!(
+        "Document has no value for the unique field `{}`.",
+        schema.get_field_name(field)
+      ),
+    )
+  })
 }
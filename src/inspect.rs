@@ -0,0 +1,146 @@
+use std::path::Path;
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use serde_json::Value as JsonValue;
+use tantivy as tv;
+use tantivy::directory::footer::Footer;
+use tantivy::directory::{Directory, MmapDirectory};
+
+/// What `Index.inspect` finds about an index, without opening it.
+#[napi(object, object_from_js = false)]
+pub struct IndexInspection {
+  /// The index format version of the oldest segment, undefined for an index
+  /// without segments.
+  pub index_format_version: Option<u32>,
+  /// The version of tantivy which wrote the oldest segment, such as '0.22.0'.
+  pub tantivy_version: Option<String>,
+  /// The schema, as stored in `meta.json`.
+  pub schema: JsonValue,
+  /// Number of segments of the last commit.
+  pub num_segments: u32,
+  /// Number of documents of the last commit, the deleted ones excluded.
+  pub num_docs: f64,
+  /// Whether this version of the binding can open the index.
+  pub compatible: bool,
+  /// Why the index can't be opened, and how to migrate it.
+  pub advice: Option<String>,
+}
+
+/// The version written in the footer of every file of a segment, as a JSON
+/// object with `major`, `minor`, `patch` and `index_format_version` keys.
+fn segment_version(directory: &MmapDirectory, segment_id: &str) -> Result<JsonValue> {
+  // Every segment has a doc store.
+  let path = format!("{}.store", segment_id.replace('-', ""));
+  let file = directory
+    .open_read(Path::new(&path))
+    .map_err(crate::to_napi_error)?;
+  let (footer, _) = Footer::extract_footer(file)
+    .map_err(|e| Error::new(Status::GenericFailure, format!("{}: {}", path, e)))?;
+  serde_json::to_value(&footer.version).map_err(crate::to_napi_error)
+}
+
+/// Read the meta of an index and the footers of its segments, see
+/// `Index.inspect`.
+pub(crate) fn inspect(path: &str) -> Result<IndexInspection> {
+  let meta_path = Path::new(path).join("meta.json");
+  let meta = std::fs::read_to_string(&meta_path).map_err(|e| {
+    Error::new(
+      Status::InvalidArg,
+      format!("No index found at '{}': {}", path, e),
+    )
+  })?;
+  let meta: JsonValue = serde_json::from_str(&meta).map_err(|e| {
+    Error::new(
+      Status::InvalidArg,
+      format!("Invalid meta.json in '{}': {}", path, e),
+    )
+  })?;
+  let segments = meta["segments"].as_array().cloned().unwrap_or_default();
+  let num_docs = segments
+    .iter()
+    .map(|segment| {
+      let max_doc = segment["max_doc"].as_u64().unwrap_or(0);
+      let num_deleted_docs = segment["deletes"]["num_deleted_docs"].as_u64().unwrap_or(0);
+      max_doc.saturating_sub(num_deleted_docs)
+    })
+    .sum::<u64>();
+
+  let directory = MmapDirectory::open(path).map_err(crate::to_napi_error)?;
+  let mut oldest_version: Option<JsonValue> = None;
+  let mut advice = None;
+  for segment in &segments {
+    let segment_id = segment["segment_id"].as_str().unwrap_or_default();
+    match segment_version(&directory, segment_id) {
+      Ok(version) => {
+        let format_version = version["index_format_version"].as_u64();
+        let oldest_format_version = oldest_version
+          .as_ref()
+          .and_then(|oldest| oldest["index_format_version"].as_u64());
+        if oldest_format_version.is_none() || format_version < oldest_format_version {
+          oldest_version = Some(version);
+        }
+      }
+      Err(e) => {
+        advice = Some(format!(
+          "The segment files can't be read, the index is corrupted or was written \
+           by a version of tantivy older than 0.15: {}. Restore it from a backup, \
+           or reindex its documents with the version that wrote it.",
+          e.reason
+        ));
+        break;
+      }
+    }
+  }
+
+  let index_format_version = oldest_version
+    .as_ref()
+    .and_then(|version| version["index_format_version"].as_u64())
+    .map(|version| version as u32);
+  let tantivy_version = oldest_version.as_ref().map(|version| {
+    format!(
+      "{}.{}.{}",
+      version["major"], version["minor"], version["patch"]
+    )
+  });
+  if advice.is_none() {
+    advice = match index_format_version {
+      Some(version) if version < tv::INDEX_FORMAT_OLDEST_SUPPORTED_VERSION => Some(format!(
+        "The index was written by tantivy {} in index format v{}, older than the oldest \
+         format this binding reads (v{}). Export its stored documents with a version of \
+         the binding using tantivy {}, and add them to a new index.",
+        tantivy_version.as_deref().unwrap_or_default(),
+        version,
+        tv::INDEX_FORMAT_OLDEST_SUPPORTED_VERSION,
+        tantivy_version.as_deref().unwrap_or_default(),
+      )),
+      Some(version) if version > tv::INDEX_FORMAT_VERSION => Some(format!(
+        "The index was written by tantivy {} in index format v{}, newer than the format \
+         of this binding (v{}). Upgrade the binding to open it.",
+        tantivy_version.as_deref().unwrap_or_default(),
+        version,
+        tv::INDEX_FORMAT_VERSION,
+      )),
+      _ => None,
+    };
+  }
+  if advice.is_none() {
+    if let Err(e) = serde_json::from_value::<tv::schema::Schema>(meta["schema"].clone()) {
+      advice = Some(format!(
+        "The schema can't be read by this binding: {}. The index was likely written by \
+         a newer version of tantivy, upgrade the binding to open it.",
+        e
+      ));
+    }
+  }
+
+  Ok(IndexInspection {
+    index_format_version,
+    tantivy_version,
+    schema: meta["schema"].clone(),
+    num_segments: segments.len() as u32,
+    num_docs: num_docs as f64,
+    compatible: advice.is_none(),
+    advice,
+  })
+}
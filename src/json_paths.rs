@@ -0,0 +1,156 @@
+use tantivy as tv;
+use tantivy::query::{Occur, Query, QueryParser, QueryParserError};
+use tantivy::query_grammar::{Delimiter, UserInputAst, UserInputLeaf, UserInputLiteral};
+
+/// A query parser whose terms without field also search paths of JSON fields.
+///
+/// A JSON field among the default fields of a parser only matches the terms
+/// without field at the root of its objects, so those fields are left out of
+/// `parser` and searched through `paths` instead.
+///
+/// The quoted phrases can also be given a default slop and be turned into
+/// phrase prefixes, see `ParseQueryOptions`.
+pub(crate) struct JsonPathsParser {
+  pub(crate) parser: QueryParser,
+  /// Full names of the paths, such as `attrs.color`.
+  pub(crate) paths: Vec<String>,
+  /// Whether `parser` has default fields left.
+  pub(crate) has_default_fields: bool,
+  /// Slop of the quoted phrases without one.
+  pub(crate) phrase_slop: u32,
+  /// Whether the quoted phrases ending with `*` are phrase prefixes.
+  pub(crate) phrase_prefix: bool,
+}
+
+impl JsonPathsParser {
+  pub(crate) fn parse_query(&self, query: &str) -> Result<Box<dyn Query>, QueryParserError> {
+    if self.is_plain() {
+      return self.parser.parse_query(query);
+    }
+    let ast = tv::query_grammar::parse_query(query)
+      .map_err(|_| QueryParserError::SyntaxError(query.to_string()))?;
+    self
+      .parser
+      .build_query_from_user_input_ast(self.expand(ast))
+  }
+
+  pub(crate) fn parse_query_lenient(&self, query: &str) -> (Box<dyn Query>, Vec<QueryParserError>) {
+    if self.is_plain() {
+      return self.parser.parse_query_lenient(query);
+    }
+    let (ast, errors) = tv::query_grammar::parse_query_lenient(query);
+    let mut errors: Vec<QueryParserError> = errors
+      .into_iter()
+      .map(|error| {
+        QueryParserError::SyntaxError(format!("{} at position {}", error.message, error.pos))
+      })
+      .collect();
+    let (query, mut ast_errors) = self
+      .parser
+      .build_query_from_user_input_ast_lenient(self.expand(ast));
+    errors.append(&mut ast_errors);
+    (query, errors)
+  }
+
+  pub(crate) fn expand(&self, ast: UserInputAst) -> UserInputAst {
+    let ast = expand_json_paths(ast, &self.paths, self.has_default_fields);
+    if self.phrase_slop == 0 && !self.phrase_prefix {
+      return ast;
+    }
+    map_literals(ast, &mut |literal| {
+      if literal.delimiter != Delimiter::DoubleQuotes || literal.prefix {
+        return;
+      }
+      if self.phrase_prefix && literal.phrase.ends_with('*') {
+        literal.phrase = literal.phrase.trim_end_matches('*').to_string();
+        literal.prefix = true;
+      } else if literal.slop == 0 {
+        literal.slop = self.phrase_slop;
+      }
+    })
+  }
+
+  /// Whether the parser parses queries the way tantivy does.
+  fn is_plain(&self) -> bool {
+    self.paths.is_empty() && self.phrase_slop == 0 && !self.phrase_prefix
+  }
+}
+
+/// Apply `f` to every term and phrase of a query.
+fn map_literals(ast: UserInputAst, f: &mut impl FnMut(&mut UserInputLiteral)) -> UserInputAst {
+  match ast {
+    UserInputAst::Clause(clauses) => UserInputAst::Clause(
+      clauses
+        .into_iter()
+        .map(|(occur, clause)| (occur, map_literals(clause, f)))
+        .collect(),
+    ),
+    UserInputAst::Boost(ast, boost) => UserInputAst::Boost(Box::new(map_literals(*ast, f)), boost),
+    UserInputAst::Leaf(mut leaf) => {
+      if let UserInputLeaf::Literal(literal) = leaf.as_mut() {
+        f(literal);
+      }
+      UserInputAst::Leaf(leaf)
+    }
+  }
+}
+
+/// Rewrites the terms and phrases of a query that have no field so that
+/// they search paths of JSON fields, e.g. `red` into `(red attrs.color:red)`.
+///
+/// `paths` are full field names, such as `attrs.color`. When
+/// `keep_unqualified` is false, the original leaves are dropped, there being
+/// no other default field to search them on.
+pub(crate) fn expand_json_paths(
+  ast: UserInputAst,
+  paths: &[String],
+  keep_unqualified: bool,
+) -> UserInputAst {
+  match ast {
+    UserInputAst::Clause(clauses) => UserInputAst::Clause(
+      clauses
+        .into_iter()
+        .map(|(occur, clause)| (occur, expand_json_paths(clause, paths, keep_unqualified)))
+        .collect(),
+    ),
+    UserInputAst::Boost(ast, boost) => UserInputAst::Boost(
+      Box::new(expand_json_paths(*ast, paths, keep_unqualified)),
+      boost,
+    ),
+    UserInputAst::Leaf(leaf) => {
+      let mut alternatives: Vec<UserInputAst> = paths
+        .iter()
+        .filter_map(|path| with_field(&leaf, path))
+        .map(|leaf| UserInputAst::Leaf(Box::new(leaf)))
+        .collect();
+      if alternatives.is_empty() {
+        return UserInputAst::Leaf(leaf);
+      }
+      if keep_unqualified {
+        alternatives.insert(0, UserInputAst::Leaf(leaf));
+      }
+      if alternatives.len() == 1 {
+        return alternatives.remove(0);
+      }
+      UserInputAst::Clause(
+        alternatives
+          .into_iter()
+          .map(|alternative| (Some(Occur::Should), alternative))
+          .collect(),
+      )
+    }
+  }
+}
+
+/// The term or phrase searching `field` instead of the default fields, if it has no field.
+fn with_field(leaf: &UserInputLeaf, field: &str) -> Option<UserInputLeaf> {
+  match leaf {
+    UserInputLeaf::Literal(literal) if literal.field_name.is_none() => {
+      Some(UserInputLeaf::Literal(UserInputLiteral {
+        field_name: Some(field.to_string()),
+        ..literal.clone()
+      }))
+    }
+    _ => None,
+  }
+}
@@ -14,6 +14,14 @@ pub(crate) fn to_napi_error(e: impl std::error::Error) -> Error {
   Error::new(Status::GenericFailure, format!("{}", e))
 }
 
+/// 64-bit FNV-1a, whose output doesn't depend on the process, unlike the
+/// hashers of the standard library.
+pub(crate) fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+  bytes.into_iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+    (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+  })
+}
+
 pub(crate) fn get_field(
   schema: &tv::schema::Schema,
   field_name: &str,
@@ -53,11 +61,11 @@ pub(crate) fn make_term_for_type(
       Ok(tv::Term::from_field_text(field, &str_val))
     }
     crate::schema::FieldType::U64 => {
-      let num_val = field_value.coerce_to_number()?.get_uint32()? as u64;
+      let num_val = value_to_u64(field_name, &field_value)?;
       Ok(tv::Term::from_field_u64(field, num_val))
     }
     crate::schema::FieldType::I64 => {
-      let num_val = field_value.coerce_to_number()?.get_int64()?;
+      let num_val = value_to_i64(field_name, &field_value)?;
       Ok(tv::Term::from_field_i64(field, num_val))
     }
     crate::schema::FieldType::F64 => {
@@ -103,17 +111,116 @@ pub(crate) fn make_term_for_type(
   }
 }
 
+/// The largest integer a JS number holds exactly, `Number.MAX_SAFE_INTEGER`.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// An integer given as a JS number, a BigInt or a numeric string.
+enum IntegerValue {
+  Number(f64),
+  BigInt(BigInt),
+  Str(String),
+}
+
+fn integer_value(value: &Unknown) -> Result<IntegerValue> {
+  Ok(match value.get_type()? {
+    ValueType::BigInt => IntegerValue::BigInt(unsafe { value.cast::<BigInt>()? }),
+    ValueType::String => IntegerValue::Str(value.coerce_to_string()?.into_utf8()?.into_owned()?),
+    _ => IntegerValue::Number(value.coerce_to_number()?.get_double()?),
+  })
+}
+
+fn invalid_integer(field_name: &str, value: impl std::fmt::Display, type_name: &str) -> Error {
+  Error::new(
+    Status::InvalidArg,
+    format!(
+      "Value {} of field '{}' is not a valid {} integer. Integers beyond \
+       Number.MAX_SAFE_INTEGER must be given as a BigInt or a string.",
+      value, field_name, type_name
+    ),
+  )
+}
+
+fn is_safe_integer(number: f64) -> bool {
+  number.fract() == 0.0 && number.abs() <= MAX_SAFE_INTEGER
+}
+
+/// Convert a value to an unsigned 64-bit integer, rejecting the values that
+/// do not fit exactly instead of clamping them.
+pub(crate) fn value_to_u64(field_name: &str, value: &Unknown) -> Result<u64> {
+  match integer_value(value)? {
+    IntegerValue::Number(number) if is_safe_integer(number) && number >= 0.0 => Ok(number as u64),
+    IntegerValue::Number(number) => Err(invalid_integer(field_name, number, "u64")),
+    IntegerValue::BigInt(bigint) => match bigint.get_u64() {
+      (false, number, true) => Ok(number),
+      _ => Err(invalid_integer(field_name, "(BigInt)", "u64")),
+    },
+    IntegerValue::Str(text) => text
+      .trim()
+      .parse()
+      .map_err(|_| invalid_integer(field_name, format!("'{}'", text), "u64")),
+  }
+}
+
+/// Convert a value to a signed 64-bit integer, rejecting the values that do
+/// not fit exactly instead of clamping them.
+pub(crate) fn value_to_i64(field_name: &str, value: &Unknown) -> Result<i64> {
+  match integer_value(value)? {
+    IntegerValue::Number(number) if is_safe_integer(number) => Ok(number as i64),
+    IntegerValue::Number(number) => Err(invalid_integer(field_name, number, "i64")),
+    IntegerValue::BigInt(bigint) => match bigint.get_i64() {
+      (number, true) => Ok(number),
+      _ => Err(invalid_integer(field_name, "(BigInt)", "i64")),
+    },
+    IntegerValue::Str(text) => text
+      .trim()
+      .parse()
+      .map_err(|_| invalid_integer(field_name, format!("'{}'", text), "i64")),
+  }
+}
+
+pub mod cache;
+pub mod char_filter;
+mod datemath;
+pub mod dictionary;
 pub mod document;
 pub mod explanation;
 pub mod facet;
+pub mod facet_navigator;
+pub mod fastfield;
+mod fingerprint;
+pub mod fusion;
+pub mod geo;
+pub mod handle;
 pub mod index;
+pub mod inspect;
+mod json_paths;
+pub mod logging;
+pub mod metrics;
+pub mod named;
+pub mod nested;
 pub mod parser_error;
+mod path_hierarchy;
 pub mod query;
+pub mod query_cost;
+pub mod query_debug;
+pub mod query_visitor;
 pub mod schema;
 pub mod schemabuilder;
 pub mod searcher;
+pub mod serialized_writer;
+pub mod session;
 pub mod snippet;
+pub mod span_near;
+mod stemmer;
+mod structured_query;
+pub mod tenant;
+pub mod term;
+pub mod token_filter;
 pub mod tokenizer;
+mod url_email;
+mod user_query;
+pub mod vector;
+pub mod write_stream;
 pub use document::Document;
 pub use facet::Facet;
 pub use index::{Index, IndexWriter};
@@ -129,6 +236,7 @@ pub use schema::{FieldType, Schema};
 pub use schemabuilder::SchemaBuilder;
 pub use searcher::Searcher;
 pub use snippet::{Snippet, SnippetGenerator};
+pub use term::Term;
 pub use tokenizer::{
   Filter, FilterStatic, TextAnalyzer, TextAnalyzerBuilder, Tokenizer, TokenizerStatic,
 };
@@ -0,0 +1,95 @@
+use std::sync::{Mutex, OnceLock};
+
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+/// A log record of Tantivy or of the binding, see `setLogHandler`.
+#[napi(object, object_from_js = false)]
+pub struct LogRecord {
+  /// 'error', 'warn', 'info', 'debug' or 'trace'.
+  pub level: String,
+  /// The module that emitted the record, e.g. `tantivy::indexer::segment_updater`.
+  pub target: String,
+  pub message: String,
+}
+
+/// A JS callback called from any thread. Being weak, it does not keep the
+/// process alive.
+type LogCallback = ThreadsafeFunction<LogRecord, (), LogRecord, Status, false, true>;
+
+/// Forwards the records of the `log` crate, used by Tantivy, to a JS callback.
+struct JsLogger {
+  callback: Mutex<Option<LogCallback>>,
+}
+
+static LOGGER: JsLogger = JsLogger {
+  callback: Mutex::new(None),
+};
+
+impl log::Log for JsLogger {
+  fn enabled(&self, metadata: &log::Metadata) -> bool {
+    metadata.level() <= log::max_level()
+  }
+
+  fn log(&self, record: &log::Record) {
+    if !self.enabled(record.metadata()) {
+      return;
+    }
+    if let Some(callback) = self.callback.lock().unwrap().as_ref() {
+      let record = LogRecord {
+        level: record.level().as_str().to_lowercase(),
+        target: record.target().to_string(),
+        message: record.args().to_string(),
+      };
+      callback.call(record, ThreadsafeFunctionCallMode::NonBlocking);
+    }
+  }
+
+  fn flush(&self) {}
+}
+
+/// Forward the log records of Tantivy, such as merges, garbage collection of
+/// the index files and warnings, to a callback.
+///
+/// The callback is called asynchronously, on the main thread, with the
+/// records emitted by any thread, including the indexing and merging threads.
+/// It does not keep the process alive.
+///
+/// @param callback - Called with each `LogRecord`. If not given, records are
+///         no longer forwarded.
+/// @param level - The most verbose level forwarded: 'error', 'warn', 'info'
+///         (default), 'debug' or 'trace'.
+#[napi]
+pub fn set_log_handler(callback: Option<LogCallback>, level: Option<String>) -> Result<()> {
+  let level = match level.as_deref().unwrap_or("info") {
+    "error" => log::LevelFilter::Error,
+    "warn" => log::LevelFilter::Warn,
+    "info" => log::LevelFilter::Info,
+    "debug" => log::LevelFilter::Debug,
+    "trace" => log::LevelFilter::Trace,
+    _ => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Invalid log level, valid choices are: 'error', 'warn', 'info', 'debug' and 'trace'",
+      ))
+    }
+  };
+
+  static INSTALLED: OnceLock<bool> = OnceLock::new();
+  if !*INSTALLED.get_or_init(|| log::set_logger(&LOGGER).is_ok()) {
+    return Err(Error::new(
+      Status::GenericFailure,
+      "Another logger is already installed in this process",
+    ));
+  }
+
+  let mut current = LOGGER.callback.lock().unwrap();
+  log::set_max_level(if callback.is_some() {
+    level
+  } else {
+    log::LevelFilter::Off
+  });
+  *current = callback;
+  Ok(())
+}
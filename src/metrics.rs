@@ -0,0 +1,181 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use napi_derive::napi;
+use tantivy as tv;
+
+/// Upper bounds, in milliseconds, of the buckets of the search latency histogram.
+const LATENCY_BUCKETS_MS: [f64; 12] = [
+  1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+];
+
+/// Counters of the process, shared by every index and worker thread.
+struct Counters {
+  documents_added: AtomicU64,
+  commits: AtomicU64,
+  merges: AtomicU64,
+  searches: AtomicU64,
+  /// Number of searches per latency bucket, the last one being unbounded.
+  search_latency_buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+  search_latency_sum_us: AtomicU64,
+  doc_store_cache_hits: AtomicU64,
+  doc_store_cache_misses: AtomicU64,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU64 = AtomicU64::new(0);
+
+static COUNTERS: Counters = Counters {
+  documents_added: ZERO,
+  commits: ZERO,
+  merges: ZERO,
+  searches: ZERO,
+  search_latency_buckets: [ZERO; LATENCY_BUCKETS_MS.len() + 1],
+  search_latency_sum_us: ZERO,
+  doc_store_cache_hits: ZERO,
+  doc_store_cache_misses: ZERO,
+};
+
+pub(crate) fn record_documents_added(count: usize) {
+  COUNTERS
+    .documents_added
+    .fetch_add(count as u64, Ordering::Relaxed);
+}
+
+/// Record the documents added by a batch of operations.
+pub(crate) fn record_operations(operations: &[tv::indexer::UserOperation]) {
+  let added = operations
+    .iter()
+    .filter(|operation| matches!(operation, tv::indexer::UserOperation::Add(_)))
+    .count();
+  record_documents_added(added);
+}
+
+pub(crate) fn record_commit() {
+  COUNTERS.commits.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Record a search started at `start`.
+pub(crate) fn record_search(start: Instant) {
+  let elapsed = start.elapsed();
+  let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+  let bucket = LATENCY_BUCKETS_MS.partition_point(|&bound| bound < elapsed_ms);
+  COUNTERS.searches.fetch_add(1, Ordering::Relaxed);
+  COUNTERS.search_latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+  COUNTERS
+    .search_latency_sum_us
+    .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// Record the doc store cache lookups made between two snapshots of the
+/// statistics of a searcher.
+pub(crate) fn record_doc_store_cache(before: tv::store::CacheStats, after: tv::store::CacheStats) {
+  COUNTERS.doc_store_cache_hits.fetch_add(
+    after.cache_hits.saturating_sub(before.cache_hits) as u64,
+    Ordering::Relaxed,
+  );
+  COUNTERS.doc_store_cache_misses.fetch_add(
+    after.cache_misses.saturating_sub(before.cache_misses) as u64,
+    Ordering::Relaxed,
+  );
+}
+
+/// Merge policy counting the merges scheduled by another one.
+#[derive(Debug)]
+pub(crate) struct CountingMergePolicy {
+  pub(crate) inner: std::sync::Arc<dyn tv::indexer::MergePolicy>,
+}
+
+impl tv::indexer::MergePolicy for CountingMergePolicy {
+  fn compute_merge_candidates(
+    &self,
+    segments: &[tv::index::SegmentMeta],
+  ) -> Vec<tv::indexer::MergeCandidate> {
+    let candidates = self.inner.compute_merge_candidates(segments);
+    COUNTERS
+      .merges
+      .fetch_add(candidates.len() as u64, Ordering::Relaxed);
+    candidates
+  }
+}
+
+/// A bucket of the search latency histogram.
+#[napi(object, object_from_js = false)]
+pub struct LatencyBucket {
+  /// Upper bound of the bucket, in milliseconds. The last bucket is unbounded
+  /// (`Infinity`).
+  pub le: f64,
+  /// Number of searches that took at most `le` milliseconds. Like the buckets
+  /// of a Prometheus histogram, the counts are cumulative.
+  pub count: f64,
+}
+
+/// Cumulative counters of the process, see `metrics`.
+#[napi(object, object_from_js = false)]
+pub struct Metrics {
+  /// Number of documents added by the index writers, including the child
+  /// documents of nested documents.
+  pub documents_added: f64,
+  /// Number of commits.
+  pub commits: f64,
+  /// Number of segment merges scheduled by the index writers.
+  pub merges: f64,
+  /// Number of searches executed, including aggregations and knn searches.
+  pub searches: f64,
+  /// Histogram of the search latencies.
+  pub search_latency_buckets: Vec<LatencyBucket>,
+  /// Total time spent searching, in milliseconds.
+  pub search_latency_sum_ms: f64,
+  /// Number of documents fetched from the doc store cache.
+  pub doc_store_cache_hits: f64,
+  /// Number of documents whose block had to be read and decompressed.
+  pub doc_store_cache_misses: f64,
+  /// `docStoreCacheHits / (docStoreCacheHits + docStoreCacheMisses)`, 0 before
+  /// the first document is fetched.
+  pub doc_store_cache_hit_rate: f64,
+}
+
+fn load(counter: &AtomicU64) -> f64 {
+  counter.load(Ordering::Relaxed) as f64
+}
+
+/// Snapshot the counters of the process, to be exported to a monitoring
+/// system such as Prometheus.
+///
+/// The counters are cumulative since the process started, over every index
+/// and worker thread, and never reset.
+///
+/// @returns Metrics object.
+#[napi]
+pub fn metrics() -> Metrics {
+  let mut count = 0.0;
+  let mut search_latency_buckets = Vec::with_capacity(LATENCY_BUCKETS_MS.len() + 1);
+  for (idx, bucket) in COUNTERS.search_latency_buckets.iter().enumerate() {
+    count += load(bucket);
+    search_latency_buckets.push(LatencyBucket {
+      le: LATENCY_BUCKETS_MS
+        .get(idx)
+        .copied()
+        .unwrap_or(f64::INFINITY),
+      count,
+    });
+  }
+  let doc_store_cache_hits = load(&COUNTERS.doc_store_cache_hits);
+  let doc_store_cache_misses = load(&COUNTERS.doc_store_cache_misses);
+  let lookups = doc_store_cache_hits + doc_store_cache_misses;
+  Metrics {
+    documents_added: load(&COUNTERS.documents_added),
+    commits: load(&COUNTERS.commits),
+    merges: load(&COUNTERS.merges),
+    searches: load(&COUNTERS.searches),
+    search_latency_buckets,
+    search_latency_sum_ms: load(&COUNTERS.search_latency_sum_us) / 1000.0,
+    doc_store_cache_hits,
+    doc_store_cache_misses,
+    doc_store_cache_hit_rate: if lookups > 0.0 {
+      doc_store_cache_hits / lookups
+    } else {
+      0.0
+    },
+  }
+}
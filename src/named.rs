@@ -0,0 +1,116 @@
+use tantivy as tv;
+use tantivy::query::{BooleanQuery, EnableScoring, Query, Weight};
+use tantivy::{DocAddress, DocSet, Searcher};
+
+/// A query carrying a name, reported by `Searcher.matchedClauses` when the
+/// query matches a document, like the named queries of Elasticsearch.
+///
+/// The name has no effect on matching nor on scoring.
+#[derive(Debug)]
+pub(crate) struct NamedQuery {
+  name: String,
+  query: Box<dyn Query>,
+}
+
+impl Clone for NamedQuery {
+  fn clone(&self) -> Self {
+    NamedQuery {
+      name: self.name.clone(),
+      query: self.query.box_clone(),
+    }
+  }
+}
+
+impl NamedQuery {
+  pub(crate) fn new(name: String, query: Box<dyn Query>) -> Self {
+    NamedQuery { name, query }
+  }
+
+  pub(crate) fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub(crate) fn query(&self) -> &dyn Query {
+    self.query.as_ref()
+  }
+}
+
+impl Query for NamedQuery {
+  fn weight(&self, enable_scoring: EnableScoring<'_>) -> tv::Result<Box<dyn Weight>> {
+    self.query.weight(enable_scoring)
+  }
+
+  fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a tv::Term, bool)) {
+    self.query.query_terms(visitor)
+  }
+}
+
+/// The named queries found in a query, walking through boolean queries and
+/// named queries, in the order they appear.
+fn named_queries(query: &dyn Query) -> Vec<&NamedQuery> {
+  fn collect<'a>(query: &'a dyn Query, named: &mut Vec<&'a NamedQuery>) {
+    if let Some(named_query) = query.downcast_ref::<NamedQuery>() {
+      named.push(named_query);
+      collect(named_query.query(), named);
+    } else if let Some(boolean_query) = query.downcast_ref::<BooleanQuery>() {
+      for (_, clause) in boolean_query.clauses() {
+        collect(clause.as_ref(), named);
+      }
+    }
+  }
+  let mut named = Vec::new();
+  collect(query, &mut named);
+  named
+}
+
+/// Tells which named queries of a query match a document, their weights
+/// being built once for all the documents checked.
+pub(crate) struct NamedMatcher {
+  weights: Vec<(String, Box<dyn Weight>)>,
+}
+
+impl NamedMatcher {
+  /// A matcher for the named queries of a query, or None if it has none.
+  pub(crate) fn for_query(searcher: &Searcher, query: &dyn Query) -> tv::Result<Option<Self>> {
+    let named = named_queries(query);
+    if named.is_empty() {
+      return Ok(None);
+    }
+    let enable_scoring = EnableScoring::disabled_from_searcher(searcher);
+    let weights = named
+      .into_iter()
+      .map(|named_query| {
+        let weight = named_query.query().weight(enable_scoring)?;
+        Ok((named_query.name().to_string(), weight))
+      })
+      .collect::<tv::Result<Vec<_>>>()?;
+    Ok(Some(NamedMatcher { weights }))
+  }
+
+  /// The names of the named queries matching a document, without duplicates.
+  ///
+  /// A named query reports whether it matches on its own: a named clause of
+  /// a boolean query is reported even if the document is excluded by another
+  /// clause.
+  pub(crate) fn matched_names(
+    &self,
+    searcher: &Searcher,
+    doc_address: DocAddress,
+  ) -> tv::Result<Vec<String>> {
+    let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+    let mut names: Vec<String> = Vec::new();
+    for (name, weight) in &self.weights {
+      if names.contains(name) {
+        continue;
+      }
+      let mut scorer = weight.scorer(segment_reader, 1.0)?;
+      if scorer.doc() < doc_address.doc_id {
+        scorer.seek(doc_address.doc_id);
+      }
+      if scorer.doc() == doc_address.doc_id {
+        names.push(name.clone());
+      }
+    }
+    Ok(names)
+  }
+}
@@ -0,0 +1,394 @@
+use std::fmt;
+use std::sync::Arc;
+
+use napi::{Error, Result, Status};
+use tantivy as tv;
+use tantivy::query::{
+  BooleanQuery, ConstScoreQuery, EnableScoring, Explanation, Occur, Query, RegexQuery, Scorer,
+  TermQuery, Weight,
+};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{DocId, DocSet, Score, SegmentReader, TERMINATED};
+
+use crate::cache::ConstScoreDocSet;
+
+/// Name of the field holding the nested document markers.
+pub(crate) const NESTED_FIELD: &str = "_nested";
+/// Marker value of the parent document of a block.
+pub(crate) const PARENT_MARKER: &str = "_parent";
+
+/// Look up the nested marker field, failing if the schema did not opt in.
+pub(crate) fn nested_field(schema: &tv::schema::Schema) -> Result<Field> {
+  schema.get_field(NESTED_FIELD).map_err(|_| {
+    Error::new(
+      Status::InvalidArg,
+      "Schema does not support nested documents, see SchemaBuilder.enableNestedDocuments()",
+    )
+  })
+}
+
+/// Check that `path` can be used to mark child documents.
+pub(crate) fn check_path(path: &str) -> Result<()> {
+  if path.is_empty() || path == PARENT_MARKER {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Invalid nested path '{}'.", path),
+    ));
+  }
+  Ok(())
+}
+
+/// Query matching the parent documents of all the nested blocks.
+pub(crate) fn parent_query(field: Field) -> TermQuery {
+  TermQuery::new(
+    tv::Term::from_field_text(field, PARENT_MARKER),
+    IndexRecordOption::Basic,
+  )
+}
+
+/// The query deleting the documents matching `query`, along with the
+/// children of the parents among them. Left behind, the children would be
+/// joined to the next parent once their segment is merged. The query is kept
+/// as is if the schema has no nested documents.
+pub(crate) fn deletion_query(schema: &tv::schema::Schema, query: Box<dyn Query>) -> Box<dyn Query> {
+  match schema.get_field(NESTED_FIELD) {
+    Ok(field) => Box::new(WithChildrenQuery { field, query }),
+    Err(_) => query,
+  }
+}
+
+/// The query deleting the documents containing `term`, see `deletion_query`,
+/// or None if a plain term deletion does it.
+pub(crate) fn term_deletion_query(
+  schema: &tv::schema::Schema,
+  term: &tv::Term,
+) -> Option<Box<dyn Query>> {
+  if schema.get_field(NESTED_FIELD).is_err() || !schema.get_field_entry(term.field()).is_indexed() {
+    return None;
+  }
+  let query = TermQuery::new(term.clone(), IndexRecordOption::Basic);
+  Some(deletion_query(schema, Box::new(query)))
+}
+
+/// Delete the documents containing `term`, see `deletion_query`.
+pub(crate) fn delete_term(
+  writer: &tv::IndexWriter,
+  schema: &tv::schema::Schema,
+  term: tv::Term,
+) -> tv::Result<u64> {
+  match term_deletion_query(schema, &term) {
+    Some(query) => writer.delete_query(query),
+    None => Ok(writer.delete_term(term)),
+  }
+}
+
+/// Matches the documents of a query, and the children of the parent
+/// documents among them.
+struct WithChildrenQuery {
+  field: Field,
+  query: Box<dyn Query>,
+}
+
+impl Clone for WithChildrenQuery {
+  fn clone(&self) -> Self {
+    WithChildrenQuery {
+      field: self.field,
+      query: self.query.box_clone(),
+    }
+  }
+}
+
+impl fmt::Debug for WithChildrenQuery {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "WithChildren({:?})", self.query)
+  }
+}
+
+impl Query for WithChildrenQuery {
+  fn weight(&self, enable_scoring: EnableScoring<'_>) -> tv::Result<Box<dyn Weight>> {
+    let enable_scoring = EnableScoring::disabled_from_schema(enable_scoring.schema());
+    // Every document with a nested marker, but the parents, is a child.
+    let child_query = BooleanQuery::new(vec![
+      (
+        Occur::Should,
+        Box::new(RegexQuery::from_pattern(".*", self.field)?),
+      ),
+      (Occur::MustNot, Box::new(parent_query(self.field))),
+    ]);
+    Ok(Box::new(WithChildrenWeight {
+      weight: self.query.weight(enable_scoring)?,
+      parent_weight: parent_query(self.field).weight(enable_scoring)?,
+      child_weight: child_query.weight(enable_scoring)?,
+    }))
+  }
+
+  fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a tv::Term, bool)) {
+    self.query.query_terms(visitor);
+  }
+}
+
+struct WithChildrenWeight {
+  weight: Box<dyn Weight>,
+  parent_weight: Box<dyn Weight>,
+  child_weight: Box<dyn Weight>,
+}
+
+impl Weight for WithChildrenWeight {
+  fn scorer(&self, reader: &SegmentReader, boost: Score) -> tv::Result<Box<dyn Scorer>> {
+    let parents = scorer_docs(self.parent_weight.scorer(reader, 1.0)?);
+    let children = scorer_docs(self.child_weight.scorer(reader, 1.0)?);
+    let mut docs = Vec::new();
+    for doc in scorer_docs(self.weight.scorer(reader, 1.0)?) {
+      let idx = parents.partition_point(|&parent| parent < doc);
+      if parents.get(idx) == Some(&doc) {
+        // The children of a parent come after the previous parent.
+        let start = if idx == 0 { 0 } else { parents[idx - 1] + 1 };
+        let first = children.partition_point(|&child| child < start);
+        let end = children.partition_point(|&child| child < doc);
+        docs.extend_from_slice(&children[first..end]);
+      }
+      docs.push(doc);
+    }
+    docs.sort_unstable();
+    docs.dedup();
+    Ok(Box::new(ConstScoreDocSet::new(Arc::new(docs), boost)))
+  }
+
+  fn explain(&self, reader: &SegmentReader, doc: DocId) -> tv::Result<Explanation> {
+    let mut scorer = self.scorer(reader, 1.0)?;
+    if scorer.seek(doc) != doc {
+      return Err(tv::TantivyError::InvalidArgument(format!(
+        "Document #({doc}) does not match"
+      )));
+    }
+    Ok(Explanation::new("WithChildren", scorer.score()))
+  }
+}
+
+/// The documents of a scorer, in doc id order.
+fn scorer_docs(mut scorer: Box<dyn Scorer>) -> Vec<DocId> {
+  let mut docs = Vec::new();
+  let mut doc = scorer.doc();
+  while doc != TERMINATED {
+    docs.push(doc);
+    doc = scorer.advance();
+  }
+  docs
+}
+
+/// How the scores of the matching children are combined into the parent score.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum NestedScoreMode {
+  Max,
+  Avg,
+  Sum,
+  None,
+}
+
+impl NestedScoreMode {
+  pub(crate) fn parse(score_mode: Option<&str>) -> Result<Self> {
+    match score_mode.unwrap_or("max") {
+      "max" => Ok(NestedScoreMode::Max),
+      "avg" => Ok(NestedScoreMode::Avg),
+      "sum" => Ok(NestedScoreMode::Sum),
+      "none" => Ok(NestedScoreMode::None),
+      _ => Err(Error::new(
+        Status::InvalidArg,
+        "Invalid score mode, valid choices are: 'max', 'avg', 'sum' and 'none'",
+      )),
+    }
+  }
+}
+
+/// Block join query matching the parents of the children matching a query.
+///
+/// Children of a block are indexed right before their parent by
+/// `IndexWriter.addNestedDocument`, in a single batch, so that they end up
+/// adjacent in the same segment. The parent of a child is then the first
+/// parent document following it. Deleting a parent deletes its children
+/// too, so blocks stay adjacent through merges, and tantivy 0.25 has no
+/// index sorting that could reorder them.
+pub(crate) struct NestedQuery {
+  field: Field,
+  path: String,
+  child: Box<dyn Query>,
+  score_mode: NestedScoreMode,
+}
+
+impl NestedQuery {
+  pub(crate) fn new(
+    field: Field,
+    path: String,
+    child: Box<dyn Query>,
+    score_mode: NestedScoreMode,
+  ) -> Self {
+    NestedQuery {
+      field,
+      path,
+      child,
+      score_mode,
+    }
+  }
+
+  pub(crate) fn field(&self) -> Field {
+    self.field
+  }
+
+  pub(crate) fn path(&self) -> &str {
+    &self.path
+  }
+
+  pub(crate) fn child(&self) -> &dyn Query {
+    self.child.as_ref()
+  }
+
+  pub(crate) fn score_mode(&self) -> NestedScoreMode {
+    self.score_mode
+  }
+}
+
+impl Clone for NestedQuery {
+  fn clone(&self) -> Self {
+    NestedQuery {
+      field: self.field,
+      path: self.path.clone(),
+      child: self.child.box_clone(),
+      score_mode: self.score_mode,
+    }
+  }
+}
+
+impl fmt::Debug for NestedQuery {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "Nested(path={:?}, {:?}, {:?})",
+      self.path, self.child, self.score_mode
+    )
+  }
+}
+
+impl Query for NestedQuery {
+  fn weight(&self, enable_scoring: EnableScoring<'_>) -> tv::Result<Box<dyn Weight>> {
+    let parent_weight = parent_query(self.field)
+      .weight(EnableScoring::disabled_from_schema(enable_scoring.schema()))?;
+    // The path term only restricts the matches, it must not alter the scores.
+    let path_query = ConstScoreQuery::new(
+      Box::new(TermQuery::new(
+        tv::Term::from_field_text(self.field, &self.path),
+        IndexRecordOption::Basic,
+      )),
+      0.0,
+    );
+    let child_query = BooleanQuery::new(vec![
+      (Occur::Must, self.child.box_clone()),
+      (Occur::Must, Box::new(path_query)),
+    ]);
+    Ok(Box::new(NestedWeight {
+      parent_weight,
+      child_weight: child_query.weight(enable_scoring)?,
+      score_mode: self.score_mode,
+    }))
+  }
+
+  fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a tv::Term, bool)) {
+    self.child.query_terms(visitor);
+  }
+}
+
+struct NestedWeight {
+  parent_weight: Box<dyn Weight>,
+  child_weight: Box<dyn Weight>,
+  score_mode: NestedScoreMode,
+}
+
+impl Weight for NestedWeight {
+  fn scorer(&self, reader: &SegmentReader, boost: Score) -> tv::Result<Box<dyn Scorer>> {
+    let mut parents = Vec::new();
+    let mut parent_scorer = self.parent_weight.scorer(reader, 1.0)?;
+    let mut doc = parent_scorer.doc();
+    while doc != TERMINATED {
+      parents.push(doc);
+      doc = parent_scorer.advance();
+    }
+
+    // (parent, sum, max, count), in parent order since children are visited
+    // in doc id order.
+    let mut matches: Vec<(DocId, Score, Score, u32)> = Vec::new();
+    let mut child_scorer = self.child_weight.scorer(reader, boost)?;
+    let mut doc = child_scorer.doc();
+    while doc != TERMINATED {
+      if !reader.is_deleted(doc) {
+        let idx = parents.partition_point(|&parent| parent <= doc);
+        if let Some(&parent) = parents.get(idx) {
+          let score = child_scorer.score();
+          match matches.last_mut() {
+            Some(last) if last.0 == parent => {
+              last.1 += score;
+              last.2 = last.2.max(score);
+              last.3 += 1;
+            }
+            _ => matches.push((parent, score, score, 1)),
+          }
+        }
+      }
+      doc = child_scorer.advance();
+    }
+
+    let docs = matches
+      .into_iter()
+      .map(|(parent, sum, max, count)| {
+        let score = match self.score_mode {
+          NestedScoreMode::Max => max,
+          NestedScoreMode::Avg => sum / count as Score,
+          NestedScoreMode::Sum => sum,
+          NestedScoreMode::None => boost,
+        };
+        (parent, score)
+      })
+      .collect();
+    Ok(Box::new(NestedScorer { docs, cursor: 0 }))
+  }
+
+  fn explain(&self, reader: &SegmentReader, doc: DocId) -> tv::Result<Explanation> {
+    let mut scorer = self.scorer(reader, 1.0)?;
+    if scorer.seek(doc) != doc {
+      return Err(tv::TantivyError::InvalidArgument(format!(
+        "Document #({doc}) does not match"
+      )));
+    }
+    Ok(Explanation::new("Nested", scorer.score()))
+  }
+}
+
+/// Scorer over the precomputed parent documents of a segment.
+struct NestedScorer {
+  docs: Vec<(DocId, Score)>,
+  cursor: usize,
+}
+
+impl DocSet for NestedScorer {
+  fn advance(&mut self) -> DocId {
+    if self.cursor < self.docs.len() {
+      self.cursor += 1;
+    }
+    self.doc()
+  }
+
+  fn doc(&self) -> DocId {
+    self
+      .docs
+      .get(self.cursor)
+      .map_or(TERMINATED, |(doc, _)| *doc)
+  }
+
+  fn size_hint(&self) -> u32 {
+    self.docs.len() as u32
+  }
+}
+
+impl Scorer for NestedScorer {
+  fn score(&mut self) -> Score {
+    self.docs.get(self.cursor).map_or(0.0, |(_, score)| *score)
+  }
+}
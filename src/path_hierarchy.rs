@@ -0,0 +1,46 @@
+use tantivy::tokenizer::{Token, Tokenizer};
+
+use crate::token_filter::VecTokenStream;
+
+/// Tokenizer emitting every ancestor of a path along with the path itself.
+///
+/// `/usr/local/bin` gives `/usr`, `/usr/local` and `/usr/local/bin`, all at
+/// the same position, so that a term query on a directory matches every path
+/// under it. Empty segments, such as the one of a leading delimiter, do not
+/// end a token.
+#[derive(Clone)]
+pub(crate) struct PathHierarchyTokenizer {
+  delimiter: String,
+}
+
+impl PathHierarchyTokenizer {
+  pub(crate) fn new(delimiter: String) -> Self {
+    PathHierarchyTokenizer { delimiter }
+  }
+}
+
+impl Tokenizer for PathHierarchyTokenizer {
+  type TokenStream<'a> = VecTokenStream;
+
+  fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+    let mut tokens = Vec::new();
+    let prefix = |end: usize| Token {
+      offset_from: 0,
+      offset_to: end,
+      position: 0,
+      text: text[..end].to_string(),
+      position_length: 1,
+    };
+    let mut segment_start = 0;
+    for (idx, _) in text.match_indices(self.delimiter.as_str()) {
+      if idx > segment_start {
+        tokens.push(prefix(idx));
+      }
+      segment_start = idx + self.delimiter.len();
+    }
+    if segment_start < text.len() {
+      tokens.push(prefix(text.len()));
+    }
+    VecTokenStream::new(tokens)
+  }
+}
@@ -1,11 +1,23 @@
 use crate::{
-  explanation::Explanation, get_field, make_term, make_term_for_type, schema::FieldType,
-  searcher::DocAddress, to_napi_error, Schema,
+  explanation::Explanation,
+  geo::{GeoBoundingBox, GeoPoint},
+  get_field, handle,
+  index::Index,
+  make_term, make_term_for_type,
+  named::NamedQuery,
+  query_cost::{CostEstimator, QueryCost},
+  query_visitor::QueryLeaf,
+  schema::FieldType,
+  searcher::DocAddress,
+  span_near::SpanNearQuery,
+  term::Term,
+  to_napi_error, Schema,
 };
 use core::ops::Bound as OpsBound;
 use napi::bindgen_prelude::*;
 use napi::{Error, Result, Status};
 use napi_derive::napi;
+use std::collections::HashMap;
 use tantivy as tv;
 
 /// Represents a Tantivy Occur type for BooleanQuery
@@ -26,6 +38,265 @@ impl From<Occur> for tv::query::Occur {
   }
 }
 
+/// Wrap a query in a BoostQuery, if a boost is given.
+pub(crate) fn boosted(
+  query: Box<dyn tv::query::Query>,
+  boost: Option<f64>,
+) -> Box<dyn tv::query::Query> {
+  match boost {
+    Some(boost) => Box::new(BoostedQuery::new(query, boost as f32)),
+    None => query,
+  }
+}
+
+pub(crate) fn disjunction_max(
+  subqueries: Vec<Box<dyn tv::query::Query>>,
+  tie_breaker: Option<f64>,
+) -> Query {
+  let dismax_query = DisjunctionQuery::new(subqueries, tie_breaker.unwrap_or(0.0) as f32);
+  Query {
+    inner: Box::new(dismax_query),
+  }
+}
+
+/// Options of `Query.regexQuery`
+#[napi(object)]
+pub struct RegexQueryOptions {
+  /// Match letters regardless of their case (default: false)
+  pub case_insensitive: Option<bool>,
+  /// Let `.` match line breaks too (default: false)
+  pub dot_all: Option<bool>,
+  /// Maximum size, in bytes, of the automaton running the query, each of its
+  /// states taking 4 KiB on 64-bit platforms. Larger patterns are rejected,
+  /// use it when the pattern comes from user input. Tantivy always rejects
+  /// patterns compiling to more than 10 MiB or 1000 automaton states.
+  pub size_limit: Option<u32>,
+}
+
+/// Options of `Query.phraseFromText`
+#[napi(object)]
+pub struct PhraseFromTextOptions {
+  /// The number of gaps permitted between the words of the phrase (default: 0)
+  pub slop: Option<u32>,
+}
+
+/// Options of `Query.rangeQuery`
+#[napi(object)]
+pub struct RangeQueryOptions {
+  /// Match the documents by scanning the fast field (true) or by walking the
+  /// terms of the inverted index (false). By default, the fast field is used
+  /// if the field has one. Scanning the fast field is best for broad ranges,
+  /// the inverted index for selective ones.
+  pub use_fast_field: Option<bool>,
+}
+
+/// A range query run on the fast field or on the inverted index of its
+/// field, rather than on the one tantivy picks.
+#[derive(Clone, Debug)]
+pub(crate) struct HintedRangeQuery {
+  range: tv::query::RangeQuery,
+  use_fast_field: bool,
+  lower_bound: OpsBound<tv::Term>,
+  upper_bound: OpsBound<tv::Term>,
+}
+
+impl HintedRangeQuery {
+  pub(crate) fn use_fast_field(&self) -> bool {
+    self.use_fast_field
+  }
+
+  pub(crate) fn bounds(&self) -> (&OpsBound<tv::Term>, &OpsBound<tv::Term>) {
+    (&self.lower_bound, &self.upper_bound)
+  }
+}
+
+impl tv::query::Query for HintedRangeQuery {
+  fn weight(
+    &self,
+    enable_scoring: tv::query::EnableScoring<'_>,
+  ) -> tv::Result<Box<dyn tv::query::Weight>> {
+    let (lower_bound, upper_bound) = (self.lower_bound.clone(), self.upper_bound.clone());
+    if self.use_fast_field {
+      tv::query::FastFieldRangeQuery::new(lower_bound, upper_bound).weight(enable_scoring)
+    } else {
+      tv::query::InvertedIndexRangeQuery::new(lower_bound, upper_bound).weight(enable_scoring)
+    }
+  }
+}
+
+/// The range query a query is, whether it has an execution hint or not.
+pub(crate) fn as_range_query(query: &dyn tv::query::Query) -> Option<&tv::query::RangeQuery> {
+  match query.downcast_ref::<HintedRangeQuery>() {
+    Some(hinted) => Some(&hinted.range),
+    None => query.downcast_ref::<tv::query::RangeQuery>(),
+  }
+}
+
+/// A BoostQuery whose boost and query can be read back, which tantivy's
+/// keeps private.
+pub(crate) struct BoostedQuery {
+  query: Box<dyn tv::query::Query>,
+  boost: f32,
+}
+
+impl BoostedQuery {
+  pub(crate) fn new(query: Box<dyn tv::query::Query>, boost: f32) -> Self {
+    BoostedQuery { query, boost }
+  }
+
+  pub(crate) fn query(&self) -> &dyn tv::query::Query {
+    self.query.as_ref()
+  }
+
+  pub(crate) fn boost(&self) -> f32 {
+    self.boost
+  }
+
+  fn to_tantivy(&self) -> tv::query::BoostQuery {
+    tv::query::BoostQuery::new(self.query.box_clone(), self.boost)
+  }
+}
+
+impl Clone for BoostedQuery {
+  fn clone(&self) -> Self {
+    BoostedQuery::new(self.query.box_clone(), self.boost)
+  }
+}
+
+impl std::fmt::Debug for BoostedQuery {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    std::fmt::Debug::fmt(&self.to_tantivy(), f)
+  }
+}
+
+impl tv::query::Query for BoostedQuery {
+  fn weight(
+    &self,
+    enable_scoring: tv::query::EnableScoring<'_>,
+  ) -> tv::Result<Box<dyn tv::query::Weight>> {
+    self.to_tantivy().weight(enable_scoring)
+  }
+
+  fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a tv::Term, bool)) {
+    self.query.query_terms(visitor)
+  }
+}
+
+/// A ConstScoreQuery whose score and query can be read back, which
+/// tantivy's keeps private.
+pub(crate) struct ConstScoredQuery {
+  query: Box<dyn tv::query::Query>,
+  score: f32,
+}
+
+impl ConstScoredQuery {
+  pub(crate) fn new(query: Box<dyn tv::query::Query>, score: f32) -> Self {
+    ConstScoredQuery { query, score }
+  }
+
+  pub(crate) fn query(&self) -> &dyn tv::query::Query {
+    self.query.as_ref()
+  }
+
+  pub(crate) fn score(&self) -> f32 {
+    self.score
+  }
+
+  fn to_tantivy(&self) -> tv::query::ConstScoreQuery {
+    tv::query::ConstScoreQuery::new(self.query.box_clone(), self.score)
+  }
+}
+
+impl Clone for ConstScoredQuery {
+  fn clone(&self) -> Self {
+    ConstScoredQuery::new(self.query.box_clone(), self.score)
+  }
+}
+
+impl std::fmt::Debug for ConstScoredQuery {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    std::fmt::Debug::fmt(&self.to_tantivy(), f)
+  }
+}
+
+impl tv::query::Query for ConstScoredQuery {
+  fn weight(
+    &self,
+    enable_scoring: tv::query::EnableScoring<'_>,
+  ) -> tv::Result<Box<dyn tv::query::Weight>> {
+    self.to_tantivy().weight(enable_scoring)
+  }
+
+  fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a tv::Term, bool)) {
+    self.query.query_terms(visitor)
+  }
+}
+
+/// A DisjunctionMaxQuery whose disjuncts and tie breaker can be read back,
+/// which tantivy's keeps private.
+pub(crate) struct DisjunctionQuery {
+  disjuncts: Vec<Box<dyn tv::query::Query>>,
+  tie_breaker: f32,
+}
+
+impl DisjunctionQuery {
+  pub(crate) fn new(disjuncts: Vec<Box<dyn tv::query::Query>>, tie_breaker: f32) -> Self {
+    DisjunctionQuery {
+      disjuncts,
+      tie_breaker,
+    }
+  }
+
+  pub(crate) fn disjuncts(&self) -> &[Box<dyn tv::query::Query>] {
+    &self.disjuncts
+  }
+
+  pub(crate) fn tie_breaker(&self) -> f32 {
+    self.tie_breaker
+  }
+
+  fn to_tantivy(&self) -> tv::query::DisjunctionMaxQuery {
+    let disjuncts = self
+      .disjuncts
+      .iter()
+      .map(|query| query.box_clone())
+      .collect();
+    tv::query::DisjunctionMaxQuery::with_tie_breaker(disjuncts, self.tie_breaker)
+  }
+}
+
+impl Clone for DisjunctionQuery {
+  fn clone(&self) -> Self {
+    let disjuncts = self
+      .disjuncts
+      .iter()
+      .map(|query| query.box_clone())
+      .collect();
+    DisjunctionQuery::new(disjuncts, self.tie_breaker)
+  }
+}
+
+impl std::fmt::Debug for DisjunctionQuery {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    std::fmt::Debug::fmt(&self.to_tantivy(), f)
+  }
+}
+
+impl tv::query::Query for DisjunctionQuery {
+  fn weight(
+    &self,
+    enable_scoring: tv::query::EnableScoring<'_>,
+  ) -> tv::Result<Box<dyn tv::query::Weight>> {
+    self.to_tantivy().weight(enable_scoring)
+  }
+
+  fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a tv::Term, bool)) {
+    for disjunct in &self.disjuncts {
+      disjunct.query_terms(visitor);
+    }
+  }
+}
+
 /// Tantivy's Query
 #[napi]
 pub struct Query {
@@ -42,7 +313,7 @@ impl Clone for Query {
 
 impl Query {
   pub(crate) fn get(&self) -> &dyn tv::query::Query {
-    &self.inner
+    self.inner.as_ref()
   }
 }
 
@@ -54,16 +325,55 @@ impl Query {
     format!("Query({:?})", self.get())
   }
 
+  /// A stable hash of the query tree, e.g. to key a cache of results or to
+  /// count the most popular queries.
+  ///
+  /// Equivalent queries have the same fingerprint: the clauses of a boolean
+  /// query, the subqueries of a disjunction max query and the terms of a term
+  /// set query may come in any order, at any depth of boosted, constant
+  /// score or named queries. Fields are identified by their position in the
+  /// schema, so the fingerprint is only meaningful among the queries of an
+  /// index. Regex, fuzzy and phrase queries, and the boosts of parsed
+  /// queries, are hashed through their description, which may change with
+  /// the version of tantivy.
+  ///
+  /// @returns 16 hexadecimal digits.
+  #[napi]
+  pub fn fingerprint(&self) -> String {
+    format!("{:016x}", crate::fingerprint::fingerprint(self.get()))
+  }
+
+  /// Register the query to be shared with the worker threads, see
+  /// `Index.toHandle`.
+  ///
+  /// @returns A handle to pass to `Query.fromHandle` in another worker.
+  #[napi]
+  pub fn to_handle(&self) -> u32 {
+    handle::register(handle::Shared::Query(self.inner.box_clone()))
+  }
+
+  /// Create a query from a handle returned by `Query.toHandle`.
+  #[napi(factory)]
+  pub fn from_handle(handle: u32) -> Result<Query> {
+    let inner = handle::resolve(handle, "Query", |shared| match shared {
+      handle::Shared::Query(query) => Some(query.box_clone()),
+      _ => None,
+    })?;
+    Ok(Query { inner })
+  }
+
   /// Construct a Tantivy's TermQuery
   #[napi(factory)]
   pub fn term_query(
     schema: &Schema,
     field_name: String,
-    field_value: Unknown,
+    field_value: Either<&Term, Unknown>,
     index_option: Option<String>,
   ) -> Result<Query> {
     let index_option = index_option.unwrap_or_else(|| "position".to_string());
-    let term = make_term(&schema.inner, &field_name, field_value)?;
+    let term = Term::resolve(&schema.inner, &field_name, field_value, |value| {
+      make_term(&schema.inner, &field_name, value)
+    })?;
     let index_option = match index_option.as_str() {
       "position" => tv::schema::IndexRecordOption::WithFreqsAndPositions,
       "freq" => tv::schema::IndexRecordOption::WithFreqs,
@@ -86,11 +396,15 @@ impl Query {
   pub fn term_set_query(
     schema: &Schema,
     field_name: String,
-    field_values: Vec<Unknown>,
+    field_values: Vec<Either<&Term, Unknown>>,
   ) -> Result<Query> {
     let terms = field_values
       .into_iter()
-      .map(|field_value| make_term(&schema.inner, &field_name, field_value))
+      .map(|field_value| {
+        Term::resolve(&schema.inner, &field_name, field_value, |value| {
+          make_term(&schema.inner, &field_name, value)
+        })
+      })
       .collect::<Result<Vec<_>>>()?;
     let inner = tv::query::TermSetQuery::new(terms);
     Ok(Query {
@@ -188,6 +502,7 @@ impl Query {
     slop: Option<u32>,
   ) -> Result<Query> {
     let slop = slop.unwrap_or(0);
+    check_positions(&schema.inner, &field_name)?;
     let mut terms_with_offset = Vec::with_capacity(words.len());
     for (idx, word) in words.into_iter().enumerate() {
       // For now, we'll use the list index as the offset since napi-rs
@@ -207,14 +522,148 @@ impl Query {
     })
   }
 
+  /// Construct a Tantivy's PhraseQuery from text analyzed like the field
+  ///
+  /// The text is run through the tokenizer of the field, and each term is placed
+  /// at the position of its token, so that the words dropped by the tokenizer,
+  /// such as stop words, leave the same gaps as in the indexed documents. Text
+  /// yielding a single term gives a TermQuery.
+  ///
+  /// # Arguments
+  ///
+  /// * `schema` - Schema of the target index.
+  /// * `field_name` - Text field to be searched. It must be indexed with positions.
+  /// * `text` - Text of the phrase.
+  /// * `options` - (Optional) Slop of the phrase.
+  /// * `index` - (Optional) Index providing the tokenizers, needed when the field uses
+  ///   one registered by `Index.registerTokenizer`. Only the built-in ones are known otherwise.
+  #[napi(factory)]
+  pub fn phrase_from_text(
+    schema: &Schema,
+    field_name: String,
+    text: String,
+    options: Option<PhraseFromTextOptions>,
+    index: Option<&Index>,
+  ) -> Result<Query> {
+    let slop = options.and_then(|options| options.slop).unwrap_or(0);
+    let field = get_field(&schema.inner, &field_name)?;
+    let indexing = match schema.inner.get_field_entry(field).field_type() {
+      tv::schema::FieldType::Str(options) => options.get_indexing_options(),
+      _ => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Field '{}' is not a text field.", field_name),
+        ))
+      }
+    };
+    let Some(indexing) = indexing else {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Field '{}' is not indexed.", field_name),
+      ));
+    };
+
+    let tokenizers = match index {
+      Some(index) => index.index.tokenizers().clone(),
+      None => {
+        let tokenizers = tv::tokenizer::TokenizerManager::default();
+        Index::register_custom_text_analyzers(&tokenizers);
+        tokenizers
+      }
+    };
+    let mut analyzer = tokenizers.get(indexing.tokenizer()).ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!(
+          "Tokenizer '{}' of field '{}' is not registered.",
+          indexing.tokenizer(),
+          field_name
+        ),
+      )
+    })?;
+    let mut terms_with_offset = Vec::new();
+    let mut token_stream = analyzer.token_stream(&text);
+    token_stream.process(&mut |token| {
+      terms_with_offset.push((
+        token.position,
+        tv::Term::from_field_text(field, &token.text),
+      ));
+    });
+
+    match terms_with_offset.len() {
+      0 => Err(Error::new(
+        Status::InvalidArg,
+        format!("Text '{}' has no terms for field '{}'.", text, field_name),
+      )),
+      1 => {
+        let (_, term) = terms_with_offset.remove(0);
+        Ok(Query {
+          inner: Box::new(tv::query::TermQuery::new(
+            term,
+            tv::schema::IndexRecordOption::WithFreqs,
+          )),
+        })
+      }
+      _ => {
+        check_positions(&schema.inner, &field_name)?;
+        let inner = tv::query::PhraseQuery::new_with_offset_and_slop(terms_with_offset, slop);
+        Ok(Query {
+          inner: Box::new(inner),
+        })
+      }
+    }
+  }
+
+  /// Construct an ordered proximity query
+  ///
+  /// Matches the documents where the words appear in the given order, with at most
+  /// `slop` other words between the first and the last one. Unlike the slop of a
+  /// PhraseQuery, which lets the words swap places, the order is always kept:
+  /// `['patent', 'claim']` with a slop of 2 matches "patent for a claim" but not
+  /// "claim a patent".
+  ///
+  /// # Arguments
+  ///
+  /// * `schema` - Schema of the target index.
+  /// * `field_name` - Field name to be searched. It must be indexed with positions.
+  /// * `words` - Words that must appear in order.
+  /// * `slop` - (Optional) The number of other words permitted within the span. Default is 0.
+  #[napi(factory)]
+  pub fn span_near_query(
+    schema: &Schema,
+    field_name: String,
+    words: Vec<String>,
+    slop: Option<u32>,
+  ) -> Result<Query> {
+    if words.is_empty() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "words must not be empty.".to_string(),
+      ));
+    }
+    let field = get_field(&schema.inner, &field_name)?;
+    check_positions(&schema.inner, &field_name)?;
+    let terms = words
+      .iter()
+      .map(|word| tv::Term::from_field_text(field, word))
+      .collect();
+    Ok(Query {
+      inner: Box::new(SpanNearQuery::new(field, terms, slop.unwrap_or(0))),
+    })
+  }
+
   /// Construct a Tantivy's BooleanQuery
+  ///
+  /// Every subquery is given as `{ occur, query, name? }`. A clause with a
+  /// name is reported in `SearchHit.matchedQueries` and by
+  /// `Searcher.matchedClauses` when it matches, see `Query.named`.
   #[napi(factory)]
   pub fn boolean_query(subqueries: Vec<Object>) -> Result<Query> {
     let mut dyn_subqueries = Vec::new();
 
     for subquery_obj in subqueries {
       // Extract the occur and query from the object
-      // Expected format: { occur: Occur, query: Query }
+      // Expected format: { occur: Occur, query: Query, name?: string }
       let occur_value: Unknown = subquery_obj
         .get("occur")?
         .ok_or_else(|| Error::new(Status::InvalidArg, "Missing 'occur' field in subquery"))?;
@@ -236,7 +685,12 @@ impl Query {
         }
       };
 
-      dyn_subqueries.push((occur, query_value.inner.box_clone()));
+      let name: Option<String> = subquery_obj.get("name")?;
+      let subquery = match name {
+        Some(name) => query_value.named(name).inner,
+        None => query_value.inner.box_clone(),
+      };
+      dyn_subqueries.push((occur, subquery));
     }
 
     let inner = tv::query::BooleanQuery::from(dyn_subqueries);
@@ -247,45 +701,202 @@ impl Query {
   }
 
   /// Construct a Tantivy's DisjunctionMaxQuery
+  ///
+  /// @param subqueries - Queries, or `{ query: Query, boost?: number }`
+  ///         objects boosting their query.
+  /// @param tieBreaker - Weight of the scores of the subqueries other than the
+  ///         best matching one. Defaults to 0.
   #[napi(factory)]
-  pub fn disjunction_max_query(subqueries: Vec<&Query>, tie_breaker: Option<f64>) -> Result<Query> {
-    let inner_queries: Vec<Box<dyn tv::query::Query>> = subqueries
-      .iter()
-      .map(|query| query.inner.box_clone())
-      .collect();
+  pub fn disjunction_max_query(
+    subqueries: Vec<Either<ClassInstance<Query>, Object>>,
+    tie_breaker: Option<f64>,
+  ) -> Result<Query> {
+    let mut inner_queries: Vec<Box<dyn tv::query::Query>> = Vec::with_capacity(subqueries.len());
+    for subquery in subqueries {
+      let inner = match subquery {
+        Either::A(query) => query.inner.box_clone(),
+        Either::B(subquery_obj) => {
+          // Expected format: { query: Query, boost?: number }
+          let query: ClassInstance<Query> = subquery_obj
+            .get("query")?
+            .ok_or_else(|| Error::new(Status::InvalidArg, "Missing 'query' field in subquery"))?;
+          boosted(query.inner.box_clone(), subquery_obj.get("boost")?)
+        }
+      };
+      inner_queries.push(inner);
+    }
+    Ok(disjunction_max(inner_queries, tie_breaker))
+  }
 
-    let dismax_query = if let Some(tie_breaker) = tie_breaker {
-      tv::query::DisjunctionMaxQuery::with_tie_breaker(inner_queries, tie_breaker as f32)
+  /// Construct a Tantivy's BoostQuery
+  #[napi(factory)]
+  pub fn boost_query(query: &Query, boost: f64) -> Result<Query> {
+    let inner = BoostedQuery::new(query.inner.box_clone(), boost as f32);
+    Ok(Query {
+      inner: Box::new(inner),
+    })
+  }
+
+  /// Construct a Tantivy's RegexQuery
+  ///
+  /// # Arguments
+  ///
+  /// * `schema` - Schema of the target index.
+  /// * `field_name` - Field to be searched.
+  /// * `regex_pattern` - Regular expression that the whole term must match.
+  /// * `options` - Flags of the pattern and limit of its size.
+  #[napi(factory)]
+  pub fn regex_query(
+    schema: &Schema,
+    field_name: String,
+    regex_pattern: String,
+    options: Option<RegexQueryOptions>,
+  ) -> Result<Query> {
+    let field = get_field(&schema.inner, &field_name)?;
+
+    let mut flags = String::new();
+    if let Some(options) = &options {
+      if options.case_insensitive.unwrap_or(false) {
+        flags.push('i');
+      }
+      if options.dot_all.unwrap_or(false) {
+        flags.push('s');
+      }
+    }
+    let regex_pattern = if flags.is_empty() {
+      regex_pattern
     } else {
-      tv::query::DisjunctionMaxQuery::new(inner_queries)
+      format!("(?{}){}", flags, regex_pattern)
     };
 
+    let regex = tantivy_fst::Regex::new(&regex_pattern).map_err(|e| {
+      to_napi_error(tv::TantivyError::InvalidArgument(format!(
+        "RegexQueryError: {}",
+        e
+      )))
+    })?;
+    if let Some(size_limit) = options.and_then(|options| options.size_limit) {
+      let size = automaton_size(&regex);
+      if size > size_limit as usize {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!(
+            "The pattern compiles to an automaton of {} bytes, more than the limit of {} bytes.",
+            size, size_limit
+          ),
+        ));
+      }
+    }
     Ok(Query {
-      inner: Box::new(dismax_query),
+      inner: Box::new(tv::query::RegexQuery::from_regex(regex, field)),
     })
   }
 
-  /// Construct a Tantivy's BoostQuery
+  /// Construct a query matching the geo points inside a bounding box
+  ///
+  /// # Arguments
+  ///
+  /// * `schema` - Schema of the target index.
+  /// * `field_name` - Geo point field to be searched.
+  /// * `bounding_box` - The box, in degrees. It crosses the antimeridian if `left` is
+  ///   greater than `right`.
   #[napi(factory)]
-  pub fn boost_query(query: &Query, boost: f64) -> Result<Query> {
-    let inner = tv::query::BoostQuery::new(query.inner.box_clone(), boost as f32);
+  pub fn geo_bounding_box_query(
+    schema: &Schema,
+    field_name: String,
+    bounding_box: GeoBoundingBox,
+  ) -> Result<Query> {
+    let inner = crate::geo::bounding_box_query(&schema.inner, &field_name, &bounding_box)?;
+    Ok(Query { inner })
+  }
+
+  /// Construct a query matching the geo points within a distance of a point
+  ///
+  /// # Arguments
+  ///
+  /// * `schema` - Schema of the target index.
+  /// * `field_name` - Geo point field to be searched.
+  /// * `center` - The point the distance is computed from.
+  /// * `radius` - The maximum distance, in meters.
+  ///
+  /// A document with several points matches if any of them is within the radius.
+  #[napi(factory)]
+  pub fn geo_distance_query(
+    schema: &Schema,
+    field_name: String,
+    center: GeoPoint,
+    radius: f64,
+  ) -> Result<Query> {
+    let inner = crate::geo::distance_query(&schema.inner, &field_name, &center, radius)?;
+    Ok(Query { inner })
+  }
+
+  /// Construct a block join query over nested documents
+  ///
+  /// Matches the parent documents having at least one child, under the given
+  /// path, that matches `child_query`. The children must have been indexed with
+  /// `IndexWriter.addNestedDocument`.
+  ///
+  /// # Arguments
+  ///
+  /// * `schema` - Schema of the target index, with nested documents enabled.
+  /// * `path` - Nested path of the children.
+  /// * `child_query` - Query the children are matched against.
+  /// * `score_mode` - (Optional) How the scores of the matching children are combined:
+  ///   'max', 'avg', 'sum' or 'none'. Default is 'max'.
+  #[napi(factory)]
+  pub fn nested_query(
+    schema: &Schema,
+    path: String,
+    child_query: &Query,
+    score_mode: Option<String>,
+  ) -> Result<Query> {
+    crate::nested::check_path(&path)?;
+    let field = crate::nested::nested_field(&schema.inner)?;
+    let score_mode = crate::nested::NestedScoreMode::parse(score_mode.as_deref())?;
+    let inner =
+      crate::nested::NestedQuery::new(field, path, child_query.inner.box_clone(), score_mode);
     Ok(Query {
       inner: Box::new(inner),
     })
   }
 
-  /// Construct a Tantivy's RegexQuery
+  /// Construct a query matching all the parent documents of nested blocks
+  ///
+  /// Child documents are regular documents of the index, combine other queries
+  /// with this one to leave them out of the results.
   #[napi(factory)]
-  pub fn regex_query(schema: &Schema, field_name: String, regex_pattern: String) -> Result<Query> {
-    let field = get_field(&schema.inner, &field_name)?;
+  pub fn nested_parent_query(schema: &Schema) -> Result<Query> {
+    let field = crate::nested::nested_field(&schema.inner)?;
+    Ok(Query {
+      inner: Box::new(crate::nested::parent_query(field)),
+    })
+  }
 
-    let inner_result = tv::query::RegexQuery::from_pattern(&regex_pattern, field);
-    match inner_result {
-      Ok(inner) => Ok(Query {
-        inner: Box::new(inner),
-      }),
-      Err(e) => Err(to_napi_error(e)),
+  /// Construct a query matching all terms starting with the given prefix
+  ///
+  /// The prefix is matched literally, regex metacharacters are escaped.
+  /// Unlike a prefix `FuzzyTermQuery`, no edit distance is applied.
+  ///
+  /// # Arguments
+  ///
+  /// * `schema` - Schema of the target index.
+  /// * `field_name` - Field name to be searched.
+  /// * `prefix` - Prefix of the indexed terms to match. Must not be empty.
+  #[napi(factory)]
+  pub fn prefix_query(schema: &Schema, field_name: String, prefix: String) -> Result<Query> {
+    if prefix.is_empty() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "prefix must not be empty.".to_string(),
+      ));
     }
+    let field = get_field(&schema.inner, &field_name)?;
+    let pattern = format!("{}.*", escape_regex(&prefix));
+    let inner = tv::query::RegexQuery::from_pattern(&pattern, field).map_err(to_napi_error)?;
+    Ok(Query {
+      inner: Box::new(inner),
+    })
   }
 
   #[napi(factory)]
@@ -336,21 +947,23 @@ impl Query {
   /// Construct a Tantivy's ConstScoreQuery
   #[napi(factory)]
   pub fn const_score_query(query: &Query, score: f64) -> Result<Query> {
-    let inner = tv::query::ConstScoreQuery::new(query.inner.box_clone(), score as f32);
+    let inner = ConstScoredQuery::new(query.inner.box_clone(), score as f32);
     Ok(Query {
       inner: Box::new(inner),
     })
   }
 
   #[napi(factory)]
+  #[allow(clippy::too_many_arguments)]
   pub fn range_query(
     schema: &Schema,
     field_name: String,
     field_type: FieldType,
-    lower_bound: Unknown,
-    upper_bound: Unknown,
+    lower_bound: Either<&Term, Unknown>,
+    upper_bound: Either<&Term, Unknown>,
     include_lower: Option<bool>,
     include_upper: Option<bool>,
+    options: Option<RangeQueryOptions>,
   ) -> Result<Query> {
     let include_lower = include_lower.unwrap_or(true);
     let include_upper = include_upper.unwrap_or(true);
@@ -406,10 +1019,13 @@ impl Query {
       ));
     }
 
-    let lower_bound_term =
-      make_term_for_type(&schema.inner, &field_name, field_type.clone(), lower_bound)?;
-    let upper_bound_term =
-      make_term_for_type(&schema.inner, &field_name, field_type.clone(), upper_bound)?;
+    let make_bound_term = |bound| {
+      Term::resolve(&schema.inner, &field_name, bound, |value| {
+        make_term_for_type(&schema.inner, &field_name, field_type.clone(), value)
+      })
+    };
+    let lower_bound_term = make_bound_term(lower_bound)?;
+    let upper_bound_term = make_bound_term(upper_bound)?;
 
     let lower_bound = if include_lower {
       OpsBound::Included(lower_bound_term)
@@ -423,11 +1039,33 @@ impl Query {
       OpsBound::Excluded(upper_bound_term)
     };
 
-    let inner = tv::query::RangeQuery::new(lower_bound, upper_bound);
+    let range = tv::query::RangeQuery::new(lower_bound.clone(), upper_bound.clone());
+    let use_fast_field = options.and_then(|options| options.use_fast_field);
+    let inner: Box<dyn tv::query::Query> = match use_fast_field {
+      None => Box::new(range),
+      Some(use_fast_field) => {
+        if use_fast_field && !actual_field_entry.is_fast() {
+          return Err(Error::new(
+            Status::InvalidArg,
+            format!("Field '{}' is not a fast field.", field_name),
+          ));
+        }
+        if !use_fast_field && !actual_field_entry.is_indexed() {
+          return Err(Error::new(
+            Status::InvalidArg,
+            format!("Field '{}' is not indexed.", field_name),
+          ));
+        }
+        Box::new(HintedRangeQuery {
+          range,
+          use_fast_field,
+          lower_bound,
+          upper_bound,
+        })
+      }
+    };
 
-    Ok(Query {
-      inner: Box::new(inner),
-    })
+    Ok(Query { inner })
   }
 
   /// Construct a Tantivy's PhrasePrefixQuery
@@ -455,6 +1093,7 @@ impl Query {
       ));
     }
     let field = get_field(&schema.inner, &field_name)?;
+    check_positions(&schema.inner, &field_name)?;
     let terms: Vec<tv::Term> = words
       .iter()
       .map(|w| tv::Term::from_field_text(field, w))
@@ -468,6 +1107,33 @@ impl Query {
     })
   }
 
+  /// Construct a query from its description as data, e.g. received as JSON
+  /// from a client, sparing the injection concerns of query strings.
+  ///
+  /// Every query is an object with a single key naming its kind:
+  /// - `{ bool: { must?, should?, mustNot?, filter? } }`, lists of queries,
+  ///   the `filter` ones being required without contributing to the score.
+  /// - `{ term: { field: value } }` and `{ terms: { field: [values] } }`, the
+  ///   text values not being tokenized.
+  /// - `{ range: { field: { gt?, gte?, lt?, lte? } } }` on numeric, date and
+  ///   IP address fields.
+  /// - `{ prefix: { field: prefix } }`, `{ exists: { field } }`, `{ all: {} }`
+  ///   and `{ boost: { query, boost } }`.
+  ///
+  /// Dates are seconds timestamps, like in `Query.termQuery`, or RFC 3339
+  /// strings. Integers beyond `Number.MAX_SAFE_INTEGER` are given as strings.
+  ///
+  /// # Arguments
+  ///
+  /// * `schema` - Schema of the target index.
+  /// * `object` - The description of the query.
+  #[napi(factory)]
+  pub fn from_object(schema: &Schema, object: serde_json::Value) -> Result<Query> {
+    Ok(Query {
+      inner: crate::structured_query::build(&schema.inner, object)?,
+    })
+  }
+
   /// Construct a Tantivy's RegexPhraseQuery
   ///
   /// Matches a specific sequence of regex patterns in positional order, with optional slop.
@@ -496,6 +1162,7 @@ impl Query {
       ));
     }
     let field = get_field(&schema.inner, &field_name)?;
+    check_positions(&schema.inner, &field_name)?;
     let terms_with_offset: Vec<(usize, String)> = patterns.into_iter().enumerate().collect();
     let mut inner = tv::query::RegexPhraseQuery::new_with_offset(field, terms_with_offset);
     if let Some(s) = slop {
@@ -509,6 +1176,70 @@ impl Query {
     })
   }
 
+  /// Apply per-field boosts to this query.
+  ///
+  /// Boolean queries are walked recursively and every sub-query targeting a
+  /// single field listed in `boosts` is wrapped in a BoostQuery, like
+  /// `Index.parseQuery` does with its `fieldBoosts` argument.
+  ///
+  /// # Arguments
+  ///
+  /// * `schema` - Schema of the target index.
+  /// * `boosts` - Boost factor per field name.
+  #[napi]
+  pub fn with_field_boosts(&self, schema: &Schema, boosts: HashMap<String, f64>) -> Result<Query> {
+    let mut field_boosts = HashMap::with_capacity(boosts.len());
+    for (field_name, boost) in boosts {
+      let field = get_field(&schema.inner, &field_name)?;
+      field_boosts.insert(field, boost as tv::Score);
+    }
+    Ok(Query {
+      inner: apply_field_boosts(self.get(), &field_boosts),
+    })
+  }
+
+  /// Walk the leaves of this query, e.g. to expand synonyms or enforce a
+  /// policy on a query parsed from user input.
+  ///
+  /// Boolean and named queries are walked into, any other query is a leaf,
+  /// including a boosted boolean query. The callback may return a query
+  /// replacing the leaf, which keeps the boost of the leaf.
+  ///
+  /// # Arguments
+  ///
+  /// * `schema` - Schema of the target index, naming the fields of the leaves.
+  /// * `callback` - Function receiving each leaf, see `QueryLeaf`, and
+  ///   returning the query replacing it, or nothing to keep it.
+  ///
+  /// Returns the query with its leaves replaced.
+  #[napi]
+  pub fn visit<'env>(
+    &self,
+    schema: &Schema,
+    callback: Function<'env, QueryLeaf, Option<ClassInstance<'env, Query>>>,
+  ) -> Result<Query> {
+    let inner = crate::query_visitor::visit(&schema.inner, self.get(), None, &mut |leaf| {
+      Ok(callback.call(leaf)?.map(|query| query.inner.box_clone()))
+    })?;
+    Ok(Query { inner })
+  }
+
+  /// Give a name to this query, reported by `Searcher.matchedClauses` for
+  /// the documents it matches.
+  ///
+  /// The name has no effect on matching nor on scoring, so named queries can
+  /// be used as clauses of a boolean query to tell which of them matched.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - Name of the query.
+  #[napi]
+  pub fn named(&self, name: String) -> Query {
+    Query {
+      inner: Box::new(NamedQuery::new(name, self.inner.box_clone())),
+    }
+  }
+
   /// Explain how this query matches a given document.
   ///
   /// This method provides detailed information about how the document matched the query
@@ -533,4 +1264,140 @@ impl Query {
       .map_err(to_napi_error)?;
     Ok(Explanation::new(explanation))
   }
+
+  /// Estimate how many documents this query matches, without executing it.
+  ///
+  /// The estimate is computed per clause from the document frequencies of
+  /// the terms, so applications can reject or rewrite expensive queries,
+  /// such as huge lists of alternatives or regexes, before searching.
+  ///
+  /// # Arguments
+  /// * `searcher` - The searcher whose term statistics are used
+  ///
+  /// # Returns
+  /// * `QueryCost` - The estimated cost of the query and of its clauses
+  #[napi]
+  pub fn estimate_cost(&self, searcher: &crate::searcher::Searcher) -> Result<QueryCost> {
+    let estimator = CostEstimator {
+      searcher: &searcher.inner,
+    };
+    estimator.estimate(self.get()).map_err(to_napi_error)
+  }
+}
+
+/// Escape the characters that have a special meaning in a tantivy regex.
+pub(crate) fn escape_regex(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    if matches!(
+      c,
+      '\\'
+        | '.'
+        | '+'
+        | '*'
+        | '?'
+        | '('
+        | ')'
+        | '|'
+        | '['
+        | ']'
+        | '{'
+        | '}'
+        | '^'
+        | '$'
+        | '#'
+        | '&'
+        | '-'
+        | '~'
+    ) {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+/// Rebuild `query`, boosting the sub-queries that target a single boosted field.
+fn apply_field_boosts(
+  query: &dyn tv::query::Query,
+  boosts: &HashMap<tv::schema::Field, tv::Score>,
+) -> Box<dyn tv::query::Query> {
+  if let Some(boolean_query) = query.downcast_ref::<tv::query::BooleanQuery>() {
+    let clauses = boolean_query
+      .clauses()
+      .iter()
+      .map(|(occur, subquery)| (*occur, apply_field_boosts(subquery.as_ref(), boosts)))
+      .collect();
+    return Box::new(tv::query::BooleanQuery::with_minimum_required_clauses(
+      clauses,
+      boolean_query.get_minimum_number_should_match(),
+    ));
+  }
+  if let Some(named_query) = query.downcast_ref::<NamedQuery>() {
+    let inner = apply_field_boosts(named_query.query(), boosts);
+    return Box::new(NamedQuery::new(named_query.name().to_string(), inner));
+  }
+  match query_field(query).and_then(|field| boosts.get(&field)) {
+    Some(&boost) => Box::new(BoostedQuery::new(query.box_clone(), boost)),
+    None => query.box_clone(),
+  }
+}
+
+/// Fail on a phrase against a field without positions when building the
+/// query, like `Index.parseQuery` does, rather than when searching.
+fn check_positions(schema: &tv::schema::Schema, field_name: &str) -> Result<()> {
+  let field = get_field(schema, field_name)?;
+  if !crate::schema::has_positions(schema.get_field_entry(field)) {
+    return Err(to_napi_error(
+      tv::query::QueryParserError::FieldDoesNotHavePositionsIndexed(field_name.to_string()),
+    ));
+  }
+  Ok(())
+}
+
+/// The field a query targets, if it targets a single one.
+pub(crate) fn query_field(query: &dyn tv::query::Query) -> Option<tv::schema::Field> {
+  if let Some(term_query) = query.downcast_ref::<tv::query::TermQuery>() {
+    return Some(term_query.term().field());
+  }
+  if let Some(phrase_query) = query.downcast_ref::<tv::query::PhraseQuery>() {
+    return Some(phrase_query.field());
+  }
+  if let Some(phrase_prefix_query) = query.downcast_ref::<tv::query::PhrasePrefixQuery>() {
+    return Some(phrase_prefix_query.field());
+  }
+  if let Some(regex_phrase_query) = query.downcast_ref::<tv::query::RegexPhraseQuery>() {
+    return Some(regex_phrase_query.field());
+  }
+  if let Some(range_query) = as_range_query(query) {
+    return Some(range_query.field());
+  }
+  let mut fields = Vec::new();
+  query.query_terms(&mut |term, _| fields.push(term.field()));
+  fields.sort_unstable();
+  fields.dedup();
+  match fields.as_slice() {
+    [field] => Some(*field),
+    _ => None,
+  }
+}
+
+/// The size, in bytes, of the transitions of the states of a regex automaton,
+/// see `RegexQueryOptions.sizeLimit`.
+fn automaton_size(regex: &tantivy_fst::Regex) -> usize {
+  use tantivy_fst::Automaton;
+
+  let mut states: std::collections::HashSet<usize> = regex.start().into_iter().collect();
+  let mut pending = vec![regex.start()];
+  while let Some(state) = pending.pop() {
+    for byte in 0..=u8::MAX {
+      if let Some(next) = regex.accept(&state, byte) {
+        if states.insert(next) {
+          pending.push(Some(next));
+        }
+      }
+    }
+  }
+  // Every state has a transition for each byte.
+  states.len() * std::mem::size_of::<[Option<usize>; 256]>()
 }
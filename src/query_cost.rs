@@ -0,0 +1,172 @@
+use napi_derive::napi;
+use tantivy as tv;
+use tantivy::query::{
+  AllQuery, BooleanQuery, EmptyQuery, ExistsQuery, FuzzyTermQuery, Occur, PhrasePrefixQuery,
+  PhraseQuery, Query, RegexPhraseQuery, RegexQuery, TermQuery, TermSetQuery,
+};
+
+use crate::named::NamedQuery;
+use crate::query::as_range_query;
+use crate::span_near::SpanNearQuery;
+
+/// Estimated cost of a query, see `Query.estimateCost`.
+#[napi(object, object_from_js = false)]
+#[derive(Clone)]
+pub struct QueryCost {
+  /// Kind of the query: 'bool', 'term', 'term_set', 'phrase', 'phrase_prefix',
+  /// 'span_near', 'regex', 'regex_phrase', 'fuzzy', 'range', 'exists', 'all', 'empty' or 'other'.
+  pub kind: String,
+  /// Occurrence of the query in its parent boolean query: 'must', 'should' or 'must_not'.
+  pub occur: Option<String>,
+  /// The field the query targets, if it targets a single one.
+  pub field: Option<String>,
+  /// Number of terms whose document frequency was looked up.
+  pub terms: u32,
+  /// Approximate number of matching documents. It is an upper bound, and the
+  /// queries walking the term dictionary (regex, fuzzy, range, exists...) are
+  /// estimated at the number of documents of the index.
+  pub estimated_docs: u32,
+  /// Costs of the clauses of a boolean query.
+  pub clauses: Option<Vec<QueryCost>>,
+}
+
+/// Estimates the cost of queries from the document frequencies of their terms.
+pub(crate) struct CostEstimator<'a> {
+  pub(crate) searcher: &'a tv::Searcher,
+}
+
+impl CostEstimator<'_> {
+  pub(crate) fn estimate(&self, query: &dyn Query) -> tv::Result<QueryCost> {
+    let num_docs = self.num_docs();
+    if let Some(boolean_query) = query.downcast_ref::<BooleanQuery>() {
+      let mut clauses = Vec::with_capacity(boolean_query.clauses().len());
+      for (occur, clause) in boolean_query.clauses() {
+        let mut cost = self.estimate(clause.as_ref())?;
+        cost.occur = Some(occur_name(*occur).to_string());
+        clauses.push((*occur, cost));
+      }
+      let must = clauses
+        .iter()
+        .filter(|(occur, _)| *occur == Occur::Must)
+        .map(|(_, cost)| cost.estimated_docs)
+        .min();
+      let should = clauses
+        .iter()
+        .filter(|(occur, _)| *occur == Occur::Should)
+        .map(|(_, cost)| cost.estimated_docs as u64)
+        .reduce(|a, b| a + b);
+      let estimated_docs = match (must, should) {
+        (Some(must), _) => must,
+        (None, Some(should)) => should.min(num_docs as u64) as u32,
+        // Queries only excluding documents match nothing.
+        (None, None) => 0,
+      };
+      return Ok(QueryCost {
+        kind: "bool".to_string(),
+        occur: None,
+        field: None,
+        terms: clauses.iter().map(|(_, cost)| cost.terms).sum(),
+        estimated_docs,
+        clauses: Some(clauses.into_iter().map(|(_, cost)| cost).collect()),
+      });
+    }
+    if let Some(named_query) = query.downcast_ref::<NamedQuery>() {
+      return self.estimate(named_query.query());
+    }
+    if let Some(term_query) = query.downcast_ref::<TermQuery>() {
+      let estimated_docs = self.doc_freq(term_query.term())?;
+      return Ok(self.leaf("term", query, 1, estimated_docs));
+    }
+    if query.downcast_ref::<TermSetQuery>().is_some() {
+      return self.terms_cost("term_set", query);
+    }
+    if let Some(phrase_query) = query.downcast_ref::<PhraseQuery>() {
+      return self.phrase_cost("phrase", query, &phrase_query.phrase_terms());
+    }
+    if let Some(phrase_prefix_query) = query.downcast_ref::<PhrasePrefixQuery>() {
+      return self.phrase_cost("phrase_prefix", query, &phrase_prefix_query.phrase_terms());
+    }
+    if let Some(span_near_query) = query.downcast_ref::<SpanNearQuery>() {
+      return self.phrase_cost("span_near", query, span_near_query.terms());
+    }
+    if query.downcast_ref::<EmptyQuery>().is_some() {
+      return Ok(self.leaf("empty", query, 0, 0));
+    }
+    let kind = if query.downcast_ref::<RegexQuery>().is_some() {
+      "regex"
+    } else if query.downcast_ref::<RegexPhraseQuery>().is_some() {
+      "regex_phrase"
+    } else if query.downcast_ref::<FuzzyTermQuery>().is_some() {
+      "fuzzy"
+    } else if as_range_query(query).is_some() {
+      "range"
+    } else if query.downcast_ref::<ExistsQuery>().is_some() {
+      "exists"
+    } else if query.downcast_ref::<AllQuery>().is_some() {
+      "all"
+    } else {
+      // Boosts, constant scores, disjunctions... hide their sub-queries, but
+      // report their terms.
+      return self.terms_cost("other", query);
+    };
+    Ok(self.leaf(kind, query, 0, num_docs))
+  }
+
+  /// Cost of a query matching the documents containing any of its terms.
+  fn terms_cost(&self, kind: &str, query: &dyn Query) -> tv::Result<QueryCost> {
+    let mut terms = Vec::new();
+    query.query_terms(&mut |term, _| terms.push(term.clone()));
+    if terms.is_empty() {
+      return Ok(self.leaf(kind, query, 0, self.num_docs()));
+    }
+    let mut estimated_docs = 0u64;
+    for term in &terms {
+      estimated_docs += self.doc_freq(term)? as u64;
+    }
+    let estimated_docs = estimated_docs.min(self.num_docs() as u64) as u32;
+    Ok(self.leaf(kind, query, terms.len() as u32, estimated_docs))
+  }
+
+  /// Cost of a query matching the documents containing all of its terms.
+  fn phrase_cost(
+    &self,
+    kind: &str,
+    query: &dyn Query,
+    terms: &[tv::Term],
+  ) -> tv::Result<QueryCost> {
+    let mut estimated_docs = self.num_docs();
+    for term in terms {
+      estimated_docs = estimated_docs.min(self.doc_freq(term)?);
+    }
+    Ok(self.leaf(kind, query, terms.len() as u32, estimated_docs))
+  }
+
+  fn leaf(&self, kind: &str, query: &dyn Query, terms: u32, estimated_docs: u32) -> QueryCost {
+    let field = crate::query::query_field(query)
+      .map(|field| self.searcher.schema().get_field_name(field).to_string());
+    QueryCost {
+      kind: kind.to_string(),
+      occur: None,
+      field,
+      terms,
+      estimated_docs,
+      clauses: None,
+    }
+  }
+
+  fn doc_freq(&self, term: &tv::Term) -> tv::Result<u32> {
+    Ok(self.searcher.doc_freq(term)?.min(u32::MAX as u64) as u32)
+  }
+
+  fn num_docs(&self) -> u32 {
+    self.searcher.num_docs().min(u32::MAX as u64) as u32
+  }
+}
+
+pub(crate) fn occur_name(occur: Occur) -> &'static str {
+  match occur {
+    Occur::Must => "must",
+    Occur::Should => "should",
+    Occur::MustNot => "must_not",
+  }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use napi_derive::napi;
+use serde_json::{json, Value as JsonValue};
+use tantivy as tv;
+use tantivy::query::{
+  AllQuery, BooleanQuery, EmptyQuery, PhrasePrefixQuery, PhraseQuery, Query, QueryParser,
+  QueryParserError, RangeQuery, TermQuery, TermSetQuery,
+};
+use tantivy::query_grammar::{UserInputAst, UserInputBound, UserInputLeaf};
+use tantivy::schema::Type;
+
+/// Options of `Index.debugQuery`, with the meaning they have in `Index.parseQuery`.
+#[napi(object, object_to_js = false)]
+#[derive(Clone, Default)]
+pub struct DebugQueryOptions {
+  pub default_field_names: Option<Vec<String>>,
+  pub field_boosts: Option<HashMap<String, f64>>,
+  pub fuzzy_fields: Option<HashMap<String, (bool, u8, bool)>>,
+  pub now: Option<i64>,
+  pub json_paths: Option<HashMap<String, Vec<String>>>,
+}
+
+/// Describes the queries produced by a query parser as JSON trees.
+///
+/// The parser must not have field boosts nor fuzzy fields: those wrap the
+/// leaves in queries that cannot be looked into, they are reported from
+/// `field_boosts` and `fuzzy_fields` instead.
+pub(crate) struct QueryDebugger<'a> {
+  pub(crate) parser: &'a QueryParser,
+  pub(crate) schema: &'a tv::schema::Schema,
+  pub(crate) field_boosts: &'a HashMap<String, f64>,
+  pub(crate) fuzzy_fields: &'a HashMap<String, (bool, u8, bool)>,
+}
+
+impl QueryDebugger<'_> {
+  pub(crate) fn describe(&self, ast: UserInputAst) -> Result<JsonValue, QueryParserError> {
+    Ok(match ast {
+      UserInputAst::Clause(clauses) => {
+        let clauses = clauses
+          .into_iter()
+          .map(|(occur, clause)| Ok(json!({ "occur": occur, "query": self.describe(clause)? })))
+          .collect::<Result<Vec<_>, QueryParserError>>()?;
+        json!({ "type": "bool", "clauses": clauses })
+      }
+      UserInputAst::Boost(ast, boost) => {
+        json!({ "type": "boost", "boost": boost, "query": self.describe(*ast)? })
+      }
+      UserInputAst::Leaf(leaf) => match *leaf {
+        UserInputLeaf::All => json!({ "type": "all" }),
+        UserInputLeaf::Exists { field } => json!({ "type": "exists", "field": field }),
+        UserInputLeaf::Range {
+          field,
+          lower,
+          upper,
+        } => {
+          let leaf = UserInputLeaf::Range {
+            field,
+            lower: lower.clone(),
+            upper: upper.clone(),
+          };
+          let query = self
+            .parser
+            .build_query_from_user_input_ast(UserInputAst::Leaf(Box::new(leaf)))?;
+          let mut description = self.describe_query(query.as_ref());
+          add_range_bounds(&mut description, &bound_json(&lower), &bound_json(&upper));
+          description
+        }
+        leaf => {
+          let query = self
+            .parser
+            .build_query_from_user_input_ast(UserInputAst::Leaf(Box::new(leaf)))?;
+          self.describe_query(query.as_ref())
+        }
+      },
+    })
+  }
+
+  /// Describe a query built from a single leaf of the user input.
+  fn describe_query(&self, query: &dyn Query) -> JsonValue {
+    if let Some(boolean_query) = query.downcast_ref::<BooleanQuery>() {
+      let clauses: Vec<JsonValue> = boolean_query
+        .clauses()
+        .iter()
+        .map(|(occur, clause)| json!({ "occur": occur, "query": self.describe_query(clause.as_ref()) }))
+        .collect();
+      return json!({ "type": "bool", "clauses": clauses });
+    }
+    if let Some(term_query) = query.downcast_ref::<TermQuery>() {
+      let term = term_query.term();
+      let field_name = self.schema.get_field_name(term.field());
+      let description = match self.fuzzy_fields.get(field_name) {
+        Some((prefix, distance, transposition_cost_one)) => json!({
+          "type": "fuzzy",
+          "field": field_name,
+          "term": term_value(term),
+          "distance": distance,
+          "prefix": prefix,
+          "transposition_cost_one": transposition_cost_one,
+        }),
+        None => json!({ "type": "term", "field": field_name, "term": term_value(term) }),
+      };
+      return self.with_field_boost(description, term.field());
+    }
+    if let Some(phrase_query) = query.downcast_ref::<PhraseQuery>() {
+      return self.describe_phrase("phrase", phrase_query.field(), &phrase_query.phrase_terms());
+    }
+    if let Some(phrase_prefix_query) = query.downcast_ref::<PhrasePrefixQuery>() {
+      return self.describe_phrase(
+        "phrase_prefix",
+        phrase_prefix_query.field(),
+        &phrase_prefix_query.phrase_terms(),
+      );
+    }
+    if let Some(range_query) = query.downcast_ref::<RangeQuery>() {
+      let field_name = self.schema.get_field_name(range_query.field());
+      return self.with_field_boost(
+        json!({ "type": "range", "field": field_name }),
+        range_query.field(),
+      );
+    }
+    if query.downcast_ref::<TermSetQuery>().is_some() {
+      let mut terms = Vec::new();
+      let mut field = None;
+      query.query_terms(&mut |term, _| {
+        field = Some(term.field());
+        terms.push(term_value(term));
+      });
+      let field_name = field.map(|field| self.schema.get_field_name(field));
+      return json!({ "type": "term_set", "field": field_name, "terms": terms });
+    }
+    if query.downcast_ref::<AllQuery>().is_some() {
+      return json!({ "type": "all" });
+    }
+    if query.downcast_ref::<EmptyQuery>().is_some() {
+      return json!({ "type": "empty" });
+    }
+    json!({ "type": "other", "debug": format!("{query:?}") })
+  }
+
+  fn describe_phrase(&self, kind: &str, field: tv::schema::Field, terms: &[tv::Term]) -> JsonValue {
+    let terms: Vec<JsonValue> = terms.iter().map(term_value).collect();
+    self.with_field_boost(
+      json!({ "type": kind, "field": self.schema.get_field_name(field), "terms": terms }),
+      field,
+    )
+  }
+
+  fn with_field_boost(&self, mut description: JsonValue, field: tv::schema::Field) -> JsonValue {
+    if let Some(boost) = self.field_boosts.get(self.schema.get_field_name(field)) {
+      description["boost"] = json!(boost);
+    }
+    description
+  }
+}
+
+fn bound_json(bound: &UserInputBound) -> JsonValue {
+  match bound {
+    UserInputBound::Inclusive(value) => json!({ "value": value, "inclusive": true }),
+    UserInputBound::Exclusive(value) => json!({ "value": value, "inclusive": false }),
+    UserInputBound::Unbounded => JsonValue::Null,
+  }
+}
+
+/// Add the bounds given by the user to the range queries of a description.
+fn add_range_bounds(description: &mut JsonValue, lower: &JsonValue, upper: &JsonValue) {
+  if description["type"] == "range" {
+    description["lower"] = lower.clone();
+    description["upper"] = upper.clone();
+  } else if let Some(clauses) = description["clauses"].as_array_mut() {
+    for clause in clauses {
+      add_range_bounds(&mut clause["query"], lower, upper);
+    }
+  }
+}
+
+/// The value of a term, as it was analyzed.
+pub(crate) fn term_value(term: &tv::Term) -> JsonValue {
+  let value = term.value();
+  match value.typ() {
+    Type::Str => json!(value.as_str()),
+    Type::U64 => json!(value.as_u64()),
+    Type::I64 => json!(value.as_i64()),
+    Type::F64 => json!(value.as_f64()),
+    Type::Bool => json!(value.as_bool()),
+    Type::Date => json!(value.as_date().map(|date| date.into_timestamp_millis())),
+    Type::Facet => json!(value.as_facet().map(|facet| facet.to_string())),
+    Type::IpAddr => json!(value.as_ip_addr().map(|addr| match addr.to_ipv4_mapped() {
+      Some(ipv4) => ipv4.to_string(),
+      None => addr.to_string(),
+    })),
+    Type::Bytes => json!(value.as_bytes()),
+    Type::Json => json!(format!("{:?}", term)),
+  }
+}
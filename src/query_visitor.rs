@@ -0,0 +1,124 @@
+use napi::Result;
+use napi_derive::napi;
+use serde_json::Value as JsonValue;
+use tantivy::query::{BooleanQuery, Occur, Query};
+use tantivy::schema::Schema;
+
+use crate::named::NamedQuery;
+use crate::query::BoostedQuery;
+
+/// A leaf of a query tree, see `Query.visit`.
+#[napi(object, object_from_js = false)]
+pub struct QueryLeaf {
+  /// Kind of the query: 'term', 'term_set', 'phrase', 'phrase_prefix',
+  /// 'span_near', 'regex', 'regex_phrase', 'fuzzy', 'range', 'exists', 'all',
+  /// 'empty', 'bool' for a boosted boolean query, or 'other'.
+  pub kind: String,
+  /// Occurrence of the leaf in its parent boolean query: 'must', 'should' or 'must_not'.
+  pub occur: Option<String>,
+  /// The field the query targets, if it targets a single one.
+  pub field: Option<String>,
+  /// The terms searched, as they were analyzed.
+  pub terms: Vec<JsonValue>,
+  /// The boost of the leaf, 1 if it is not boosted.
+  pub boost: f64,
+}
+
+/// The kinds of the leaves, by the name of their query type.
+const LEAF_KINDS: &[(&str, &str)] = &[
+  ("TermQuery", "term"),
+  ("TermSetQuery", "term_set"),
+  ("PhraseQuery", "phrase"),
+  ("PhrasePrefixQuery", "phrase_prefix"),
+  ("SpanNearQuery", "span_near"),
+  ("RegexQuery", "regex"),
+  ("RegexPhraseQuery", "regex_phrase"),
+  ("FuzzyTermQuery", "fuzzy"),
+  ("RangeQuery", "range"),
+  ("HintedRangeQuery", "range"),
+  ("ExistsQuery", "exists"),
+  ("AllQuery", "all"),
+  ("EmptyQuery", "empty"),
+  ("BooleanQuery", "bool"),
+];
+
+/// Rebuild a query, replacing each of its leaves by the query `rewrite`
+/// returns for it, if any.
+///
+/// Boolean and named queries are walked into, any other query is a leaf. A
+/// replacement is given the boost of the leaf it replaces.
+pub(crate) fn visit(
+  schema: &Schema,
+  query: &dyn Query,
+  occur: Option<Occur>,
+  rewrite: &mut dyn FnMut(QueryLeaf) -> Result<Option<Box<dyn Query>>>,
+) -> Result<Box<dyn Query>> {
+  if let Some(boolean_query) = query.downcast_ref::<BooleanQuery>() {
+    let clauses = boolean_query
+      .clauses()
+      .iter()
+      .map(|(occur, subquery)| {
+        Ok((
+          *occur,
+          visit(schema, subquery.as_ref(), Some(*occur), rewrite)?,
+        ))
+      })
+      .collect::<Result<Vec<_>>>()?;
+    return Ok(Box::new(BooleanQuery::with_minimum_required_clauses(
+      clauses,
+      boolean_query.get_minimum_number_should_match(),
+    )));
+  }
+  if let Some(named_query) = query.downcast_ref::<NamedQuery>() {
+    let inner = visit(schema, named_query.query(), occur, rewrite)?;
+    return Ok(Box::new(NamedQuery::new(
+      named_query.name().to_string(),
+      inner,
+    )));
+  }
+
+  let (kind, boost) = kind_and_boost(query);
+  let mut terms = Vec::new();
+  query.query_terms(&mut |term, _| terms.push(crate::query_debug::term_value(term)));
+  let leaf = QueryLeaf {
+    kind: kind.to_string(),
+    occur: occur.map(|occur| crate::query_cost::occur_name(occur).to_string()),
+    field: crate::query::query_field(query).map(|field| schema.get_field_name(field).to_string()),
+    terms,
+    boost: boost as f64,
+  };
+  Ok(match rewrite(leaf)? {
+    Some(replacement) if boost != 1.0 => Box::new(BoostedQuery::new(replacement, boost)),
+    Some(replacement) => replacement,
+    None => query.box_clone(),
+  })
+}
+
+/// The kind of a leaf, and the boost applied to it.
+///
+/// A `BoostQuery` keeps its boost and the query it wraps private, so both
+/// are read from its debug output, `Boost(query=TermQuery(..), boost=2)`.
+fn kind_and_boost(query: &dyn Query) -> (&'static str, f32) {
+  let debug = format!("{query:?}");
+  let mut inner = debug.as_str();
+  let mut boost = 1.0;
+  while let Some(rest) = inner.strip_prefix("Boost(query=") {
+    let Some((wrapped, factor)) = rest
+      .strip_suffix(')')
+      .and_then(|rest| rest.rsplit_once(", boost="))
+    else {
+      break;
+    };
+    boost *= factor.parse::<f32>().unwrap_or(1.0);
+    inner = wrapped;
+  }
+  let type_name = inner
+    .split(|c: char| !c.is_alphanumeric())
+    .next()
+    .unwrap_or_default();
+  let kind = LEAF_KINDS
+    .iter()
+    .find(|(name, _)| *name == type_name)
+    .map_or("other", |(_, kind)| kind);
+  (kind, boost)
+}
@@ -1,5 +1,8 @@
 use napi_derive::napi;
+use serde::Serialize;
 use serde_json;
+
+use crate::handle;
 use tantivy as tv;
 use tantivy::schema::Schema as TantivySchema;
 
@@ -53,6 +56,114 @@ impl FieldType {
   }
 }
 
+/// Differences between two schemas, see `Schema.diff`.
+#[napi(object, object_from_js = false)]
+#[derive(Clone, Serialize)]
+pub struct SchemaDiff {
+  /// Names of the fields only in the new schema.
+  pub added: Vec<String>,
+  /// Names of the fields only in the old schema.
+  pub removed: Vec<String>,
+  /// Fields in both schemas whose type or options differ.
+  pub changed: Vec<FieldChange>,
+}
+
+/// A field whose type or options differ between two schemas.
+#[napi(object, object_from_js = false)]
+#[derive(Clone, Serialize)]
+pub struct FieldChange {
+  pub name: String,
+  /// The differing options, as paths such as `stored` or `indexing.tokenizer`,
+  /// or `type` when the type of the field changed.
+  pub options: Vec<String>,
+  /// The field in the old schema, in the format of `Schema.toJSON`.
+  pub before: serde_json::Value,
+  /// The field in the new schema, in the format of `Schema.toJSON`.
+  pub after: serde_json::Value,
+}
+
+impl SchemaDiff {
+  pub(crate) fn between(before: &TantivySchema, after: &TantivySchema) -> Self {
+    let mut diff = SchemaDiff {
+      added: Vec::new(),
+      removed: Vec::new(),
+      changed: Vec::new(),
+    };
+    for (_, entry) in before.fields() {
+      let Ok(field) = after.get_field(entry.name()) else {
+        diff.removed.push(entry.name().to_string());
+        continue;
+      };
+      let before = serde_json::to_value(entry).unwrap_or_default();
+      let after = serde_json::to_value(after.get_field_entry(field)).unwrap_or_default();
+      let mut options = Vec::new();
+      if before["type"] != after["type"] {
+        options.push("type".to_string());
+      } else {
+        diff_options(&before["options"], &after["options"], "", &mut options);
+      }
+      if !options.is_empty() {
+        diff.changed.push(FieldChange {
+          name: entry.name().to_string(),
+          options,
+          before,
+          after,
+        });
+      }
+    }
+    for (_, entry) in after.fields() {
+      if before.get_field(entry.name()).is_err() {
+        diff.added.push(entry.name().to_string());
+      }
+    }
+    diff
+  }
+
+  pub(crate) fn is_empty(&self) -> bool {
+    self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+  }
+}
+
+/// Collect the paths of the options differing between `before` and `after`.
+fn diff_options(
+  before: &serde_json::Value,
+  after: &serde_json::Value,
+  path: &str,
+  options: &mut Vec<String>,
+) {
+  match (before, after) {
+    (serde_json::Value::Object(before), serde_json::Value::Object(after)) => {
+      let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+      keys.sort();
+      keys.dedup();
+      for key in keys {
+        let null = serde_json::Value::Null;
+        let key_path = if path.is_empty() {
+          key.clone()
+        } else {
+          format!("{}.{}", path, key)
+        };
+        diff_options(
+          before.get(key).unwrap_or(&null),
+          after.get(key).unwrap_or(&null),
+          &key_path,
+          options,
+        );
+      }
+    }
+    (before, after) if before != after => options.push(path.to_string()),
+    _ => {}
+  }
+}
+
+/// Whether a field is indexed with positions, as phrase queries require.
+pub(crate) fn has_positions(field_entry: &tv::schema::FieldEntry) -> bool {
+  field_entry
+    .field_type()
+    .get_index_record_option()
+    .is_some_and(|option| option.has_positions())
+}
+
 /// Tantivy schema.
 ///
 /// The schema is very strict. To build the schema the `SchemaBuilder` class is
@@ -115,12 +226,60 @@ impl Schema {
     self.inner.get_field(&field_name).is_ok()
   }
 
+  /// Compare this schema with another one.
+  ///
+  /// Fields are matched by name, `added` and `removed` being relative to
+  /// this schema.
+  ///
+  /// @param other - The new schema.
+  #[napi]
+  pub fn diff(&self, other: &Schema) -> SchemaDiff {
+    SchemaDiff::between(&self.inner, &other.inner)
+  }
+
+  /// Check if phrase queries can be run against a field, that is whether the
+  /// field is a text or JSON field indexed with positions.
+  ///
+  /// Phrase queries on other fields are rejected by `Index.parseQuery` and the
+  /// phrase factories of `Query` with a "does not have positions indexed" error.
+  ///
+  /// @param fieldName - The name of the field.
+  #[napi]
+  pub fn supports_phrase_queries(&self, field_name: String) -> napi::Result<bool> {
+    let field = self.inner.get_field(&field_name).map_err(|_| {
+      napi::Error::new(
+        napi::Status::InvalidArg,
+        format!("Field '{}' not found", field_name),
+      )
+    })?;
+    Ok(has_positions(self.inner.get_field_entry(field)))
+  }
+
   /// Get a string representation of the schema
   #[napi]
   #[allow(clippy::inherent_to_string)]
   pub fn to_string(&self) -> String {
     format!("{:?}", self.inner)
   }
+
+  /// Register the schema to be shared with the worker threads, see
+  /// `Index.toHandle`.
+  ///
+  /// @returns A handle to pass to `Schema.fromHandle` in another worker.
+  #[napi]
+  pub fn to_handle(&self) -> u32 {
+    handle::register(handle::Shared::Schema(self.inner.clone()))
+  }
+
+  /// Create a schema from a handle returned by `Schema.toHandle`.
+  #[napi(factory)]
+  pub fn from_handle(handle: u32) -> napi::Result<Schema> {
+    let schema = handle::resolve(handle, "Schema", |shared| match shared {
+      handle::Shared::Schema(schema) => Some(schema.clone()),
+      _ => None,
+    })?;
+    Ok(Schema::new(schema))
+  }
 }
 
 impl Schema {
@@ -1,10 +1,13 @@
+use std::collections::HashSet;
+
 use crate::schema::Schema;
+use napi::bindgen_prelude::Either;
 use napi::{Error, Result, Status};
 use napi_derive::napi;
 use tantivy::schema::{
   BytesOptions, DateOptions, IndexRecordOption, IpAddrOptions, NumericOptions,
   Schema as TantivySchema, SchemaBuilder as TantivySchemaBuilder, TextFieldIndexing, TextOptions,
-  INDEXED,
+  INDEXED, STORED,
 };
 
 /// A SchemaBuilder can be used to create a Schema.
@@ -22,6 +25,7 @@ use tantivy::schema::{
 #[napi]
 pub struct SchemaBuilder {
   inner: Option<TantivySchemaBuilder>,
+  field_names: HashSet<String>,
 }
 
 /// Text field indexing options
@@ -29,14 +33,30 @@ pub struct SchemaBuilder {
 pub struct TextFieldOptions {
   /// Store the field value (can be retrieved from search results)
   pub stored: Option<bool>,
-  /// Fast field access (column-oriented storage)
-  pub fast: Option<bool>,
+  /// Index the field (enables searching, default: true). A field that is
+  /// neither indexed nor fast is only stored, e.g. for opaque payloads.
+  pub indexed: Option<bool>,
+  /// Fast field access (column-oriented storage). `true` normalizes the
+  /// fast values with the tokenizer of the field. A string names the
+  /// normalizer instead: 'raw' keeps the text as is, 'lowercase' lowercases
+  /// it, e.g. for case-insensitive aggregations, and any other name is a
+  /// tokenizer registered with `Index.registerTokenizer`.
+  pub fast: Option<Either<bool, String>>,
   /// Tokenizer name to use (default: "default")
   pub tokenizer_name: Option<String>,
   /// Index record option: "basic", "freq", or "position" (default: "position")
   pub index_option: Option<String>,
 }
 
+/// Keyword field options
+#[napi(object)]
+pub struct KeywordFieldOptions {
+  /// Store the field value (can be retrieved from search results)
+  pub stored: Option<bool>,
+  /// Fast field access (column-oriented storage)
+  pub fast: Option<bool>,
+}
+
 /// Numeric field options (for integers, floats, dates)
 #[napi(object)]
 pub struct NumericFieldOptions {
@@ -83,6 +103,7 @@ impl SchemaBuilder {
   pub fn new() -> Self {
     Self {
       inner: Some(TantivySchema::builder()),
+      field_names: HashSet::new(),
     }
   }
 
@@ -106,6 +127,7 @@ impl SchemaBuilder {
     name: String,
     options: Option<TextFieldOptions>,
   ) -> Result<&Self> {
+    self.check_field_name("addTextField", &name, &name)?;
     let builder = self
       .inner
       .as_mut()
@@ -113,6 +135,40 @@ impl SchemaBuilder {
 
     let opts = Self::build_text_options(options)?;
     builder.add_text_field(&name, opts);
+    self.field_names.insert(name);
+    Ok(self)
+  }
+
+  /// Add a keyword field to the schema.
+  ///
+  /// A keyword field is a text field indexed as a single untokenized term,
+  /// for identifiers and tags matched exactly, e.g. with
+  /// `IndexWriter.deleteDocumentsByTerm`.
+  ///
+  /// @param name - The name of the field
+  /// @param options - Keyword field options
+  /// @returns Self for method chaining
+  #[napi]
+  pub fn add_keyword_field(
+    &mut self,
+    name: String,
+    options: Option<KeywordFieldOptions>,
+  ) -> Result<&Self> {
+    self.check_field_name("addKeywordField", &name, &name)?;
+    let builder = self
+      .inner
+      .as_mut()
+      .ok_or_else(|| Error::new(Status::InvalidArg, "Schema builder is no longer valid"))?;
+
+    let opts = Self::build_text_options(Some(TextFieldOptions {
+      stored: options.as_ref().and_then(|o| o.stored),
+      indexed: None,
+      fast: options.as_ref().and_then(|o| o.fast).map(Either::A),
+      tokenizer_name: Some("raw".to_string()),
+      index_option: Some("basic".to_string()),
+    }))?;
+    builder.add_text_field(&name, opts);
+    self.field_names.insert(name);
     Ok(self)
   }
 
@@ -127,6 +183,7 @@ impl SchemaBuilder {
     name: String,
     options: Option<NumericFieldOptions>,
   ) -> Result<&Self> {
+    self.check_field_name("addIntegerField", &name, &name)?;
     let builder = self
       .inner
       .as_mut()
@@ -134,6 +191,7 @@ impl SchemaBuilder {
 
     let opts = Self::build_numeric_options(options);
     builder.add_i64_field(&name, opts);
+    self.field_names.insert(name);
     Ok(self)
   }
 
@@ -148,6 +206,7 @@ impl SchemaBuilder {
     name: String,
     options: Option<NumericFieldOptions>,
   ) -> Result<&Self> {
+    self.check_field_name("addUnsignedField", &name, &name)?;
     let builder = self
       .inner
       .as_mut()
@@ -155,6 +214,7 @@ impl SchemaBuilder {
 
     let opts = Self::build_numeric_options(options);
     builder.add_u64_field(&name, opts);
+    self.field_names.insert(name);
     Ok(self)
   }
 
@@ -169,6 +229,7 @@ impl SchemaBuilder {
     name: String,
     options: Option<NumericFieldOptions>,
   ) -> Result<&Self> {
+    self.check_field_name("addFloatField", &name, &name)?;
     let builder = self
       .inner
       .as_mut()
@@ -176,6 +237,7 @@ impl SchemaBuilder {
 
     let opts = Self::build_numeric_options(options);
     builder.add_f64_field(&name, opts);
+    self.field_names.insert(name);
     Ok(self)
   }
 
@@ -190,6 +252,7 @@ impl SchemaBuilder {
     name: String,
     options: Option<NumericFieldOptions>,
   ) -> Result<&Self> {
+    self.check_field_name("addBooleanField", &name, &name)?;
     let builder = self
       .inner
       .as_mut()
@@ -197,6 +260,7 @@ impl SchemaBuilder {
 
     let opts = Self::build_numeric_options(options);
     builder.add_bool_field(&name, opts);
+    self.field_names.insert(name);
     Ok(self)
   }
 
@@ -211,6 +275,7 @@ impl SchemaBuilder {
     name: String,
     options: Option<NumericFieldOptions>,
   ) -> Result<&Self> {
+    self.check_field_name("addDateField", &name, &name)?;
     let builder = self
       .inner
       .as_mut()
@@ -218,6 +283,7 @@ impl SchemaBuilder {
 
     let opts = Self::build_date_options(options);
     builder.add_date_field(&name, opts);
+    self.field_names.insert(name);
     Ok(self)
   }
 
@@ -232,6 +298,7 @@ impl SchemaBuilder {
     name: String,
     options: Option<TextFieldOptions>,
   ) -> Result<&Self> {
+    self.check_field_name("addJsonField", &name, &name)?;
     let builder = self
       .inner
       .as_mut()
@@ -239,6 +306,28 @@ impl SchemaBuilder {
 
     let opts = Self::build_text_options(options)?;
     builder.add_json_field(&name, opts);
+    self.field_names.insert(name);
+    Ok(self)
+  }
+
+  /// Add a JSON field to the schema that is only stored.
+  ///
+  /// The values are returned with the documents but neither tokenized nor
+  /// indexed, which suits opaque payloads such as metadata or render blobs.
+  /// Same as `addJsonField(name, { stored: true, indexed: false })`.
+  ///
+  /// @param name - The name of the field
+  /// @returns Self for method chaining
+  #[napi]
+  pub fn add_stored_json_field(&mut self, name: String) -> Result<&Self> {
+    self.check_field_name("addStoredJsonField", &name, &name)?;
+    let builder = self
+      .inner
+      .as_mut()
+      .ok_or_else(|| Error::new(Status::InvalidArg, "Schema builder is no longer valid"))?;
+
+    builder.add_json_field(&name, STORED);
+    self.field_names.insert(name);
     Ok(self)
   }
 
@@ -248,12 +337,14 @@ impl SchemaBuilder {
   /// @returns Self for method chaining
   #[napi]
   pub fn add_facet_field(&mut self, name: String) -> Result<&Self> {
+    self.check_field_name("addFacetField", &name, &name)?;
     let builder = self
       .inner
       .as_mut()
       .ok_or_else(|| Error::new(Status::InvalidArg, "Schema builder is no longer valid"))?;
 
     builder.add_facet_field(&name, INDEXED);
+    self.field_names.insert(name);
     Ok(self)
   }
 
@@ -268,6 +359,7 @@ impl SchemaBuilder {
     name: String,
     options: Option<BytesFieldOptions>,
   ) -> Result<&Self> {
+    self.check_field_name("addBytesField", &name, &name)?;
     let builder = self
       .inner
       .as_mut()
@@ -275,6 +367,7 @@ impl SchemaBuilder {
 
     let opts = Self::build_bytes_options(options);
     builder.add_bytes_field(&name, opts);
+    self.field_names.insert(name);
     Ok(self)
   }
 
@@ -289,6 +382,7 @@ impl SchemaBuilder {
     name: String,
     options: Option<IpAddrFieldOptions>,
   ) -> Result<&Self> {
+    self.check_field_name("addIpAddrField", &name, &name)?;
     let builder = self
       .inner
       .as_mut()
@@ -296,6 +390,96 @@ impl SchemaBuilder {
 
     let opts = Self::build_ip_addr_options(options);
     builder.add_ip_addr_field(&name, opts);
+    self.field_names.insert(name);
+    Ok(self)
+  }
+
+  /// Add a dense vector field to the schema.
+  ///
+  /// Vectors are stored as little-endian f32 values in a fast bytes field,
+  /// to be ranked with `Searcher.knn`.
+  ///
+  /// @param name - The name of the field
+  /// @param options - Bytes field options
+  /// @returns Self for method chaining
+  #[napi]
+  pub fn add_vector_field(
+    &mut self,
+    name: String,
+    options: Option<BytesFieldOptions>,
+  ) -> Result<&Self> {
+    self.check_field_name("addVectorField", &name, &name)?;
+    let builder = self
+      .inner
+      .as_mut()
+      .ok_or_else(|| Error::new(Status::InvalidArg, "Schema builder is no longer valid"))?;
+
+    let opts = Self::build_bytes_options(options).set_fast();
+    builder.add_bytes_field(&name, opts);
+    self.field_names.insert(name);
+    Ok(self)
+  }
+
+  /// Add a geo point field to the schema.
+  ///
+  /// The point is stored as two float fast fields, `<name>.lat` and
+  /// `<name>.lon`, which are always fast.
+  ///
+  /// @param name - The name of the field
+  /// @param options - Numeric field options, applied to both coordinates
+  /// @returns Self for method chaining
+  #[napi]
+  pub fn add_geo_point_field(
+    &mut self,
+    name: String,
+    options: Option<NumericFieldOptions>,
+  ) -> Result<&Self> {
+    let lat_name = crate::geo::lat_field_name(&name);
+    let lon_name = crate::geo::lon_field_name(&name);
+    self.check_field_name("addGeoPointField", &name, &lat_name)?;
+    self.check_field_name("addGeoPointField", &name, &lon_name)?;
+    let builder = self
+      .inner
+      .as_mut()
+      .ok_or_else(|| Error::new(Status::InvalidArg, "Schema builder is no longer valid"))?;
+
+    let opts = Self::build_numeric_options(options).set_fast();
+    builder.add_f64_field(&lat_name, opts.clone());
+    builder.add_f64_field(&lon_name, opts);
+    self.field_names.insert(lat_name);
+    self.field_names.insert(lon_name);
+    Ok(self)
+  }
+
+  /// Enable nested documents for this schema.
+  ///
+  /// Adds the internal `_nested` field used to mark the parent and child
+  /// documents indexed with `IndexWriter.addNestedDocument`.
+  ///
+  /// @returns Self for method chaining
+  #[napi]
+  pub fn enable_nested_documents(&mut self) -> Result<&Self> {
+    if self.field_names.contains(crate::nested::NESTED_FIELD) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "SchemaBuilder.enableNestedDocuments(): nested documents are already enabled",
+      ));
+    }
+    let builder = self
+      .inner
+      .as_mut()
+      .ok_or_else(|| Error::new(Status::InvalidArg, "Schema builder is no longer valid"))?;
+
+    let indexing = TextFieldIndexing::default()
+      .set_tokenizer("raw")
+      .set_index_option(IndexRecordOption::Basic);
+    builder.add_text_field(
+      crate::nested::NESTED_FIELD,
+      TextOptions::default().set_indexing_options(indexing),
+    );
+    self
+      .field_names
+      .insert(crate::nested::NESTED_FIELD.to_string());
     Ok(self)
   }
 
@@ -317,6 +501,36 @@ impl SchemaBuilder {
 }
 
 impl SchemaBuilder {
+  /// Check that the builder is still valid and that a field can be added
+  /// under `field_name`.
+  ///
+  /// `method` and `name` are the builder method adding the field and its
+  /// argument, to point at the faulty call in the error.
+  fn check_field_name(&self, method: &str, name: &str, field_name: &str) -> Result<()> {
+    if self.inner.is_none() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Schema builder is no longer valid",
+      ));
+    }
+    let reason = if !tantivy::schema::is_valid_field_name(field_name) {
+      "is not a valid field name, it must be non-empty and must not start with '-'"
+    } else if field_name == crate::nested::NESTED_FIELD {
+      "is reserved for nested documents, see SchemaBuilder.enableNestedDocuments()"
+    } else if self.field_names.contains(field_name) {
+      "already exists in the schema"
+    } else {
+      return Ok(());
+    };
+    Err(Error::new(
+      Status::InvalidArg,
+      format!(
+        "SchemaBuilder.{}('{}'): field '{}' {}",
+        method, name, field_name, reason
+      ),
+    ))
+  }
+
   fn build_numeric_options(options: Option<NumericFieldOptions>) -> NumericOptions {
     let mut opts = NumericOptions::default();
 
@@ -391,7 +605,6 @@ impl SchemaBuilder {
 
   fn build_text_options(options: Option<TextFieldOptions>) -> Result<TextOptions> {
     let stored = options.as_ref().and_then(|o| o.stored).unwrap_or(false);
-    let fast = options.as_ref().and_then(|o| o.fast).unwrap_or(false);
     let tokenizer_name = options
       .as_ref()
       .and_then(|o| o.tokenizer_name.as_deref())
@@ -413,19 +626,37 @@ impl SchemaBuilder {
       }
     };
 
-    let indexing = TextFieldIndexing::default()
-      .set_tokenizer(tokenizer_name)
-      .set_index_option(index_record_option);
-
-    let mut text_options = TextOptions::default().set_indexing_options(indexing);
+    let mut text_options = TextOptions::default();
+    if options.as_ref().and_then(|o| o.indexed).unwrap_or(true) {
+      let indexing = TextFieldIndexing::default()
+        .set_tokenizer(tokenizer_name)
+        .set_index_option(index_record_option);
+      text_options = text_options.set_indexing_options(indexing);
+    } else if options.as_ref().is_some_and(|o| o.index_option.is_some()) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "The index option of a field that is not indexed must not be set",
+      ));
+    }
 
     if stored {
       text_options = text_options.set_stored();
     }
 
-    if fast {
-      let text_tokenizer = if tokenizer_name != "raw" {
-        Some(tokenizer_name)
+    let fast_normalizer = match options.as_ref().and_then(|o| o.fast.as_ref()) {
+      None | Some(Either::A(false)) => None,
+      Some(Either::A(true)) => Some(tokenizer_name),
+      Some(Either::B(normalizer)) if normalizer.is_empty() => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          "The fast normalizer of a text field must not be empty",
+        ))
+      }
+      Some(Either::B(normalizer)) => Some(normalizer.as_str()),
+    };
+    if let Some(normalizer) = fast_normalizer {
+      let text_tokenizer = if normalizer != "raw" {
+        Some(normalizer)
       } else {
         None
       };
@@ -1,11 +1,23 @@
-use crate::{document::Document, query::Query};
+use crate::{
+  document::Document,
+  fastfield::{FastFieldReader, TermOrdinals},
+  named::NamedMatcher,
+  query::Query,
+  term::Term,
+};
 use napi::bindgen_prelude::*;
 use napi::{Error, Result, Status};
 use napi_derive::napi;
 use serde::{Deserialize, Serialize};
+use std::collections::{hash_map::Entry, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tantivy as tv;
 use tantivy::aggregation::AggregationCollector;
-use tantivy::collector::{Count, MultiCollector, TopDocs};
+use tantivy::collector::{
+  Collector, Count, FacetCollector, MultiCollector, SegmentCollector, TopDocs,
+};
 use tantivy::TantivyDocument;
 // Bring the trait into scope. This is required for the `to_named_doc` method.
 // However, node-tantivy declares its own `Document` class, so we need to avoid
@@ -18,10 +30,12 @@ use tantivy::Document as _;
 #[napi]
 pub struct Searcher {
   pub(crate) inner: tv::Searcher,
+  /// The term ordinals of the text fast fields, built on first use.
+  term_ordinals: Mutex<HashMap<String, Arc<TermOrdinals>>>,
 }
 
 #[napi]
-#[derive(Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
 /// Enum representing the direction in which something should be sorted.
 pub enum Order {
   /// Ascending. Smaller values appear first.
@@ -48,6 +62,51 @@ pub struct SearchResult {
   /// How many documents matched the query. Only available if `count` was set
   /// to true during the search.
   pub count: Option<u32>,
+  /// Timings of the search, only available if `SearchOptions.profile` was set.
+  pub profile: Option<SearchProfile>,
+}
+
+#[napi(object)]
+#[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
+/// Timings of a search, in milliseconds. Parsing the query is not included.
+pub struct SearchProfile {
+  /// Time spent matching, scoring and collecting the documents, including
+  /// the aggregations computed in the same pass, such as facet counts.
+  pub collect_ms: f64,
+  /// Time spent fetching the stored documents of the hits, for
+  /// `Searcher.searchDocs`.
+  pub fetch_ms: Option<f64>,
+  /// One entry per segment, in the order of the segments.
+  pub segments: Vec<SegmentProfile>,
+}
+
+#[napi(object)]
+#[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
+/// Timings of a search in a segment.
+pub struct SegmentProfile {
+  pub segment_ord: u32,
+  /// Number of documents of the segment, deleted ones included.
+  pub max_doc: u32,
+  /// Number of documents of the segment matching the query, which the
+  /// collectors were fed.
+  pub docs_collected: u32,
+  /// Time spent matching, scoring and collecting the documents of the segment.
+  pub collect_ms: f64,
+}
+
+/// The document counts of a segment, see `Searcher.segments`.
+#[napi(object, object_from_js = false)]
+pub struct SegmentInfo {
+  pub segment_ord: u32,
+  /// The id of the segment, which its files are named after.
+  pub segment_id: String,
+  /// Number of documents of the segment, deleted ones included.
+  pub max_doc: u32,
+  /// Number of live documents of the segment.
+  pub num_docs: u32,
+  /// Number of deleted documents of the segment, whose space is reclaimed
+  /// when the segment is merged.
+  pub num_deleted_docs: u32,
 }
 
 #[napi(object)]
@@ -56,6 +115,334 @@ pub struct SearchHit {
   pub score: Option<f64>,
   pub order: Option<f64>,
   pub doc_address: DocAddress,
+  /// Single integer id of the document, only stable for the searcher that
+  /// returned the hit. See `Searcher.docAddressForGlobalDoc`.
+  pub global_doc: u32,
+  /// Names of the sub-queries matching the document, see `Query.named`.
+  /// Only set if the query has named sub-queries.
+  pub matched_queries: Option<Vec<String>>,
+}
+
+#[napi(object, object_to_js = false)]
+#[derive(Default)]
+/// Additional options of a search.
+pub struct SearchOptions<'env> {
+  /// If false, relevance scoring is skipped: matching documents are
+  /// collected in index order and all get a constant score of 1.
+  /// Defaults to true. Ignored when ordering by a field.
+  pub scoring: Option<bool>,
+  /// How hits with the same score (or the same value of the ordering field)
+  /// are ordered: 'docAddress' (default) returns them by ascending document
+  /// address, so that pages are stable as long as the searcher is the same.
+  /// 'none' leaves their order unspecified.
+  pub tie_break: Option<String>,
+  /// If true, the result holds the timings of the search, see `SearchProfile`.
+  pub profile: Option<bool>,
+  /// Rescale the scores of the hits into [0, 1], over all the matching
+  /// documents rather than the returned page: 'max' divides them by the
+  /// highest score, 'minmax' maps the lowest score to 0 and the highest to 1,
+  /// and 'none' (default) leaves them as is. When all the documents have the
+  /// same score, 'minmax' gives them 1. Ignored when ordering by a field.
+  pub normalize_scores: Option<String>,
+  /// Discard the documents scoring below this BM25 score while collecting
+  /// them, so that they are neither returned nor counted. When ordering by a
+  /// field, the documents are still scored to be filtered. Can't be used
+  /// along with `scoring: false`.
+  pub min_score: Option<f64>,
+  /// Rescore the best hits by BM25 score with a JavaScript function, see
+  /// `RescoreOptions`. Can't be used when ordering by a field, along with
+  /// `scoring: false` or with `normalizeScores`.
+  pub rescore: Option<RescoreOptions<'env>>,
+}
+
+/// Options of `SearchOptions.rescore`.
+#[napi(object, object_to_js = false)]
+pub struct RescoreOptions<'env> {
+  /// Number of the best hits by BM25 score to rescore (default: 100). The
+  /// hits beyond them, if the page reaches past them, follow in BM25 order.
+  pub top_n: Option<u32>,
+  /// Fast fields whose values are passed to the callback, see `RescoreBatch.fields`.
+  pub fields: Option<Vec<String>>,
+  /// Called once with the hits to rescore, it returns their new scores, in
+  /// the order of the batch.
+  pub callback: Function<'env, RescoreBatch, Either<Float64Array, Vec<f64>>>,
+}
+
+/// The hits passed to `RescoreOptions.callback`, column by column: the
+/// values of a hit are at the same index in every array.
+#[napi(object, object_from_js = false)]
+pub struct RescoreBatch {
+  /// The BM25 scores of the hits, in decreasing order.
+  pub scores: Float64Array,
+  /// The global ids of the hits, see `SearchHit.globalDoc`.
+  pub global_docs: Uint32Array,
+  /// The values of every fast field of `RescoreOptions.fields`, as returned
+  /// by `Searcher.fastFieldValuesForDocs`.
+  pub fields: HashMap<String, Float64Array>,
+}
+
+#[napi(object)]
+#[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
+/// Options of `Searcher.searchDocs`.
+pub struct SearchDocsOptions {
+  /// The maximum number of hits to return. Defaults to 10.
+  pub limit: Option<u32>,
+  /// The offset from which the hits have to be returned.
+  pub offset: Option<u32>,
+  /// Should the number of matching documents be returned as well.
+  /// Defaults to true.
+  pub count: Option<bool>,
+  /// If given, only these stored fields are included in the documents.
+  pub fields: Option<Vec<String>>,
+  /// See `SearchOptions.scoring`.
+  pub scoring: Option<bool>,
+  /// See `SearchOptions.tieBreak`.
+  pub tie_break: Option<String>,
+  /// See `SearchOptions.profile`.
+  pub profile: Option<bool>,
+  /// See `SearchOptions.normalizeScores`.
+  pub normalize_scores: Option<String>,
+  /// See `SearchOptions.minScore`.
+  pub min_score: Option<f64>,
+}
+
+/// Options of `Searcher.docObject`.
+#[napi(object)]
+#[derive(Default)]
+pub struct DocObjectOptions {
+  /// If given, only these stored fields are included in the object.
+  pub fields: Option<Vec<String>>,
+}
+
+/// Options of `Searcher.similarDocuments`.
+#[napi(object)]
+#[derive(Default)]
+pub struct SimilarDocumentsOptions {
+  /// The number of similar documents to return. Defaults to 10.
+  pub limit: Option<u32>,
+  /// The stored fields the terms are taken from. Defaults to every stored
+  /// field.
+  pub fields: Option<Vec<String>>,
+  /// Words that are never used as terms.
+  pub stop_words: Option<Vec<String>>,
+  /// Ignore the terms found in fewer documents. Defaults to 5.
+  pub min_doc_frequency: Option<u32>,
+  /// Ignore the terms occurring fewer times in the document. Defaults to 2.
+  pub min_term_frequency: Option<u32>,
+  /// The maximum number of terms of the query. Defaults to 25.
+  pub max_query_terms: Option<u32>,
+}
+
+#[napi(object)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+/// A search hit along with its stored document.
+pub struct SearchDocHit {
+  pub score: Option<f64>,
+  pub doc_address: DocAddress,
+  pub global_doc: u32,
+  /// The stored document, in the format of `Document.toDict`. Bytes values
+  /// are arrays of numbers.
+  pub doc: serde_json::Value,
+}
+
+#[napi(object)]
+#[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
+/// Object holding the result of `Searcher.searchDocs`.
+pub struct SearchDocsResult {
+  pub hits: Vec<SearchDocHit>,
+  /// How many documents matched the query, if `count` was not disabled.
+  pub count: Option<u32>,
+  /// Timings of the search, only available if `SearchDocsOptions.profile`
+  /// was set.
+  pub profile: Option<SearchProfile>,
+}
+
+#[napi(object)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+/// A facet count block requested from `Searcher.searchFaceted`.
+pub struct FacetRequest {
+  /// A facet field.
+  pub field: String,
+  /// The facet whose children are counted, e.g. '/category'. Defaults to '/'.
+  pub root: Option<String>,
+  /// If given, only the `topK` most frequent children are returned.
+  pub top_k: Option<u32>,
+}
+
+#[napi(object)]
+#[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
+/// Options of `Searcher.searchFaceted`.
+pub struct SearchFacetedOptions {
+  pub facets: Vec<FacetRequest>,
+  /// The maximum number of hits to return. Defaults to 10.
+  pub limit: Option<u32>,
+  /// The offset from which the hits have to be returned.
+  pub offset: Option<u32>,
+  /// See `SearchOptions.profile`.
+  pub profile: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+/// The facets selected in a dimension of `Searcher.searchFacetedMultiSelect`.
+pub struct FacetFilter {
+  /// A facet field.
+  pub field: String,
+  /// The selected facets, e.g. ['/color/red', '/color/blue']. Documents
+  /// under any of them match.
+  pub facets: Vec<String>,
+}
+
+#[napi(object)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+/// A facet and the number of matching documents under it.
+pub struct FacetCount {
+  pub facet: String,
+  pub count: u32,
+}
+
+#[napi(object)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+/// The counts of the children of a facet among the matching documents.
+pub struct FacetCounts {
+  pub field: String,
+  pub root: String,
+  /// The children of `root`, by decreasing count if `topK` was given and in
+  /// facet order otherwise.
+  pub counts: Vec<FacetCount>,
+}
+
+#[napi(object)]
+#[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
+/// Object holding the result of `Searcher.searchFaceted`.
+pub struct SearchFacetedResult {
+  pub hits: Vec<SearchHit>,
+  /// How many documents matched the query.
+  pub count: u32,
+  /// One block per requested facet, in the order of the requests.
+  pub facets: Vec<FacetCounts>,
+  /// Timings of the search, only available if `SearchFacetedOptions.profile`
+  /// was set.
+  pub profile: Option<SearchProfile>,
+}
+
+/// The field the hits of a `SearchRequest` are ordered by.
+#[napi(object)]
+pub struct SearchSort {
+  /// A numeric or date fast field. The `order` of the hits is its value,
+  /// dates being milliseconds timestamps.
+  pub field: String,
+  /// Defaults to descending.
+  pub order: Option<Order>,
+  /// Where the documents without a value for the field go: 'first', 'last'
+  /// (default) or, given a number, where a document holding this value
+  /// would, dates being milliseconds timestamps. Hits placed first or last
+  /// have no `order`.
+  pub missing: Option<Either<String, f64>>,
+}
+
+/// Everything a search returns, see `Searcher.execute`.
+#[napi(object, object_to_js = false)]
+pub struct SearchRequest<'env> {
+  /// The query selecting the documents.
+  pub query: ClassInstance<'env, Query>,
+  /// The maximum number of hits to return. Defaults to 10.
+  pub limit: Option<u32>,
+  /// The offset from which the hits have to be returned.
+  pub offset: Option<u32>,
+  /// Order the hits by a field rather than by score.
+  pub sort: Option<SearchSort>,
+  /// The facets to count, see `Searcher.searchFaceted`.
+  pub facets: Option<Vec<FacetRequest>>,
+  /// Aggregations computed over the matching documents, as an object or a
+  /// JSON string in the format accepted by `Searcher.aggregate`.
+  pub aggregations: Option<serde_json::Value>,
+  /// Text fields highlighted in every hit, see `SnippetGenerator`.
+  pub snippet_fields: Option<Vec<String>>,
+  /// The stored fields returned with every hit, or true to return all of
+  /// them. By default, the documents are not fetched.
+  pub retrieve_fields: Option<Either<bool, Vec<String>>>,
+  /// Stop collecting the matching documents once the search has run for this
+  /// long. The response then only holds the documents collected so far.
+  pub timeout_ms: Option<u32>,
+}
+
+/// A hit of `Searcher.execute`.
+#[napi(object, object_from_js = false)]
+pub struct SearchResponseHit {
+  /// The score of the hit, when the hits are ordered by score.
+  pub score: Option<f64>,
+  /// The value of the sort field, when the hits are ordered by a field.
+  pub order: Option<f64>,
+  pub doc_address: DocAddress,
+  pub global_doc: u32,
+  /// Names of the sub-queries matching the document, see `Query.named`.
+  /// Only set if the query has named sub-queries.
+  pub matched_queries: Option<Vec<String>>,
+  /// The stored document, in the format of `Document.toDict`, if
+  /// `retrieveFields` was given.
+  pub doc: Option<serde_json::Value>,
+  /// The HTML snippet of every field of `snippetFields`.
+  pub snippets: Option<HashMap<String, String>>,
+}
+
+/// The result of `Searcher.execute`.
+#[napi(object, object_from_js = false)]
+pub struct SearchResponse {
+  pub hits: Vec<SearchResponseHit>,
+  /// How many documents matched the query.
+  pub count: u32,
+  /// One block per requested facet, in the order of the requests.
+  pub facets: Vec<FacetCounts>,
+  /// The results of the aggregations, if any were requested.
+  pub aggregations: Option<serde_json::Value>,
+  /// True if the timeout stopped the collection of the matching documents.
+  pub timed_out: bool,
+}
+
+#[napi(object)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+/// A distinct fast field value and the number of matching documents holding it.
+pub struct DistinctValue {
+  pub value: serde_json::Value,
+  pub count: u32,
+}
+
+#[napi(object)]
+#[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
+/// Object holding the result of a distinct count.
+pub struct DistinctCounts {
+  /// Exact number of distinct values among the matching documents.
+  pub distinct: u32,
+  /// The most frequent values, only filled if a `limit` was given.
+  pub values: Vec<DistinctValue>,
+}
+
+/// Statistics of a numeric fast field, see `Searcher.fieldMinMax`.
+///
+/// Dates are milliseconds timestamps and booleans are 0 or 1. Every value of
+/// a document with several values is counted.
+#[napi(object, object_from_js = false)]
+pub struct FieldMinMax {
+  /// The smallest value, if any matching document has a value.
+  pub min: Option<f64>,
+  /// The largest value, if any matching document has a value.
+  pub max: Option<f64>,
+  /// Number of values of the matching documents.
+  pub count: u32,
+  /// Sum of the values of the matching documents.
+  pub sum: f64,
+  /// Mean of the values of the matching documents, if there are any.
+  pub mean: Option<f64>,
+}
+
+impl Searcher {
+  pub(crate) fn new(inner: tv::Searcher) -> Self {
+    Searcher {
+      inner,
+      term_ordinals: Default::default(),
+    }
+  }
 }
 
 #[napi]
@@ -69,12 +456,13 @@ impl Searcher {
   ///         the query be returned as well. Defaults to true.
   /// @param orderByField - A schema field that the results
   ///         should be ordered by. The field must be declared as a fast field
-  ///         when building the schema. Note, this only works for unsigned
-  ///         fields.
+  ///         when building the schema. The `order` of the hits is the value
+  ///         of the field, dates being milliseconds timestamps.
   /// @param offset - The offset from which the results have
   ///         to be returned.
   /// @param order - The order in which the results
   ///         should be sorted. If not specified, defaults to descending.
+  /// @param options - Additional search options, see `SearchOptions`.
   ///
   /// @returns SearchResult object.
   ///
@@ -89,13 +477,44 @@ impl Searcher {
     order_by_field: Option<String>,
     offset: Option<u32>,
     order: Option<Order>,
+    options: Option<SearchOptions>,
   ) -> Result<SearchResult> {
     let limit = limit.unwrap_or(10) as usize;
     let count = count.unwrap_or(true);
     let offset = offset.unwrap_or(0) as usize;
     let order = order.unwrap_or(Order::Desc);
+    let options = options.unwrap_or_default();
+    let profile = options.profile.unwrap_or(false);
+    let break_ties = match options.tie_break.as_deref().unwrap_or("docAddress") {
+      "docAddress" => true,
+      "none" => false,
+      _ => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          "Invalid tie break, valid choices are: 'docAddress' and 'none'",
+        ))
+      }
+    };
+    let normalization = ScoreNormalization::parse(options.normalize_scores.as_deref())?;
+    let min_score = options.min_score.map(|min_score| min_score as tv::Score);
+    if min_score.is_some() && options.scoring == Some(false) && order_by_field.is_none() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "A minimum score can't be used in an unscored search",
+      ));
+    }
+    if options.rescore.is_some()
+      && (order_by_field.is_some()
+        || options.scoring == Some(false)
+        || !matches!(normalization, ScoreNormalization::None))
+    {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "Rescoring can't be used when ordering by a field, in an unscored search or with normalized scores",
+      ));
+    }
 
-    if let Some(order_by_field) = order_by_field {
+    let mut result = if let Some(order_by_field) = order_by_field {
       // Order by field search
       let mut multicollector = MultiCollector::new();
 
@@ -105,28 +524,86 @@ impl Searcher {
         None
       };
 
-      let collector = TopDocs::with_limit(limit)
-        .and_offset(offset)
-        .order_by_u64_field(&order_by_field, order.into());
+      // With a tie break, the page is taken once the hits are sorted.
+      let collector = if break_ties {
+        TopDocs::with_limit(offset + limit)
+      } else {
+        TopDocs::with_limit(limit).and_offset(offset)
+      }
+      .order_by_u64_field(&order_by_field, order.into());
       let top_docs_handle = multicollector.add_collector(collector);
 
-      let mut multifruit = self
-        .inner
-        .search(&query.inner, &multicollector)
-        .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+      let collector = MinScoreCollector {
+        inner: multicollector,
+        min_score,
+      };
+      let (mut multifruit, profile) = self.collect(query.get(), &collector, profile)?;
 
-      let top_docs = top_docs_handle.extract(&mut multifruit);
+      let mut top_docs = top_docs_handle.extract(&mut multifruit);
+      if break_ties {
+        // Compare the exact values, greater ones first as tantivy reverses
+        // them to order them ascending.
+        top_docs.sort_by(|(a, a_doc), (b, b_doc)| b.cmp(a).then_with(|| a_doc.cmp(b_doc)));
+        top_docs.drain(..offset.min(top_docs.len()));
+      }
+      let value_type = sort_field_type(self.inner.schema(), &order_by_field)?;
       let hits: Vec<SearchHit> = top_docs
         .iter()
         .map(|(f, d)| SearchHit {
           score: None,
-          order: Some(*f as f64),
+          // Tantivy reverses the values to order them ascending.
+          order: Some(crate::fastfield::sort_value_to_f64(
+            value_type,
+            match order {
+              Order::Asc => u64::MAX - *f,
+              Order::Desc => *f,
+            },
+          )),
+          doc_address: DocAddress::from(d),
+          global_doc: self.global_doc_id(d),
+          matched_queries: None,
+        })
+        .collect();
+
+      let count = count_handle.map(|h| h.extract(&mut multifruit) as u32);
+      SearchResult {
+        hits,
+        count,
+        profile,
+      }
+    } else if options.scoring == Some(false) {
+      // Unscored search, in doc id order
+      let mut multicollector = MultiCollector::new();
+
+      let count_handle = if count {
+        Some(multicollector.add_collector(Count))
+      } else {
+        None
+      };
+
+      let collector = DocIdOrderCollector { limit, offset };
+      let docs_handle = multicollector.add_collector(collector);
+
+      let (mut multifruit, profile) = self.collect(query.get(), &multicollector, profile)?;
+
+      let docs = docs_handle.extract(&mut multifruit);
+      let hits: Vec<SearchHit> = docs
+        .iter()
+        .map(|d| SearchHit {
+          score: Some(1.0),
+          order: None,
           doc_address: DocAddress::from(d),
+          global_doc: self.global_doc_id(d),
+          matched_queries: None,
         })
         .collect();
 
       let count = count_handle.map(|h| h.extract(&mut multifruit) as u32);
-      Ok(SearchResult { hits, count })
+      SearchResult {
+        hits,
+        count,
+        profile,
+      }
     } else {
       // Score-based search
       let mut multicollector = MultiCollector::new();
@@ -137,127 +614,2487 @@ impl Searcher {
         None
       };
 
-      let collector = TopDocs::with_limit(limit).and_offset(offset);
+      let collector = match &options.rescore {
+        // The page is taken once the hits are rescored.
+        Some(rescore) => TopDocs::with_limit(rescore_window(rescore).max(offset + limit)),
+        // With a tie break, the page is taken once the hits are sorted.
+        None if break_ties => TopDocs::with_limit(offset + limit),
+        None => TopDocs::with_limit(limit).and_offset(offset),
+      };
       let top_docs_handle = multicollector.add_collector(collector);
+      let score_range_handle = match normalization {
+        ScoreNormalization::None => None,
+        _ => Some(multicollector.add_collector(ScoreRangeCollector)),
+      };
 
-      let mut multifruit = self
-        .inner
-        .search(&query.inner, &multicollector)
-        .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+      let collector = MinScoreCollector {
+        inner: multicollector,
+        min_score,
+      };
+      let (mut multifruit, profile) = self.collect(query.get(), &collector, profile)?;
 
       let top_docs = top_docs_handle.extract(&mut multifruit);
-      let hits: Vec<SearchHit> = top_docs
+      let score_range = score_range_handle.and_then(|handle| handle.extract(&mut multifruit));
+      let mut hits: Vec<SearchHit> = top_docs
         .iter()
         .map(|(f, d)| SearchHit {
-          score: Some(*f as f64),
+          score: Some(normalization.apply(*f as f64, score_range)),
           order: None,
           doc_address: DocAddress::from(d),
+          global_doc: self.global_doc_id(d),
+          matched_queries: None,
         })
         .collect();
+      if break_ties {
+        sort_hits(&mut hits, |hit| hit.score, Order::Desc);
+      }
+      if let Some(rescore) = &options.rescore {
+        self.rescore(&mut hits, rescore)?;
+        hits = hits.into_iter().skip(offset).take(limit).collect();
+      } else if break_ties {
+        hits.drain(..offset.min(hits.len()));
+      }
 
       let count = count_handle.map(|h| h.extract(&mut multifruit) as u32);
-      Ok(SearchResult { hits, count })
+      SearchResult {
+        hits,
+        count,
+        profile,
+      }
+    };
+    self.set_matched_queries(query.get(), &mut result.hits)?;
+    Ok(result)
+  }
+
+  /// Search a single segment of the index, e.g. to split a search over a
+  /// large index between worker threads, each searching some of the
+  /// segments listed by `segments`, and to merge their hits.
+  ///
+  /// Scores are computed with the statistics of the whole index, so that
+  /// the hits of different segments can be compared.
+  ///
+  /// @param segmentOrd - The ordinal of the segment, see `SegmentInfo.segmentOrd`.
+  /// @param query - The query that will be used for the search.
+  /// @param limit - The maximum number of search results to return.
+  ///         Defaults to 10.
+  ///
+  /// @returns SearchResult object, counting the matching documents of the
+  ///          segment.
+  #[napi]
+  pub fn search_segment(
+    &self,
+    segment_ord: u32,
+    query: &Query,
+    limit: Option<u32>,
+  ) -> Result<SearchResult> {
+    if segment_ord as usize >= self.inner.segment_readers().len() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Segment {} is out of range.", segment_ord),
+      ));
     }
+    let start = Instant::now();
+    let collector = (Count, TopDocs::with_limit(limit.unwrap_or(10) as usize));
+    let (count, top_docs) = query
+      .get()
+      .weight(tv::query::EnableScoring::enabled_from_searcher(&self.inner))
+      .and_then(|weight| {
+        let fruit = collector.collect_segment(
+          weight.as_ref(),
+          segment_ord,
+          self.inner.segment_reader(segment_ord),
+        )?;
+        collector.merge_fruits(vec![fruit])
+      })
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    crate::metrics::record_search(start);
+
+    let mut hits: Vec<SearchHit> = top_docs
+      .iter()
+      .map(|(score, d)| SearchHit {
+        score: Some(*score as f64),
+        order: None,
+        doc_address: DocAddress::from(d),
+        global_doc: self.global_doc_id(d),
+        matched_queries: None,
+      })
+      .collect();
+    sort_hits(&mut hits, |hit| hit.score, Order::Desc);
+    self.set_matched_queries(query.get(), &mut hits)?;
+    Ok(SearchResult {
+      hits,
+      count: Some(count as u32),
+      profile: None,
+    })
   }
 
+  /// Search the index and fetch the stored documents of the hits.
+  ///
+  /// @param query - The query that will be used for the search.
+  /// @param options - Search options, see `SearchDocsOptions`.
+  ///
+  /// @returns SearchDocsResult object.
   #[napi]
-  pub fn aggregate(&self, query: &Query, agg: Unknown) -> Result<String> {
-    // Convert the JS object to JSON string first
-    let agg_str = agg.coerce_to_string()?.into_utf8()?.into_owned()?;
+  pub fn search_docs(
+    &self,
+    query: &Query,
+    options: Option<SearchDocsOptions>,
+  ) -> Result<SearchDocsResult> {
+    let options = options.unwrap_or_default();
+    let result = self.search(
+      query,
+      options.limit,
+      options.count,
+      None,
+      options.offset,
+      None,
+      Some(SearchOptions {
+        scoring: options.scoring,
+        tie_break: options.tie_break,
+        profile: options.profile,
+        normalize_scores: options.normalize_scores,
+        min_score: options.min_score,
+        rescore: None,
+      }),
+    )?;
 
-    let agg_collector = AggregationCollector::from_aggs(
-      serde_json::from_str(&agg_str).map_err(|e| {
-        Error::new(
-          Status::InvalidArg,
-          format!("Invalid aggregation JSON: {}", e),
-        )
-      })?,
-      Default::default(),
+    let start = Instant::now();
+    let fields = options.fields.as_deref();
+    let hits = result
+      .hits
+      .into_iter()
+      .map(|hit| {
+        let doc = self.doc(hit.doc_address.clone())?;
+        Ok(SearchDocHit {
+          score: hit.score,
+          doc_address: hit.doc_address,
+          global_doc: hit.global_doc,
+          doc: doc.to_json_dict(fields),
+        })
+      })
+      .collect::<Result<Vec<_>>>()?;
+    let profile = result.profile.map(|profile| SearchProfile {
+      fetch_ms: Some(elapsed_ms(start)),
+      ..profile
+    });
+    Ok(SearchDocsResult {
+      hits,
+      count: result.count,
+      profile,
+    })
+  }
+
+  /// Search the index and count facets of the matching documents, in a single pass.
+  ///
+  /// @param query - The query that will be used for the search.
+  /// @param options - The facets to count and the page of hits to return,
+  ///         see `SearchFacetedOptions`.
+  ///
+  /// @returns SearchFacetedResult object.
+  #[napi]
+  pub fn search_faceted(
+    &self,
+    query: &Query,
+    options: SearchFacetedOptions,
+  ) -> Result<SearchFacetedResult> {
+    let schema = self.inner.schema();
+    let mut multicollector = MultiCollector::new();
+    let count_handle = multicollector.add_collector(Count);
+    let top_docs_handle = multicollector.add_collector(
+      TopDocs::with_limit(options.limit.unwrap_or(10) as usize)
+        .and_offset(options.offset.unwrap_or(0) as usize),
     );
 
-    let agg_res = self
-      .inner
-      .search(&query.inner, &agg_collector)
-      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    let mut facet_handles = Vec::with_capacity(options.facets.len());
+    for request in &options.facets {
+      check_facet_field(schema, &request.field)?;
+      let root = facet_root(request)?;
+      let mut facet_collector = FacetCollector::for_field(&request.field);
+      facet_collector.add_facet(root.clone());
+      facet_handles.push((root, multicollector.add_collector(facet_collector)));
+    }
 
-    let result_str = serde_json::to_string(&agg_res)
-      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    let (mut multifruit, profile) = self.collect(
+      query.get(),
+      &multicollector,
+      options.profile.unwrap_or(false),
+    )?;
 
-    Ok(result_str)
-  }
+    let mut hits: Vec<SearchHit> = top_docs_handle
+      .extract(&mut multifruit)
+      .iter()
+      .map(|(f, d)| SearchHit {
+        score: Some(*f as f64),
+        order: None,
+        doc_address: DocAddress::from(d),
+        global_doc: self.global_doc_id(d),
+        matched_queries: None,
+      })
+      .collect();
+    sort_hits(&mut hits, |hit| hit.score, Order::Desc);
+    self.set_matched_queries(query.get(), &mut hits)?;
 
-  /// Returns the overall number of documents in the index.
-  #[napi(getter)]
-  pub fn num_docs(&self) -> u32 {
-    self.inner.num_docs() as u32
-  }
+    let facets = options
+      .facets
+      .iter()
+      .zip(facet_handles)
+      .map(|(request, (root, handle))| facet_counts(request, root, handle.extract(&mut multifruit)))
+      .collect();
 
-  /// Returns the number of segments in the index.
-  #[napi(getter)]
-  pub fn num_segments(&self) -> u32 {
-    self.inner.segment_readers().len() as u32
+    Ok(SearchFacetedResult {
+      hits,
+      count: count_handle.extract(&mut multifruit) as u32,
+      facets,
+      profile,
+    })
   }
 
-  /// Return the overall number of documents containing
-  /// the given term.
+  /// Search the index with facet filters and count the facets of the
+  /// matching documents, the "multi-select" way, in a single pass.
+  ///
+  /// The hits and the count are those of the documents matching `query` and
+  /// every filter. The facets of a dimension are counted without the filters
+  /// of that dimension, so that the other values of the dimension can still
+  /// be selected: with 'red' selected, the count of 'blue' is the number of
+  /// documents that selecting 'blue' as well would add.
+  ///
+  /// @param query - The query selecting the documents, before any filter.
+  /// @param filters - The facets selected in every dimension, see `FacetFilter`.
+  ///         Filters of different fields must all match.
+  /// @param options - The facets to count and the page of hits to return,
+  ///         see `SearchFacetedOptions`.
+  ///
+  /// @returns SearchFacetedResult object.
   #[napi]
-  pub fn doc_freq(&self, field_name: String, field_value: Unknown) -> Result<u32> {
-    // Wrap the tantivy Searcher `doc_freq` method to return a Result.
+  pub fn search_faceted_multi_select(
+    &self,
+    query: &Query,
+    filters: Vec<FacetFilter>,
+    options: SearchFacetedOptions,
+  ) -> Result<SearchFacetedResult> {
     let schema = self.inner.schema();
-    let term = crate::make_term(schema, &field_name, field_value)?;
-    self
-      .inner
-      .doc_freq(&term)
-      .map(|count| count as u32)
-      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+    let enable_scoring = tv::query::EnableScoring::disabled_from_searcher(&self.inner);
+    let mut weights = Vec::with_capacity(filters.len());
+    for filter in &filters {
+      let field = check_facet_field(schema, &filter.field)?;
+      let terms = filter
+        .facets
+        .iter()
+        .map(|facet| {
+          let facet = tv::schema::Facet::from_text(facet)
+            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+          Ok(tv::Term::from_facet(field, &facet))
+        })
+        .collect::<Result<Vec<_>>>()?;
+      let query = tv::query::TermSetQuery::new(terms);
+      let weight = tv::query::Query::weight(&query, enable_scoring)
+        .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+      weights.push((field, weight));
+    }
+
+    let mut facets = Vec::with_capacity(options.facets.len());
+    let mut roots = Vec::with_capacity(options.facets.len());
+    for request in &options.facets {
+      let field = check_facet_field(schema, &request.field)?;
+      let root = facet_root(request)?;
+      let mut facet_collector = FacetCollector::for_field(&request.field);
+      facet_collector.add_facet(root.clone());
+      // The filters applying to the counts of this facet.
+      let applied = weights
+        .iter()
+        .map(|(filter_field, _)| *filter_field != field)
+        .collect();
+      facets.push((applied, facet_collector));
+      roots.push(root);
+    }
+
+    let collector = MultiSelectCollector {
+      filters: weights.into_iter().map(|(_, weight)| weight).collect(),
+      top_docs: TopDocs::with_limit(options.limit.unwrap_or(10) as usize)
+        .and_offset(options.offset.unwrap_or(0) as usize),
+      facets,
+    };
+    let ((top_docs, count, facet_fruits), profile) =
+      self.collect(query.get(), &collector, options.profile.unwrap_or(false))?;
+
+    let mut hits: Vec<SearchHit> = top_docs
+      .iter()
+      .map(|(f, d)| SearchHit {
+        score: Some(*f as f64),
+        order: None,
+        doc_address: DocAddress::from(d),
+        global_doc: self.global_doc_id(d),
+        matched_queries: None,
+      })
+      .collect();
+    sort_hits(&mut hits, |hit| hit.score, Order::Desc);
+    self.set_matched_queries(query.get(), &mut hits)?;
+
+    let facets = options
+      .facets
+      .iter()
+      .zip(roots)
+      .zip(facet_fruits)
+      .map(|((request, root), counts)| facet_counts(request, root, counts))
+      .collect();
+
+    Ok(SearchFacetedResult {
+      hits,
+      count: count as u32,
+      facets,
+      profile,
+    })
   }
 
-  /// Fetches a document from Tantivy's store given a DocAddress.
+  /// Run a search described by a single request object: the page of hits,
+  /// possibly ordered by a field, along with their documents and snippets,
+  /// the facet counts and the aggregations.
   ///
-  /// @param docAddress - The DocAddress that is associated with
-  ///         the document that we wish to fetch.
+  /// The hits, the count, the facets and the aggregations scoped to the
+  /// query are collected in a single pass over the matching documents.
   ///
-  /// @returns The Document, raises ValueError if the document can't be found.
+  /// @param request - See `SearchRequest`.
+  ///
+  /// @returns SearchResponse object.
   #[napi]
-  pub fn doc(&self, doc_address: DocAddress) -> Result<Document> {
-    let doc: TantivyDocument = self
-      .inner
-      .doc((&doc_address).into())
-      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
-    let named_doc = doc.to_named_doc(self.inner.schema());
-    Ok(crate::document::Document {
-      field_values: named_doc.0,
-    })
-  }
-}
+  pub fn execute(&self, request: SearchRequest) -> Result<SearchResponse> {
+    let schema = self.inner.schema();
+    let query = request.query.get();
+    let limit = request.limit.unwrap_or(10) as usize;
+    let offset = request.offset.unwrap_or(0) as usize;
+    let order = request
+      .sort
+      .as_ref()
+      .and_then(|sort| sort.order)
+      .unwrap_or(Order::Desc);
 
-/// DocAddress contains all the necessary information to identify a document
-/// given a Searcher object.
-///
-/// It consists in an id identifying its segment, and its segment-local DocId.
-/// The id used for the segment is actually an ordinal in the list of segment
-/// hold by a Searcher.
-#[napi(object)]
-#[derive(Clone, Debug, Deserialize, PartialEq, PartialOrd, Eq, Ord, Serialize)]
-pub struct DocAddress {
-  pub segment_ord: u32,
-  pub doc: u32,
-}
+    // The page is taken once the ties are broken.
+    let mut multicollector = MultiCollector::new();
+    let count_handle = multicollector.add_collector(Count);
+    let (score_handle, sort_handle) = match &request.sort {
+      Some(sort) => {
+        crate::fastfield::check_fast_field(schema, &sort.field)?;
+        let value_type = sort_field_type(schema, &sort.field)?;
+        let collector = TopDocs::with_limit(offset + limit).custom_score(FieldSortScorer {
+          field: sort.field.clone(),
+          order,
+          missing: MissingValues::parse(&sort.field, value_type, sort.missing.as_ref())?,
+        });
+        let handle = multicollector.add_collector(collector);
+        (None, Some((handle, value_type)))
+      }
+      None => {
+        let collector = TopDocs::with_limit(offset + limit);
+        (Some(multicollector.add_collector(collector)), None)
+      }
+    };
 
-impl From<&tv::DocAddress> for DocAddress {
-  fn from(doc_address: &tv::DocAddress) -> Self {
-    DocAddress {
-      segment_ord: doc_address.segment_ord,
-      doc: doc_address.doc_id,
+    let facet_requests = request.facets.unwrap_or_default();
+    let mut facet_handles = Vec::with_capacity(facet_requests.len());
+    for facet_request in &facet_requests {
+      check_facet_field(schema, &facet_request.field)?;
+      let root = facet_root(facet_request)?;
+      let mut facet_collector = FacetCollector::for_field(&facet_request.field);
+      facet_collector.add_facet(root.clone());
+      facet_handles.push((root, multicollector.add_collector(facet_collector)));
     }
-  }
-}
 
-impl From<&DocAddress> for tv::DocAddress {
-  fn from(val: &DocAddress) -> Self {
-    tv::DocAddress {
-      segment_ord: val.segment_ord,
-      doc_id: val.doc,
+    let (aggs, global_aggs) = match &request.aggregations {
+      None => Default::default(),
+      Some(serde_json::Value::String(aggregations)) => parse_scoped_aggregations(aggregations)?,
+      Some(aggregations) => parse_scoped_aggregations(&aggregations.to_string())?,
+    };
+    self.check_aggregation_fields(&aggs)?;
+    self.check_aggregation_fields(&global_aggs)?;
+    let has_aggregations = request.aggregations.is_some();
+    let agg_handle = if aggs.is_empty() {
+      None
+    } else {
+      Some(multicollector.add_collector(AggregationCollector::from_aggs(aggs, Default::default())))
+    };
+
+    let snippet_generators = request
+      .snippet_fields
+      .unwrap_or_default()
+      .into_iter()
+      .map(|field_name| {
+        let field = crate::get_field(schema, &field_name)?;
+        let generator = tv::snippet::SnippetGenerator::create(&self.inner, query, field)
+          .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+        Ok((field_name, generator))
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    let collector = TimeoutCollector {
+      inner: multicollector,
+      deadline: request
+        .timeout_ms
+        .map(|timeout_ms| Instant::now() + Duration::from_millis(timeout_ms as u64)),
+      timed_out: Default::default(),
+    };
+    let timed_out = collector.timed_out.clone();
+    let (mut multifruit, global_agg_res) = if global_aggs.is_empty() {
+      (self.collect(query, &collector, false)?.0, None)
+    } else {
+      let (multifruit, global_agg_res) =
+        self.collect_with_global_aggs(query, collector, global_aggs)?;
+      (multifruit, Some(global_agg_res))
+    };
+
+    let mut hits: Vec<SearchHit> = match (score_handle, sort_handle) {
+      (Some(handle), _) => handle
+        .extract(&mut multifruit)
+        .iter()
+        .map(|(score, d)| SearchHit {
+          score: Some(*score as f64),
+          order: None,
+          doc_address: DocAddress::from(d),
+          global_doc: self.global_doc_id(d),
+          matched_queries: None,
+        })
+        .collect(),
+      (None, Some((handle, value_type))) => {
+        let mut top_docs = handle.extract(&mut multifruit);
+        // Break the ties by ascending document address.
+        top_docs.sort_by(|(a, a_doc), (b, b_doc)| b.cmp(a).then_with(|| a_doc.cmp(b_doc)));
+        top_docs
+          .iter()
+          .map(|(key, d)| SearchHit {
+            score: None,
+            order: key
+              .value(order)
+              .map(|value| crate::fastfield::sort_value_to_f64(value_type, value)),
+            doc_address: DocAddress::from(d),
+            global_doc: self.global_doc_id(d),
+            matched_queries: None,
+          })
+          .collect()
+      }
+      (None, None) => Vec::new(),
+    };
+    if request.sort.is_none() {
+      sort_hits(&mut hits, |hit| hit.score, Order::Desc);
     }
+    hits.drain(..offset.min(hits.len()));
+    self.set_matched_queries(query, &mut hits)?;
+
+    let facets = facet_requests
+      .iter()
+      .zip(facet_handles)
+      .map(|(facet_request, (root, handle))| {
+        facet_counts(facet_request, root, handle.extract(&mut multifruit))
+      })
+      .collect();
+
+    let aggregations = if has_aggregations {
+      let mut results = match agg_handle {
+        Some(handle) => aggregation_results(&handle.extract(&mut multifruit))?,
+        None => serde_json::Map::new(),
+      };
+      if let Some(global_agg_res) = &global_agg_res {
+        results.extend(aggregation_results(global_agg_res)?);
+      }
+      Some(serde_json::Value::Object(results))
+    } else {
+      None
+    };
+
+    let fields = match &request.retrieve_fields {
+      None | Some(Either::A(false)) => None,
+      Some(Either::A(true)) => Some(None),
+      Some(Either::B(fields)) => Some(Some(fields.as_slice())),
+    };
+    let hits = hits
+      .into_iter()
+      .map(|hit| {
+        let mut response_hit = SearchResponseHit {
+          score: hit.score,
+          order: hit.order,
+          doc_address: hit.doc_address.clone(),
+          global_doc: hit.global_doc,
+          matched_queries: hit.matched_queries.clone(),
+          doc: None,
+          snippets: None,
+        };
+        if fields.is_none() && snippet_generators.is_empty() {
+          return Ok(response_hit);
+        }
+        let cache_stats = self.inner.doc_store_cache_stats();
+        let doc: TantivyDocument = self
+          .inner
+          .doc((&hit.doc_address).into())
+          .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+        crate::metrics::record_doc_store_cache(cache_stats, self.inner.doc_store_cache_stats());
+        if !snippet_generators.is_empty() {
+          response_hit.snippets = Some(
+            snippet_generators
+              .iter()
+              .map(|(field_name, generator)| {
+                (
+                  field_name.clone(),
+                  generator.snippet_from_doc(&doc).to_html(),
+                )
+              })
+              .collect(),
+          );
+        }
+        if let Some(fields) = fields {
+          let document = Document {
+            field_values: doc.to_named_doc(schema).0,
+          };
+          response_hit.doc = Some(document.to_json_dict(fields));
+        }
+        Ok(response_hit)
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    Ok(SearchResponse {
+      hits,
+      count: count_handle.extract(&mut multifruit) as u32,
+      facets,
+      aggregations,
+      timed_out: timed_out.load(Ordering::Relaxed),
+    })
+  }
+
+  /// Compute aggregations over the documents matching a query.
+  ///
+  /// Fields of JSON fast fields are aggregated by their path, e.g.
+  /// `{ colors: { terms: { field: 'attributes.color' } } }`.
+  ///
+  /// A top-level aggregation flagged with `global: true` is computed over
+  /// every document of the index instead, e.g. to show "N of M" counts next to
+  /// the ones of the query:
+  /// `{ colors: { terms: { field: 'color' } }, allColors: { terms: { field: 'color' }, global: true } }`.
+  ///
+  /// @param query - The query selecting the documents, or null to aggregate
+  ///         every document.
+  /// @param agg - The aggregation request, in Elasticsearch's format.
+  ///
+  /// @returns A JSON string with the results of the aggregations.
+  #[napi]
+  pub fn aggregate(&self, query: Option<&Query>, agg: Unknown) -> Result<String> {
+    let agg_str = agg.coerce_to_string()?.into_utf8()?.into_owned()?;
+    let (aggs, global_aggs) = parse_scoped_aggregations(&agg_str)?;
+    self.check_aggregation_fields(&aggs)?;
+    self.check_aggregation_fields(&global_aggs)?;
+
+    let all_query = tv::query::AllQuery;
+    let query = query.map_or(&all_query as &dyn tv::query::Query, |query| query.get());
+    let agg_collector = AggregationCollector::from_aggs(aggs, Default::default());
+    let (agg_res, global_agg_res) = if global_aggs.is_empty() {
+      (self.collect(query, &agg_collector, false)?.0, None)
+    } else {
+      let (agg_res, global_agg_res) =
+        self.collect_with_global_aggs(query, agg_collector, global_aggs)?;
+      (agg_res, Some(global_agg_res))
+    };
+    let mut results = aggregation_results(&agg_res)?;
+    if let Some(global_agg_res) = &global_agg_res {
+      results.extend(aggregation_results(global_agg_res)?);
+    }
+
+    let result_str = serde_json::to_string(&results)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+
+    Ok(result_str)
+  }
+
+  /// Find the k nearest neighbors of a vector.
+  ///
+  /// Vectors are compared by brute force, among the documents matching the
+  /// filter. Documents whose vector has another number of dimensions than
+  /// `queryVector` are ignored.
+  ///
+  /// @param fieldName - A field declared with `SchemaBuilder.addVectorField`.
+  /// @param queryVector - The vector to search the neighbors of.
+  /// @param k - The number of neighbors to return.
+  /// @param filter - Restrict the search to the documents matching this query.
+  /// @param metric - The similarity used as score: 'cosine' (default), 'dot' or 'l2'.
+  ///
+  /// @returns SearchResult object, the hits being ordered by decreasing similarity.
+  #[napi]
+  pub fn knn(
+    &self,
+    field_name: String,
+    query_vector: Vec<f64>,
+    k: u32,
+    filter: Option<&Query>,
+    metric: Option<String>,
+  ) -> Result<SearchResult> {
+    crate::fastfield::check_fast_field(self.inner.schema(), &field_name)?;
+    let metric = crate::vector::VectorMetric::parse(metric.as_deref())?;
+    if query_vector.is_empty() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "The query vector must not be empty.",
+      ));
+    }
+    let query_vector: Vec<f32> = query_vector.iter().map(|value| *value as f32).collect();
+
+    let collector = TopDocs::with_limit(k.max(1) as usize).tweak_score(
+      move |segment_reader: &tv::SegmentReader| {
+        let column = segment_reader
+          .fast_fields()
+          .bytes(&field_name)
+          .ok()
+          .flatten();
+        let query_vector = query_vector.clone();
+        let mut bytes = Vec::new();
+        move |doc: tv::DocId, _score: tv::Score| {
+          let Some(column) = &column else {
+            return f32::NEG_INFINITY;
+          };
+          let mut best = f32::NEG_INFINITY;
+          for ord in column.term_ords(doc) {
+            bytes.clear();
+            if column.ord_to_bytes(ord, &mut bytes).is_err() {
+              continue;
+            }
+            match crate::vector::decode_vector(&bytes) {
+              Some(vector) if vector.len() == query_vector.len() => {
+                best = best.max(metric.similarity(&query_vector, &vector));
+              }
+              _ => {}
+            }
+          }
+          best
+        }
+      },
+    );
+
+    let all_query = tv::query::AllQuery;
+    let query = filter.map_or(&all_query as &dyn tv::query::Query, |filter| filter.get());
+    let start = Instant::now();
+    let top_docs = self
+      .inner
+      .search(query, &collector)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    crate::metrics::record_search(start);
+
+    let hits: Vec<SearchHit> = top_docs
+      .iter()
+      .filter(|(score, _)| score.is_finite())
+      .take(k as usize)
+      .map(|(score, d)| SearchHit {
+        score: Some(*score as f64),
+        order: None,
+        doc_address: DocAddress::from(d),
+        global_doc: self.global_doc_id(d),
+        matched_queries: None,
+      })
+      .collect();
+    Ok(SearchResult {
+      hits,
+      count: None,
+      profile: None,
+    })
+  }
+
+  /// Compute aggregations over several filtered subsets of the matching documents.
+  ///
+  /// All the buckets are filled in a single pass over the documents matching
+  /// `query`, which is cheaper than one search per filter.
+  ///
+  /// @param query - The query selecting the documents.
+  /// @param filters - The buckets, as an object mapping a bucket name to a Query.
+  /// @param subAggs - Optional aggregation request (in the format accepted by
+  ///         `aggregate`) computed in every bucket.
+  ///
+  /// @returns A JSON string mapping every bucket name to its `doc_count` and the
+  ///         results of the sub-aggregations.
+  #[napi]
+  pub fn aggregate_filters(
+    &self,
+    query: &Query,
+    filters: Object,
+    sub_aggs: Option<Unknown>,
+  ) -> Result<String> {
+    let enable_scoring = tv::query::EnableScoring::disabled_from_searcher(&self.inner);
+    let mut weights = Vec::new();
+    for name in Object::keys(&filters)? {
+      let filter: ClassInstance<Query> = filters
+        .get(&name)?
+        .ok_or_else(|| Error::new(Status::InvalidArg, format!("Missing filter '{}'", name)))?;
+      let weight = filter
+        .inner
+        .weight(enable_scoring)
+        .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+      weights.push((name, weight));
+    }
+    let aggs = match sub_aggs {
+      Some(sub_aggs) => {
+        let sub_aggs = parse_aggregations(sub_aggs)?;
+        self.check_aggregation_fields(&sub_aggs)?;
+        Some(AggregationCollector::from_aggs(
+          sub_aggs,
+          Default::default(),
+        ))
+      }
+      None => None,
+    };
+
+    let collector = FiltersCollector { weights, aggs };
+    let start = Instant::now();
+    let buckets = self
+      .inner
+      .search(&query.inner, &collector)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    crate::metrics::record_search(start);
+
+    serde_json::to_string(&buckets).map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+  }
+
+  /// Count the distinct values of a fast field among the documents matching a query.
+  ///
+  /// The count is exact. This is cheaper than a terms aggregation when the
+  /// cardinality of the field is small.
+  ///
+  /// @param query - The query selecting the documents.
+  /// @param fieldName - A field declared as fast in the schema.
+  /// @param limit - If given, the most frequent values (at most `limit` of them)
+  ///         are returned along with their document counts.
+  ///
+  /// @returns DistinctCounts object.
+  #[napi]
+  pub fn distinct_counts(
+    &self,
+    query: &Query,
+    field_name: String,
+    limit: Option<u32>,
+  ) -> Result<DistinctCounts> {
+    crate::fastfield::check_fast_field(self.inner.schema(), &field_name)?;
+
+    let collector = DistinctCollector { field_name };
+    let start = Instant::now();
+    let counts = self
+      .inner
+      .search(&query.inner, &collector)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    crate::metrics::record_search(start);
+
+    let distinct = counts.len() as u32;
+    let values = match limit {
+      Some(limit) => {
+        let mut values: Vec<DistinctValue> = counts
+          .into_values()
+          .map(|(value, count)| DistinctValue { value, count })
+          .collect();
+        values.sort_by(|a, b| {
+          b.count
+            .cmp(&a.count)
+            .then_with(|| a.value.to_string().cmp(&b.value.to_string()))
+        });
+        values.truncate(limit as usize);
+        values
+      }
+      None => Vec::new(),
+    };
+    Ok(DistinctCounts { distinct, values })
+  }
+
+  /// The bounds and a few statistics of a numeric fast field over the
+  /// documents matching a query, e.g. to set the bounds of a range slider.
+  ///
+  /// The values are read from the fast field column while collecting, which
+  /// is cheaper than a stats aggregation.
+  ///
+  /// @param fieldName - A fast field of type integer, unsigned, float, boolean or date.
+  /// @param query - The query selecting the documents, or null for every document.
+  ///
+  /// @returns FieldMinMax object.
+  #[napi]
+  pub fn field_min_max(&self, field_name: String, query: Option<&Query>) -> Result<FieldMinMax> {
+    crate::fastfield::check_numeric_fast_field(self.inner.schema(), &field_name)?;
+
+    let all_query = tv::query::AllQuery;
+    let query = query.map_or(&all_query as &dyn tv::query::Query, |query| query.get());
+    let collector = MinMaxCollector { field_name };
+    let start = Instant::now();
+    let stats = self
+      .inner
+      .search(query, &collector)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    crate::metrics::record_search(start);
+
+    Ok(FieldMinMax {
+      min: stats.min,
+      max: stats.max,
+      count: stats.count as u32,
+      sum: stats.sum,
+      mean: (stats.count > 0).then(|| stats.sum / stats.count as f64),
+    })
+  }
+
+  /// Read the value of a numeric fast field for a batch of documents.
+  ///
+  /// This is meant to enrich a page of hits (timestamps, prices...) without
+  /// fetching the stored documents. The column of every segment is opened once.
+  ///
+  /// @param fieldName - A fast field of type integer, unsigned, float, boolean or date.
+  /// @param docAddresses - The addresses of the documents, e.g. taken from `SearchResult.hits`.
+  ///
+  /// @returns A Float64Array holding the value of every document, in the order
+  ///         of `docAddresses`. Dates are milliseconds timestamps and booleans
+  ///         are 0 or 1. Documents without a value get `NaN`, for documents
+  ///         with several values the first one is returned.
+  #[napi]
+  pub fn fast_field_values_for_docs(
+    &self,
+    field_name: String,
+    doc_addresses: Vec<DocAddress>,
+  ) -> Result<Float64Array> {
+    let doc_addresses = doc_addresses
+      .iter()
+      .map(|doc_address| {
+        let doc_address = tv::DocAddress::from(doc_address);
+        self.check_doc_address(&doc_address)?;
+        Ok(doc_address)
+      })
+      .collect::<Result<Vec<_>>>()?;
+    let values = self.numeric_fast_field_values(&field_name, &doc_addresses)?;
+    Ok(Float64Array::new(values))
+  }
+
+  /// Read the term ordinal of a text fast field for a batch of documents,
+  /// e.g. to group or deduplicate hits on small integers rather than on
+  /// strings.
+  ///
+  /// Ordinals are shared by all the segments of this searcher and follow
+  /// the sorted order of the terms. They are only stable within this
+  /// searcher, and the first call for a field reads its whole dictionary.
+  ///
+  /// @param fieldName - A text fast field.
+  /// @param docAddresses - The addresses of the documents, e.g. taken from `SearchResult.hits`.
+  ///
+  /// @returns An Int32Array holding the ordinal of every document, in the
+  ///         order of `docAddresses`. Documents without a value get -1, for
+  ///         documents with several values the first one is returned.
+  #[napi]
+  pub fn term_ords(
+    &self,
+    field_name: String,
+    doc_addresses: Vec<DocAddress>,
+  ) -> Result<Int32Array> {
+    let doc_addresses = doc_addresses
+      .iter()
+      .map(|doc_address| {
+        let doc_address = tv::DocAddress::from(doc_address);
+        self.check_doc_address(&doc_address)?;
+        Ok(doc_address)
+      })
+      .collect::<Result<Vec<_>>>()?;
+    let ords = self
+      .term_ordinals(&field_name)?
+      .doc_ords(&self.inner, &field_name, &doc_addresses)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    Ok(Int32Array::new(
+      ords
+        .into_iter()
+        .map(|ord| ord.map_or(-1, |ord| ord as i32))
+        .collect(),
+    ))
+  }
+
+  /// The term of a text fast field having the given ordinal, see `termOrds`.
+  ///
+  /// @param fieldName - A text fast field.
+  /// @param ord - An ordinal returned by `termOrds` for this field.
+  #[napi]
+  pub fn ord_to_term(&self, field_name: String, ord: u32) -> Result<String> {
+    let term_ordinals = self.term_ordinals(&field_name)?;
+    match term_ordinals.term(ord) {
+      Some(term) => Ok(term.to_string()),
+      None => Err(Error::new(
+        Status::InvalidArg,
+        format!("Term ordinal {} is out of range.", ord),
+      )),
+    }
+  }
+
+  /// Map a document address to the single integer id used by `SearchHit.globalDoc`.
+  ///
+  /// Global ids are only stable within this searcher.
+  ///
+  /// @param docAddress - The address of the document.
+  #[napi]
+  pub fn global_doc(&self, doc_address: DocAddress) -> Result<u32> {
+    let doc_address = tv::DocAddress::from(&doc_address);
+    self.check_doc_address(&doc_address)?;
+    Ok(self.global_doc_id(&doc_address))
+  }
+
+  /// Map a global document id, as found in `SearchHit.globalDoc`, back to its address.
+  ///
+  /// @param globalDoc - The global id of the document, obtained from this searcher.
+  #[napi]
+  pub fn doc_address_for_global_doc(&self, global_doc: u32) -> Result<DocAddress> {
+    let mut base = 0;
+    for (segment_ord, segment_reader) in self.inner.segment_readers().iter().enumerate() {
+      let max_doc = segment_reader.max_doc();
+      if global_doc < base + max_doc {
+        return Ok(DocAddress {
+          segment_ord: segment_ord as u32,
+          doc: global_doc - base,
+        });
+      }
+      base += max_doc;
+    }
+    Err(Error::new(
+      Status::InvalidArg,
+      format!("Global doc {} is out of range.", global_doc),
+    ))
+  }
+
+  /// The names of the sub-queries, named with `Query.named`, matching a
+  /// document, in the order they appear in the query.
+  ///
+  /// Each named sub-query is checked on its own, so a named clause of a
+  /// boolean query is reported even if another clause excludes the document.
+  ///
+  /// @param query - The query containing named sub-queries.
+  /// @param docAddress - The address of the document.
+  #[napi]
+  pub fn matched_clauses(&self, query: &Query, doc_address: DocAddress) -> Result<Vec<String>> {
+    let doc_address = tv::DocAddress::from(&doc_address);
+    self.check_doc_address(&doc_address)?;
+    let names = match NamedMatcher::for_query(&self.inner, query.get()) {
+      Ok(Some(matcher)) => matcher.matched_names(&self.inner, doc_address),
+      Ok(None) => Ok(Vec::new()),
+      Err(e) => Err(e),
+    };
+    names.map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+  }
+
+  /// Returns the overall number of documents in the index.
+  #[napi(getter)]
+  pub fn num_docs(&self) -> u32 {
+    self.inner.num_docs() as u32
+  }
+
+  /// Returns the number of segments in the index.
+  #[napi(getter)]
+  pub fn num_segments(&self) -> u32 {
+    self.inner.segment_readers().len() as u32
+  }
+
+  /// Returns the number of deleted documents still held by the segments of
+  /// the index, until they are merged, see `Index.compact`.
+  #[napi(getter)]
+  pub fn num_deleted_docs(&self) -> u32 {
+    self
+      .inner
+      .segment_readers()
+      .iter()
+      .map(|segment_reader| segment_reader.num_deleted_docs())
+      .sum()
+  }
+
+  /// The document counts of every segment, in the order of the segments.
+  #[napi]
+  pub fn segments(&self) -> Vec<SegmentInfo> {
+    self
+      .inner
+      .segment_readers()
+      .iter()
+      .enumerate()
+      .map(|(segment_ord, segment_reader)| SegmentInfo {
+        segment_ord: segment_ord as u32,
+        segment_id: segment_reader.segment_id().uuid_string(),
+        max_doc: segment_reader.max_doc(),
+        num_docs: segment_reader.num_docs(),
+        num_deleted_docs: segment_reader.num_deleted_docs(),
+      })
+      .collect()
+  }
+
+  /// The index generation this searcher is pinned to.
+  ///
+  /// All calls made on this searcher observe this generation. A searcher is
+  /// stale when `Index.generation` has moved past it.
+  #[napi(getter)]
+  pub fn generation(&self) -> u32 {
+    self.inner.generation().generation_id() as u32
+  }
+
+  /// An identifier of the content this searcher sees, e.g. to key the
+  /// results cached by the application.
+  ///
+  /// It is a hash of the segments of the searcher and of the opstamps of
+  /// their deletes: unlike `generation`, it is the same in every process
+  /// and after a restart, and it only changes when a commit or a merge
+  /// changes the segments, not on every reload.
+  ///
+  /// @returns 16 hexadecimal digits.
+  #[napi]
+  pub fn generation_id(&self) -> String {
+    let segments = self.inner.generation().segments();
+    let hash = crate::fnv1a(segments.iter().flat_map(|(segment_id, delete_opstamp)| {
+      let delete_opstamp = delete_opstamp.unwrap_or(u64::MAX);
+      segment_id
+        .uuid_string()
+        .into_bytes()
+        .into_iter()
+        .chain(delete_opstamp.to_le_bytes())
+    }));
+    format!("{:016x}", hash)
+  }
+
+  /// Return the overall number of documents containing
+  /// the given term.
+  #[napi]
+  pub fn doc_freq(&self, field_name: String, field_value: Either<&Term, Unknown>) -> Result<u32> {
+    // Wrap the tantivy Searcher `doc_freq` method to return a Result.
+    let schema = self.inner.schema();
+    let term = Term::resolve(schema, &field_name, field_value, |value| {
+      crate::make_term(schema, &field_name, value)
+    })?;
+    self
+      .inner
+      .doc_freq(&term)
+      .map(|count| count as u32)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+  }
+
+  /// Search the documents similar to a document, such as related articles,
+  /// with a MoreLikeThisQuery built from its stored fields.
+  ///
+  /// @param docAddress - The address of the document.
+  /// @param options - See `SimilarDocumentsOptions`.
+  ///
+  /// @returns The hits, by decreasing score, the document itself excluded.
+  #[napi]
+  pub fn similar_documents(
+    &self,
+    doc_address: DocAddress,
+    options: Option<SimilarDocumentsOptions>,
+  ) -> Result<Vec<SearchHit>> {
+    let options = options.unwrap_or_default();
+    let limit = options.limit.unwrap_or(10) as usize;
+    let address = tv::DocAddress::from(&doc_address);
+
+    let mut builder = tv::query::MoreLikeThisQuery::builder();
+    if let Some(value) = options.min_doc_frequency {
+      builder = builder.with_min_doc_frequency(value as u64);
+    }
+    if let Some(value) = options.min_term_frequency {
+      builder = builder.with_min_term_frequency(value as usize);
+    }
+    if let Some(value) = options.max_query_terms {
+      builder = builder.with_max_query_terms(value as usize);
+    }
+    if let Some(stop_words) = options.stop_words {
+      builder = builder.with_stop_words(stop_words);
+    }
+    let query = match options.fields {
+      Some(field_names) => {
+        let schema = self.inner.schema();
+        let doc: TantivyDocument = self
+          .inner
+          .doc(address)
+          .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+        let doc_fields = field_names
+          .iter()
+          .map(|field_name| {
+            let field = crate::get_field(schema, field_name)?;
+            let values = doc
+              .get_all(field)
+              .map(tv::schema::OwnedValue::from)
+              .collect();
+            Ok((field, values))
+          })
+          .collect::<Result<Vec<_>>>()?;
+        builder.with_document_fields(doc_fields)
+      }
+      None => builder.with_document(address),
+    };
+
+    // One more hit, in case the document is among the best ones.
+    let collector = TopDocs::with_limit(limit + 1);
+    let (top_docs, _) = self.collect(&query, &collector, false)?;
+    let mut hits: Vec<SearchHit> = top_docs
+      .iter()
+      .filter(|(_, d)| *d != address)
+      .map(|(f, d)| SearchHit {
+        score: Some(*f as f64),
+        order: None,
+        doc_address: DocAddress::from(d),
+        global_doc: self.global_doc_id(d),
+        matched_queries: None,
+      })
+      .collect();
+    sort_hits(&mut hits, |hit| hit.score, Order::Desc);
+    hits.truncate(limit);
+    Ok(hits)
+  }
+
+  /// Fetches a document from Tantivy's store given a DocAddress.
+  ///
+  /// @param docAddress - The DocAddress that is associated with
+  ///         the document that we wish to fetch.
+  ///
+  /// @returns The Document, raises ValueError if the document can't be found.
+  #[napi]
+  pub fn doc(&self, doc_address: DocAddress) -> Result<Document> {
+    let cache_stats = self.inner.doc_store_cache_stats();
+    let doc: TantivyDocument = self
+      .inner
+      .doc((&doc_address).into())
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    crate::metrics::record_doc_store_cache(cache_stats, self.inner.doc_store_cache_stats());
+    let named_doc = doc.to_named_doc(self.inner.schema());
+    Ok(crate::document::Document {
+      field_values: named_doc.0,
+    })
+  }
+
+  /// Fetches a document as a plain object, without creating a `Document`.
+  ///
+  /// A field having a single value is mapped to that value, and a field
+  /// having several values to their list. Dates are milliseconds timestamps
+  /// and bytes are arrays of numbers.
+  ///
+  /// @param docAddress - The DocAddress that is associated with
+  ///         the document that we wish to fetch.
+  /// @param options - See `DocObjectOptions`.
+  ///
+  /// @returns The object, raises ValueError if the document can't be found.
+  #[napi]
+  pub fn doc_object(
+    &self,
+    doc_address: DocAddress,
+    options: Option<DocObjectOptions>,
+  ) -> Result<serde_json::Value> {
+    let fields = options.and_then(|options| options.fields);
+    Ok(self.doc(doc_address)?.to_json_object(fields.as_deref()))
+  }
+}
+
+impl Searcher {
+  /// Run a search, timing it per segment if `profile` is true.
+  pub(crate) fn collect<C: Collector>(
+    &self,
+    query: &dyn tv::query::Query,
+    collector: &C,
+    profile: bool,
+  ) -> Result<(C::Fruit, Option<SearchProfile>)> {
+    let start = Instant::now();
+    if !profile {
+      let fruit = self
+        .inner
+        .search(query, collector)
+        .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+      crate::metrics::record_search(start);
+      return Ok((fruit, None));
+    }
+    let (fruit, segments) = self
+      .inner
+      .search(query, &ProfiledCollector { inner: collector })
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    crate::metrics::record_search(start);
+    let profile = SearchProfile {
+      collect_ms: elapsed_ms(start),
+      fetch_ms: None,
+      segments,
+    };
+    Ok((fruit, Some(profile)))
+  }
+
+  /// Collect the documents matching a query while computing aggregations
+  /// over every document, in a single walk of the index.
+  fn collect_with_global_aggs<C: Collector>(
+    &self,
+    query: &dyn tv::query::Query,
+    collector: C,
+    global_aggs: tv::aggregation::agg_req::Aggregations,
+  ) -> Result<(C::Fruit, tv::aggregation::agg_result::AggregationResults)> {
+    let enable_scoring = if collector.requires_scoring() {
+      tv::query::EnableScoring::enabled_from_searcher(&self.inner)
+    } else {
+      tv::query::EnableScoring::disabled_from_searcher(&self.inner)
+    };
+    let scoped_collector = QueryScopedCollector {
+      weight: query
+        .weight(enable_scoring)
+        .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?,
+      inner: collector,
+    };
+    let global_collector = AggregationCollector::from_aggs(global_aggs, Default::default());
+    let (fruits, _) = self.collect(
+      &tv::query::AllQuery,
+      &(scoped_collector, global_collector),
+      false,
+    )?;
+    Ok(fruits)
+  }
+
+  /// Check that the fields of an aggregation request are fast fields, and
+  /// that the paths in JSON fields hold values in at least one segment.
+  fn check_aggregation_fields(&self, aggs: &tv::aggregation::agg_req::Aggregations) -> Result<()> {
+    let schema = self.inner.schema();
+    for field_name in tv::aggregation::agg_req::get_fast_field_names(aggs) {
+      let Some((field, path)) = schema.find_field(&field_name) else {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Field '{}' is not defined in the schema.", field_name),
+        ));
+      };
+      let field_entry = schema.get_field_entry(field);
+      if !field_entry.is_fast() {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("Field '{}' is not a fast field.", field_entry.name()),
+        ));
+      }
+      if path.is_empty() || self.inner.num_docs() == 0 {
+        continue;
+      }
+      let mut found = false;
+      for segment_reader in self.inner.segment_readers() {
+        let handles = segment_reader
+          .fast_fields()
+          .dynamic_column_handles(&field_name)
+          .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+        if !handles.is_empty() {
+          found = true;
+          break;
+        }
+      }
+      if !found {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!(
+            "Path '{}' of JSON field '{}' has no fast values in the index.",
+            path,
+            field_entry.name()
+          ),
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  /// The value of a numeric fast field for every document, see
+  /// `fastFieldValuesForDocs`.
+  fn numeric_fast_field_values(
+    &self,
+    field_name: &str,
+    doc_addresses: &[tv::DocAddress],
+  ) -> Result<Vec<f64>> {
+    crate::fastfield::check_numeric_fast_field(self.inner.schema(), field_name)?;
+
+    let mut readers: HashMap<u32, Option<FastFieldReader>> = HashMap::new();
+    let mut values = Vec::with_capacity(doc_addresses.len());
+    for doc_address in doc_addresses {
+      let reader = match readers.entry(doc_address.segment_ord) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => {
+          let segment_reader = self.inner.segment_reader(doc_address.segment_ord);
+          let reader = FastFieldReader::open(segment_reader, field_name)
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+          entry.insert(reader)
+        }
+      };
+      let value = reader
+        .as_ref()
+        .and_then(|reader| {
+          let raw = reader.raw_values(doc_address.doc_id).next()?;
+          reader.to_f64(raw)
+        })
+        .unwrap_or(f64::NAN);
+      values.push(value);
+    }
+    Ok(values)
+  }
+
+  /// Replace the scores of the first `RescoreOptions.topN` hits with those
+  /// returned by `RescoreOptions.callback` and sort them again, the other
+  /// hits following them.
+  fn rescore(&self, hits: &mut [SearchHit], rescore: &RescoreOptions) -> Result<()> {
+    let top_n = rescore_window(rescore).min(hits.len());
+    let rescored = &mut hits[..top_n];
+    let doc_addresses: Vec<tv::DocAddress> = rescored
+      .iter()
+      .map(|hit| tv::DocAddress::from(&hit.doc_address))
+      .collect();
+    let mut fields = HashMap::new();
+    for field_name in rescore.fields.iter().flatten() {
+      let values = self.numeric_fast_field_values(field_name, &doc_addresses)?;
+      fields.insert(field_name.clone(), Float64Array::new(values));
+    }
+    let batch = RescoreBatch {
+      scores: Float64Array::new(
+        rescored
+          .iter()
+          .map(|hit| hit.score.unwrap_or(0.0))
+          .collect(),
+      ),
+      global_docs: Uint32Array::new(rescored.iter().map(|hit| hit.global_doc).collect()),
+      fields,
+    };
+    let scores = match rescore.callback.call(batch)? {
+      Either::A(scores) => scores.to_vec(),
+      Either::B(scores) => scores,
+    };
+    if scores.len() != rescored.len() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!(
+          "The rescore callback returned {} scores for {} hits.",
+          scores.len(),
+          rescored.len()
+        ),
+      ));
+    }
+    for (hit, score) in rescored.iter_mut().zip(scores) {
+      hit.score = Some(score);
+    }
+    sort_hits(rescored, |hit| hit.score, Order::Desc);
+    Ok(())
+  }
+
+  /// The term ordinals of a text fast field, built on first use.
+  fn term_ordinals(&self, field_name: &str) -> Result<Arc<TermOrdinals>> {
+    let schema = self.inner.schema();
+    crate::fastfield::check_fast_field(schema, field_name)?;
+    let field = crate::get_field(schema, field_name)?;
+    if schema.get_field_entry(field).field_type().value_type() != tv::schema::Type::Str {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Fast field '{}' is not a text field.", field_name),
+      ));
+    }
+    let mut term_ordinals = self.term_ordinals.lock().unwrap();
+    if let Some(ordinals) = term_ordinals.get(field_name) {
+      return Ok(ordinals.clone());
+    }
+    let ordinals = TermOrdinals::build(&self.inner, field_name)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    let ordinals = Arc::new(ordinals);
+    term_ordinals.insert(field_name.to_string(), ordinals.clone());
+    Ok(ordinals)
+  }
+
+  /// Set the names of the named sub-queries matching every hit, if the
+  /// query has any.
+  fn set_matched_queries(
+    &self,
+    query: &dyn tv::query::Query,
+    hits: &mut [SearchHit],
+  ) -> Result<()> {
+    let matcher = NamedMatcher::for_query(&self.inner, query)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    let Some(matcher) = matcher else {
+      return Ok(());
+    };
+    for hit in hits {
+      let names = matcher
+        .matched_names(&self.inner, (&hit.doc_address).into())
+        .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+      hit.matched_queries = Some(names);
+    }
+    Ok(())
+  }
+
+  fn global_doc_id(&self, doc_address: &tv::DocAddress) -> u32 {
+    let base: u32 = self.inner.segment_readers()[..doc_address.segment_ord as usize]
+      .iter()
+      .map(|segment_reader| segment_reader.max_doc())
+      .sum();
+    base + doc_address.doc_id
+  }
+
+  fn check_doc_address(&self, doc_address: &tv::DocAddress) -> Result<()> {
+    let in_range = self
+      .inner
+      .segment_readers()
+      .get(doc_address.segment_ord as usize)
+      .is_some_and(|segment_reader| doc_address.doc_id < segment_reader.max_doc());
+    if !in_range {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Invalid document address {:?}.", doc_address),
+      ));
+    }
+    Ok(())
+  }
+}
+
+/// DocAddress contains all the necessary information to identify a document
+/// given a Searcher object.
+///
+/// It consists in an id identifying its segment, and its segment-local DocId.
+/// The id used for the segment is actually an ordinal in the list of segment
+/// hold by a Searcher.
+#[napi(object)]
+#[derive(Clone, Debug, Deserialize, PartialEq, PartialOrd, Eq, Ord, Serialize)]
+pub struct DocAddress {
+  pub segment_ord: u32,
+  pub doc: u32,
+}
+
+impl From<&tv::DocAddress> for DocAddress {
+  fn from(doc_address: &tv::DocAddress) -> Self {
+    DocAddress {
+      segment_ord: doc_address.segment_ord,
+      doc: doc_address.doc_id,
+    }
+  }
+}
+
+impl From<&DocAddress> for tv::DocAddress {
+  fn from(val: &DocAddress) -> Self {
+    tv::DocAddress {
+      segment_ord: val.segment_ord,
+      doc_id: val.doc,
+    }
+  }
+}
+
+/// The number of hits to rescore, see `RescoreOptions.topN`.
+fn rescore_window(rescore: &RescoreOptions) -> usize {
+  rescore.top_n.unwrap_or(100) as usize
+}
+
+/// Sort hits by a key in the given order, then by ascending document address.
+fn sort_hits(hits: &mut [SearchHit], key: impl Fn(&SearchHit) -> Option<f64>, order: Order) {
+  hits.sort_by(|a, b| {
+    let by_key = key(a).unwrap_or(0.0).total_cmp(&key(b).unwrap_or(0.0));
+    let by_key = match order {
+      Order::Asc => by_key,
+      Order::Desc => by_key.reverse(),
+    };
+    by_key.then_with(|| a.doc_address.cmp(&b.doc_address))
+  });
+}
+
+pub(crate) fn check_facet_field(
+  schema: &tv::schema::Schema,
+  field_name: &str,
+) -> Result<tv::schema::Field> {
+  let field = crate::get_field(schema, field_name)?;
+  if schema.get_field_entry(field).field_type().value_type() != tv::schema::Type::Facet {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Field '{}' is not a facet field.", field_name),
+    ));
+  }
+  Ok(field)
+}
+
+fn facet_root(request: &FacetRequest) -> Result<tv::schema::Facet> {
+  tv::schema::Facet::from_text(request.root.as_deref().unwrap_or("/"))
+    .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))
+}
+
+fn facet_counts(
+  request: &FacetRequest,
+  root: tv::schema::Facet,
+  facet_counts: tv::collector::FacetCounts,
+) -> FacetCounts {
+  let counts: Vec<(&tv::schema::Facet, u64)> = match request.top_k {
+    Some(top_k) => facet_counts.top_k(root.clone(), top_k as usize),
+    None => facet_counts.get(root.clone()).collect(),
+  };
+  FacetCounts {
+    field: request.field.clone(),
+    root: root.to_string(),
+    counts: counts
+      .into_iter()
+      .map(|(facet, count)| FacetCount {
+        facet: facet.to_string(),
+        count: count as u32,
+      })
+      .collect(),
+  }
+}
+
+fn parse_aggregations(agg: Unknown) -> Result<tv::aggregation::agg_req::Aggregations> {
+  // Convert the JS object to JSON string first
+  let agg_str = agg.coerce_to_string()?.into_utf8()?.into_owned()?;
+  serde_json::from_str(&agg_str).map_err(|e| {
+    Error::new(
+      Status::InvalidArg,
+      format!("Invalid aggregation JSON: {}", e),
+    )
+  })
+}
+
+/// Parse an aggregation request, splitting the aggregations scoped to the
+/// query from the ones flagged with `global: true`.
+fn parse_scoped_aggregations(
+  agg_str: &str,
+) -> Result<(
+  tv::aggregation::agg_req::Aggregations,
+  tv::aggregation::agg_req::Aggregations,
+)> {
+  let invalid = |e: serde_json::Error| {
+    Error::new(
+      Status::InvalidArg,
+      format!("Invalid aggregation JSON: {}", e),
+    )
+  };
+  let request: serde_json::Map<String, serde_json::Value> =
+    serde_json::from_str(agg_str).map_err(invalid)?;
+  let mut scoped = serde_json::Map::new();
+  let mut global = serde_json::Map::new();
+  for (name, mut aggregation) in request {
+    let is_global = match aggregation
+      .as_object_mut()
+      .and_then(|aggregation| aggregation.remove("global"))
+    {
+      None => false,
+      Some(serde_json::Value::Bool(is_global)) => is_global,
+      Some(_) => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!(
+            "The global flag of aggregation '{}' must be a boolean.",
+            name
+          ),
+        ))
+      }
+    };
+    if is_global {
+      global.insert(name, aggregation);
+    } else {
+      scoped.insert(name, aggregation);
+    }
+  }
+  Ok((
+    serde_json::from_value(serde_json::Value::Object(scoped)).map_err(invalid)?,
+    serde_json::from_value(serde_json::Value::Object(global)).map_err(invalid)?,
+  ))
+}
+
+/// The results of aggregations, by aggregation name.
+fn aggregation_results(
+  results: &tv::aggregation::agg_result::AggregationResults,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+  match serde_json::to_value(results) {
+    Ok(serde_json::Value::Object(results)) => Ok(results),
+    Ok(_) => Ok(serde_json::Map::new()),
+    Err(e) => Err(Error::new(Status::GenericFailure, e.to_string())),
+  }
+}
+
+/// Counts the documents matching each filter, and runs the sub-aggregations on them.
+struct FiltersCollector {
+  weights: Vec<(String, Box<dyn tv::query::Weight>)>,
+  aggs: Option<AggregationCollector>,
+}
+
+type AggregationSegmentFruit =
+  <<AggregationCollector as Collector>::Child as SegmentCollector>::Fruit;
+
+struct FilterBucket {
+  scorer: Box<dyn tv::query::Scorer>,
+  doc_count: u64,
+  aggs: Option<<AggregationCollector as Collector>::Child>,
+}
+
+struct FiltersSegmentCollector {
+  buckets: Vec<FilterBucket>,
+}
+
+impl Collector for FiltersCollector {
+  type Fruit = serde_json::Map<String, serde_json::Value>;
+  type Child = FiltersSegmentCollector;
+
+  fn for_segment(
+    &self,
+    segment_local_id: tv::SegmentOrdinal,
+    segment: &tv::SegmentReader,
+  ) -> tv::Result<Self::Child> {
+    let mut buckets = Vec::with_capacity(self.weights.len());
+    for (_, weight) in &self.weights {
+      buckets.push(FilterBucket {
+        scorer: weight.scorer(segment, 1.0)?,
+        doc_count: 0,
+        aggs: match &self.aggs {
+          Some(aggs) => Some(aggs.for_segment(segment_local_id, segment)?),
+          None => None,
+        },
+      });
+    }
+    Ok(FiltersSegmentCollector { buckets })
+  }
+
+  fn requires_scoring(&self) -> bool {
+    false
+  }
+
+  fn merge_fruits(
+    &self,
+    segment_fruits: Vec<Vec<(u64, Option<AggregationSegmentFruit>)>>,
+  ) -> tv::Result<Self::Fruit> {
+    let mut doc_counts = vec![0u64; self.weights.len()];
+    let mut agg_fruits: Vec<Vec<AggregationSegmentFruit>> =
+      (0..self.weights.len()).map(|_| Vec::new()).collect();
+    for segment_fruit in segment_fruits {
+      for (idx, (doc_count, agg_fruit)) in segment_fruit.into_iter().enumerate() {
+        doc_counts[idx] += doc_count;
+        agg_fruits[idx].extend(agg_fruit);
+      }
+    }
+
+    let mut buckets = serde_json::Map::new();
+    for (((name, _), doc_count), agg_fruits) in self.weights.iter().zip(doc_counts).zip(agg_fruits)
+    {
+      let mut bucket = serde_json::Map::new();
+      bucket.insert("doc_count".to_string(), doc_count.into());
+      if let Some(aggs) = &self.aggs {
+        let results = aggs.merge_fruits(agg_fruits)?;
+        if let serde_json::Value::Object(results) = serde_json::to_value(results)
+          .map_err(|e| tv::TantivyError::InternalError(e.to_string()))?
+        {
+          bucket.extend(results);
+        }
+      }
+      buckets.insert(name.clone(), serde_json::Value::Object(bucket));
+    }
+    Ok(buckets)
+  }
+}
+
+impl SegmentCollector for FiltersSegmentCollector {
+  type Fruit = Vec<(u64, Option<AggregationSegmentFruit>)>;
+
+  fn collect(&mut self, doc: tv::DocId, score: tv::Score) {
+    for bucket in &mut self.buckets {
+      if bucket.scorer.doc() < doc {
+        bucket.scorer.seek(doc);
+      }
+      if bucket.scorer.doc() == doc {
+        bucket.doc_count += 1;
+        if let Some(aggs) = &mut bucket.aggs {
+          aggs.collect(doc, score);
+        }
+      }
+    }
+  }
+
+  fn harvest(self) -> Self::Fruit {
+    self
+      .buckets
+      .into_iter()
+      .map(|bucket| (bucket.doc_count, bucket.aggs.map(|aggs| aggs.harvest())))
+      .collect()
+  }
+}
+
+/// Collects the top documents matching every filter, and counts the facets
+/// of the documents matching the filters applying to each facet.
+struct MultiSelectCollector {
+  filters: Vec<Box<dyn tv::query::Weight>>,
+  top_docs: TopDocs,
+  /// The facet collectors, with whether each filter applies to them.
+  facets: Vec<(Vec<bool>, FacetCollector)>,
+}
+
+type TopDocsSegmentFruit = <<TopDocs as Collector>::Child as SegmentCollector>::Fruit;
+
+struct MultiSelectSegmentCollector {
+  filters: Vec<Box<dyn tv::query::Scorer>>,
+  matched: Vec<bool>,
+  top_docs: <TopDocs as Collector>::Child,
+  count: u64,
+  facets: Vec<(Vec<bool>, <FacetCollector as Collector>::Child)>,
+}
+
+impl Collector for MultiSelectCollector {
+  type Fruit = (
+    Vec<(tv::Score, tv::DocAddress)>,
+    u64,
+    Vec<tv::collector::FacetCounts>,
+  );
+  type Child = MultiSelectSegmentCollector;
+
+  fn for_segment(
+    &self,
+    segment_local_id: tv::SegmentOrdinal,
+    segment: &tv::SegmentReader,
+  ) -> tv::Result<Self::Child> {
+    let mut filters = Vec::with_capacity(self.filters.len());
+    for weight in &self.filters {
+      filters.push(weight.scorer(segment, 1.0)?);
+    }
+    let mut facets = Vec::with_capacity(self.facets.len());
+    for (applied, facet_collector) in &self.facets {
+      facets.push((
+        applied.clone(),
+        facet_collector.for_segment(segment_local_id, segment)?,
+      ));
+    }
+    Ok(MultiSelectSegmentCollector {
+      matched: vec![false; filters.len()],
+      filters,
+      top_docs: self.top_docs.for_segment(segment_local_id, segment)?,
+      count: 0,
+      facets,
+    })
+  }
+
+  fn requires_scoring(&self) -> bool {
+    true
+  }
+
+  fn merge_fruits(
+    &self,
+    segment_fruits: Vec<(TopDocsSegmentFruit, u64, Vec<tv::collector::FacetCounts>)>,
+  ) -> tv::Result<Self::Fruit> {
+    let mut top_docs_fruits = Vec::with_capacity(segment_fruits.len());
+    let mut count = 0;
+    let mut facet_fruits: Vec<Vec<tv::collector::FacetCounts>> =
+      (0..self.facets.len()).map(|_| Vec::new()).collect();
+    for (top_docs, segment_count, facet_counts) in segment_fruits {
+      top_docs_fruits.push(top_docs);
+      count += segment_count;
+      for (idx, facet_counts) in facet_counts.into_iter().enumerate() {
+        facet_fruits[idx].push(facet_counts);
+      }
+    }
+    let facets = self
+      .facets
+      .iter()
+      .zip(facet_fruits)
+      .map(|((_, facet_collector), fruits)| facet_collector.merge_fruits(fruits))
+      .collect::<tv::Result<Vec<_>>>()?;
+    Ok((self.top_docs.merge_fruits(top_docs_fruits)?, count, facets))
+  }
+}
+
+impl SegmentCollector for MultiSelectSegmentCollector {
+  type Fruit = (TopDocsSegmentFruit, u64, Vec<tv::collector::FacetCounts>);
+
+  fn collect(&mut self, doc: tv::DocId, score: tv::Score) {
+    for (scorer, matched) in self.filters.iter_mut().zip(&mut self.matched) {
+      if scorer.doc() < doc {
+        scorer.seek(doc);
+      }
+      *matched = scorer.doc() == doc;
+    }
+    if self.matched.iter().all(|matched| *matched) {
+      self.top_docs.collect(doc, score);
+      self.count += 1;
+    }
+    for (applied, facet_collector) in &mut self.facets {
+      let selected = applied
+        .iter()
+        .zip(&self.matched)
+        .all(|(applied, matched)| !applied || *matched);
+      if selected {
+        facet_collector.collect(doc, score);
+      }
+    }
+  }
+
+  fn harvest(self) -> Self::Fruit {
+    (
+      self.top_docs.harvest(),
+      self.count,
+      self
+        .facets
+        .into_iter()
+        .map(|(_, facet_collector)| facet_collector.harvest())
+        .collect(),
+    )
+  }
+}
+
+/// Counts, for every distinct value of a fast field, the matching documents
+/// holding it. Values are keyed by their JSON serialization so that text
+/// values coming from different segments are merged correctly.
+struct DistinctCollector {
+  field_name: String,
+}
+
+type DistinctFruit = HashMap<String, (serde_json::Value, u32)>;
+
+struct DistinctSegmentCollector {
+  reader: Option<FastFieldReader>,
+  counts: HashMap<u64, u32>,
+}
+
+impl Collector for DistinctCollector {
+  type Fruit = DistinctFruit;
+  type Child = DistinctSegmentCollector;
+
+  fn for_segment(
+    &self,
+    _segment_local_id: tv::SegmentOrdinal,
+    segment: &tv::SegmentReader,
+  ) -> tv::Result<Self::Child> {
+    Ok(DistinctSegmentCollector {
+      reader: FastFieldReader::open(segment, &self.field_name)?,
+      counts: HashMap::new(),
+    })
+  }
+
+  fn requires_scoring(&self) -> bool {
+    false
+  }
+
+  fn merge_fruits(
+    &self,
+    segment_fruits: Vec<tv::Result<DistinctFruit>>,
+  ) -> tv::Result<Self::Fruit> {
+    let mut merged = DistinctFruit::new();
+    for segment_fruit in segment_fruits {
+      for (key, (value, count)) in segment_fruit? {
+        merged.entry(key).or_insert((value, 0)).1 += count;
+      }
+    }
+    Ok(merged)
+  }
+}
+
+impl SegmentCollector for DistinctSegmentCollector {
+  type Fruit = tv::Result<DistinctFruit>;
+
+  fn collect(&mut self, doc: tv::DocId, _score: tv::Score) {
+    if let Some(reader) = &self.reader {
+      let mut raw_values: Vec<u64> = reader.raw_values(doc).collect();
+      raw_values.sort_unstable();
+      raw_values.dedup();
+      for raw in raw_values {
+        *self.counts.entry(raw).or_insert(0) += 1;
+      }
+    }
+  }
+
+  fn harvest(self) -> Self::Fruit {
+    let mut fruit = DistinctFruit::new();
+    if let Some(reader) = &self.reader {
+      for (raw, count) in self.counts {
+        let value = reader.to_json(raw)?;
+        fruit.insert(value.to_string(), (value, count));
+      }
+    }
+    Ok(fruit)
+  }
+}
+
+/// Collector of the bounds, the count and the sum of the values of a numeric
+/// fast field.
+struct MinMaxCollector {
+  field_name: String,
+}
+
+#[derive(Default)]
+struct MinMaxFruit {
+  min: Option<f64>,
+  max: Option<f64>,
+  count: u64,
+  sum: f64,
+}
+
+impl MinMaxFruit {
+  fn add(&mut self, value: f64) {
+    self.min = Some(self.min.map_or(value, |min| min.min(value)));
+    self.max = Some(self.max.map_or(value, |max| max.max(value)));
+    self.count += 1;
+    self.sum += value;
+  }
+
+  fn merge(&mut self, other: MinMaxFruit) {
+    if let Some(min) = other.min {
+      self.min = Some(self.min.map_or(min, |own| own.min(min)));
+    }
+    if let Some(max) = other.max {
+      self.max = Some(self.max.map_or(max, |own| own.max(max)));
+    }
+    self.count += other.count;
+    self.sum += other.sum;
+  }
+}
+
+struct MinMaxSegmentCollector {
+  reader: Option<FastFieldReader>,
+  fruit: MinMaxFruit,
+}
+
+impl Collector for MinMaxCollector {
+  type Fruit = MinMaxFruit;
+  type Child = MinMaxSegmentCollector;
+
+  fn for_segment(
+    &self,
+    _segment_local_id: tv::SegmentOrdinal,
+    segment: &tv::SegmentReader,
+  ) -> tv::Result<Self::Child> {
+    Ok(MinMaxSegmentCollector {
+      reader: FastFieldReader::open(segment, &self.field_name)?,
+      fruit: MinMaxFruit::default(),
+    })
+  }
+
+  fn requires_scoring(&self) -> bool {
+    false
+  }
+
+  fn merge_fruits(&self, segment_fruits: Vec<MinMaxFruit>) -> tv::Result<Self::Fruit> {
+    let mut merged = MinMaxFruit::default();
+    for segment_fruit in segment_fruits {
+      merged.merge(segment_fruit);
+    }
+    Ok(merged)
+  }
+}
+
+impl SegmentCollector for MinMaxSegmentCollector {
+  type Fruit = MinMaxFruit;
+
+  fn collect(&mut self, doc: tv::DocId, _score: tv::Score) {
+    if let Some(reader) = &self.reader {
+      for raw in reader.raw_values(doc) {
+        if let Some(value) = reader.to_f64(raw) {
+          self.fruit.add(value);
+        }
+      }
+    }
+  }
+
+  fn harvest(self) -> Self::Fruit {
+    self.fruit
+  }
+}
+
+fn elapsed_ms(start: Instant) -> f64 {
+  start.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Collector timing the collection of each segment by another collector.
+struct ProfiledCollector<'a, C> {
+  inner: &'a C,
+}
+
+struct ProfiledSegmentCollector<S> {
+  inner: S,
+  profile: SegmentProfile,
+  start: Instant,
+}
+
+impl<C: Collector> Collector for ProfiledCollector<'_, C> {
+  type Fruit = (C::Fruit, Vec<SegmentProfile>);
+  type Child = ProfiledSegmentCollector<C::Child>;
+
+  fn for_segment(
+    &self,
+    segment_local_id: tv::SegmentOrdinal,
+    segment: &tv::SegmentReader,
+  ) -> tv::Result<Self::Child> {
+    let start = Instant::now();
+    Ok(ProfiledSegmentCollector {
+      inner: self.inner.for_segment(segment_local_id, segment)?,
+      profile: SegmentProfile {
+        segment_ord: segment_local_id,
+        max_doc: segment.max_doc(),
+        docs_collected: 0,
+        collect_ms: 0.0,
+      },
+      start,
+    })
+  }
+
+  fn requires_scoring(&self) -> bool {
+    self.inner.requires_scoring()
+  }
+
+  fn merge_fruits(
+    &self,
+    segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+  ) -> tv::Result<Self::Fruit> {
+    let (fruits, segments): (Vec<_>, Vec<_>) = segment_fruits.into_iter().unzip();
+    Ok((self.inner.merge_fruits(fruits)?, segments))
+  }
+}
+
+impl<S: SegmentCollector> SegmentCollector for ProfiledSegmentCollector<S> {
+  type Fruit = (S::Fruit, SegmentProfile);
+
+  fn collect(&mut self, doc: tv::DocId, score: tv::Score) {
+    self.profile.docs_collected += 1;
+    self.inner.collect(doc, score);
+  }
+
+  fn collect_block(&mut self, docs: &[tv::DocId]) {
+    self.profile.docs_collected += docs.len() as u32;
+    self.inner.collect_block(docs);
+  }
+
+  fn harvest(self) -> Self::Fruit {
+    let mut profile = self.profile;
+    profile.collect_ms = elapsed_ms(self.start);
+    (self.inner.harvest(), profile)
+  }
+}
+
+/// The type of the values of a sort field.
+fn sort_field_type(schema: &tv::schema::Schema, field_name: &str) -> Result<tv::schema::Type> {
+  let field = crate::get_field(schema, field_name)?;
+  Ok(schema.get_field_entry(field).field_type().value_type())
+}
+
+/// Where the documents without a value for the sort field go, see
+/// `SearchSort.missing`.
+#[derive(Clone, Copy)]
+enum MissingValues {
+  First,
+  Last,
+  Value(u64),
+}
+
+impl MissingValues {
+  fn parse(
+    field_name: &str,
+    value_type: tv::schema::Type,
+    missing: Option<&Either<String, f64>>,
+  ) -> Result<Self> {
+    match missing {
+      None => Ok(MissingValues::Last),
+      Some(Either::A(placement)) => match placement.as_str() {
+        "first" => Ok(MissingValues::First),
+        "last" => Ok(MissingValues::Last),
+        _ => Err(Error::new(
+          Status::InvalidArg,
+          "Invalid missing values placement, valid choices are: 'first', 'last' or a number",
+        )),
+      },
+      Some(Either::B(value)) => match crate::fastfield::f64_to_sort_value(value_type, *value) {
+        Some(value) => Ok(MissingValues::Value(value)),
+        None => Err(Error::new(
+          Status::InvalidArg,
+          format!(
+            "The missing value {} doesn't fit the type of sort field '{}'.",
+            value, field_name
+          ),
+        )),
+      },
+    }
+  }
+}
+
+/// The sort key of a document: whether it sorts before the documents with a
+/// value (2), is one of them (1) or sorts after them (0), then its value,
+/// reversed for an ascending order. Greater keys come first.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct FieldSortKey(u8, u64);
+
+impl FieldSortKey {
+  /// The value of the sort field the key was computed from, if any.
+  fn value(&self, order: Order) -> Option<u64> {
+    match (self.0, order) {
+      (1, Order::Desc) => Some(self.1),
+      (1, Order::Asc) => Some(u64::MAX - self.1),
+      _ => None,
+    }
+  }
+}
+
+/// Orders the documents by a fast field, placing the ones without a value
+/// as requested, where tantivy would give them a default value.
+struct FieldSortScorer {
+  field: String,
+  order: Order,
+  missing: MissingValues,
+}
+
+struct FieldSortSegmentScorer {
+  column: Option<tv::columnar::Column<u64>>,
+  order: Order,
+  missing: MissingValues,
+}
+
+impl tv::collector::CustomScorer<FieldSortKey> for FieldSortScorer {
+  type Child = FieldSortSegmentScorer;
+
+  fn segment_scorer(&self, segment_reader: &tv::SegmentReader) -> tv::Result<Self::Child> {
+    // Like tantivy, read the values of any numeric type as u64, the mapping
+    // being monotonic.
+    let column = segment_reader
+      .fast_fields()
+      .u64_lenient(&self.field)?
+      .map(|(column, _)| column);
+    Ok(FieldSortSegmentScorer {
+      column,
+      order: self.order,
+      missing: self.missing,
+    })
+  }
+}
+
+impl tv::collector::CustomSegmentScorer<FieldSortKey> for FieldSortSegmentScorer {
+  fn score(&mut self, doc: tv::DocId) -> FieldSortKey {
+    let value = match self.column.as_ref().and_then(|column| column.first(doc)) {
+      Some(value) => value,
+      None => match self.missing {
+        MissingValues::First => return FieldSortKey(2, 0),
+        MissingValues::Last => return FieldSortKey(0, 0),
+        MissingValues::Value(value) => value,
+      },
+    };
+    match self.order {
+      Order::Desc => FieldSortKey(1, value),
+      Order::Asc => FieldSortKey(1, u64::MAX - value),
+    }
+  }
+}
+
+/// Collector passing to another one only the documents scoring at least
+/// `min_score`, if any.
+struct MinScoreCollector<C> {
+  inner: C,
+  min_score: Option<tv::Score>,
+}
+
+struct MinScoreSegmentCollector<S> {
+  inner: S,
+  min_score: Option<tv::Score>,
+}
+
+impl<C: Collector> Collector for MinScoreCollector<C> {
+  type Fruit = C::Fruit;
+  type Child = MinScoreSegmentCollector<C::Child>;
+
+  fn for_segment(
+    &self,
+    segment_local_id: tv::SegmentOrdinal,
+    segment: &tv::SegmentReader,
+  ) -> tv::Result<Self::Child> {
+    Ok(MinScoreSegmentCollector {
+      inner: self.inner.for_segment(segment_local_id, segment)?,
+      min_score: self.min_score,
+    })
+  }
+
+  fn requires_scoring(&self) -> bool {
+    self.min_score.is_some() || self.inner.requires_scoring()
+  }
+
+  fn merge_fruits(
+    &self,
+    segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+  ) -> tv::Result<Self::Fruit> {
+    self.inner.merge_fruits(segment_fruits)
+  }
+}
+
+impl<S: SegmentCollector> SegmentCollector for MinScoreSegmentCollector<S> {
+  type Fruit = S::Fruit;
+
+  fn collect(&mut self, doc: tv::DocId, score: tv::Score) {
+    if self.min_score.is_none_or(|min_score| score >= min_score) {
+      self.inner.collect(doc, score);
+    }
+  }
+
+  fn collect_block(&mut self, docs: &[tv::DocId]) {
+    // Blocks are only collected without scoring, so without a minimum score.
+    self.inner.collect_block(docs);
+  }
+
+  fn harvest(self) -> Self::Fruit {
+    self.inner.harvest()
+  }
+}
+
+/// Collector passing to another one the documents matched before a deadline,
+/// if any.
+///
+/// Tantivy can't interrupt the matching of the documents of a segment, so
+/// the remaining ones are skipped rather than collected.
+struct TimeoutCollector<C> {
+  inner: C,
+  deadline: Option<Instant>,
+  timed_out: Arc<AtomicBool>,
+}
+
+struct TimeoutSegmentCollector<S> {
+  inner: S,
+  deadline: Option<Instant>,
+  timed_out: Arc<AtomicBool>,
+  /// Number of documents collected since the deadline was last checked.
+  unchecked: u32,
+}
+
+impl<S> TimeoutSegmentCollector<S> {
+  /// Whether the deadline has passed, checking the clock every few
+  /// documents only.
+  fn expired(&mut self, num_docs: u32) -> bool {
+    let Some(deadline) = self.deadline else {
+      return false;
+    };
+    if self.timed_out.load(Ordering::Relaxed) {
+      return true;
+    }
+    self.unchecked += num_docs;
+    if self.unchecked < 256 {
+      return false;
+    }
+    self.unchecked = 0;
+    if Instant::now() < deadline {
+      return false;
+    }
+    self.timed_out.store(true, Ordering::Relaxed);
+    true
+  }
+}
+
+impl<C: Collector> Collector for TimeoutCollector<C> {
+  type Fruit = C::Fruit;
+  type Child = TimeoutSegmentCollector<C::Child>;
+
+  fn for_segment(
+    &self,
+    segment_local_id: tv::SegmentOrdinal,
+    segment: &tv::SegmentReader,
+  ) -> tv::Result<Self::Child> {
+    if self
+      .deadline
+      .is_some_and(|deadline| Instant::now() >= deadline)
+    {
+      self.timed_out.store(true, Ordering::Relaxed);
+    }
+    Ok(TimeoutSegmentCollector {
+      inner: self.inner.for_segment(segment_local_id, segment)?,
+      deadline: self.deadline,
+      timed_out: self.timed_out.clone(),
+      unchecked: 0,
+    })
+  }
+
+  fn requires_scoring(&self) -> bool {
+    self.inner.requires_scoring()
+  }
+
+  fn merge_fruits(
+    &self,
+    segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+  ) -> tv::Result<Self::Fruit> {
+    self.inner.merge_fruits(segment_fruits)
+  }
+}
+
+impl<S: SegmentCollector> SegmentCollector for TimeoutSegmentCollector<S> {
+  type Fruit = S::Fruit;
+
+  fn collect(&mut self, doc: tv::DocId, score: tv::Score) {
+    if !self.expired(1) {
+      self.inner.collect(doc, score);
+    }
+  }
+
+  fn collect_block(&mut self, docs: &[tv::DocId]) {
+    if !self.expired(docs.len() as u32) {
+      self.inner.collect_block(docs);
+    }
+  }
+
+  fn harvest(self) -> Self::Fruit {
+    self.inner.harvest()
+  }
+}
+
+/// Collects the documents matching a query, with their scores, out of a
+/// search walking more documents.
+struct QueryScopedCollector<C> {
+  weight: Box<dyn tv::query::Weight>,
+  inner: C,
+}
+
+struct QueryScopedSegmentCollector<S> {
+  scorer: Box<dyn tv::query::Scorer>,
+  inner: S,
+}
+
+impl<C: Collector> Collector for QueryScopedCollector<C> {
+  type Fruit = C::Fruit;
+  type Child = QueryScopedSegmentCollector<C::Child>;
+
+  fn for_segment(
+    &self,
+    segment_local_id: tv::SegmentOrdinal,
+    segment: &tv::SegmentReader,
+  ) -> tv::Result<Self::Child> {
+    Ok(QueryScopedSegmentCollector {
+      scorer: self.weight.scorer(segment, 1.0)?,
+      inner: self.inner.for_segment(segment_local_id, segment)?,
+    })
+  }
+
+  fn requires_scoring(&self) -> bool {
+    // The scores are the ones of the query's scorer.
+    false
+  }
+
+  fn merge_fruits(
+    &self,
+    segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+  ) -> tv::Result<Self::Fruit> {
+    self.inner.merge_fruits(segment_fruits)
+  }
+}
+
+impl<S: SegmentCollector> SegmentCollector for QueryScopedSegmentCollector<S> {
+  type Fruit = S::Fruit;
+
+  fn collect(&mut self, doc: tv::DocId, _score: tv::Score) {
+    if self.scorer.doc() < doc {
+      self.scorer.seek(doc);
+    }
+    if self.scorer.doc() == doc {
+      self.inner.collect(doc, self.scorer.score());
+    }
+  }
+
+  fn harvest(self) -> Self::Fruit {
+    self.inner.harvest()
+  }
+}
+
+/// How the scores of the hits are rescaled, see `SearchOptions.normalizeScores`.
+#[derive(Clone, Copy, PartialEq)]
+enum ScoreNormalization {
+  None,
+  Max,
+  MinMax,
+}
+
+impl ScoreNormalization {
+  fn parse(mode: Option<&str>) -> Result<Self> {
+    match mode.unwrap_or("none") {
+      "none" => Ok(ScoreNormalization::None),
+      "max" => Ok(ScoreNormalization::Max),
+      "minmax" => Ok(ScoreNormalization::MinMax),
+      _ => Err(Error::new(
+        Status::InvalidArg,
+        "Invalid score normalization, valid choices are: 'max', 'minmax' and 'none'",
+      )),
+    }
+  }
+
+  /// Rescale a score given the lowest and the highest scores of the
+  /// matching documents.
+  fn apply(self, score: f64, range: Option<(f64, f64)>) -> f64 {
+    let Some((min, max)) = range else {
+      return score;
+    };
+    match self {
+      ScoreNormalization::None => score,
+      ScoreNormalization::Max if max > 0.0 => score / max,
+      ScoreNormalization::Max => score,
+      ScoreNormalization::MinMax if max > min => (score - min) / (max - min),
+      ScoreNormalization::MinMax => 1.0,
+    }
+  }
+}
+
+/// Collects the lowest and the highest scores of the matching documents.
+struct ScoreRangeCollector;
+
+struct ScoreRangeSegmentCollector {
+  range: Option<(f64, f64)>,
+}
+
+impl Collector for ScoreRangeCollector {
+  type Fruit = Option<(f64, f64)>;
+  type Child = ScoreRangeSegmentCollector;
+
+  fn for_segment(
+    &self,
+    _segment_local_id: tv::SegmentOrdinal,
+    _segment: &tv::SegmentReader,
+  ) -> tv::Result<Self::Child> {
+    Ok(ScoreRangeSegmentCollector { range: None })
+  }
+
+  fn requires_scoring(&self) -> bool {
+    true
+  }
+
+  fn merge_fruits(&self, segment_fruits: Vec<Option<(f64, f64)>>) -> tv::Result<Self::Fruit> {
+    Ok(
+      segment_fruits
+        .into_iter()
+        .flatten()
+        .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b))),
+    )
+  }
+}
+
+impl SegmentCollector for ScoreRangeSegmentCollector {
+  type Fruit = Option<(f64, f64)>;
+
+  fn collect(&mut self, _doc: tv::DocId, score: tv::Score) {
+    let score = score as f64;
+    self.range = Some(match self.range {
+      Some((min, max)) => (min.min(score), max.max(score)),
+      None => (score, score),
+    });
+  }
+
+  fn harvest(self) -> Self::Fruit {
+    self.range
+  }
+}
+
+/// Collects the first `offset + limit` matching documents in doc id order,
+/// without scoring them.
+struct DocIdOrderCollector {
+  limit: usize,
+  offset: usize,
+}
+
+struct DocIdOrderSegmentCollector {
+  segment_ord: tv::SegmentOrdinal,
+  max_docs: usize,
+  docs: Vec<tv::DocAddress>,
+}
+
+impl Collector for DocIdOrderCollector {
+  type Fruit = Vec<tv::DocAddress>;
+  type Child = DocIdOrderSegmentCollector;
+
+  fn for_segment(
+    &self,
+    segment_local_id: tv::SegmentOrdinal,
+    _segment: &tv::SegmentReader,
+  ) -> tv::Result<Self::Child> {
+    Ok(DocIdOrderSegmentCollector {
+      segment_ord: segment_local_id,
+      max_docs: self.offset + self.limit,
+      docs: Vec::new(),
+    })
+  }
+
+  fn requires_scoring(&self) -> bool {
+    false
+  }
+
+  fn merge_fruits(&self, segment_fruits: Vec<Vec<tv::DocAddress>>) -> tv::Result<Self::Fruit> {
+    let mut docs: Vec<tv::DocAddress> = segment_fruits.into_iter().flatten().collect();
+    docs.sort();
+    Ok(
+      docs
+        .into_iter()
+        .skip(self.offset)
+        .take(self.limit)
+        .collect(),
+    )
+  }
+}
+
+impl SegmentCollector for DocIdOrderSegmentCollector {
+  type Fruit = Vec<tv::DocAddress>;
+
+  fn collect(&mut self, doc: tv::DocId, _score: tv::Score) {
+    if self.docs.len() < self.max_docs {
+      self.docs.push(tv::DocAddress::new(self.segment_ord, doc));
+    }
+  }
+
+  fn harvest(self) -> Self::Fruit {
+    self.docs
   }
 }
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+use napi::bindgen_prelude::*;
+use napi::{Env, Error, JsDeferred, Result, Status};
+use napi_derive::napi;
+use tantivy as tv;
+
+use crate::{
+  document::Document,
+  index::{Index, IndexWriterOptions},
+  query::Query,
+  term::Term,
+  to_napi_error,
+};
+
+/// An operation run by the thread owning an index writer.
+enum Operation {
+  Run(Vec<tv::indexer::UserOperation>),
+  DeleteQuery(Box<dyn tv::query::Query>),
+  Commit,
+  Rollback,
+}
+
+/// Settles the promise of an operation, from the thread running it.
+type Reply = JsDeferred<BigInt, Box<dyn FnOnce(Env) -> Result<BigInt>>>;
+
+/// The queue of operations of an index writer, run one at a time, in order,
+/// by a thread owning the writer.
+struct WriterQueue {
+  operations: Sender<(Operation, Reply)>,
+  schema: tv::schema::Schema,
+  unique_field: Option<tv::schema::Field>,
+}
+
+impl WriterQueue {
+  fn start(
+    mut writer: tv::IndexWriter,
+    schema: tv::schema::Schema,
+    unique_field: Option<tv::schema::Field>,
+  ) -> Self {
+    let (operations, received) = channel::<(Operation, Reply)>();
+    std::thread::spawn(move || {
+      // The loop ends when every handle of the queue has been dropped.
+      for (operation, reply) in received {
+        let result = match operation {
+          Operation::Run(operations) => {
+            crate::metrics::record_operations(&operations);
+            writer.run(operations)
+          }
+          Operation::DeleteQuery(query) => writer.delete_query(query),
+          Operation::Commit => writer.commit().inspect(|_| crate::metrics::record_commit()),
+          Operation::Rollback => writer.rollback(),
+        };
+        match result {
+          Ok(opstamp) => reply.resolve(Box::new(move |_| Ok(BigInt::from(opstamp)))),
+          Err(error) => reply.reject(to_napi_error(error)),
+        }
+      }
+      let _ = writer.wait_merging_threads();
+    });
+    WriterQueue {
+      operations,
+      schema,
+      unique_field,
+    }
+  }
+
+  /// Queue an operation, returning a promise settled once it has run.
+  fn push<'env>(&self, env: &'env Env, operation: Operation) -> Result<Object<'env>> {
+    let (reply, promise) = env.create_deferred()?;
+    self
+      .operations
+      .send((operation, reply))
+      .map_err(|_| Error::new(Status::GenericFailure, "The index writer has stopped"))?;
+    Ok(promise)
+  }
+}
+
+/// The queues of the indexes stored on disk, by directory.
+fn shared_queues() -> &'static Mutex<HashMap<PathBuf, Weak<WriterQueue>>> {
+  static QUEUES: OnceLock<Mutex<HashMap<PathBuf, Weak<WriterQueue>>>> = OnceLock::new();
+  QUEUES.get_or_init(Default::default)
+}
+
+/// An index writer whose operations are queued and run one at a time, in
+/// the order they were made.
+///
+/// Every method returns a promise resolved with the `opstamp` of the
+/// operation once it has run, so that concurrent requests of a server can
+/// write to the index without coordinating. The writer is created by
+/// `Index.serializedWriter()`.
+#[napi]
+pub struct SerializedWriter {
+  queue: Option<Arc<WriterQueue>>,
+}
+
+impl SerializedWriter {
+  pub(crate) fn open(index: &Index, options: IndexWriterOptions) -> Result<Self> {
+    let Some(path) = &index.path else {
+      return Ok(SerializedWriter {
+        queue: Some(Arc::new(Self::start(index, options)?)),
+      });
+    };
+    let mut queues = shared_queues().lock().unwrap();
+    if let Some(queue) = queues.get(path).and_then(Weak::upgrade) {
+      return Ok(SerializedWriter { queue: Some(queue) });
+    }
+    let queue = Arc::new(Self::start(index, options)?);
+    queues.retain(|_, queue| queue.strong_count() > 0);
+    queues.insert(path.clone(), Arc::downgrade(&queue));
+    Ok(SerializedWriter { queue: Some(queue) })
+  }
+
+  fn start(index: &Index, options: IndexWriterOptions) -> Result<WriterQueue> {
+    let mut writer = index.create_writer(options)?;
+    let inner = writer.take_inner()?;
+    Ok(WriterQueue::start(
+      inner,
+      writer.schema.clone(),
+      writer.unique_field,
+    ))
+  }
+
+  fn queue(&self) -> Result<&WriterQueue> {
+    self.queue.as_deref().ok_or_else(|| {
+      Error::new(
+        Status::GenericFailure,
+        "SerializedWriter was closed and no longer in a valid state",
+      )
+    })
+  }
+}
+
+#[napi]
+impl SerializedWriter {
+  /// Queue the addition of a document.
+  ///
+  /// With a unique field, see `IndexWriterOptions.uniqueField`, the documents
+  /// having the same value are deleted first.
+  ///
+  /// @param doc - The document to add.
+  ///
+  /// @returns A promise resolved with the `opstamp` of the addition.
+  #[napi(ts_return_type = "Promise<bigint>")]
+  pub fn add_document<'env>(&self, env: &'env Env, doc: &Document) -> Result<Object<'env>> {
+    let queue = self.queue()?;
+    let doc = crate::index::convert_document(&queue.schema, doc)?;
+    let mut operations = Vec::with_capacity(2);
+    crate::index::push_add_operations(&queue.schema, queue.unique_field, doc, &mut operations)?;
+    queue.push(env, Operation::Run(operations))
+  }
+
+  /// Queue the deletion of the documents containing a term, see
+  /// `IndexWriter.deleteDocumentsByTerm`.
+  ///
+  /// @param fieldName - The field name for which we want to filter deleted docs.
+  /// @param fieldValue - JavaScript value with the value we want to filter.
+  ///
+  /// @returns A promise resolved with the `opstamp` of the deletion.
+  #[napi(ts_return_type = "Promise<bigint>")]
+  pub fn delete_documents_by_term<'env>(
+    &self,
+    env: &'env Env,
+    field_name: String,
+    field_value: Either<&Term, Unknown>,
+  ) -> Result<Object<'env>> {
+    let queue = self.queue()?;
+    let term = Term::resolve(&queue.schema, &field_name, field_value, |value| {
+      crate::make_term(&queue.schema, &field_name, value)
+    })?;
+    match crate::nested::term_deletion_query(&queue.schema, &term) {
+      Some(query) => queue.push(env, Operation::DeleteQuery(query)),
+      None => queue.push(
+        env,
+        Operation::Run(vec![tv::indexer::UserOperation::Delete(term)]),
+      ),
+    }
+  }
+
+  /// Queue the deletion of the documents matching a query.
+  ///
+  /// @param query - The query to filter the deleted documents.
+  ///
+  /// @returns A promise resolved with the `opstamp` of the deletion.
+  #[napi(ts_return_type = "Promise<bigint>")]
+  pub fn delete_documents_by_query<'env>(
+    &self,
+    env: &'env Env,
+    query: &Query,
+  ) -> Result<Object<'env>> {
+    let queue = self.queue()?;
+    let query = crate::nested::deletion_query(&queue.schema, query.inner.box_clone());
+    queue.push(env, Operation::DeleteQuery(query))
+  }
+
+  /// Queue a commit of the operations queued before it.
+  ///
+  /// @returns A promise resolved with the `opstamp` of the last operation
+  ///         that made it in the commit.
+  #[napi(ts_return_type = "Promise<bigint>")]
+  pub fn commit<'env>(&self, env: &'env Env) -> Result<Object<'env>> {
+    self.queue()?.push(env, Operation::Commit)
+  }
+
+  /// Queue a rollback of the operations queued since the last commit.
+  ///
+  /// @returns A promise resolved with the `opstamp` of the last commit.
+  #[napi(ts_return_type = "Promise<bigint>")]
+  pub fn rollback<'env>(&self, env: &'env Env) -> Result<Object<'env>> {
+    self.queue()?.push(env, Operation::Rollback)
+  }
+
+  /// Release this writer.
+  ///
+  /// The queued operations still run. The underlying index writer is dropped,
+  /// releasing the lock of the index, once every `SerializedWriter` sharing it
+  /// is closed or garbage collected. Further calls raise an error.
+  #[napi]
+  pub fn close(&mut self) {
+    self.queue = None;
+  }
+}
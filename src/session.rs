@@ -0,0 +1,154 @@
+use napi::bindgen_prelude::*;
+use napi::Result;
+use napi_derive::napi;
+use tantivy as tv;
+
+use crate::{
+  document::Document,
+  index::IndexWriter,
+  query::Query,
+  searcher::{Order, SearchOptions, SearchResult, Searcher},
+  term::Term,
+  to_napi_error,
+};
+
+/// An index writer and a reader whose searches see the writes made before
+/// them.
+///
+/// Before a search, the pending operations are committed and the reader of
+/// the index is reloaded. Searches following each other without writes in
+/// between cost no more than on a plain searcher, but every search following
+/// a write costs a commit: this is meant for tests and small applications,
+/// while bulk indexing is best done with an `IndexWriter` committing in
+/// batches. The session is created by `Index.session()`.
+///
+/// Example:
+/// ```javascript
+/// const session = index.session();
+/// session.add(Document.fromDict({ title: "The Old Man and the Sea" }));
+/// session.search(index.parseQuery("sea")).count; // 1
+/// ```
+#[napi]
+pub struct IndexSession {
+  writer: IndexWriter,
+  reader: tv::IndexReader,
+  /// True if operations were made since the last commit.
+  pending: bool,
+}
+
+impl IndexSession {
+  pub(crate) fn new(writer: IndexWriter, reader: tv::IndexReader) -> Self {
+    IndexSession {
+      writer,
+      reader,
+      pending: false,
+    }
+  }
+}
+
+#[napi]
+impl IndexSession {
+  /// Add a document, see `IndexWriter.addDocument`.
+  ///
+  /// @param doc - The document to add.
+  ///
+  /// @returns The `opstamp` of the addition.
+  #[napi]
+  pub fn add(&mut self, doc: &Document) -> Result<u64> {
+    let opstamp = self.writer.add_document(doc)?;
+    self.pending = true;
+    Ok(opstamp)
+  }
+
+  /// Delete the documents containing a term, see
+  /// `IndexWriter.deleteDocumentsByTerm`.
+  ///
+  /// @param fieldName - The field name for which we want to filter deleted docs.
+  /// @param fieldValue - JavaScript value with the value we want to filter.
+  ///
+  /// @returns The `opstamp` of the deletion.
+  #[napi]
+  pub fn delete_documents_by_term(
+    &mut self,
+    field_name: String,
+    field_value: Either<&Term, Unknown>,
+  ) -> Result<u64> {
+    let opstamp = self
+      .writer
+      .delete_documents_by_term(field_name, field_value)?;
+    self.pending = true;
+    Ok(opstamp)
+  }
+
+  /// Delete the documents matching a query.
+  ///
+  /// @param query - The query to filter the deleted documents.
+  ///
+  /// @returns The `opstamp` of the deletion.
+  #[napi]
+  pub fn delete_documents_by_query(&mut self, query: &Query) -> Result<u64> {
+    let opstamp = self.writer.delete_documents_by_query(query)?;
+    self.pending = true;
+    Ok(opstamp)
+  }
+
+  /// Commit the pending operations, if any, and reload the reader so that
+  /// they are visible to the searchers of the index.
+  ///
+  /// Searches made through the session do it on their own.
+  #[napi]
+  pub fn refresh(&mut self) -> Result<()> {
+    if self.pending {
+      self.writer.commit(None)?;
+      self.reader.reload().map_err(to_napi_error)?;
+      self.pending = false;
+    }
+    Ok(())
+  }
+
+  /// Cancel the operations made since the last refresh.
+  ///
+  /// @returns The `opstamp` of the last commit.
+  #[napi]
+  pub fn rollback(&mut self) -> Result<u64> {
+    let opstamp = self.writer.rollback()?;
+    self.pending = false;
+    Ok(opstamp)
+  }
+
+  /// A searcher seeing every operation made so far.
+  #[napi]
+  pub fn searcher(&mut self) -> Result<Searcher> {
+    self.refresh()?;
+    Ok(Searcher::new(self.reader.searcher()))
+  }
+
+  /// Search the index, every operation made so far included, see
+  /// `Searcher.search`.
+  ///
+  /// @returns SearchResult object.
+  #[napi]
+  #[allow(clippy::too_many_arguments)]
+  pub fn search(
+    &mut self,
+    query: &Query,
+    limit: Option<u32>,
+    count: Option<bool>,
+    order_by_field: Option<String>,
+    offset: Option<u32>,
+    order: Option<Order>,
+    options: Option<SearchOptions>,
+  ) -> Result<SearchResult> {
+    self
+      .searcher()?
+      .search(query, limit, count, order_by_field, offset, order, options)
+  }
+
+  /// Commit the pending operations and release the writer, see
+  /// `IndexWriter.waitMergingThreads`. Further calls raise an error.
+  #[napi]
+  pub fn close(&mut self) -> Result<()> {
+    self.refresh()?;
+    self.writer.wait_merging_threads()
+  }
+}
@@ -1,8 +1,17 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use tantivy as tv;
 // Bring the trait into scope to use methods like `as_str()` on `OwnedValue`.
 use tantivy::schema::Value;
+use tantivy::tokenizer::{TextAnalyzer as TvTextAnalyzer, Token, TokenStream, Tokenizer};
+
+use crate::token_filter::VecTokenStream;
+
+/// Maximum number of characters of a snippet, unless set otherwise.
+const DEFAULT_MAX_NUM_CHARS: usize = 150;
 
 /// Tantivy Snippet
 ///
@@ -11,6 +20,8 @@ use tantivy::schema::Value;
 #[napi]
 pub struct Snippet {
   pub(crate) inner: tv::snippet::Snippet,
+  pub(crate) field_name: String,
+  pub(crate) score: f64,
 }
 
 #[napi(object)]
@@ -43,12 +54,35 @@ impl Snippet {
   pub fn fragment(&self) -> Result<String> {
     Ok(self.inner.fragment().to_string())
   }
+
+  /// The field the snippet was generated from.
+  #[napi(getter)]
+  pub fn field_name(&self) -> String {
+    self.field_name.clone()
+  }
+
+  /// Relevance of the fragment: the sum of the weights of the highlighted
+  /// terms, rarer terms weighing more. 0 if nothing is highlighted.
+  #[napi(getter)]
+  pub fn score(&self) -> f64 {
+    self.score
+  }
 }
 
 #[napi]
 pub struct SnippetGenerator {
   pub(crate) field_name: String,
   pub(crate) inner: tv::snippet::SnippetGenerator,
+  field: tv::schema::Field,
+  /// Weight of the query terms, computed like tantivy does to pick fragments.
+  term_scores: BTreeMap<String, f64>,
+  strip_html: bool,
+  max_num_chars: usize,
+  /// The analyzer the field is indexed with.
+  index_analyzer: TvTextAnalyzer,
+  /// The analyzer splitting the text into highlighted spans, if not the
+  /// one of the field, see `setAnalyzer`.
+  span_analyzer: Option<TvTextAnalyzer>,
 }
 
 #[napi]
@@ -68,13 +102,78 @@ impl SnippetGenerator {
     })?;
     let generator = tv::snippet::SnippetGenerator::create(&searcher.inner, query.get(), field)
       .map_err(|e| Error::new(napi::Status::GenericFailure, e.to_string()))?;
+    let index_analyzer = searcher
+      .inner
+      .index()
+      .tokenizer_for_field(field)
+      .map_err(|e| Error::new(napi::Status::GenericFailure, e.to_string()))?;
+
+    let mut terms = Vec::new();
+    query.get().query_terms(&mut |term, _| {
+      if term.field() == field {
+        terms.push(term.clone());
+      }
+    });
+    let mut term_scores = BTreeMap::new();
+    for term in terms {
+      if let Some(text) = term.value().as_str() {
+        let doc_freq = searcher
+          .inner
+          .doc_freq(&term)
+          .map_err(|e| Error::new(napi::Status::GenericFailure, e.to_string()))?;
+        if doc_freq > 0 {
+          term_scores.insert(text.to_string(), 1.0 / (1.0 + doc_freq as f64));
+        }
+      }
+    }
 
     Ok(SnippetGenerator {
       field_name,
       inner: generator,
+      field,
+      term_scores,
+      strip_html: false,
+      max_num_chars: DEFAULT_MAX_NUM_CHARS,
+      index_analyzer,
+      span_analyzer: None,
     })
   }
 
+  /// Generate a snippet for each of several fields of a document, and return
+  /// the one with the highest score.
+  ///
+  /// @param searcher - The searcher the query is run on.
+  /// @param query - The query whose terms are highlighted.
+  /// @param schema - The schema of the index.
+  /// @param doc - The document to generate the snippets from.
+  /// @param fieldNames - The candidate fields. On equal scores, the first
+  ///         field wins.
+  /// @param maxNumChars - Maximum number of characters of the snippets.
+  ///
+  /// @returns The best snippet, or undefined if no field is given.
+  #[napi]
+  pub fn best_field(
+    searcher: &crate::Searcher,
+    query: &crate::Query,
+    schema: &crate::Schema,
+    doc: &crate::Document,
+    field_names: Vec<String>,
+    max_num_chars: Option<u32>,
+  ) -> Result<Option<Snippet>> {
+    let mut best: Option<Snippet> = None;
+    for field_name in field_names {
+      let mut generator = SnippetGenerator::create(searcher, query, schema, field_name)?;
+      if let Some(max_num_chars) = max_num_chars {
+        generator.set_max_num_chars(max_num_chars);
+      }
+      let snippet = generator.snippet_from_doc(doc);
+      if best.as_ref().is_none_or(|best| snippet.score > best.score) {
+        best = Some(snippet);
+      }
+    }
+    Ok(best)
+  }
+
   #[napi]
   pub fn snippet_from_doc(&self, doc: &crate::Document) -> crate::Snippet {
     let text: String = doc
@@ -82,13 +181,129 @@ impl SnippetGenerator {
       .flat_map(|ov| ov.as_str())
       .collect::<Vec<&str>>()
       .join(" ");
+    // Markup is stripped as the `html_strip` character filter does, and the
+    // runs of whitespace it leaves collapsed.
+    let text = if self.strip_html {
+      crate::char_filter::html_text(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+    } else {
+      text
+    };
 
     let result = self.inner.snippet(&text);
-    Snippet { inner: result }
+    let score = result
+      .highlighted()
+      .iter()
+      .filter_map(|range| self.span_score(result.fragment().get(range.clone())?))
+      .sum();
+    Snippet {
+      inner: result,
+      field_name: self.field_name.clone(),
+      score,
+    }
   }
 
   #[napi]
   pub fn set_max_num_chars(&mut self, max_num_chars: u32) {
-    self.inner.set_max_num_chars(max_num_chars as usize);
+    self.max_num_chars = max_num_chars as usize;
+    self.inner.set_max_num_chars(self.max_num_chars);
+  }
+
+  /// Split the text into highlighted spans with another analyzer than the
+  /// one the field is indexed with, e.g. one without stemming or keeping
+  /// hyphenated words whole, so that the highlighted spans are exactly the
+  /// words of the text.
+  ///
+  /// A span is highlighted if the analyzer of the field turns it into one of
+  /// the terms of the query, so `running` is still highlighted for a query
+  /// on `run` when the field is stemmed.
+  ///
+  /// @param analyzer - The analyzer of the spans, or null to use the one of
+  ///         the field again.
+  #[napi]
+  pub fn set_analyzer(&mut self, analyzer: Option<&crate::tokenizer::TextAnalyzer>) {
+    self.span_analyzer = analyzer.map(|analyzer| analyzer.analyzer.clone());
+    let tokenizer = match &self.span_analyzer {
+      Some(span_analyzer) => TvTextAnalyzer::from(SpanTokenizer {
+        spans: span_analyzer.clone(),
+        terms: self.index_analyzer.clone(),
+        query_terms: Arc::new(self.term_scores.keys().cloned().collect()),
+      }),
+      None => self.index_analyzer.clone(),
+    };
+    let terms_text = self
+      .term_scores
+      .iter()
+      .map(|(term, score)| (term.clone(), *score as tv::Score))
+      .collect();
+    self.inner =
+      tv::snippet::SnippetGenerator::new(terms_text, tokenizer, self.field, self.max_num_chars);
+  }
+
+  /// Strip the HTML markup of the field before computing the snippets.
+  ///
+  /// Tags, comments and the content of `script` and `style` elements are
+  /// removed and entities are decoded, so that fragments never cut through a
+  /// tag and `toHtml` escapes the text only once. The fragments and the
+  /// highlighted ranges are then those of the text without markup.
+  ///
+  /// @param strip - Whether to strip the markup. Defaults to false.
+  #[napi]
+  pub fn set_strip_html(&mut self, strip: bool) {
+    self.strip_html = strip;
+  }
+}
+
+impl SnippetGenerator {
+  /// Weight of the query term a highlighted span stands for, found by
+  /// running the span through the analyzer of the field as the scores are
+  /// keyed by the terms of the query.
+  fn span_score(&self, span: &str) -> Option<f64> {
+    let mut score = None;
+    self
+      .index_analyzer
+      .clone()
+      .token_stream(span)
+      .process(&mut |token| {
+        score = score.or_else(|| self.term_scores.get(&token.text).copied());
+      });
+    score
+  }
+}
+
+/// Tokenizer giving the spans of one analyzer the terms of another: every
+/// span is turned into the first query term the other analyzer produces for
+/// it, or into an empty token if there is none.
+#[derive(Clone)]
+struct SpanTokenizer {
+  spans: TvTextAnalyzer,
+  terms: TvTextAnalyzer,
+  query_terms: Arc<BTreeSet<String>>,
+}
+
+impl Tokenizer for SpanTokenizer {
+  type TokenStream<'a> = VecTokenStream;
+
+  fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+    let mut tokens = Vec::new();
+    let mut spans = self.spans.token_stream(text);
+    while let Some(span) = spans.next() {
+      let mut term = None;
+      let span_text = text
+        .get(span.offset_from..span.offset_to)
+        .unwrap_or_default();
+      self.terms.token_stream(span_text).process(&mut |token| {
+        if term.is_none() && self.query_terms.contains(&token.text) {
+          term = Some(token.text.clone());
+        }
+      });
+      tokens.push(Token {
+        text: term.unwrap_or_default(),
+        ..span.clone()
+      });
+    }
+    VecTokenStream::new(tokens)
   }
 }
@@ -0,0 +1,204 @@
+use tantivy as tv;
+use tantivy::postings::{Postings, SegmentPostings};
+use tantivy::query::{Bm25Weight, EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{DocId, DocSet, Score, SegmentReader, TERMINATED};
+
+/// Ordered proximity query, matching the documents where the terms appear in
+/// order, with at most `slop` other positions between the first and the last.
+///
+/// Unlike the slop of a PhraseQuery, which also lets the terms swap places at
+/// a cost, the terms must keep their order: `a b` with slop 1 matches
+/// `a x b` but never `b a`.
+#[derive(Clone, Debug)]
+pub(crate) struct SpanNearQuery {
+  field: Field,
+  terms: Vec<tv::Term>,
+  slop: u32,
+}
+
+impl SpanNearQuery {
+  pub(crate) fn new(field: Field, terms: Vec<tv::Term>, slop: u32) -> Self {
+    SpanNearQuery { field, terms, slop }
+  }
+
+  pub(crate) fn terms(&self) -> &[tv::Term] {
+    &self.terms
+  }
+
+  pub(crate) fn slop(&self) -> u32 {
+    self.slop
+  }
+}
+
+impl Query for SpanNearQuery {
+  fn weight(&self, enable_scoring: EnableScoring<'_>) -> tv::Result<Box<dyn Weight>> {
+    let field_entry = enable_scoring.schema().get_field_entry(self.field);
+    if !crate::schema::has_positions(field_entry) {
+      return Err(tv::TantivyError::SchemaError(format!(
+        "Applied span near query on field {:?}, which does not have positions indexed",
+        field_entry.name()
+      )));
+    }
+    let bm25_weight = match enable_scoring {
+      EnableScoring::Enabled {
+        statistics_provider,
+        ..
+      } => Some(Bm25Weight::for_terms(statistics_provider, &self.terms)?),
+      EnableScoring::Disabled { .. } => None,
+    };
+    Ok(Box::new(SpanNearWeight {
+      query: self.clone(),
+      bm25_weight,
+    }))
+  }
+
+  fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a tv::Term, bool)) {
+    for term in &self.terms {
+      visitor(term, true);
+    }
+  }
+}
+
+struct SpanNearWeight {
+  query: SpanNearQuery,
+  bm25_weight: Option<Bm25Weight>,
+}
+
+impl SpanNearWeight {
+  /// The matching documents of a segment, with their field norm id and the
+  /// number of matching spans.
+  fn matches(&self, reader: &SegmentReader) -> tv::Result<Vec<(DocId, u8, u32)>> {
+    let inverted_index = reader.inverted_index(self.query.field)?;
+    let mut postings: Vec<SegmentPostings> = Vec::with_capacity(self.query.terms.len());
+    for term in &self.query.terms {
+      match inverted_index.read_postings(term, IndexRecordOption::WithFreqsAndPositions)? {
+        Some(term_postings) => postings.push(term_postings),
+        None => return Ok(Vec::new()),
+      }
+    }
+    let Some((first, others)) = postings.split_first_mut() else {
+      return Ok(Vec::new());
+    };
+    let fieldnorm_reader = reader.get_fieldnorms_reader(self.query.field)?;
+
+    let mut matches = Vec::new();
+    let mut positions: Vec<Vec<u32>> = vec![Vec::new(); others.len() + 1];
+    let mut doc = first.doc();
+    'docs: while doc != TERMINATED {
+      for term_postings in others.iter_mut() {
+        if term_postings.doc() < doc {
+          term_postings.seek(doc);
+        }
+        if term_postings.doc() > doc {
+          doc = first.seek(term_postings.doc());
+          continue 'docs;
+        }
+      }
+      if !reader.is_deleted(doc) {
+        first.positions(&mut positions[0]);
+        for (term_postings, term_positions) in others.iter_mut().zip(&mut positions[1..]) {
+          term_postings.positions(term_positions);
+        }
+        let spans = count_spans(&positions, self.query.slop);
+        if spans > 0 {
+          matches.push((doc, fieldnorm_reader.fieldnorm_id(doc), spans));
+        }
+      }
+      doc = first.advance();
+    }
+    Ok(matches)
+  }
+}
+
+impl Weight for SpanNearWeight {
+  fn scorer(&self, reader: &SegmentReader, boost: Score) -> tv::Result<Box<dyn Scorer>> {
+    let bm25_weight = self
+      .bm25_weight
+      .as_ref()
+      .map(|bm25_weight| bm25_weight.boost_by(boost));
+    let docs = self
+      .matches(reader)?
+      .into_iter()
+      .map(|(doc, fieldnorm_id, spans)| {
+        let score = bm25_weight
+          .as_ref()
+          .map_or(boost, |bm25_weight| bm25_weight.score(fieldnorm_id, spans));
+        (doc, score)
+      })
+      .collect();
+    Ok(Box::new(SpanNearScorer { docs, cursor: 0 }))
+  }
+
+  fn explain(&self, reader: &SegmentReader, doc: DocId) -> tv::Result<Explanation> {
+    let matches = self.matches(reader)?;
+    let Ok(idx) = matches.binary_search_by_key(&doc, |(doc, _, _)| *doc) else {
+      return Err(tv::TantivyError::InvalidArgument(format!(
+        "Document #({doc}) does not match"
+      )));
+    };
+    let (_, fieldnorm_id, spans) = matches[idx];
+    Ok(match &self.bm25_weight {
+      Some(bm25_weight) => bm25_weight.explain(fieldnorm_id, spans),
+      None => Explanation::new("SpanNear", 1.0),
+    })
+  }
+}
+
+/// Number of positions of the first term starting an ordered span of all the
+/// terms, each term following the previous one, within `slop` gaps.
+fn count_spans(positions: &[Vec<u32>], slop: u32) -> u32 {
+  let Some((first, others)) = positions.split_first() else {
+    return 0;
+  };
+  let mut spans = 0;
+  for &start in first {
+    // Picking the nearest following position of each term gives the
+    // shortest span starting at `start`.
+    let mut end = start;
+    for term_positions in others {
+      let idx = term_positions.partition_point(|&position| position <= end);
+      match term_positions.get(idx) {
+        Some(&position) => end = position,
+        // No later start can complete a span either.
+        None => return spans,
+      }
+    }
+    if end - start - others.len() as u32 <= slop {
+      spans += 1;
+    }
+  }
+  spans
+}
+
+/// Scorer over the precomputed matching documents of a segment.
+struct SpanNearScorer {
+  docs: Vec<(DocId, Score)>,
+  cursor: usize,
+}
+
+impl DocSet for SpanNearScorer {
+  fn advance(&mut self) -> DocId {
+    if self.cursor < self.docs.len() {
+      self.cursor += 1;
+    }
+    self.doc()
+  }
+
+  fn doc(&self) -> DocId {
+    self
+      .docs
+      .get(self.cursor)
+      .map_or(TERMINATED, |(doc, _)| *doc)
+  }
+
+  fn size_hint(&self) -> u32 {
+    self.docs.len() as u32
+  }
+}
+
+impl Scorer for SpanNearScorer {
+  fn score(&mut self) -> Score {
+    self.docs.get(self.cursor).map_or(0.0, |(_, score)| *score)
+  }
+}
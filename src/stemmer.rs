@@ -0,0 +1,287 @@
+/// Stem a word with the original Porter algorithm (1980), as implemented by
+/// Martin Porter's reference C code. Words that are not ASCII are left as is.
+pub(crate) fn porter(word: &str) -> Option<String> {
+  if !word.is_ascii() || word.len() <= 2 {
+    return None;
+  }
+  let mut stemmer = Porter {
+    b: word.as_bytes().to_vec(),
+    k: word.len() as isize - 1,
+    j: 0,
+  };
+  stemmer.step1ab();
+  if stemmer.k > 0 {
+    stemmer.step1c();
+    stemmer.step2();
+    stemmer.step3();
+    stemmer.step4();
+    stemmer.step5();
+  }
+  stemmer.b.truncate(stemmer.k as usize + 1);
+  String::from_utf8(stemmer.b).ok()
+}
+
+/// Stem a word by only removing its plural, like Lucene's minimal English
+/// stemmer (the S-stemmer of Harman, 1991).
+pub(crate) fn english_light(word: &str) -> Option<String> {
+  let chars: Vec<char> = word.chars().collect();
+  let len = chars.len();
+  if len < 3 || chars[len - 1] != 's' {
+    return None;
+  }
+  let stem_len = match chars[len - 2] {
+    'u' | 's' => return None,
+    'e' if len > 3 && chars[len - 3] == 'i' && !matches!(chars[len - 4], 'a' | 'e') => {
+      // -ies but not -aies or -eies becomes -y.
+      let mut stem: String = chars[..len - 3].iter().collect();
+      stem.push('y');
+      return Some(stem);
+    }
+    'e' if matches!(chars[len - 3], 'i' | 'a' | 'o' | 'e') => return None,
+    _ => len - 1,
+  };
+  Some(chars[..stem_len].iter().collect())
+}
+
+/// State of the Porter stemmer: the word being stemmed is `b[0..=k]`, and
+/// `j` is the end of the stem once a suffix is matched by `ends`.
+struct Porter {
+  b: Vec<u8>,
+  k: isize,
+  j: isize,
+}
+
+impl Porter {
+  fn at(&self, i: isize) -> u8 {
+    self.b[i as usize]
+  }
+
+  /// Whether `b[i]` is a consonant.
+  fn cons(&self, i: isize) -> bool {
+    match self.at(i) {
+      b'a' | b'e' | b'i' | b'o' | b'u' => false,
+      b'y' => i == 0 || !self.cons(i - 1),
+      _ => true,
+    }
+  }
+
+  /// The number of consonant sequences between 0 and j, i.e. the `m` of
+  /// `[C](VC){m}[V]`.
+  fn m(&self) -> usize {
+    let mut n = 0;
+    let mut i = 0;
+    loop {
+      if i > self.j {
+        return n;
+      }
+      if !self.cons(i) {
+        break;
+      }
+      i += 1;
+    }
+    i += 1;
+    loop {
+      loop {
+        if i > self.j {
+          return n;
+        }
+        if self.cons(i) {
+          break;
+        }
+        i += 1;
+      }
+      i += 1;
+      n += 1;
+      loop {
+        if i > self.j {
+          return n;
+        }
+        if !self.cons(i) {
+          break;
+        }
+        i += 1;
+      }
+      i += 1;
+    }
+  }
+
+  /// Whether 0..=j contains a vowel.
+  fn vowel_in_stem(&self) -> bool {
+    (0..=self.j).any(|i| !self.cons(i))
+  }
+
+  /// Whether j-1 and j are the same consonant.
+  fn double_cons(&self, j: isize) -> bool {
+    j >= 1 && self.at(j) == self.at(j - 1) && self.cons(j)
+  }
+
+  /// Whether i-2, i-1 and i are consonant - vowel - consonant, the last
+  /// consonant not being w, x or y.
+  fn cvc(&self, i: isize) -> bool {
+    if i < 2 || !self.cons(i) || self.cons(i - 1) || !self.cons(i - 2) {
+      return false;
+    }
+    !matches!(self.at(i), b'w' | b'x' | b'y')
+  }
+
+  /// Whether the word ends with `s`, setting j to the end of the stem if so.
+  fn ends(&mut self, s: &str) -> bool {
+    let len = s.len() as isize;
+    if len > self.k + 1 || &self.b[(self.k - len + 1) as usize..=self.k as usize] != s.as_bytes() {
+      return false;
+    }
+    self.j = self.k - len;
+    true
+  }
+
+  /// Replace the suffix after j with `s`.
+  fn set_to(&mut self, s: &str) {
+    let start = (self.j + 1) as usize;
+    self.b.truncate(start);
+    self.b.extend_from_slice(s.as_bytes());
+    self.k = self.j + s.len() as isize;
+  }
+
+  /// Replace the suffix after j with `s` if the stem has a consonant sequence.
+  fn r(&mut self, s: &str) {
+    if self.m() > 0 {
+      self.set_to(s);
+    }
+  }
+
+  /// Replace the first of the suffixes the word ends with.
+  fn replace_first(&mut self, rules: &[(&str, &str)]) {
+    for (suffix, replacement) in rules {
+      if self.ends(suffix) {
+        self.r(replacement);
+        return;
+      }
+    }
+  }
+
+  /// Remove plurals, -ed and -ing.
+  fn step1ab(&mut self) {
+    if self.at(self.k) == b's' {
+      if self.ends("sses") {
+        self.k -= 2;
+      } else if self.ends("ies") {
+        self.set_to("i");
+      } else if self.at(self.k - 1) != b's' {
+        self.k -= 1;
+      }
+    }
+    if self.ends("eed") {
+      if self.m() > 0 {
+        self.k -= 1;
+      }
+    } else if (self.ends("ed") || self.ends("ing")) && self.vowel_in_stem() {
+      self.k = self.j;
+      if self.ends("at") {
+        self.set_to("ate");
+      } else if self.ends("bl") {
+        self.set_to("ble");
+      } else if self.ends("iz") {
+        self.set_to("ize");
+      } else if self.double_cons(self.k) {
+        self.k -= 1;
+        if matches!(self.at(self.k), b'l' | b's' | b'z') {
+          self.k += 1;
+        }
+      } else if self.m() == 1 && self.cvc(self.k) {
+        self.set_to("e");
+      }
+    }
+  }
+
+  /// Turn a terminal y into i when there is another vowel in the stem.
+  fn step1c(&mut self) {
+    if self.ends("y") && self.vowel_in_stem() {
+      let k = self.k as usize;
+      self.b[k] = b'i';
+    }
+  }
+
+  /// Map double suffixes to single ones.
+  fn step2(&mut self) {
+    let rules: &[(&str, &str)] = match self.at(self.k - 1) {
+      b'a' => &[("ational", "ate"), ("tional", "tion")],
+      b'c' => &[("enci", "ence"), ("anci", "ance")],
+      b'e' => &[("izer", "ize")],
+      b'l' => &[
+        ("bli", "ble"),
+        ("alli", "al"),
+        ("entli", "ent"),
+        ("eli", "e"),
+        ("ousli", "ous"),
+      ],
+      b'o' => &[("ization", "ize"), ("ation", "ate"), ("ator", "ate")],
+      b's' => &[
+        ("alism", "al"),
+        ("iveness", "ive"),
+        ("fulness", "ful"),
+        ("ousness", "ous"),
+      ],
+      b't' => &[("aliti", "al"), ("iviti", "ive"), ("biliti", "ble")],
+      b'g' => &[("logi", "log")],
+      _ => return,
+    };
+    self.replace_first(rules);
+  }
+
+  /// Handle -ic-, -full, -ness etc.
+  fn step3(&mut self) {
+    let rules: &[(&str, &str)] = match self.at(self.k) {
+      b'e' => &[("icate", "ic"), ("ative", ""), ("alize", "al")],
+      b'i' => &[("iciti", "ic")],
+      b'l' => &[("ical", "ic"), ("ful", "")],
+      b's' => &[("ness", "")],
+      _ => return,
+    };
+    self.replace_first(rules);
+  }
+
+  /// Remove -ant, -ence etc. when the stem has more than one consonant
+  /// sequence.
+  fn step4(&mut self) {
+    let suffixes: &[&str] = match self.at(self.k - 1) {
+      b'a' => &["al"],
+      b'c' => &["ance", "ence"],
+      b'e' => &["er"],
+      b'i' => &["ic"],
+      b'l' => &["able", "ible"],
+      b'n' => &["ant", "ement", "ment", "ent"],
+      b'o' => {
+        if self.ends("ion") && self.j >= 0 && matches!(self.at(self.j), b's' | b't') {
+          &[]
+        } else {
+          &["ou"]
+        }
+      }
+      b's' => &["ism"],
+      b't' => &["ate", "iti"],
+      b'u' => &["ous"],
+      b'v' => &["ive"],
+      b'z' => &["ize"],
+      _ => return,
+    };
+    let matched = suffixes.is_empty() || suffixes.iter().any(|suffix| self.ends(suffix));
+    if matched && self.m() > 1 {
+      self.k = self.j;
+    }
+  }
+
+  /// Remove a final -e, and turn -ll into -l, when the stem has more than one
+  /// consonant sequence.
+  fn step5(&mut self) {
+    self.j = self.k;
+    if self.at(self.k) == b'e' {
+      let m = self.m();
+      if m > 1 || m == 1 && !self.cvc(self.k - 1) {
+        self.k -= 1;
+      }
+    }
+    if self.at(self.k) == b'l' && self.double_cons(self.k) && self.m() > 1 {
+      self.k -= 1;
+    }
+  }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::ops::Bound;
+
+use napi::{Error, Result, Status};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use tantivy as tv;
+use tantivy::query::{
+  AllQuery, BooleanQuery, ExistsQuery, Occur, RangeQuery, RegexQuery, TermQuery, TermSetQuery,
+};
+use tantivy::schema::{FieldType, IndexRecordOption, Schema};
+
+use crate::get_field;
+use crate::query::{BoostedQuery, ConstScoredQuery};
+
+/// A query given as data, see `Query.fromObject`. Every query is an object
+/// with a single key naming its kind.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+enum QueryObject {
+  Bool(BoolObject),
+  Term(HashMap<String, JsonValue>),
+  Terms(HashMap<String, Vec<JsonValue>>),
+  Range(HashMap<String, RangeObject>),
+  Prefix(HashMap<String, String>),
+  Exists { field: String },
+  All {},
+  Boost { query: Box<QueryObject>, boost: f64 },
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+struct BoolObject {
+  must: Vec<QueryObject>,
+  should: Vec<QueryObject>,
+  must_not: Vec<QueryObject>,
+  /// Required like `must`, without contributing to the score.
+  filter: Vec<QueryObject>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RangeObject {
+  gt: Option<JsonValue>,
+  gte: Option<JsonValue>,
+  lt: Option<JsonValue>,
+  lte: Option<JsonValue>,
+}
+
+/// Build the query described by an object, see `Query.fromObject`.
+pub(crate) fn build(schema: &Schema, object: JsonValue) -> Result<Box<dyn tv::query::Query>> {
+  let object: QueryObject = serde_json::from_value(object)
+    .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid query object: {}", e)))?;
+  build_query(schema, object)
+}
+
+fn build_query(schema: &Schema, object: QueryObject) -> Result<Box<dyn tv::query::Query>> {
+  Ok(match object {
+    QueryObject::Bool(bool_object) => {
+      let clauses = [
+        (Occur::Must, bool_object.must, false),
+        (Occur::Should, bool_object.should, false),
+        (Occur::MustNot, bool_object.must_not, false),
+        (Occur::Must, bool_object.filter, true),
+      ];
+      let mut subqueries = Vec::new();
+      for (occur, objects, filter) in clauses {
+        for object in objects {
+          let query = build_query(schema, object)?;
+          let query: Box<dyn tv::query::Query> = if filter {
+            Box::new(ConstScoredQuery::new(query, 0.0))
+          } else {
+            query
+          };
+          subqueries.push((occur, query));
+        }
+      }
+      Box::new(BooleanQuery::new(subqueries))
+    }
+    QueryObject::Term(term) => {
+      let (field_name, value) = single_entry("term", term)?;
+      let term = json_term(schema, &field_name, &value)?;
+      Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
+    }
+    QueryObject::Terms(terms) => {
+      let (field_name, values) = single_entry("terms", terms)?;
+      let terms = values
+        .iter()
+        .map(|value| json_term(schema, &field_name, value))
+        .collect::<Result<Vec<_>>>()?;
+      Box::new(TermSetQuery::new(terms))
+    }
+    QueryObject::Range(range) => {
+      let (field_name, range) = single_entry("range", range)?;
+      let field = get_field(schema, &field_name)?;
+      match schema.get_field_entry(field).field_type() {
+        FieldType::U64(_)
+        | FieldType::I64(_)
+        | FieldType::F64(_)
+        | FieldType::Date(_)
+        | FieldType::IpAddr(_) => {}
+        _ => {
+          return Err(Error::new(
+            Status::InvalidArg,
+            format!("Range queries are not supported on field '{}'.", field_name),
+          ))
+        }
+      }
+      let bound = |included: Option<JsonValue>, excluded: Option<JsonValue>| {
+        Ok(match (included, excluded) {
+          (Some(_), Some(_)) => {
+            return Err(Error::new(
+              Status::InvalidArg,
+              format!(
+                "The range of field '{}' has two bounds on the same side.",
+                field_name
+              ),
+            ))
+          }
+          (Some(value), None) => Bound::Included(json_term(schema, &field_name, &value)?),
+          (None, Some(value)) => Bound::Excluded(json_term(schema, &field_name, &value)?),
+          (None, None) => Bound::Unbounded,
+        })
+      };
+      let lower = bound(range.gte, range.gt)?;
+      let upper = bound(range.lte, range.lt)?;
+      Box::new(RangeQuery::new(lower, upper))
+    }
+    QueryObject::Prefix(prefix) => {
+      let (field_name, prefix) = single_entry("prefix", prefix)?;
+      let field = get_field(schema, &field_name)?;
+      let pattern = format!("{}.*", crate::query::escape_regex(&prefix));
+      Box::new(RegexQuery::from_pattern(&pattern, field).map_err(crate::to_napi_error)?)
+    }
+    QueryObject::Exists { field } => {
+      get_field(schema, &field)?;
+      Box::new(ExistsQuery::new(field, false))
+    }
+    QueryObject::All {} => Box::new(AllQuery),
+    QueryObject::Boost { query, boost } => Box::new(BoostedQuery::new(
+      build_query(schema, *query)?,
+      boost as f32,
+    )),
+  })
+}
+
+/// The field and value of a query naming a single field, such as
+/// `{ term: { title: 'sea' } }`.
+fn single_entry<T>(kind: &str, entries: HashMap<String, T>) -> Result<(String, T)> {
+  let mut entries = entries.into_iter();
+  match (entries.next(), entries.next()) {
+    (Some(entry), None) => Ok(entry),
+    _ => Err(Error::new(
+      Status::InvalidArg,
+      format!("A {} query must name exactly one field.", kind),
+    )),
+  }
+}
+
+/// The term of a field for a JSON value: text is not tokenized, dates are
+/// seconds timestamps or RFC 3339 strings.
+fn json_term(schema: &Schema, field_name: &str, value: &JsonValue) -> Result<tv::Term> {
+  let field = get_field(schema, field_name)?;
+  let field_type = schema.get_field_entry(field).field_type();
+  let term = match field_type {
+    FieldType::Str(_) => value
+      .as_str()
+      .map(|text| tv::Term::from_field_text(field, text)),
+    FieldType::U64(_) => integer(value).map(|v| tv::Term::from_field_u64(field, v)),
+    FieldType::I64(_) => integer(value).map(|v| tv::Term::from_field_i64(field, v)),
+    FieldType::F64(_) => value.as_f64().map(|v| tv::Term::from_field_f64(field, v)),
+    FieldType::Bool(_) => value.as_bool().map(|v| tv::Term::from_field_bool(field, v)),
+    FieldType::Date(_) => match value {
+      JsonValue::String(text) => chrono::DateTime::parse_from_rfc3339(text)
+        .ok()
+        .map(|date| tv::DateTime::from_timestamp_millis(date.timestamp_millis())),
+      _ => value.as_i64().map(tv::DateTime::from_timestamp_secs),
+    }
+    .map(|date| tv::Term::from_field_date_for_search(field, date)),
+    FieldType::Facet(_) => value
+      .as_str()
+      .and_then(|text| tv::schema::Facet::from_text(text).ok())
+      .map(|facet| tv::Term::from_facet(field, &facet)),
+    FieldType::IpAddr(_) => value
+      .as_str()
+      .and_then(|text| text.parse::<std::net::IpAddr>().ok())
+      .map(|ip_addr| match ip_addr {
+        std::net::IpAddr::V6(v6) => v6,
+        std::net::IpAddr::V4(v4) => v4.to_ipv6_mapped(),
+      })
+      .map(|ip_addr| tv::Term::from_field_ip_addr(field, ip_addr)),
+    FieldType::Bytes(_) | FieldType::JsonObject(_) => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Field '{}' can't be queried by value.", field_name),
+      ))
+    }
+  };
+  term.ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      format!(
+        "Value {} is not a valid {:?} value for field '{}'.",
+        value,
+        field_type.value_type(),
+        field_name
+      ),
+    )
+  })
+}
+
+/// An integer given as a JSON number or a numeric string, for the integers
+/// beyond `Number.MAX_SAFE_INTEGER`.
+fn integer<T: TryFrom<i64> + TryFrom<u64> + std::str::FromStr>(value: &JsonValue) -> Option<T> {
+  match value {
+    JsonValue::String(text) => text.trim().parse().ok(),
+    _ => value
+      .as_u64()
+      .and_then(|v| T::try_from(v).ok())
+      .or_else(|| value.as_i64().and_then(|v| T::try_from(v).ok())),
+  }
+}
@@ -0,0 +1,247 @@
+use napi::bindgen_prelude::*;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use tantivy as tv;
+use tantivy::query::{BooleanQuery, ConstScoreQuery, TermQuery};
+use tantivy::schema::IndexRecordOption;
+
+use crate::{
+  document::Document,
+  index::IndexWriter,
+  query::Query,
+  searcher::{Order, SearchOptions, SearchResult, Searcher},
+  term::Term,
+  to_napi_error,
+};
+
+/// An index shared by several tenants, each of them only seeing its own
+/// documents.
+///
+/// Every document added is given the tenant id in the tenant field, and
+/// every search and deletion is restricted to the documents of the tenant,
+/// so that a query forgetting the tenant can't leak nor delete the documents
+/// of another one. With a unique field, see `IndexWriterOptions.uniqueField`,
+/// the documents are only deduplicated within their tenant. The tenant index
+/// is created by `Index.tenantIndex()`.
+///
+/// Example:
+/// ```javascript
+/// const tenants = index.tenantIndex("tenant");
+/// tenants.add("acme", Document.fromDict({ title: "The Old Man and the Sea" }));
+/// tenants.commit();
+/// tenants.search("acme", index.parseQuery("sea")).count; // 1
+/// tenants.search("globex", index.parseQuery("sea")).count; // 0
+/// ```
+#[napi]
+pub struct TenantIndex {
+  writer: IndexWriter,
+  reader: tv::IndexReader,
+  tenant_field: tv::schema::Field,
+}
+
+impl TenantIndex {
+  pub(crate) fn new(
+    writer: IndexWriter,
+    reader: tv::IndexReader,
+    tenant_field: tv::schema::Field,
+  ) -> Self {
+    TenantIndex {
+      writer,
+      reader,
+      tenant_field,
+    }
+  }
+
+  fn tenant_term(&self, tenant_id: &str) -> tv::Term {
+    tv::Term::from_field_text(self.tenant_field, tenant_id)
+  }
+
+  /// The query matching the documents of a tenant, without score.
+  fn tenant_filter(&self, tenant_id: &str) -> Box<dyn tv::query::Query> {
+    let term_query = TermQuery::new(self.tenant_term(tenant_id), IndexRecordOption::Basic);
+    Box::new(ConstScoreQuery::new(Box::new(term_query), 0.0))
+  }
+
+  /// Restrict a query to the documents of a tenant, keeping its scores.
+  fn scoped(&self, tenant_id: &str, query: Box<dyn tv::query::Query>) -> Box<dyn tv::query::Query> {
+    Box::new(BooleanQuery::intersection(vec![
+      query,
+      self.tenant_filter(tenant_id),
+    ]))
+  }
+}
+
+#[napi]
+impl TenantIndex {
+  /// Add a document of a tenant, see `IndexWriter.addDocument`.
+  ///
+  /// @param tenantId - The tenant owning the document.
+  /// @param doc - The document to add. It should not have a value of the
+  ///         tenant field, unless it is the tenant id.
+  ///
+  /// @returns The `opstamp` of the addition.
+  #[napi]
+  pub fn add(&mut self, tenant_id: String, doc: &Document) -> Result<u64> {
+    use tv::schema::Value;
+
+    let mut tantivy_doc = crate::index::convert_document(&self.writer.schema, doc)?;
+    let other_tenant = tantivy_doc
+      .get_all(self.tenant_field)
+      .any(|value| value.as_str() != Some(tenant_id.as_str()));
+    if other_tenant {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!(
+          "The document belongs to another tenant than '{}'.",
+          tenant_id
+        ),
+      ));
+    }
+    if tantivy_doc.get_first(self.tenant_field).is_none() {
+      tantivy_doc.add_text(self.tenant_field, &tenant_id);
+    }
+    self
+      .writer
+      .add_scoped(tantivy_doc, self.tenant_filter(&tenant_id))
+  }
+
+  /// Delete the documents of a tenant containing a term, see
+  /// `IndexWriter.deleteDocumentsByTerm`.
+  ///
+  /// @param tenantId - The tenant owning the documents.
+  /// @param fieldName - The field name for which we want to filter deleted docs.
+  /// @param fieldValue - JavaScript value with the value we want to filter.
+  ///
+  /// @returns The `opstamp` of the deletion.
+  #[napi]
+  pub fn delete_documents_by_term(
+    &mut self,
+    tenant_id: String,
+    field_name: String,
+    field_value: Either<&Term, Unknown>,
+  ) -> Result<u64> {
+    let schema = &self.writer.schema;
+    let term = Term::resolve(schema, &field_name, field_value, |value| {
+      crate::make_term(schema, &field_name, value)
+    })?;
+    let query = Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+    let query = crate::nested::deletion_query(schema, self.scoped(&tenant_id, query));
+    self
+      .writer
+      .inner()?
+      .delete_query(query)
+      .map_err(to_napi_error)
+  }
+
+  /// Delete the documents of a tenant matching a query.
+  ///
+  /// @param tenantId - The tenant owning the documents.
+  /// @param query - The query to filter the deleted documents.
+  ///
+  /// @returns The `opstamp` of the deletion.
+  #[napi]
+  pub fn delete_documents_by_query(&mut self, tenant_id: String, query: &Query) -> Result<u64> {
+    let query = self.scoped(&tenant_id, query.inner.box_clone());
+    let query = crate::nested::deletion_query(&self.writer.schema, query);
+    self
+      .writer
+      .inner()?
+      .delete_query(query)
+      .map_err(to_napi_error)
+  }
+
+  /// Delete all the documents of a tenant.
+  ///
+  /// @param tenantId - The tenant whose documents are deleted.
+  ///
+  /// @returns The `opstamp` of the deletion.
+  #[napi]
+  pub fn delete_tenant(&mut self, tenant_id: String) -> Result<u64> {
+    Ok(
+      self
+        .writer
+        .inner()?
+        .delete_term(self.tenant_term(&tenant_id)),
+    )
+  }
+
+  /// Commit the pending operations and reload the reader so that they are
+  /// visible to the searches, see `IndexWriter.commit`.
+  ///
+  /// @returns The `opstamp` of the commit.
+  #[napi]
+  pub fn commit(&mut self) -> Result<u64> {
+    let opstamp = self.writer.commit(None)?;
+    self.reader.reload().map_err(to_napi_error)?;
+    Ok(opstamp)
+  }
+
+  /// Search the documents of a tenant, see `Searcher.search`.
+  ///
+  /// @param tenantId - The tenant owning the documents.
+  /// @param query - The query, restricted to the documents of the tenant
+  ///         without changing their scores.
+  ///
+  /// @returns SearchResult object.
+  #[napi]
+  #[allow(clippy::too_many_arguments)]
+  pub fn search(
+    &self,
+    tenant_id: String,
+    query: &Query,
+    limit: Option<u32>,
+    count: Option<bool>,
+    order_by_field: Option<String>,
+    offset: Option<u32>,
+    order: Option<Order>,
+    options: Option<SearchOptions>,
+  ) -> Result<SearchResult> {
+    let query = Query {
+      inner: self.scoped(&tenant_id, query.inner.box_clone()),
+    };
+    Searcher::new(self.reader.searcher()).search(
+      &query,
+      limit,
+      count,
+      order_by_field,
+      offset,
+      order,
+      options,
+    )
+  }
+
+  /// Commit the pending operations and release the writer, see
+  /// `IndexWriter.waitMergingThreads`. Further calls raise an error.
+  #[napi]
+  pub fn close(&mut self) -> Result<()> {
+    self.commit()?;
+    self.writer.wait_merging_threads()
+  }
+}
+
+/// Check that a field can hold the tenant ids: an indexed text field using
+/// the `raw` tokenizer.
+pub(crate) fn check_tenant_field(
+  schema: &tv::schema::Schema,
+  field_name: &str,
+) -> Result<tv::schema::Field> {
+  let field = crate::get_field(schema, field_name)?;
+  let tokenizer = match schema.get_field_entry(field).field_type() {
+    tv::schema::FieldType::Str(options) => options
+      .get_indexing_options()
+      .map(|indexing| indexing.tokenizer()),
+    _ => {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Tenant field `{field_name}` is not a text field."),
+      ))
+    }
+  };
+  if tokenizer != Some("raw") {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Tenant field `{field_name}` must be indexed with the `raw` tokenizer."),
+    ));
+  }
+  Ok(field)
+}
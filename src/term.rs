@@ -0,0 +1,152 @@
+use napi::bindgen_prelude::*;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use tantivy as tv;
+
+use crate::{get_field, Schema};
+
+/// A term, the unit the index is made of: a field and a value as it was
+/// indexed.
+///
+/// Term queries, term set queries, range queries, deletions and document
+/// frequencies accept a Term in place of a value. The value is then used as
+/// is, while plain JavaScript values are converted according to the type of
+/// the field.
+///
+/// Example:
+/// ```javascript
+/// const term = Term.fromText(schema, "title", "Whale");
+/// const query = Query.termQuery(schema, "title", term);
+/// ```
+#[napi]
+#[derive(Clone)]
+pub struct Term {
+  pub(crate) inner: tv::Term,
+}
+
+impl Term {
+  /// The term of `field_name` given either as a Term, checked to belong to
+  /// that field, or as a value converted by `make`.
+  pub(crate) fn resolve(
+    schema: &tv::schema::Schema,
+    field_name: &str,
+    value: Either<&Term, Unknown>,
+    make: impl FnOnce(Unknown) -> Result<tv::Term>,
+  ) -> Result<tv::Term> {
+    match value {
+      Either::A(term) => {
+        let field = get_field(schema, field_name)?;
+        if term.inner.field() != field {
+          return Err(Error::new(
+            Status::InvalidArg,
+            format!("The term is not a term of field '{}'.", field_name),
+          ));
+        }
+        Ok(term.inner.clone())
+      }
+      Either::B(value) => make(value),
+    }
+  }
+}
+
+/// The field of a term, checking that it is of the expected type.
+fn field_of_type(
+  schema: &tv::schema::Schema,
+  field_name: &str,
+  value_type: tv::schema::Type,
+  type_name: &str,
+) -> Result<tv::schema::Field> {
+  let field = get_field(schema, field_name)?;
+  if schema.get_field_entry(field).field_type().value_type() != value_type {
+    return Err(Error::new(
+      Status::InvalidArg,
+      format!("Field '{}' is not a {} field.", field_name, type_name),
+    ));
+  }
+  Ok(field)
+}
+
+#[napi]
+impl Term {
+  /// Create the term of a text field, without tokenizing nor normalizing the
+  /// text.
+  ///
+  /// @param schema - The schema of the index.
+  /// @param fieldName - A text field.
+  /// @param text - The text of the term, as indexed.
+  #[napi(factory)]
+  pub fn from_text(schema: &Schema, field_name: String, text: String) -> Result<Term> {
+    let field = field_of_type(&schema.inner, &field_name, tv::schema::Type::Str, "text")?;
+    Ok(Term {
+      inner: tv::Term::from_field_text(field, &text),
+    })
+  }
+
+  /// Create the term of a value at a path of a JSON field.
+  ///
+  /// Strings are used as is, without tokenizing nor normalizing them. Integers
+  /// are indexed as 64-bit integers and other numbers as floats, like the
+  /// numbers of the documents.
+  ///
+  /// @param schema - The schema of the index.
+  /// @param fieldName - A JSON field.
+  /// @param path - The path of the value in the JSON object, e.g. `user.name`.
+  /// @param value - A string, a number or a boolean.
+  #[napi(factory)]
+  pub fn from_json_path(
+    schema: &Schema,
+    field_name: String,
+    path: String,
+    value: Unknown,
+  ) -> Result<Term> {
+    let field = field_of_type(&schema.inner, &field_name, tv::schema::Type::Json, "JSON")?;
+    let expand_dots = schema.inner.get_field_entry(field).is_expand_dots_enabled();
+    let mut inner = tv::Term::from_field_json_path(field, &path, expand_dots);
+    match value.get_type()? {
+      ValueType::String => {
+        let text = value.coerce_to_string()?.into_utf8()?.into_owned()?;
+        inner.append_type_and_str(&text);
+      }
+      ValueType::Number => {
+        let number = value.coerce_to_number()?.get_double()?;
+        if number.fract() == 0.0 && number.abs() < i64::MAX as f64 {
+          inner.append_type_and_fast_value(number as i64);
+        } else {
+          inner.append_type_and_fast_value(number);
+        }
+      }
+      ValueType::Boolean => {
+        inner.append_type_and_fast_value(value.coerce_to_bool()?);
+      }
+      value_type => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!(
+            "Unsupported JSON term value type: {:?}, expected a string, a number or a boolean.",
+            value_type
+          ),
+        ))
+      }
+    }
+    Ok(Term { inner })
+  }
+
+  /// Create the term of a bytes field.
+  ///
+  /// @param schema - The schema of the index.
+  /// @param fieldName - A bytes field.
+  /// @param bytes - The bytes (as Buffer or Uint8Array) of the term.
+  #[napi(factory)]
+  pub fn from_bytes(schema: &Schema, field_name: String, bytes: &[u8]) -> Result<Term> {
+    let field = field_of_type(&schema.inner, &field_name, tv::schema::Type::Bytes, "bytes")?;
+    Ok(Term {
+      inner: tv::Term::from_field_bytes(field, bytes),
+    })
+  }
+
+  #[napi]
+  #[allow(clippy::inherent_to_string)]
+  pub fn to_string(&self) -> String {
+    format!("{:?}", self.inner)
+  }
+}
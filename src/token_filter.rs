@@ -0,0 +1,185 @@
+use tantivy::tokenizer::{RawTokenizer, Token, TokenFilter, TokenStream, Tokenizer};
+
+/// Wraps a token filter so that the original tokens are kept.
+///
+/// Every token is emitted unchanged, followed, with the same position and
+/// offsets, by the tokens the wrapped filter turns it into when they differ
+/// from it. Indexing `café` with a preserving ascii folding filter indexes
+/// both `café` and `cafe`.
+#[derive(Clone)]
+pub(crate) struct PreserveOriginal<F>(pub(crate) F);
+
+impl<F: TokenFilter> TokenFilter for PreserveOriginal<F> {
+  type Tokenizer<T: Tokenizer> = PreserveOriginalFilter<T, F::Tokenizer<RawTokenizer>>;
+
+  fn transform<T: Tokenizer>(self, tokenizer: T) -> Self::Tokenizer<T> {
+    PreserveOriginalFilter {
+      tokenizer,
+      // The raw tokenizer yields its whole input as a single token, this runs
+      // the wrapped filter on one token at a time.
+      filtered: self.0.transform(RawTokenizer::default()),
+    }
+  }
+}
+
+#[derive(Clone)]
+pub(crate) struct PreserveOriginalFilter<T, G> {
+  tokenizer: T,
+  filtered: G,
+}
+
+impl<T: Tokenizer, G: Tokenizer> Tokenizer for PreserveOriginalFilter<T, G> {
+  type TokenStream<'a> = PreserveOriginalTokenStream<'a, T::TokenStream<'a>, G>;
+
+  fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+    PreserveOriginalTokenStream {
+      tail: self.tokenizer.token_stream(text),
+      filtered: &mut self.filtered,
+      pending: Vec::new(),
+      token: Token::default(),
+    }
+  }
+}
+
+pub(crate) struct PreserveOriginalTokenStream<'a, T, G> {
+  tail: T,
+  filtered: &'a mut G,
+  /// Filtered tokens still to be emitted, in reverse order.
+  pending: Vec<Token>,
+  token: Token,
+}
+
+impl<T: TokenStream, G: Tokenizer> TokenStream for PreserveOriginalTokenStream<'_, T, G> {
+  fn advance(&mut self) -> bool {
+    if let Some(token) = self.pending.pop() {
+      self.token = token;
+      return true;
+    }
+    if !self.tail.advance() {
+      return false;
+    }
+    let original = self.tail.token().clone();
+    {
+      let mut stream = self.filtered.token_stream(&original.text);
+      while stream.advance() {
+        if stream.token().text != original.text {
+          self.pending.push(Token {
+            text: stream.token().text.clone(),
+            ..original.clone()
+          });
+        }
+      }
+    }
+    self.pending.reverse();
+    self.token = original;
+    true
+  }
+
+  fn token(&self) -> &Token {
+    &self.token
+  }
+
+  fn token_mut(&mut self) -> &mut Token {
+    &mut self.token
+  }
+}
+
+/// A token filter editing the tokens in place with a function, which returns
+/// false for the tokens to remove.
+#[derive(Clone)]
+pub(crate) struct RetainTokens<F>(pub(crate) F);
+
+impl<F> TokenFilter for RetainTokens<F>
+where
+  F: Fn(&mut Token) -> bool + Clone + Send + Sync + 'static,
+{
+  type Tokenizer<T: Tokenizer> = RetainTokensFilter<T, F>;
+
+  fn transform<T: Tokenizer>(self, tokenizer: T) -> Self::Tokenizer<T> {
+    RetainTokensFilter {
+      tokenizer,
+      retain: self.0,
+    }
+  }
+}
+
+#[derive(Clone)]
+pub(crate) struct RetainTokensFilter<T, F> {
+  tokenizer: T,
+  retain: F,
+}
+
+impl<T, F> Tokenizer for RetainTokensFilter<T, F>
+where
+  T: Tokenizer,
+  F: Fn(&mut Token) -> bool + Clone + Send + Sync + 'static,
+{
+  type TokenStream<'a> = RetainTokensStream<'a, T::TokenStream<'a>, F>;
+
+  fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+    RetainTokensStream {
+      tail: self.tokenizer.token_stream(text),
+      retain: &self.retain,
+    }
+  }
+}
+
+pub(crate) struct RetainTokensStream<'a, T, F> {
+  tail: T,
+  retain: &'a F,
+}
+
+impl<T: TokenStream, F: Fn(&mut Token) -> bool> TokenStream for RetainTokensStream<'_, T, F> {
+  fn advance(&mut self) -> bool {
+    while self.tail.advance() {
+      if (self.retain)(self.tail.token_mut()) {
+        return true;
+      }
+    }
+    false
+  }
+
+  fn token(&self) -> &Token {
+    self.tail.token()
+  }
+
+  fn token_mut(&mut self) -> &mut Token {
+    self.tail.token_mut()
+  }
+}
+
+/// Token stream over tokens computed beforehand, for the tokenizers that
+/// need to look ahead in the text.
+pub(crate) struct VecTokenStream {
+  tokens: std::vec::IntoIter<Token>,
+  token: Token,
+}
+
+impl VecTokenStream {
+  pub(crate) fn new(tokens: Vec<Token>) -> Self {
+    VecTokenStream {
+      tokens: tokens.into_iter(),
+      token: Token::default(),
+    }
+  }
+}
+
+impl TokenStream for VecTokenStream {
+  fn advance(&mut self) -> bool {
+    match self.tokens.next() {
+      Some(token) => {
+        self.token = token;
+        true
+      }
+      None => false,
+    }
+  }
+
+  fn token(&self) -> &Token {
+    &self.token
+  }
+
+  fn token_mut(&mut self) -> &mut Token {
+    &mut self.token
+  }
+}
@@ -1,7 +1,15 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
 use tantivy::tokenizer as tvt;
 
+use crate::char_filter::{CharFilter, CharFilterTokenizer, CharFilterType, CompiledCharFilter};
+use crate::path_hierarchy::PathHierarchyTokenizer;
+use crate::token_filter::{PreserveOriginal, RetainTokens};
+use crate::url_email::UrlEmailTokenizer;
+
 /// All Tantivy's built-in tokenizers in one place.
 /// Each static method, e.g. Tokenizer.simple(),
 /// creates a wrapper around a Tantivy tokenizer.
@@ -20,7 +28,12 @@ use tantivy::tokenizer as tvt;
 ///
 /// https://docs.rs/tantivy/latest/tantivy/tokenizer/index.html
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+  tag = "type",
+  rename_all = "camelCase",
+  rename_all_fields = "camelCase"
+)]
 pub(crate) enum TokenizerType {
   Raw,
   Simple,
@@ -34,6 +47,10 @@ pub(crate) enum TokenizerType {
     prefix_only: bool,
   },
   Facet,
+  UaxUrlEmail,
+  PathHierarchy {
+    delimiter: String,
+  },
 }
 
 #[napi]
@@ -87,6 +104,34 @@ impl TokenizerStatic {
     }
   }
 
+  /// Tokenizer keeping the emails and the URLs whole
+  ///
+  /// Splits the text like `simple()`, but also emits each email or URL as a
+  /// single token, e.g. `user@example.com` gives `user@example.com`, `user`,
+  /// `example` and `com`.
+  #[napi]
+  pub fn uax_url_email() -> Tokenizer {
+    Tokenizer {
+      inner: TokenizerType::UaxUrlEmail,
+    }
+  }
+
+  /// Tokenizer emitting the ancestors of a path
+  ///
+  /// `/a/b/c` gives `/a`, `/a/b` and `/a/b/c`, so that a term query on a
+  /// directory, e.g. `/a/b`, matches every path under it.
+  ///
+  /// @param delimiter - The separator of the segments of the paths. Defaults
+  ///         to '/'.
+  #[napi]
+  pub fn path_hierarchy(delimiter: Option<String>) -> Tokenizer {
+    Tokenizer {
+      inner: TokenizerType::PathHierarchy {
+        delimiter: delimiter.unwrap_or_else(|| "/".to_string()),
+      },
+    }
+  }
+
   /// NgramTokenizer
   ///
   /// @param minGram - Minimum character length of each ngram.
@@ -124,15 +169,42 @@ impl TokenizerStatic {
 ///
 /// https://docs.rs/tantivy/latest/tantivy/tokenizer/index.html
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all_fields = "camelCase")]
 pub(crate) enum FilterType {
+  #[serde(rename = "alphanumOnly")]
   AlphaNumOnly,
-  AsciiFolding,
-  LowerCaser,
+  #[serde(rename = "asciiFold")]
+  AsciiFolding {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    preserve_original: bool,
+  },
+  #[serde(rename = "lowercase")]
+  LowerCaser {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    preserve_original: bool,
+  },
+  #[serde(rename = "removeLong")]
   RemoveLong { length_limit: u32 },
-  Stemmer { language: String },
+  #[serde(rename = "minLength")]
+  MinLength { length: u32 },
+  #[serde(rename = "keepWords")]
+  KeepWords { words: Vec<String> },
+  #[serde(rename = "trim")]
+  Trim,
+  #[serde(rename = "stemmer")]
+  Stemmer {
+    language: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    variant: Option<String>,
+  },
+  #[serde(rename = "stemmerCustom")]
+  StemmerCustom { name: String },
+  #[serde(rename = "stopword")]
   StopWord { language: String },
+  #[serde(rename = "customStopword")]
   CustomStopWord { stopwords: Vec<String> },
+  #[serde(rename = "splitCompound")]
   SplitCompound { constituent_words: Vec<String> },
 }
 
@@ -156,17 +228,32 @@ impl FilterStatic {
   }
 
   /// AsciiFoldingFilter
+  ///
+  /// @param preserveOriginal - If true, a token changed by the folding is
+  ///   emitted unchanged, then folded, both at its position and offsets. The
+  ///   folded term matches every spelling and the original term the exact one.
+  ///   The query parser turns `café` into a phrase of both terms at the same
+  ///   position, which only matches the exact spelling: query the folded term
+  ///   to match every spelling.
   #[napi]
-  pub fn ascii_fold() -> Filter {
+  pub fn ascii_fold(preserve_original: Option<bool>) -> Filter {
     Filter {
-      inner: FilterType::AsciiFolding,
+      inner: FilterType::AsciiFolding {
+        preserve_original: preserve_original.unwrap_or(false),
+      },
     }
   }
 
+  /// LowerCaser
+  ///
+  /// @param preserveOriginal - If true, a token changed by the lowercasing is
+  ///   emitted unchanged, then lowercased, both at its position and offsets.
   #[napi]
-  pub fn lowercase() -> Filter {
+  pub fn lowercase(preserve_original: Option<bool>) -> Filter {
     Filter {
-      inner: FilterType::LowerCaser,
+      inner: FilterType::LowerCaser {
+        preserve_original: preserve_original.unwrap_or(false),
+      },
     }
   }
 
@@ -180,11 +267,60 @@ impl FilterStatic {
     }
   }
 
+  /// Removes the tokens shorter than a length.
+  ///
+  /// @param length - min character length of token.
+  #[napi]
+  pub fn min_length(length: u32) -> Filter {
+    Filter {
+      inner: FilterType::MinLength { length },
+    }
+  }
+
+  /// Keeps only the tokens found in a list of words.
+  ///
+  /// @param words - the words to keep, compared to the tokens as they are
+  ///   when reaching this filter (e.g. after lowercasing).
+  #[napi]
+  pub fn keep_words(words: Vec<String>) -> Filter {
+    Filter {
+      inner: FilterType::KeepWords { words },
+    }
+  }
+
+  /// Removes the leading and trailing whitespace of the tokens.
+  ///
+  /// Tokens made only of whitespace are removed.
+  #[napi]
+  pub fn trim() -> Filter {
+    Filter {
+      inner: FilterType::Trim,
+    }
+  }
+
   /// Stemmer
+  ///
+  /// @param language - Stemmer language, one of the languages of `Filter.stopword()`.
+  /// @param variant - The stemming algorithm to use for the language:
+  ///   'snowball' (default) for every language, also named 'porter2' for
+  ///   english. English also has 'porter', the original Porter stemmer, and
+  ///   'light', which only removes plurals.
   #[napi]
-  pub fn stemmer(language: String) -> Filter {
+  pub fn stemmer(language: String, variant: Option<String>) -> Filter {
     Filter {
-      inner: FilterType::Stemmer { language },
+      inner: FilterType::Stemmer { language, variant },
+    }
+  }
+
+  /// Stemmer, selected by the name of its algorithm.
+  ///
+  /// @param name - A language name, as accepted by `Filter.stemmer()`, for its
+  ///   Snowball stemmer, or the name of an english stemming algorithm:
+  ///   'porter', 'porter2' or 'englishLight'.
+  #[napi]
+  pub fn stemmer_custom(name: String) -> Filter {
+    Filter {
+      inner: FilterType::StemmerCustom { name },
     }
   }
 
@@ -265,6 +401,70 @@ fn parse_language(lang: &str) -> Result<tvt::Language> {
   }
 }
 
+/// A stemming algorithm.
+enum StemmerAlgorithm {
+  Snowball(tvt::Language),
+  Porter,
+  EnglishLight,
+}
+
+/// The stemming algorithm selected by a language and a variant.
+fn parse_stemmer(language: &str, variant: Option<&str>) -> Result<StemmerAlgorithm> {
+  let lang = parse_language(language)?;
+  match (variant.map(str::to_lowercase).as_deref(), lang) {
+    (None | Some("snowball"), lang) => Ok(StemmerAlgorithm::Snowball(lang)),
+    (Some("porter2"), tvt::Language::English) => Ok(StemmerAlgorithm::Snowball(lang)),
+    (Some("porter"), tvt::Language::English) => Ok(StemmerAlgorithm::Porter),
+    (Some("light"), tvt::Language::English) => Ok(StemmerAlgorithm::EnglishLight),
+    (Some(variant), _) => Err(Error::from_reason(format!(
+      "Unsupported stemmer variant for {}: {}",
+      language, variant
+    ))),
+  }
+}
+
+/// The stemming algorithm named `name`.
+fn parse_stemmer_name(name: &str) -> Result<StemmerAlgorithm> {
+  match name.to_lowercase().as_str() {
+    "porter2" => Ok(StemmerAlgorithm::Snowball(tvt::Language::English)),
+    "porter" => Ok(StemmerAlgorithm::Porter),
+    "englishlight" => Ok(StemmerAlgorithm::EnglishLight),
+    _ => parse_language(name)
+      .map(StemmerAlgorithm::Snowball)
+      .map_err(|_| Error::from_reason(format!("Unsupported stemmer: {}", name))),
+  }
+}
+
+fn add_stemmer(
+  builder: tvt::TextAnalyzerBuilder,
+  algorithm: StemmerAlgorithm,
+) -> tvt::TextAnalyzerBuilder {
+  let stem: fn(&str) -> Option<String> = match algorithm {
+    StemmerAlgorithm::Snowball(lang) => return builder.filter_dynamic(tvt::Stemmer::new(lang)),
+    StemmerAlgorithm::Porter => crate::stemmer::porter,
+    StemmerAlgorithm::EnglishLight => crate::stemmer::english_light,
+  };
+  builder.filter_dynamic(RetainTokens(move |token: &mut tvt::Token| {
+    if let Some(stemmed) = stem(&token.text) {
+      token.text = stemmed;
+    }
+    true
+  }))
+}
+
+/// Options of `TextAnalyzer.forLanguage`
+#[napi(object)]
+pub struct LanguageAnalyzerOptions {
+  /// Remove the builtin stop words of the language (default: false)
+  pub stopwords: Option<bool>,
+  /// Fold the tokens to ASCII, after stemming (default: false)
+  pub ascii_fold: Option<bool>,
+  /// Stem the tokens (default: true)
+  pub stemmer: Option<bool>,
+  /// Remove the tokens longer than this many characters (default: 40)
+  pub length_limit: Option<u32>,
+}
+
 /// Tantivy's TextAnalyzer
 ///
 /// Do not instantiate this class directly.
@@ -277,6 +477,84 @@ pub struct TextAnalyzer {
 
 #[napi]
 impl TextAnalyzer {
+  /// Build a TextAnalyzer from its declarative description.
+  ///
+  /// @param config - An object as returned by `TextAnalyzerBuilder.toJSON()`:
+  ///   `{ tokenizer: { type: 'ngram', minGram: 2, maxGram: 3, prefixOnly: false },
+  ///   filters: [{ type: 'lowercase' }, { type: 'stemmer', language: 'english' }] }`.
+  ///   Character filters go in an optional `charFilters` list. The types are
+  ///   the names of the `Tokenizer`, `CharFilter` and `Filter` factories, the
+  ///   other properties their parameters.
+  /// @returns - TextAnalyzer with tokenizer and filters baked in.
+  #[napi(factory, js_name = "fromJSON")]
+  pub fn from_json(config: serde_json::Value) -> Result<TextAnalyzer> {
+    let config: AnalyzerConfig = serde_json::from_value(config).map_err(|e| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Invalid text analyzer config: {}", e),
+      )
+    })?;
+    Ok(TextAnalyzer {
+      analyzer: config.builder()?.build(),
+    })
+  }
+
+  /// Build the usual analyzer for a language.
+  ///
+  /// The text is split with the simple tokenizer, long tokens are removed and
+  /// tokens are lowercased, then stop words are removed, tokens are stemmed and
+  /// folded to ASCII as selected by `options`. Without options, this is the
+  /// analyzer registered as `<lang>_stem` (e.g. `de_stem`) on every index.
+  ///
+  /// @param language - A language supported by `Filter.stemmer()`.
+  /// @param options - The optional steps of the analyzer.
+  /// @returns - TextAnalyzer with tokenizer and filters baked in.
+  #[napi(factory)]
+  pub fn for_language(
+    language: String,
+    options: Option<LanguageAnalyzerOptions>,
+  ) -> Result<TextAnalyzer> {
+    let options = options.unwrap_or(LanguageAnalyzerOptions {
+      stopwords: None,
+      ascii_fold: None,
+      stemmer: None,
+      length_limit: None,
+    });
+    parse_language(&language)?;
+    let mut filters = vec![
+      FilterType::RemoveLong {
+        length_limit: options.length_limit.unwrap_or(40),
+      },
+      FilterType::LowerCaser {
+        preserve_original: false,
+      },
+    ];
+    if options.stopwords.unwrap_or(false) {
+      filters.push(FilterType::StopWord {
+        language: language.clone(),
+      });
+    }
+    if options.stemmer.unwrap_or(true) {
+      filters.push(FilterType::Stemmer {
+        language: language.clone(),
+        variant: None,
+      });
+    }
+    if options.ascii_fold.unwrap_or(false) {
+      filters.push(FilterType::AsciiFolding {
+        preserve_original: false,
+      });
+    }
+    let config = AnalyzerConfig {
+      tokenizer: TokenizerType::Simple,
+      char_filters: Vec::new(),
+      filters,
+    };
+    Ok(TextAnalyzer {
+      analyzer: config.builder()?.build(),
+    })
+  }
+
   /// Tokenize a string
   /// @param text - text to tokenize.
   /// @returns - a list of tokens/words.
@@ -307,39 +585,41 @@ impl TextAnalyzer {
 #[napi]
 pub struct TextAnalyzerBuilder {
   builder: Option<tvt::TextAnalyzerBuilder>,
+  config: AnalyzerConfig,
+}
+
+/// Declarative description of a TextAnalyzer, see `TextAnalyzer.fromJSON`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+struct AnalyzerConfig {
+  tokenizer: TokenizerType,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  char_filters: Vec<CharFilterType>,
+  #[serde(default)]
+  filters: Vec<FilterType>,
+}
+
+impl AnalyzerConfig {
+  fn builder(&self) -> Result<tvt::TextAnalyzerBuilder> {
+    let mut builder = analyzer_builder(&self.tokenizer, &self.char_filters)?;
+    for filter in &self.filters {
+      builder = add_filter(builder, filter)?;
+    }
+    Ok(builder)
+  }
 }
 
 #[napi]
 impl TextAnalyzerBuilder {
   #[napi(constructor)]
   pub fn new(tokenizer: &Tokenizer) -> Result<Self> {
-    let builder: tvt::TextAnalyzerBuilder = match &tokenizer.inner {
-      TokenizerType::Raw => tvt::TextAnalyzer::builder(tvt::RawTokenizer::default()).dynamic(),
-      TokenizerType::Simple => {
-        tvt::TextAnalyzer::builder(tvt::SimpleTokenizer::default()).dynamic()
-      }
-      TokenizerType::Whitespace => {
-        tvt::TextAnalyzer::builder(tvt::WhitespaceTokenizer::default()).dynamic()
-      }
-      TokenizerType::Regex { pattern } => tvt::TextAnalyzer::builder(
-        tvt::RegexTokenizer::new(pattern)
-          .map_err(|e| Error::from_reason(format!("Invalid regex pattern: {}", e)))?,
-      )
-      .dynamic(),
-      TokenizerType::Ngram {
-        min_gram,
-        max_gram,
-        prefix_only,
-      } => tvt::TextAnalyzer::builder(
-        tvt::NgramTokenizer::new(*min_gram as usize, *max_gram as usize, *prefix_only)
-          .map_err(|e| Error::from_reason(format!("Invalid ngram parameters: {}", e)))?,
-      )
-      .dynamic(),
-      TokenizerType::Facet => tvt::TextAnalyzer::builder(tvt::FacetTokenizer::default()).dynamic(),
-    };
-
     Ok(TextAnalyzerBuilder {
-      builder: Some(builder),
+      builder: Some(analyzer_builder(&tokenizer.inner, &[])?),
+      config: AnalyzerConfig {
+        tokenizer: tokenizer.inner.clone(),
+        char_filters: Vec::new(),
+        filters: Vec::new(),
+      },
     })
   }
 
@@ -352,43 +632,50 @@ impl TextAnalyzerBuilder {
   #[napi]
   pub fn filter(&mut self, filter: &Filter) -> Result<Self> {
     if let Some(builder) = self.builder.take() {
-      let new_builder: tvt::TextAnalyzerBuilder = match &filter.inner {
-        FilterType::AlphaNumOnly => builder.filter_dynamic(tvt::AlphaNumOnlyFilter {}),
-        FilterType::AsciiFolding => builder.filter_dynamic(tvt::AsciiFoldingFilter),
-        FilterType::LowerCaser => builder.filter_dynamic(tvt::LowerCaser),
-        FilterType::RemoveLong { length_limit } => {
-          builder.filter_dynamic(tvt::RemoveLongFilter::limit(*length_limit as usize))
-        }
-        FilterType::Stemmer { language } => match parse_language(language) {
-          Ok(lang) => builder.filter_dynamic(tvt::Stemmer::new(lang)),
-          Err(e) => return Err(e),
-        },
-        FilterType::StopWord { language } => match parse_language(language) {
-          Ok(lang) => builder.filter_dynamic(tvt::StopWordFilter::new(lang).ok_or_else(|| {
-            Error::from_reason(format!(
-              "Failed to create stop word filter for language: {:?}",
-              language
-            ))
-          })?),
-          Err(e) => return Err(e),
-        },
-        FilterType::CustomStopWord { stopwords } => {
-          builder.filter_dynamic(tvt::StopWordFilter::remove(stopwords.clone()))
-        }
-        FilterType::SplitCompound { constituent_words } => builder.filter_dynamic(
-          tvt::SplitCompoundWords::from_dictionary(constituent_words).map_err(|e| {
-            Error::from_reason(format!("Failed to create compound splitter: {}", e))
-          })?,
-        ),
-      };
+      let mut config = self.config.clone();
+      config.filters.push(filter.inner.clone());
       Ok(TextAnalyzerBuilder {
-        builder: Some(new_builder),
+        builder: Some(add_filter(builder, &filter.inner)?),
+        config,
       })
     } else {
       Err(Error::from_reason("Builder has already been consumed"))
     }
   }
 
+  /// Add a character filter, applied to the text before it is tokenized.
+  ///
+  /// @param charFilter - a CharFilter object.
+  /// @returns - A new instance of the builder
+  ///
+  /// Note: The builder is _not_ mutated in-place. Character filters run
+  /// before the tokenizer, in the order they are added, whatever the
+  /// position of the call among the `filter()` calls.
+  #[napi]
+  pub fn char_filter(&mut self, char_filter: &CharFilter) -> Result<Self> {
+    if self.builder.take().is_none() {
+      return Err(Error::from_reason("Builder has already been consumed"));
+    }
+    let mut config = self.config.clone();
+    config.char_filters.push(char_filter.inner.clone());
+    Ok(TextAnalyzerBuilder {
+      builder: Some(config.builder()?),
+      config,
+    })
+  }
+
+  /// Describe the tokenizer and filters of this builder.
+  ///
+  /// The description is plain JSON, it can be stored in a configuration file
+  /// and turned back into an analyzer with `TextAnalyzer.fromJSON()`.
+  #[napi(js_name = "toJSON")]
+  pub fn to_json(&self) -> Result<serde_json::Value> {
+    if self.builder.is_none() {
+      return Err(Error::from_reason("Builder has already been consumed"));
+    }
+    serde_json::to_value(&self.config).map_err(|e| Error::from_reason(e.to_string()))
+  }
+
   /// Build final TextAnalyzer object.
   ///
   /// @returns - TextAnalyzer with tokenizer and filters baked in.
@@ -406,3 +693,122 @@ impl TextAnalyzerBuilder {
     }
   }
 }
+
+fn analyzer_builder(
+  tokenizer: &TokenizerType,
+  char_filters: &[CharFilterType],
+) -> Result<tvt::TextAnalyzerBuilder> {
+  let char_filters = char_filters
+    .iter()
+    .map(CompiledCharFilter::new)
+    .collect::<Result<Vec<_>>>()?;
+  Ok(match tokenizer {
+    TokenizerType::Raw => with_char_filters(tvt::RawTokenizer::default(), char_filters),
+    TokenizerType::Simple => with_char_filters(tvt::SimpleTokenizer::default(), char_filters),
+    TokenizerType::Whitespace => {
+      with_char_filters(tvt::WhitespaceTokenizer::default(), char_filters)
+    }
+    TokenizerType::Regex { pattern } => with_char_filters(
+      tvt::RegexTokenizer::new(pattern)
+        .map_err(|e| Error::from_reason(format!("Invalid regex pattern: {}", e)))?,
+      char_filters,
+    ),
+    TokenizerType::Ngram {
+      min_gram,
+      max_gram,
+      prefix_only,
+    } => with_char_filters(
+      tvt::NgramTokenizer::new(*min_gram as usize, *max_gram as usize, *prefix_only)
+        .map_err(|e| Error::from_reason(format!("Invalid ngram parameters: {}", e)))?,
+      char_filters,
+    ),
+    TokenizerType::Facet => with_char_filters(tvt::FacetTokenizer::default(), char_filters),
+    TokenizerType::UaxUrlEmail => with_char_filters(UrlEmailTokenizer, char_filters),
+    TokenizerType::PathHierarchy { delimiter } => {
+      if delimiter.is_empty() {
+        return Err(Error::from_reason(
+          "The delimiter of a path hierarchy tokenizer must not be empty",
+        ));
+      }
+      with_char_filters(PathHierarchyTokenizer::new(delimiter.clone()), char_filters)
+    }
+  })
+}
+
+fn with_char_filters<T: tvt::Tokenizer>(
+  tokenizer: T,
+  char_filters: Vec<CompiledCharFilter>,
+) -> tvt::TextAnalyzerBuilder {
+  if char_filters.is_empty() {
+    tvt::TextAnalyzer::builder(tokenizer).dynamic()
+  } else {
+    tvt::TextAnalyzer::builder(CharFilterTokenizer::new(tokenizer, char_filters)).dynamic()
+  }
+}
+
+fn add_filter(
+  builder: tvt::TextAnalyzerBuilder,
+  filter: &FilterType,
+) -> Result<tvt::TextAnalyzerBuilder> {
+  Ok(match filter {
+    FilterType::AlphaNumOnly => builder.filter_dynamic(tvt::AlphaNumOnlyFilter {}),
+    FilterType::AsciiFolding { preserve_original } => match preserve_original {
+      true => builder.filter_dynamic(PreserveOriginal(tvt::AsciiFoldingFilter)),
+      false => builder.filter_dynamic(tvt::AsciiFoldingFilter),
+    },
+    FilterType::LowerCaser { preserve_original } => match preserve_original {
+      true => builder.filter_dynamic(PreserveOriginal(tvt::LowerCaser)),
+      false => builder.filter_dynamic(tvt::LowerCaser),
+    },
+    FilterType::RemoveLong { length_limit } => {
+      builder.filter_dynamic(tvt::RemoveLongFilter::limit(*length_limit as usize))
+    }
+    FilterType::MinLength { length } => {
+      let length = *length as usize;
+      builder.filter_dynamic(RetainTokens(move |token: &mut tvt::Token| {
+        token.text.chars().count() >= length
+      }))
+    }
+    FilterType::KeepWords { words } => {
+      let words: Arc<HashSet<String>> = Arc::new(words.iter().cloned().collect());
+      builder.filter_dynamic(RetainTokens(move |token: &mut tvt::Token| {
+        words.contains(&token.text)
+      }))
+    }
+    FilterType::Trim => builder.filter_dynamic(RetainTokens(|token: &mut tvt::Token| {
+      let trimmed = token.text.trim_start();
+      let leading = token.text.len() - trimmed.len();
+      let trimmed = trimmed.trim_end();
+      let trailing = token.text.len() - leading - trimmed.len();
+      if leading + trailing > 0 {
+        token.text = trimmed.to_string();
+        // Keep the offsets on the text left in the token.
+        token.offset_from = (token.offset_from + leading).min(token.offset_to);
+        token.offset_to = token
+          .offset_to
+          .saturating_sub(trailing)
+          .max(token.offset_from);
+      }
+      !token.text.is_empty()
+    })),
+    FilterType::Stemmer { language, variant } => {
+      add_stemmer(builder, parse_stemmer(language, variant.as_deref())?)
+    }
+    FilterType::StemmerCustom { name } => add_stemmer(builder, parse_stemmer_name(name)?),
+    FilterType::StopWord { language } => builder.filter_dynamic(
+      tvt::StopWordFilter::new(parse_language(language)?).ok_or_else(|| {
+        Error::from_reason(format!(
+          "Failed to create stop word filter for language: {:?}",
+          language
+        ))
+      })?,
+    ),
+    FilterType::CustomStopWord { stopwords } => {
+      builder.filter_dynamic(tvt::StopWordFilter::remove(stopwords.clone()))
+    }
+    FilterType::SplitCompound { constituent_words } => builder.filter_dynamic(
+      tvt::SplitCompoundWords::from_dictionary(constituent_words)
+        .map_err(|e| Error::from_reason(format!("Failed to create compound splitter: {}", e)))?,
+    ),
+  })
+}
@@ -0,0 +1,77 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+use tantivy::tokenizer::{Token, Tokenizer};
+
+use crate::token_filter::VecTokenStream;
+
+/// Tokenizer keeping the emails and the URLs whole.
+///
+/// Like the simple tokenizer, the text is split into runs of alphanumeric
+/// characters, but an email or a URL is also emitted as a single token, at the
+/// position of its first component: `mail user@example.com` gives `mail`,
+/// `user@example.com`, `user`, `example` and `com`, so that both the address
+/// and its parts are searchable.
+#[derive(Clone, Default)]
+pub(crate) struct UrlEmailTokenizer;
+
+impl Tokenizer for UrlEmailTokenizer {
+  type TokenStream<'a> = VecTokenStream;
+
+  fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    for (word_start, word) in runs(text, |c| !c.is_whitespace()) {
+      // Punctuation around an address, e.g. `<user@example.com>,`, is not
+      // part of it. A URL may end with a slash.
+      let trimmed = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+      let offset_from = word_start + word.len() - trimmed.len();
+      let trimmed = trimmed.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '/');
+      if is_url_or_email(trimmed) {
+        tokens.push(Token {
+          offset_from,
+          offset_to: offset_from + trimmed.len(),
+          position,
+          text: trimmed.to_string(),
+          position_length: 1,
+        });
+      }
+      for (start, component) in runs(word, char::is_alphanumeric) {
+        tokens.push(Token {
+          offset_from: word_start + start,
+          offset_to: word_start + start + component.len(),
+          position,
+          text: component.to_string(),
+          position_length: 1,
+        });
+        position += 1;
+      }
+    }
+    VecTokenStream::new(tokens)
+  }
+}
+
+/// The maximal runs of characters matching `pred`, with their byte offset.
+fn runs(text: &str, pred: impl Fn(char) -> bool) -> impl Iterator<Item = (usize, &str)> {
+  let mut chars = text.char_indices().peekable();
+  std::iter::from_fn(move || {
+    let (start, _) = chars.find(|(_, c)| pred(*c))?;
+    let mut end = text.len();
+    while let Some(&(idx, c)) = chars.peek() {
+      if !pred(c) {
+        end = idx;
+        break;
+      }
+      chars.next();
+    }
+    Some((start, &text[start..end]))
+  })
+}
+
+fn is_url_or_email(word: &str) -> bool {
+  static URL: OnceLock<Regex> = OnceLock::new();
+  static EMAIL: OnceLock<Regex> = OnceLock::new();
+  let url = URL.get_or_init(|| Regex::new(r"^(?i:(?:https?|ftp)://|www\.)\S+$").unwrap());
+  let email = EMAIL.get_or_init(|| Regex::new(r"^[\w.%+-]+@[\w-]+(?:\.[\w-]+)*\.\w{2,}$").unwrap());
+  url.is_match(word) || email.is_match(word)
+}
@@ -0,0 +1,73 @@
+use crate::index::UserQueryOptions;
+
+/// Rewrite the text typed in a search box so that the syntax it is not
+/// allowed to use is searched as plain text, see `Index.parseUserQuery`.
+///
+/// The special characters are escaped with a backslash, or replaced by a
+/// space with `strip`.
+pub(crate) fn sanitize(text: &str, options: &UserQueryOptions) -> String {
+  let allow_fields = options.allow_fields.unwrap_or(false);
+  let allow_ranges = options.allow_ranges.unwrap_or(false);
+  let allow_boosts = options.allow_boosts.unwrap_or(false);
+  let allow_operators = options.allow_operators.unwrap_or(true);
+  let allow_phrases = options.allow_phrases.unwrap_or(true);
+  let strip = options.strip.unwrap_or(false);
+
+  // A quote left open would swallow the rest of the text.
+  let num_quotes = text.chars().filter(|&c| c == '"').count();
+  let last_quote = if num_quotes % 2 == 1 {
+    text.rfind('"')
+  } else {
+    None
+  };
+
+  let mut sanitized = String::with_capacity(text.len());
+  let mut in_phrase = false;
+  let mut previous = None;
+  for (position, c) in text.char_indices() {
+    // Phrases are searched as they are, up to their closing quote.
+    if in_phrase && c != '"' {
+      sanitized.push(if c == '\\' { ' ' } else { c });
+      previous = Some(c);
+      continue;
+    }
+    let allowed = match c {
+      ':' => allow_fields,
+      '[' | ']' | '{' | '}' | '<' | '>' => allow_ranges,
+      '^' => allow_boosts,
+      '+' | '-' | '(' | ')' | '!' => allow_operators,
+      '"' => allow_phrases && Some(position) != last_quote,
+      // Slop and prefix of the phrase just closed.
+      '~' | '*' => allow_phrases && previous == Some('"'),
+      '\\' | '\'' | '`' => false,
+      _ => true,
+    };
+    if c == '"' && allowed {
+      in_phrase = !in_phrase;
+    }
+    if allowed {
+      sanitized.push(c);
+    } else if strip {
+      sanitized.push(' ');
+    } else {
+      sanitized.push('\\');
+      sanitized.push(c);
+    }
+    previous = Some(c);
+  }
+  if allow_operators {
+    return sanitized;
+  }
+  // The boolean operators are words of their own.
+  sanitized
+    .split(' ')
+    .map(|word| match word {
+      "AND" | "OR" | "NOT" if strip => "",
+      "AND" => "\\AND",
+      "OR" => "\\OR",
+      "NOT" => "\\NOT",
+      _ => word,
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
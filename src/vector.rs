@@ -0,0 +1,74 @@
+use napi::{Error, Result, Status};
+
+/// Similarity used to rank vectors in `Searcher.knn`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum VectorMetric {
+  Cosine,
+  Dot,
+  L2,
+}
+
+impl VectorMetric {
+  pub(crate) fn parse(metric: Option<&str>) -> Result<Self> {
+    match metric.unwrap_or("cosine") {
+      "cosine" => Ok(VectorMetric::Cosine),
+      "dot" => Ok(VectorMetric::Dot),
+      "l2" => Ok(VectorMetric::L2),
+      _ => Err(Error::new(
+        Status::InvalidArg,
+        "Invalid metric, valid choices are: 'cosine', 'dot' and 'l2'",
+      )),
+    }
+  }
+
+  /// Similarity between two vectors of the same length, higher is closer.
+  ///
+  /// The L2 similarity is `1 / (1 + distance)`.
+  pub(crate) fn similarity(self, a: &[f32], b: &[f32]) -> f32 {
+    match self {
+      VectorMetric::Cosine => {
+        let norms = dot(a, a).sqrt() * dot(b, b).sqrt();
+        if norms == 0.0 {
+          0.0
+        } else {
+          dot(a, b) / norms
+        }
+      }
+      VectorMetric::Dot => dot(a, b),
+      VectorMetric::L2 => {
+        let distance: f32 = a
+          .iter()
+          .zip(b)
+          .map(|(x, y)| (x - y) * (x - y))
+          .sum::<f32>()
+          .sqrt();
+        1.0 / (1.0 + distance)
+      }
+    }
+  }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+  a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Encode a vector the way it is stored in a vector field: little-endian f32s.
+pub(crate) fn encode_vector(vector: &[f64]) -> Vec<u8> {
+  vector
+    .iter()
+    .flat_map(|value| (*value as f32).to_le_bytes())
+    .collect()
+}
+
+/// Decode a stored vector, returning `None` if it is malformed.
+pub(crate) fn decode_vector(bytes: &[u8]) -> Option<Vec<f32>> {
+  if !bytes.len().is_multiple_of(4) {
+    return None;
+  }
+  Some(
+    bytes
+      .chunks_exact(4)
+      .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+      .collect(),
+  )
+}
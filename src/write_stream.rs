@@ -0,0 +1,220 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use napi::bindgen_prelude::*;
+use napi::{Env, Error, JsError, Result, Status};
+use napi_derive::napi;
+use tantivy as tv;
+
+use crate::{document::Document, index::IndexWriter};
+
+/// Options of `IndexWriter.createWriteStream`.
+#[napi(object)]
+#[derive(Default)]
+pub struct WriteStreamOptions {
+  /// What is written to the stream: 'ndjson' for text holding a JSON
+  /// document per line, e.g. read from a file, or 'document' for `Document`
+  /// objects. Defaults to 'ndjson'.
+  pub format: Option<String>,
+  /// The number of documents submitted to the writer at once, like
+  /// `addJsonBatch` does. It is also the high water mark of a 'document'
+  /// stream. Defaults to 1000.
+  pub batch_size: Option<u32>,
+  /// Commit every time this many documents were written. The stream
+  /// commits anyway once it is ended.
+  pub commit_every: Option<u32>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum StreamFormat {
+  Ndjson,
+  Document,
+}
+
+/// The documents written to a stream, waiting to be submitted to the writer.
+struct WriteStreamState {
+  writer: Reference<IndexWriter>,
+  batch_size: usize,
+  commit_every: Option<usize>,
+  /// The end of the ndjson written so far, after its last newline.
+  partial_line: Vec<u8>,
+  /// Number of ndjson lines read, to tell which one is invalid.
+  num_lines: usize,
+  operations: Vec<tv::indexer::UserOperation>,
+  num_pending: usize,
+  num_uncommitted: usize,
+}
+
+impl WriteStreamState {
+  fn write_ndjson(&mut self, chunk: &[u8]) -> Result<()> {
+    self.partial_line.extend_from_slice(chunk);
+    let Some(end) = self.partial_line.iter().rposition(|&byte| byte == b'\n') else {
+      return Ok(());
+    };
+    let rest = self.partial_line.split_off(end + 1);
+    let mut lines = std::mem::replace(&mut self.partial_line, rest);
+    // The last newline.
+    lines.pop();
+    for line in lines.split(|&byte| byte == b'\n') {
+      self.add_json_line(line)?;
+    }
+    Ok(())
+  }
+
+  fn add_json_line(&mut self, line: &[u8]) -> Result<()> {
+    self.num_lines += 1;
+    let line = std::str::from_utf8(line).map_err(|_| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Line {} is not valid UTF-8.", self.num_lines),
+      )
+    })?;
+    let line = line.trim();
+    if line.is_empty() {
+      return Ok(());
+    }
+    let doc = tv::TantivyDocument::parse_json(&self.writer.schema, line).map_err(|e| {
+      Error::new(
+        Status::InvalidArg,
+        format!("Invalid JSON document on line {}: {}", self.num_lines, e),
+      )
+    })?;
+    self.add(doc)
+  }
+
+  fn write_document(&mut self, doc: &Document) -> Result<()> {
+    let doc = crate::index::convert_document(&self.writer.schema, doc)?;
+    self.add(doc)
+  }
+
+  fn add(&mut self, doc: tv::TantivyDocument) -> Result<()> {
+    self.writer.push_add_operations(doc, &mut self.operations)?;
+    self.num_pending += 1;
+    self.num_uncommitted += 1;
+    if self.num_pending >= self.batch_size {
+      self.flush()?;
+    }
+    if self
+      .commit_every
+      .is_some_and(|commit_every| self.num_uncommitted >= commit_every)
+    {
+      self.flush()?;
+      self.writer.commit(None)?;
+      self.num_uncommitted = 0;
+    }
+    Ok(())
+  }
+
+  fn flush(&mut self) -> Result<()> {
+    if !self.operations.is_empty() {
+      self.writer.run(std::mem::take(&mut self.operations))?;
+    }
+    self.num_pending = 0;
+    Ok(())
+  }
+
+  /// Submit what is left once the stream is ended, and commit.
+  fn finish(&mut self) -> Result<()> {
+    if !self.partial_line.is_empty() {
+      let line = std::mem::take(&mut self.partial_line);
+      self.add_json_line(&line)?;
+    }
+    self.flush()?;
+    self.writer.commit(None)?;
+    Ok(())
+  }
+}
+
+/// Call the callback of `Writable.write` or `Writable.final` with the error
+/// of the operation, if any.
+fn done(env: &Env, callback: Function<Option<Unknown>, ()>, result: Result<()>) -> Result<()> {
+  match result {
+    Ok(()) => callback.call(None),
+    Err(error) => callback.call(Some(JsError::from(error).into_unknown(*env))),
+  }
+}
+
+#[napi]
+impl IndexWriter {
+  /// Create a `Writable` stream adding the documents written to it, e.g.
+  /// `await pipeline(fs.createReadStream('docs.ndjson'), writer.createWriteStream())`.
+  ///
+  /// The documents are submitted to the writer in batches, the stream
+  /// waiting for a batch to be accepted before taking more data. The stream
+  /// commits once it is ended, and fails on the first document that can't be
+  /// added.
+  ///
+  /// @param options - See `WriteStreamOptions`.
+  ///
+  /// Raises error if the writer was consumed.
+  #[napi(ts_return_type = "import('node:stream').Writable")]
+  pub fn create_write_stream(
+    &self,
+    env: Env,
+    reference: Reference<IndexWriter>,
+    options: Option<WriteStreamOptions>,
+  ) -> Result<Unknown<'_>> {
+    self.inner()?;
+    let options = options.unwrap_or_default();
+    let format = match options.format.as_deref().unwrap_or("ndjson") {
+      "ndjson" => StreamFormat::Ndjson,
+      "document" => StreamFormat::Document,
+      _ => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          "Invalid stream format, valid choices are: 'ndjson' and 'document'",
+        ))
+      }
+    };
+    let batch_size = options.batch_size.unwrap_or(1000).max(1) as usize;
+    let state = Rc::new(RefCell::new(WriteStreamState {
+      writer: reference,
+      batch_size,
+      commit_every: options
+        .commit_every
+        .map(|commit_every| commit_every.max(1) as usize),
+      partial_line: Vec::new(),
+      num_lines: 0,
+      operations: Vec::with_capacity(batch_size),
+      num_pending: 0,
+      num_uncommitted: 0,
+    }));
+
+    let write_state = state.clone();
+    // Called with the chunk, its encoding and the callback.
+    let write: Function<FnArgs<(Unknown, Unknown, Unknown)>, ()> = env
+      .create_function_from_closure("write", move |ctx| {
+        let callback = ctx.get::<Function<Option<Unknown>, ()>>(2)?;
+        let result = match format {
+          StreamFormat::Ndjson => ctx
+            .get::<Buffer>(0)
+            .and_then(|chunk| write_state.borrow_mut().write_ndjson(&chunk)),
+          StreamFormat::Document => ctx
+            .get::<ClassInstance<Document>>(0)
+            .and_then(|doc| write_state.borrow_mut().write_document(&doc)),
+        };
+        done(ctx.env, callback, result)
+      })?;
+    let finish: Function<Unknown, ()> = env.create_function_from_closure("final", move |ctx| {
+      let callback = ctx.get::<Function<Option<Unknown>, ()>>(0)?;
+      let result = state.borrow_mut().finish();
+      done(ctx.env, callback, result)
+    })?;
+
+    let mut stream_options = Object::new(&env)?;
+    stream_options.set_named_property("objectMode", format == StreamFormat::Document)?;
+    if format == StreamFormat::Document {
+      stream_options.set_named_property("highWaterMark", batch_size as u32)?;
+    }
+    stream_options.set_named_property("write", write)?;
+    stream_options.set_named_property("final", finish)?;
+
+    let process: Object = env.get_global()?.get_named_property("process")?;
+    let get_builtin_module: Function<&str, Object> =
+      process.get_named_property("getBuiltinModule")?;
+    let writable: Function<Object, Unknown> = get_builtin_module
+      .call("node:stream")?
+      .get_named_property("Writable")?;
+    writable.new_instance(stream_options)
+  }
+}